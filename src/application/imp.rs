@@ -26,7 +26,7 @@ use glib::subclass::{
 };
 use gtk4::{glib, prelude::GtkWindowExt, subclass::prelude::GtkApplicationImpl, Application};
 
-use crate::window::MViewWindow;
+use crate::{backends::extract, window::MViewWindow};
 
 #[derive(Debug, Default)]
 pub struct MviewApplicationImp {
@@ -49,12 +49,18 @@ impl ObjectImpl for MviewApplicationImp {}
 impl ApplicationImpl for MviewApplicationImp {
     fn startup(&self) {
         self.parent_startup();
+        extract::recover();
         let window = MViewWindow::new(&self.obj());
         window.present();
         self.window
             .set(window)
             .expect("Failed to initialize application window");
     }
+
+    fn shutdown(&self) {
+        extract::cleanup();
+        self.parent_shutdown();
+    }
 }
 
 impl GtkApplicationImpl for MviewApplicationImp {}
@@ -0,0 +1,233 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use super::{Content, ImageParams};
+use image::DynamicImage;
+use std::{
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+use crate::{
+    classification::{FileClassification, FileType, Preference},
+    content::{
+        loader::ContentLoader, mail, mime::Message, paginated::PaginatedContent,
+        sheet::SheetContent,
+    },
+    error::MviewResult,
+    file_view::{
+        model::{BackendRef, ItemRef, Reference, Row},
+        Cursor,
+    },
+    image::{
+        draw::draw_error,
+        provider::{image_rs::RsImageLoader, internal::InternalImageLoader},
+    },
+    mview6_error,
+    profile::performance::Performance,
+    util::path_to_filename,
+};
+
+use super::{
+    extract::{self, ExtractedFile},
+    filesystem::FileSystem,
+    Backend, Target,
+};
+
+/// Maximum size of a message, attachments included
+pub const MAX_MAIL_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Email message (.eml) or web archive (.mhtml), the message itself is the first entry,
+/// followed by the images it contains
+pub struct MailArchive {
+    path: PathBuf,
+    store: Vec<Row>,
+    /// Parsed once, the images are taken from it
+    message: Option<Arc<Message>>,
+    /// Handle when this message was extracted from an archive
+    extracted: Option<ExtractedFile>,
+}
+
+impl MailArchive {
+    pub fn new(filename: &Path) -> Self {
+        let message = read_mail(filename).ok();
+        MailArchive {
+            path: filename.into(),
+            store: message
+                .as_ref()
+                .and_then(|message| list_mail(filename, message).ok())
+                .unwrap_or_default(),
+            message,
+            extracted: extract::lookup(filename),
+        }
+    }
+
+    fn message(&self) -> MviewResult<Arc<Message>> {
+        match &self.message {
+            Some(message) => Ok(message.clone()),
+            None => read_mail(&self.path),
+        }
+    }
+
+    pub fn get_thumbnail(src: &Reference) -> MviewResult<DynamicImage> {
+        if let (BackendRef::MailArchive(filename), ItemRef::Index(index)) = src.as_tuple() {
+            let bytes = image_bytes(&read_mail(filename)?, *index)?;
+            if let Some(image) = InternalImageLoader::thumb_from_bytes(&bytes) {
+                Ok(image)
+            } else {
+                let image = RsImageLoader::dynimg_from_memory(&bytes)?;
+                let image = image.resize(175, 175, image::imageops::FilterType::Lanczos3);
+                Ok(image)
+            }
+        } else {
+            mview6_error!("invalid reference").into()
+        }
+    }
+}
+
+impl Backend for MailArchive {
+    fn class_name(&self) -> &str {
+        "MailArchive"
+    }
+
+    fn path(&self) -> PathBuf {
+        self.path.clone()
+    }
+
+    fn list(&self) -> &Vec<Row> {
+        &self.store
+    }
+
+    fn leave(&self) -> Option<(Box<dyn Backend>, Target)> {
+        match &self.extracted {
+            Some(extracted) => {
+                let (source, item) = extracted.source();
+                Some((<dyn Backend>::new_from_ref(&source), item))
+            }
+            None => Some((
+                Box::new(FileSystem::new(self.path.parent()?)),
+                Target::Name(path_to_filename(&self.path)),
+            )),
+        }
+    }
+
+    fn content(&self, item: &ItemRef, _: &ImageParams) -> Content {
+        let result = self.message().and_then(|message| {
+            if item.idx() == 0 {
+                Ok(message_content(&self.path, &message))
+            } else {
+                image_bytes(&message, item.idx())
+                    .map(|bytes| ContentLoader::content_from_memory(bytes, &self.path))
+            }
+        });
+        match result {
+            Ok(content) => content,
+            Err(error) => draw_error(&self.path, error),
+        }
+    }
+
+    fn backend_ref(&self) -> BackendRef {
+        BackendRef::MailArchive(self.path.clone())
+    }
+
+    fn item_ref(&self, cursor: &Cursor) -> ItemRef {
+        ItemRef::Index(cursor.index())
+    }
+}
+
+/// Headers, text and attachments of the message as a text sheet
+pub fn message_content(path: &Path, message: &Message) -> Content {
+    Content::new_paginated(PaginatedContent::new_sheet(SheetContent::new(
+        path,
+        mail::rows(message),
+    )))
+}
+
+/// The message at `filename`. The last message read is kept, so the backend, the render
+/// thread and the thumbnailer share it. It is parsed again only when it changed on disk.
+pub fn read_mail(filename: &Path) -> MviewResult<Arc<Message>> {
+    static LAST: Mutex<Option<(PathBuf, Option<SystemTime>, Arc<Message>)>> = Mutex::new(None);
+    let mut last = LAST.lock().unwrap();
+    let modified = fs::metadata(filename).and_then(|m| m.modified()).ok();
+    if let Some((_, _, message)) = last
+        .as_ref()
+        .filter(|(path, time, _)| path == filename && *time == modified)
+    {
+        return Ok(message.clone());
+    }
+    let message = Arc::new(parse_mail(filename)?);
+    last.replace((filename.into(), modified, message.clone()));
+    Ok(message)
+}
+
+fn parse_mail(filename: &Path) -> MviewResult<Message> {
+    let duration = Performance::start();
+    let file = fs::File::open(filename)?;
+    let mut buffer = Vec::new();
+    file.take(MAX_MAIL_SIZE).read_to_end(&mut buffer)?;
+    let message = Message::parse(&buffer);
+    duration.elapsed("parse (mail)");
+    Ok(message)
+}
+
+/// Bytes of the image with `index`: entry 0 is the message, entry n is the nth image
+fn image_bytes(message: &Message, index: u64) -> MviewResult<Vec<u8>> {
+    let image = index
+        .checked_sub(1)
+        .and_then(|index| message.images().nth(index as usize))
+        .ok_or_else(|| mview6_error!("image not found"))?;
+    Ok(image.body.clone())
+}
+
+fn list_mail(filename: &Path, message: &Message) -> MviewResult<Vec<Row>> {
+    let metadata = fs::metadata(filename)?;
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+        .map_or(0, |duration| duration.as_secs());
+
+    let mut result = vec![Row::new_index(
+        FileClassification::new(FileType::Document, Preference::Normal),
+        message
+            .headers
+            .get("subject")
+            .unwrap_or_else(|| path_to_filename(filename)),
+        metadata.len(),
+        modified,
+        0,
+    )];
+    for (i, image) in message.images().enumerate() {
+        let name = image.filename().unwrap_or_else(|| {
+            let extension = image.content_type.trim_start_matches("image/");
+            format!("image-{}.{extension}", i + 1)
+        });
+        result.push(Row::new_index(
+            FileClassification::new(FileType::Image, Preference::Normal),
+            name,
+            image.body.len() as u64,
+            modified,
+            i as u64 + 1,
+        ));
+    }
+    Ok(result)
+}
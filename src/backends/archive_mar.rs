@@ -20,8 +20,7 @@
 use super::{Content, ImageParams};
 use image::DynamicImage;
 use std::{
-    fs,
-    io::{BufReader, ErrorKind, Read, Result, Seek, SeekFrom},
+    io::{ErrorKind, Read, Result, Seek, SeekFrom},
     path::{Path, PathBuf},
     str::from_utf8,
 };
@@ -29,6 +28,7 @@ use std::{
 use crate::{
     classification::{FileClassification, FileType},
     error::MviewResult,
+    file_reader::FileReader,
     file_view::{
         model::{BackendRef, ItemRef, Reference, Row},
         Cursor,
@@ -84,9 +84,7 @@ impl MarArchive {
     pub fn get_thumbnail(src: &Reference) -> MviewResult<DynamicImage> {
         if let (BackendRef::MarArchive(filename), ItemRef::Index(index)) = src.as_tuple() {
             dbg!(filename, index);
-            let fname = Path::new(filename);
-            let file = fs::File::open(fname)?;
-            let mut reader = BufReader::new(file);
+            let mut reader = FileReader::open(filename)?;
             reader.seek(SeekFrom::Start(*index))?;
             InternalImageLoader::thumb_from_reader(&mut reader)
         } else {
@@ -126,9 +124,7 @@ impl Backend for MarArchive {
 
 fn extract_mar(filename: &Path, offset: u64) -> MviewResult<Content> {
     let duration = Performance::start();
-    let fname = std::path::Path::new(filename);
-    let file = fs::File::open(fname)?;
-    let mut reader = BufReader::new(file);
+    let mut reader = FileReader::open(filename)?;
     // println!("Offset {}", offset);
     reader.seek(SeekFrom::Start(offset))?;
     let image = InternalImageLoader::image_from_reader(&mut reader);
@@ -138,9 +134,7 @@ fn extract_mar(filename: &Path, offset: u64) -> MviewResult<Content> {
 
 fn list_mar(mar_file: &Path) -> Result<Vec<Row>> {
     let mut result = Vec::new();
-    let fname = std::path::Path::new(mar_file);
-    let file = fs::File::open(fname)?;
-    let mut reader = BufReader::new(file);
+    let mut reader = FileReader::open(mar_file)?;
 
     let mut buf = [0u8; 12];
     reader.read_exact(&mut buf)?;
@@ -26,6 +26,7 @@ use std::path::{Path, PathBuf};
 use unrar::{error::UnrarError, Archive, UnrarResult};
 
 use crate::{
+    cache::CacheKind,
     classification::{FileClassification, FileType},
     content::loader::ContentLoader,
     error::MviewResult,
@@ -39,13 +40,20 @@ use crate::{
     },
     mview6_error,
     profile::performance::Performance,
+    util::path_to_filename,
 };
 
-use super::Backend;
+use super::{
+    extract::{self, enter_entry, ExtractedFile},
+    filesystem::FileSystem,
+    Backend, Target,
+};
 
 pub struct RarArchive {
     path: PathBuf,
     store: Vec<Row>,
+    /// Handle when this archive was extracted from another archive
+    extracted: Option<ExtractedFile>,
 }
 
 impl RarArchive {
@@ -53,30 +61,22 @@ impl RarArchive {
         RarArchive {
             path: filename.into(),
             store: list_rar(filename).unwrap_or_default(),
+            extracted: extract::lookup(filename),
         }
     }
 
     pub fn get_thumbnail(src: &Reference) -> MviewResult<DynamicImage> {
         if let (BackendRef::RarArchive(filename), ItemRef::String(selection)) = src.as_tuple() {
-            if let Some(directory) = filename.parent() {
-                let mut hasher = Sha256::new();
-                hasher.update(filename.to_string_lossy().to_string().as_bytes());
-                hasher.update(selection.as_bytes());
-                let sha256sum = format!("{:x}", hasher.finalize());
-                let thumb_filename = format!("{sha256sum}.mthumb");
-                let thumb_path = directory.join(".mview").join(thumb_filename);
-
-                if Path::new(&thumb_path).exists() {
-                    RsImageLoader::dynimg_from_file(&thumb_path)
-                } else {
-                    let bytes = extract_rar(filename, selection)?;
-                    let image = RsImageLoader::dynimg_from_memory(&bytes)?;
-                    let image = image.resize(175, 175, image::imageops::FilterType::Lanczos3);
-                    ImageSaver::save_thumbnail(&thumb_path, &image);
-                    Ok(image)
-                }
+            let thumb_path = thumbnail_cache_path(filename, selection);
+
+            if Path::new(&thumb_path).exists() {
+                RsImageLoader::dynimg_from_file(&thumb_path)
             } else {
-                mview6_error!("Failed to find directory of rar file").into() // FIXME
+                let bytes = extract_rar(filename, selection)?;
+                let image = RsImageLoader::dynimg_from_memory(&bytes)?;
+                let image = image.resize(175, 175, image::imageops::FilterType::Lanczos3);
+                ImageSaver::save_thumbnail(&thumb_path, &image);
+                Ok(image)
             }
         } else {
             mview6_error!("invalid reference").into()
@@ -97,6 +97,28 @@ impl Backend for RarArchive {
         &self.store
     }
 
+    fn enter(&self, cursor: &Cursor) -> Option<Box<dyn Backend>> {
+        enter_entry(
+            &self.backend_ref(),
+            cursor,
+            &Target::Name(cursor.name()),
+            || Ok(extract_rar(&self.path, &cursor.name())?),
+        )
+    }
+
+    fn leave(&self) -> Option<(Box<dyn Backend>, Target)> {
+        match &self.extracted {
+            Some(extracted) => {
+                let (source, item) = extracted.source();
+                Some((<dyn Backend>::new_from_ref(&source), item))
+            }
+            None => Some((
+                Box::new(FileSystem::new(self.path.parent()?)),
+                Target::Name(path_to_filename(&self.path)),
+            )),
+        }
+    }
+
     fn content(&self, item: &ItemRef, _: &ImageParams) -> Content {
         match extract_rar(&self.path, item.str()) {
             Ok(bytes) => ContentLoader::content_from_memory(bytes, &self.path.join(item.str())),
@@ -152,6 +174,21 @@ fn extract_rar(rar_file: &Path, sel: &str) -> UnrarResult<Vec<u8>> {
     })
 }
 
+fn thumbnail_cache_path(rar_file: &Path, selection: &str) -> PathBuf {
+    let mut hasher = Sha256::new();
+    hasher.update(rar_file.to_string_lossy().to_string().as_bytes());
+    hasher.update(selection.as_bytes());
+    let sha256sum = format!("{:x}", hasher.finalize());
+    CacheKind::Thumbnails.path(&format!("{sha256sum}.mthumb"))
+}
+
+/// Remove the cached thumbnails of the entries of a (modified) archive
+pub fn invalidate_thumbnails(rar_file: &Path) {
+    for row in list_rar(rar_file).unwrap_or_default() {
+        let _ = std::fs::remove_file(thumbnail_cache_path(rar_file, &row.name));
+    }
+}
+
 fn list_rar(rar_file: &Path) -> UnrarResult<Vec<Row>> {
     let mut result = Vec::new();
     let archive = Archive::new(&rar_file).open_for_listing()?;
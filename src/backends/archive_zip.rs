@@ -22,16 +22,20 @@ use chrono::{Local, TimeZone};
 use human_bytes::human_bytes;
 use image::DynamicImage;
 use std::{
-    fs,
-    io::{BufReader, Read},
+    io::Read,
     path::{Path, PathBuf},
 };
 use zip::result::ZipResult;
 
 use crate::{
-    classification::{FileClassification, FileType},
+    classification::{
+        detect,
+        file_formats::{ArchiveFormat, FileFormat},
+        FileClassification, FileType,
+    },
     content::loader::ContentLoader,
     error::MviewResult,
+    file_reader::FileReader,
     file_view::{
         model::{BackendRef, ItemRef, Reference, Row},
         Cursor,
@@ -45,18 +49,32 @@ use crate::{
     util::path_to_filename,
 };
 
-use super::Backend;
+use super::{
+    extract::{self, enter_entry, ExtractedFile},
+    filesystem::FileSystem,
+    Backend, Target,
+};
+
+/// Image formats that may be used for the embedded thumbnail and that we can decode
+const OFFICE_THUMBNAIL_EXT: &[&str] = &["jpeg", "jpg", "png", "gif"];
 
 pub struct ZipArchive {
     path: PathBuf,
     store: Vec<Row>,
+    /// Office documents list all entries (the XML parts too), not just the supported ones
+    office: bool,
+    /// Handle when this archive was extracted from another archive
+    extracted: Option<ExtractedFile>,
 }
 
 impl ZipArchive {
     pub fn new(filename: &Path) -> Self {
+        let office = is_office_document(filename);
         ZipArchive {
             path: filename.into(),
-            store: list_zip(filename).unwrap_or_default(),
+            store: list_zip(filename, office).unwrap_or_default(),
+            office,
+            extracted: extract::lookup(filename),
         }
     }
 
@@ -75,6 +93,15 @@ impl ZipArchive {
     //     }
     // }
 
+    /// Full resolution image, for a preview larger than the thumbnail
+    pub fn get_preview(src: &Reference) -> MviewResult<DynamicImage> {
+        if let (BackendRef::ZipArchive(filename), ItemRef::Index(index)) = src.as_tuple() {
+            RsImageLoader::dynimg_from_memory(&extract_zip(filename, *index as usize)?)
+        } else {
+            mview6_error!("invalid reference").into()
+        }
+    }
+
     pub fn get_thumbnail(src: &Reference) -> MviewResult<DynamicImage> {
         if let (BackendRef::ZipArchive(filename), ItemRef::Index(index)) = src.as_tuple() {
             let bytes = extract_zip(filename, *index as usize)?;
@@ -104,9 +131,45 @@ impl Backend for ZipArchive {
         &self.store
     }
 
+    fn enter(&self, cursor: &Cursor) -> Option<Box<dyn Backend>> {
+        enter_entry(
+            &self.backend_ref(),
+            cursor,
+            &Target::Index(cursor.index()),
+            || Ok(extract_zip(&self.path, cursor.index() as usize)?),
+        )
+    }
+
+    fn leave(&self) -> Option<(Box<dyn Backend>, Target)> {
+        match &self.extracted {
+            Some(extracted) => {
+                let (source, item) = extracted.source();
+                Some((<dyn Backend>::new_from_ref(&source), item))
+            }
+            None => Some((
+                Box::new(FileSystem::new(self.path.parent()?)),
+                Target::Name(path_to_filename(&self.path)),
+            )),
+        }
+    }
+
     fn content(&self, item: &ItemRef, _: &ImageParams) -> Content {
-        match extract_zip(&self.path, item.idx() as usize) {
-            Ok(bytes) => ContentLoader::content_from_memory(bytes, &self.path),
+        let index = item.idx() as usize;
+        match extract_zip(&self.path, index) {
+            Ok(bytes) => {
+                // show the XML parts of office documents as text
+                let name = if self.office {
+                    zip_entry_name(&self.path, index).ok()
+                } else {
+                    None
+                };
+                match name {
+                    Some(name) if FileType::from(Path::new(&name)) == FileType::Unsupported => {
+                        ContentLoader::content_from_text(bytes, &self.path.join(name))
+                    }
+                    _ => ContentLoader::content_from_memory(bytes, &self.path),
+                }
+            }
             Err(error) => draw_error(&self.path, error.into()),
         }
     }
@@ -135,10 +198,7 @@ impl Backend for ZipArchive {
 
 fn extract_zip(filename: &Path, index: usize) -> ZipResult<Vec<u8>> {
     let duration = Performance::start();
-    let fname = std::path::Path::new(filename);
-    let file = fs::File::open(fname)?;
-    let reader = BufReader::new(file);
-    let mut archive = zip::ZipArchive::new(reader)?;
+    let mut archive = zip::ZipArchive::new(FileReader::open(filename)?)?;
     let mut file = archive.by_index(index)?;
     let mut buf = Vec::<u8>::new();
     let size = file.read_to_end(&mut buf)?;
@@ -146,13 +206,39 @@ fn extract_zip(filename: &Path, index: usize) -> ZipResult<Vec<u8>> {
     Ok(buf)
 }
 
-fn list_zip(zip_file: &Path) -> ZipResult<Vec<Row>> {
-    let mut result = Vec::new();
-    let fname = std::path::Path::new(zip_file);
-    let file = fs::File::open(fname)?;
-    let reader = BufReader::new(file);
+fn zip_entry_name(filename: &Path, index: usize) -> ZipResult<String> {
+    let mut archive = zip::ZipArchive::new(FileReader::open(filename)?)?;
+    let name = archive.by_index(index)?.name().to_string();
+    Ok(name)
+}
 
-    let mut archive = zip::ZipArchive::new(reader)?;
+/// Office Open XML documents are zip archives with an optional embedded thumbnail
+pub fn is_office_document(path: &Path) -> bool {
+    detect::format_from_path(path) == FileFormat::Archive(ArchiveFormat::Office)
+}
+
+/// Extract the embedded thumbnail (docProps/thumbnail.*) of an office document
+///
+/// The thumbnail is only stored when the application was asked to, and Windows
+/// metafile thumbnails (emf, wmf) are not supported.
+pub fn office_thumbnail(path: &Path) -> MviewResult<Vec<u8>> {
+    let mut archive = zip::ZipArchive::new(FileReader::open(path)?)?;
+    let name = archive
+        .file_names()
+        .find(|name| {
+            name.strip_prefix("docProps/thumbnail.")
+                .is_some_and(|ext| OFFICE_THUMBNAIL_EXT.contains(&ext.to_lowercase().as_str()))
+        })
+        .map(|name| name.to_string())
+        .ok_or_else(|| mview6_error!("No embedded thumbnail"))?;
+    let mut buf = Vec::new();
+    archive.by_name(&name)?.read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
+fn list_zip(zip_file: &Path, all_entries: bool) -> ZipResult<Vec<Row>> {
+    let mut result = Vec::new();
+    let mut archive = zip::ZipArchive::new(FileReader::open(zip_file)?)?;
 
     for i in 0..archive.len() {
         let file = archive.by_index(i)?;
@@ -173,7 +259,7 @@ fn list_zip(zip_file: &Path) -> ZipResult<Vec<Row>> {
             continue;
         }
 
-        if cat.file_type == FileType::Unsupported {
+        if cat.file_type == FileType::Unsupported && !all_entries {
             continue;
         }
 
@@ -0,0 +1,236 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! A collection of files picked from any folder, archive or document, kept in manual order
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::{virtual_list::VirtualList, Backend, Content, ImageParams, Target};
+use crate::{
+    classification::{FileClassification, FileType},
+    config::JsonStore,
+    content::loader::ContentLoader,
+    file_view::{
+        model::{BackendRef, ItemRef, Reference, Row},
+        Cursor, Direction,
+    },
+};
+
+/// A file in the collection: the backend holding it and the item within that backend
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CollectionEntry {
+    pub name: String,
+    pub backend: String,
+    pub path: PathBuf,
+    pub item: String,
+}
+
+impl CollectionEntry {
+    pub fn new(reference: &Reference, name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            backend: reference.backend.name().to_string(),
+            path: PathBuf::from(reference.backend.path()),
+            item: reference.item.to_string_repr(),
+        }
+    }
+
+    pub fn reference(&self) -> Reference {
+        Reference {
+            backend: BackendRef::new(&self.backend, self.path.clone()),
+            item: ItemRef::from_string_repr(&self.item).unwrap_or_default(),
+        }
+    }
+
+    /// Location of the entry on disk: the file itself, or the archive or document holding it
+    pub fn file(&self) -> PathBuf {
+        match self.reference().take_tuple() {
+            (BackendRef::FileSystem(directory), ItemRef::String(name)) => directory.join(name),
+            _ => self.path.clone(),
+        }
+    }
+
+    pub fn category(&self) -> FileClassification {
+        let file = self.file();
+        match self.reference().backend {
+            BackendRef::FileSystem(_) => FileClassification::determine(&file, file.is_dir()),
+            _ => FileClassification::determine(Path::new(&self.name), false),
+        }
+    }
+
+    pub fn content(&self, params: &ImageParams) -> Content {
+        let reference = self.reference();
+        match reference.backend {
+            BackendRef::FileSystem(_) => ContentLoader::content_from_file(&self.file()),
+            _ => <dyn Backend>::new_from_ref(&reference.backend).content(&reference.item, params),
+        }
+    }
+}
+
+const COLLECTION: JsonStore = JsonStore::new("collection.json");
+
+pub fn load_collection() -> Vec<CollectionEntry> {
+    COLLECTION.load()
+}
+
+pub fn save_collection(entries: &[CollectionEntry]) -> io::Result<()> {
+    COLLECTION.save(entries)
+}
+
+/// Move the entry at `index` one place up or down, returns its new position
+pub fn move_entry(
+    entries: &mut [CollectionEntry],
+    index: usize,
+    direction: Direction,
+) -> Option<usize> {
+    let other = match direction {
+        Direction::Up => index.checked_sub(1)?,
+        Direction::Down => index + 1,
+    };
+    if other >= entries.len() {
+        return None;
+    }
+    entries.swap(index, other);
+    Some(other)
+}
+
+/// The collection as an extended M3U playlist. Entries inside archives and documents are
+/// listed by the file that holds them.
+pub fn playlist(entries: &[CollectionEntry]) -> String {
+    let mut result = String::from("#EXTM3U\n");
+    for entry in entries {
+        result.push_str(&format!(
+            "#EXTINF:-1,{}\n{}\n",
+            entry.name,
+            entry.file().display()
+        ));
+    }
+    result
+}
+
+pub struct Collection {
+    list: VirtualList<CollectionEntry>,
+}
+
+impl Collection {
+    pub fn new(parent_backend: Box<dyn Backend>, parent_target: Target) -> Self {
+        Collection {
+            list: VirtualList::new(
+                load_collection(),
+                |index, entry| Row::new_index(entry.category(), entry.name.clone(), 0, 0, index),
+                parent_backend,
+                parent_target,
+            ),
+        }
+    }
+}
+
+impl Backend for Collection {
+    fn class_name(&self) -> &str {
+        "Collection"
+    }
+
+    fn path(&self) -> PathBuf {
+        Path::new("collection").into()
+    }
+
+    fn list(&self) -> &Vec<Row> {
+        self.list.list()
+    }
+
+    fn enter(&self, cursor: &Cursor) -> Option<Box<dyn Backend>> {
+        let entry = self.list.current(cursor)?;
+        match cursor.content() {
+            FileType::Folder | FileType::Archive | FileType::Document => {
+                Some(<dyn Backend>::new_from_path(&entry.file()))
+            }
+            _ => None,
+        }
+    }
+
+    fn leave(&self) -> Option<(Box<dyn Backend>, Target)> {
+        self.list.leave()
+    }
+
+    fn content(&self, item: &ItemRef, params: &ImageParams) -> Content {
+        match self.list.entry(item) {
+            Some(entry) => entry.content(params),
+            None => Content::default(),
+        }
+    }
+
+    fn resolve(&self, cursor: &Cursor) -> Option<Reference> {
+        self.list.current(cursor).map(CollectionEntry::reference)
+    }
+
+    fn backend_ref(&self) -> BackendRef {
+        BackendRef::Collection
+    }
+
+    fn item_ref(&self, cursor: &Cursor) -> ItemRef {
+        ItemRef::Index(cursor.index())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str) -> CollectionEntry {
+        CollectionEntry::new(
+            &Reference {
+                backend: BackendRef::FileSystem(PathBuf::from("/photos")),
+                item: ItemRef::String(name.to_string()),
+            },
+            name,
+        )
+    }
+
+    #[test]
+    fn reorder_entries() {
+        let mut entries = vec![entry("a.jpg"), entry("b.jpg"), entry("c.jpg")];
+        assert_eq!(move_entry(&mut entries, 0, Direction::Up), None);
+        assert_eq!(move_entry(&mut entries, 2, Direction::Down), None);
+        assert_eq!(move_entry(&mut entries, 0, Direction::Down), Some(1));
+        let names: Vec<_> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["b.jpg", "a.jpg", "c.jpg"]);
+    }
+
+    #[test]
+    fn playlist_lists_files() {
+        let archived = CollectionEntry::new(
+            &Reference {
+                backend: BackendRef::ZipArchive(PathBuf::from("/comics/book.cbz")),
+                item: ItemRef::Index(3),
+            },
+            "page004.jpg",
+        );
+        assert_eq!(archived.reference().item, ItemRef::Index(3));
+        let text = playlist(&[entry("a.jpg"), archived]);
+        assert_eq!(
+            text,
+            "#EXTM3U\n#EXTINF:-1,a.jpg\n/photos/a.jpg\n#EXTINF:-1,page004.jpg\n/comics/book.cbz\n"
+        );
+    }
+}
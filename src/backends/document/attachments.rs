@@ -0,0 +1,278 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Files embedded in a PDF, either in the document's name tree or as file
+//! attachment annotations on a page. These are listed after the pages.
+//!
+//! The PDF is parsed once, when the document is listed or an attachment is shown. The result
+//! is shared by the backend, the render thread and the thumbnailer, which all ask for the
+//! attachments of the same document.
+
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+use image::DynamicImage;
+use lopdf::{Dictionary, Document, Object, ObjectId};
+
+use crate::{
+    classification::{FileClassification, FileType},
+    content::{loader::ContentLoader, Content},
+    error::MviewResult,
+    file_view::model::Row,
+    image::{draw::draw_error, provider::image_rs::RsImageLoader},
+    mview6_error,
+};
+
+/// Limit on the depth of the name tree, protects against loops in broken files
+const MAX_DEPTH: usize = 16;
+
+#[derive(Debug, Clone)]
+pub struct Attachment {
+    pub name: String,
+    pub size: u64,
+    stream: ObjectId,
+}
+
+/// Decode a PDF text string: UTF-16BE with byte order mark, or PDFDocEncoding
+/// (close enough to Latin-1 for file names)
+fn text_string(bytes: &[u8]) -> String {
+    if let Some(utf16) = bytes.strip_prefix(&[0xfe, 0xff]) {
+        let units: Vec<u16> = utf16
+            .chunks_exact(2)
+            .map(|c| u16::from_be_bytes([c[0], c[1]]))
+            .collect();
+        String::from_utf16_lossy(&units)
+    } else if let Some(utf8) = bytes.strip_prefix(&[0xef, 0xbb, 0xbf]) {
+        String::from_utf8_lossy(utf8).into_owned()
+    } else {
+        bytes.iter().map(|&b| b as char).collect()
+    }
+}
+
+/// Only the final component, embedded names may contain (foreign) paths
+fn file_name(name: &str) -> String {
+    name.rsplit(['/', '\\'])
+        .next()
+        .filter(|n| !n.is_empty())
+        .unwrap_or("attachment")
+        .to_string()
+}
+
+fn dict<'a>(doc: &'a Document, object: &'a Object) -> Option<&'a Dictionary> {
+    doc.dereference(object).ok()?.1.as_dict().ok()
+}
+
+fn file_spec(doc: &Document, object: &Object) -> Option<Attachment> {
+    let spec = dict(doc, object)?;
+    let embedded = dict(doc, spec.get(b"EF").ok()?)?;
+    let stream = embedded
+        .get(b"UF")
+        .or_else(|_| embedded.get(b"F"))
+        .ok()?
+        .as_reference()
+        .ok()?;
+    let name = spec
+        .get(b"UF")
+        .or_else(|_| spec.get(b"F"))
+        .and_then(Object::as_str)
+        .map(text_string)
+        .unwrap_or_default();
+    let content = doc.get_object(stream).ok()?.as_stream().ok()?;
+    let size = content
+        .dict
+        .get(b"Params")
+        .ok()
+        .and_then(|params| dict(doc, params))
+        .and_then(|params| params.get(b"Size").ok())
+        .and_then(|size| size.as_i64().ok())
+        .map(|size| size as u64)
+        .unwrap_or(content.content.len() as u64);
+    Some(Attachment {
+        name: file_name(&name),
+        size,
+        stream,
+    })
+}
+
+fn name_tree(doc: &Document, node: &Dictionary, depth: usize, result: &mut Vec<Attachment>) {
+    if depth > MAX_DEPTH {
+        return;
+    }
+    if let Ok(names) = node.get(b"Names").and_then(Object::as_array) {
+        // Pairs of key and file specification
+        for pair in names.chunks_exact(2) {
+            result.extend(file_spec(doc, &pair[1]));
+        }
+    }
+    if let Ok(kids) = node.get(b"Kids").and_then(Object::as_array) {
+        for kid in kids {
+            if let Some(kid) = dict(doc, kid) {
+                name_tree(doc, kid, depth + 1, result);
+            }
+        }
+    }
+}
+
+fn collect(doc: &Document) -> Vec<Attachment> {
+    let mut result = Vec::new();
+    if let Some(tree) = doc
+        .catalog()
+        .ok()
+        .and_then(|catalog| dict(doc, catalog.get(b"Names").ok()?))
+        .and_then(|names| dict(doc, names.get(b"EmbeddedFiles").ok()?))
+    {
+        name_tree(doc, tree, 0, &mut result);
+    }
+    for page in doc.page_iter() {
+        for annotation in doc.get_page_annotations(page).unwrap_or_default() {
+            if annotation
+                .get(b"Subtype")
+                .and_then(Object::as_name)
+                .is_ok_and(|subtype| subtype == b"FileAttachment")
+            {
+                if let Ok(fs) = annotation.get(b"FS") {
+                    result.extend(file_spec(doc, fs));
+                }
+            }
+        }
+    }
+    // The same file can be both in the name tree and in an annotation
+    let mut seen = HashSet::new();
+    result.retain(|attachment| seen.insert(attachment.stream));
+    result
+}
+
+/// The attachments of a document
+pub struct Attachments {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    /// Only kept when there are attachments to read
+    document: Option<Document>,
+    list: Vec<Attachment>,
+}
+
+impl Attachments {
+    fn load(path: &Path) -> Self {
+        let modified = fs::metadata(path).and_then(|m| m.modified()).ok();
+        let (document, list) = match Document::load(path) {
+            Ok(doc) => {
+                let list = collect(&doc);
+                ((!list.is_empty()).then_some(doc), list)
+            }
+            Err(e) => {
+                eprintln!("Could not read attachments of {}: {e}", path.display());
+                (None, Vec::new())
+            }
+        };
+        Attachments {
+            path: path.into(),
+            modified,
+            document,
+            list,
+        }
+    }
+
+    /// Rows for the attachments, indexed after the pages
+    pub fn rows(&self, first_index: u64) -> Vec<Row> {
+        self.list
+            .iter()
+            .enumerate()
+            .map(|(i, attachment)| {
+                let cat = FileClassification::determine(Path::new(&attachment.name), false);
+                Row::new_index(
+                    cat,
+                    attachment.name.clone(),
+                    attachment.size,
+                    0,
+                    first_index + i as u64,
+                )
+            })
+            .collect()
+    }
+
+    /// Name and data of an attachment
+    pub fn data(&self, index: usize) -> MviewResult<(String, Vec<u8>)> {
+        let (Some(doc), Some(attachment)) = (&self.document, self.list.get(index)) else {
+            return mview6_error!("attachment not found").into();
+        };
+        let data = doc
+            .get_object(attachment.stream)?
+            .as_stream()?
+            .get_plain_content()?;
+        Ok((attachment.name.clone(), data))
+    }
+
+    pub fn content(&self, index: usize) -> Content {
+        match self.data(index) {
+            Ok((name, data)) => {
+                let path = self.path.join(name);
+                if FileType::from(path.as_path()) == FileType::Image {
+                    ContentLoader::content_from_memory(data, &path)
+                } else {
+                    ContentLoader::content_from_text(data, &path)
+                }
+            }
+            Err(e) => draw_error(&self.path, e),
+        }
+    }
+
+    pub fn thumbnail(&self, index: usize) -> MviewResult<DynamicImage> {
+        let (_, data) = self.data(index)?;
+        RsImageLoader::dynimg_from_memory(&data)
+    }
+}
+
+/// The attachments of the document at `path`. The last document asked for is kept, it is
+/// parsed again only when it changed on disk.
+pub fn attachments(path: &Path) -> Arc<Attachments> {
+    static LAST: Mutex<Option<Arc<Attachments>>> = Mutex::new(None);
+    let mut last = LAST.lock().unwrap();
+    let modified = fs::metadata(path).and_then(|m| m.modified()).ok();
+    if let Some(attachments) = last
+        .as_ref()
+        .filter(|a| a.path == path && a.modified == modified)
+    {
+        return attachments.clone();
+    }
+    let attachments = Arc::new(Attachments::load(path));
+    last.replace(attachments.clone());
+    attachments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names() {
+        assert_eq!(text_string(b"photo.jpg"), "photo.jpg");
+        assert_eq!(text_string(b"caf\xe9.txt"), "café.txt");
+        assert_eq!(
+            text_string(&[0xfe, 0xff, 0x00, 0x61, 0x00, 0x2e, 0x00, 0x70, 0x00, 0x6e, 0x00, 0x67]),
+            "a.png"
+        );
+        assert_eq!(file_name("C:\\data\\report.csv"), "report.csv");
+        assert_eq!(file_name("dir/"), "attachment");
+    }
+}
@@ -0,0 +1,226 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Page labels: the page numbers as printed in the document (e.g. "iv" or "A-3"),
+//! which can differ from the physical page numbers.
+
+use std::path::Path;
+
+use lopdf::{Dictionary, Document, Object};
+
+/// Limit on the depth of the number tree, protects against loops in broken files
+const MAX_DEPTH: usize = 16;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct LabelRange {
+    /// Index of the first page of the range
+    start: u32,
+    /// Numbering style: D (decimal), R/r (roman), A/a (letters) or none (prefix only)
+    style: Option<u8>,
+    prefix: String,
+    /// Number of the first page of the range
+    first: u32,
+}
+
+fn roman(mut number: u32) -> String {
+    const NUMERALS: [(u32, &str); 13] = [
+        (1000, "m"),
+        (900, "cm"),
+        (500, "d"),
+        (400, "cd"),
+        (100, "c"),
+        (90, "xc"),
+        (50, "l"),
+        (40, "xl"),
+        (10, "x"),
+        (9, "ix"),
+        (5, "v"),
+        (4, "iv"),
+        (1, "i"),
+    ];
+    let mut result = String::new();
+    for (value, numeral) in NUMERALS {
+        while number >= value {
+            result.push_str(numeral);
+            number -= value;
+        }
+    }
+    result
+}
+
+/// a..z, aa..zz, aaa..zzz, ...
+fn letters(number: u32) -> String {
+    if number == 0 {
+        return String::new();
+    }
+    let letter = (b'a' + ((number - 1) % 26) as u8) as char;
+    letter.to_string().repeat((number as usize - 1) / 26 + 1)
+}
+
+fn label(range: &LabelRange, index: u32) -> String {
+    let number = range.first + index - range.start;
+    let number = match range.style {
+        Some(b'D') => number.to_string(),
+        Some(b'R') => roman(number).to_uppercase(),
+        Some(b'r') => roman(number),
+        Some(b'A') => letters(number).to_uppercase(),
+        Some(b'a') => letters(number),
+        _ => String::new(),
+    };
+    format!("{}{number}", range.prefix)
+}
+
+fn number_tree(doc: &Document, node: &Dictionary, depth: usize, result: &mut Vec<LabelRange>) {
+    if depth > MAX_DEPTH {
+        return;
+    }
+    if let Ok(nums) = node.get(b"Nums").and_then(Object::as_array) {
+        for pair in nums.chunks_exact(2) {
+            let (Ok(start), Ok((_, Object::Dictionary(dict)))) =
+                (pair[0].as_i64(), doc.dereference(&pair[1]))
+            else {
+                continue;
+            };
+            result.push(LabelRange {
+                start: start as u32,
+                style: dict
+                    .get(b"S")
+                    .and_then(Object::as_name)
+                    .ok()
+                    .and_then(|s| s.first().copied()),
+                prefix: dict
+                    .get(b"P")
+                    .and_then(Object::as_str)
+                    .map(|p| String::from_utf8_lossy(p).into_owned())
+                    .unwrap_or_default(),
+                first: dict
+                    .get(b"St")
+                    .and_then(Object::as_i64)
+                    .map(|st| st as u32)
+                    .unwrap_or(1),
+            });
+        }
+    }
+    if let Ok(kids) = node.get(b"Kids").and_then(Object::as_array) {
+        for kid in kids {
+            if let Ok((_, Object::Dictionary(kid))) = doc.dereference(kid) {
+                number_tree(doc, kid, depth + 1, result);
+            }
+        }
+    }
+}
+
+fn labels(ranges: &mut [LabelRange], page_count: u32) -> Vec<String> {
+    ranges.sort_by_key(|range| range.start);
+    (0..page_count)
+        .map(|index| {
+            ranges
+                .iter()
+                .rev()
+                .find(|range| range.start <= index)
+                .map(|range| label(range, index))
+                .unwrap_or_else(|| (index + 1).to_string())
+        })
+        .collect()
+}
+
+/// Label of each page, empty if the document does not define labels
+pub fn page_labels(path: &Path) -> Vec<String> {
+    let Ok(doc) = Document::load(path) else {
+        return Vec::new();
+    };
+    let mut ranges = Vec::new();
+    if let Some(tree) = doc
+        .catalog()
+        .ok()
+        .and_then(|catalog| catalog.get(b"PageLabels").ok())
+        .and_then(|tree| doc.dereference(tree).ok())
+        .and_then(|(_, tree)| tree.as_dict().ok())
+    {
+        number_tree(&doc, tree, 0, &mut ranges);
+    }
+    if ranges.is_empty() {
+        return Vec::new();
+    }
+    labels(&mut ranges, doc.get_pages().len() as u32)
+}
+
+/// Page index for text entered by the user: a page label (exact, then ignoring case) or
+/// a physical page number
+pub fn find_page(labels: &[String], page_count: u64, text: &str) -> Option<u64> {
+    let text = text.trim();
+    if let Some(index) = labels.iter().position(|label| label == text) {
+        return Some(index as u64);
+    }
+    if let Some(index) = labels
+        .iter()
+        .position(|label| label.eq_ignore_ascii_case(text))
+    {
+        return Some(index as u64);
+    }
+    match text.parse::<u64>() {
+        Ok(number) if (1..=page_count).contains(&number) => Some(number - 1),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbering() {
+        assert_eq!(roman(4), "iv");
+        assert_eq!(roman(1994), "mcmxciv");
+        assert_eq!(letters(1), "a");
+        assert_eq!(letters(27), "aa");
+        assert_eq!(letters(53), "aaa");
+
+        let mut ranges = vec![
+            LabelRange {
+                start: 3,
+                style: Some(b'D'),
+                first: 1,
+                ..Default::default()
+            },
+            LabelRange {
+                start: 0,
+                style: Some(b'r'),
+                first: 1,
+                ..Default::default()
+            },
+            LabelRange {
+                start: 5,
+                style: Some(b'D'),
+                prefix: "A-".into(),
+                first: 8,
+            },
+        ];
+        let labels = labels(&mut ranges, 7);
+        assert_eq!(labels, ["i", "ii", "iii", "1", "2", "A-8", "A-9"]);
+
+        assert_eq!(find_page(&labels, 7, "ii"), Some(1));
+        assert_eq!(find_page(&labels, 7, "a-9"), Some(6));
+        // A label takes precedence over the physical page number
+        assert_eq!(find_page(&labels, 7, "2"), Some(4));
+        assert_eq!(find_page(&labels, 7, "7"), Some(6));
+        assert_eq!(find_page(&[], 7, "7"), Some(6));
+        assert_eq!(find_page(&[], 7, "8"), None);
+    }
+}
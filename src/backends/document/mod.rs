@@ -17,8 +17,12 @@
 // STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
 // OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
 
+use crate::rect::SizeD;
+
+pub mod attachments;
+pub mod labels;
 #[cfg(feature = "mupdf")]
 pub mod mupdf;
 pub mod pdfium;
@@ -89,6 +93,128 @@ pub fn pages(index: i32, last_page: i32, mode: &PageMode) -> Pages {
     }
 }
 
+/// Physical pages (zero based, inclusive) shown with logical page `index`: the page pair
+/// in dual page mode
+pub fn shown_pages(index: i32, page_count: i32, mode: &PageMode, split: bool) -> (i32, i32) {
+    if split {
+        let page = LogicalPage::new(index, true).page;
+        return (page, page);
+    }
+    match pages(index, page_count - 1, mode) {
+        Pages::Single(page) => (page, page),
+        Pages::Dual(left) => (left, left + 1),
+    }
+}
+
+/// Parse a range of one based page numbers like `3`, `3-7` or `3-` (up to the last page)
+/// into zero based pages
+pub fn parse_page_range(text: &str, page_count: i32) -> Option<(i32, i32)> {
+    let (first, last) = match text.split_once('-') {
+        Some((first, last)) => (first.trim(), last.trim()),
+        None => (text.trim(), text.trim()),
+    };
+    let first: i32 = first.parse().ok()?;
+    let last: i32 = if last.is_empty() {
+        page_count
+    } else {
+        last.parse().ok()?
+    };
+    (1 <= first && first <= last && last <= page_count).then_some((first - 1, last - 1))
+}
+
+/// Fraction of the page width both halves of a split spread show beyond the middle
+const SPLIT_OVERLAP: f64 = 0.02;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Half {
+    Left,
+    Right,
+}
+
+/// A page as navigated in the viewer: a physical page of the document, or the left or
+/// right half of one when scanned spreads are split
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LogicalPage {
+    pub page: i32,
+    half: Option<Half>,
+}
+
+impl LogicalPage {
+    pub fn new(index: i32, split: bool) -> Self {
+        if split {
+            LogicalPage {
+                page: index / 2,
+                half: Some(if index % 2 == 0 {
+                    Half::Left
+                } else {
+                    Half::Right
+                }),
+            }
+        } else {
+            LogicalPage {
+                page: index,
+                half: None,
+            }
+        }
+    }
+
+    /// Size of the logical page given the size of the physical page
+    pub fn size(&self, page: SizeD) -> SizeD {
+        match self.half {
+            None => page,
+            Some(_) => SizeD::new(page.width() * (0.5 + SPLIT_OVERLAP), page.height()),
+        }
+    }
+
+    /// Horizontal position of the logical page within the physical page
+    pub fn offset(&self, page: SizeD) -> f64 {
+        match self.half {
+            Some(Half::Right) => page.width() * (0.5 - SPLIT_OVERLAP),
+            _ => 0.0,
+        }
+    }
+
+    /// Row label in the page list
+    pub fn label(&self) -> String {
+        match self.half {
+            None => format!("Page {0:5}", self.page + 1),
+            Some(Half::Left) => format!("Page {0:5} L", self.page + 1),
+            Some(Half::Right) => format!("Page {0:5} R", self.page + 1),
+        }
+    }
+}
+
+/// Number of logical pages for a document with `page_count` physical pages
+pub fn logical_page_count(page_count: i32, split: bool) -> i32 {
+    if split {
+        page_count * 2
+    } else {
+        page_count
+    }
+}
+
+/// Number of pages examined to detect a cover
+pub const COVER_DETECT_PAGES: i32 = 5;
+
+/// Relative difference in aspect ratio that makes the first page a cover
+const COVER_ASPECT_TOLERANCE: f64 = 0.03;
+
+/// Heuristic for a cover as first page: its aspect ratio differs from the pages
+/// that follow. A cover is shown on its own so the spreads after it line up.
+pub fn is_cover(sizes: &[SizeD]) -> bool {
+    let [first, rest @ ..] = sizes else {
+        return false;
+    };
+    if rest.is_empty() {
+        return false;
+    }
+    let aspect = |size: &SizeD| size.width() / size.height();
+    let mut aspects: Vec<f64> = rest.iter().map(aspect).collect();
+    aspects.sort_by(f64::total_cmp);
+    let median = aspects[aspects.len() / 2];
+    (aspect(first) / median - 1.0).abs() > COVER_ASPECT_TOLERANCE
+}
+
 #[repr(u8)]
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub enum PdfEngine {
@@ -139,3 +265,65 @@ pub fn set_pdf_engine(pdf_engine: PdfEngine) {
 pub fn pdf_engine() -> PdfEngine {
     PDF_ENGINE.load(Ordering::Relaxed).into()
 }
+
+static SPLIT_SPREADS: AtomicBool = AtomicBool::new(false);
+
+pub fn set_split_spreads(split: bool) {
+    SPLIT_SPREADS.store(split, Ordering::Relaxed);
+}
+
+pub fn split_spreads() -> bool {
+    SPLIT_SPREADS.load(Ordering::Relaxed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cover() {
+        let a4 = SizeD::new(595.0, 842.0);
+        let wide = SizeD::new(640.0, 842.0);
+        assert!(is_cover(&[wide, a4, a4, a4]));
+        assert!(!is_cover(&[a4, a4, a4, wide]));
+        assert!(!is_cover(&[SizeD::new(596.0, 842.0), a4, a4]));
+        assert!(!is_cover(&[wide]));
+        assert!(!is_cover(&[]));
+    }
+
+    #[test]
+    fn split() {
+        let spread = SizeD::new(1000.0, 700.0);
+        let page = LogicalPage::new(5, false);
+        assert_eq!(page.page, 5);
+        assert_eq!(page.size(spread), spread);
+        assert_eq!(page.offset(spread), 0.0);
+
+        let left = LogicalPage::new(4, true);
+        let right = LogicalPage::new(5, true);
+        assert_eq!((left.page, right.page), (2, 2));
+        assert_eq!(left.size(spread), SizeD::new(520.0, 700.0));
+        assert_eq!(right.size(spread), SizeD::new(520.0, 700.0));
+        assert_eq!(left.offset(spread), 0.0);
+        assert_eq!(right.offset(spread), 480.0);
+        assert_eq!(right.label(), "Page     3 R");
+        assert_eq!(logical_page_count(3, true), 6);
+    }
+
+    #[test]
+    fn page_ranges() {
+        assert_eq!(parse_page_range("3", 10), Some((2, 2)));
+        assert_eq!(parse_page_range(" 3 - 7 ", 10), Some((2, 6)));
+        assert_eq!(parse_page_range("8-", 10), Some((7, 9)));
+        assert_eq!(parse_page_range("7-3", 10), None);
+        assert_eq!(parse_page_range("0-3", 10), None);
+        assert_eq!(parse_page_range("5-11", 10), None);
+
+        assert_eq!(shown_pages(0, 10, &PageMode::DualEvenOdd, false), (0, 0));
+        assert_eq!(shown_pages(4, 10, &PageMode::DualEvenOdd, false), (3, 4));
+        assert_eq!(shown_pages(4, 10, &PageMode::DualOddEven, false), (4, 5));
+        assert_eq!(shown_pages(9, 10, &PageMode::DualOddEven, false), (8, 9));
+        assert_eq!(shown_pages(4, 10, &PageMode::Single, false), (4, 4));
+        assert_eq!(shown_pages(5, 10, &PageMode::DualEvenOdd, true), (2, 2));
+    }
+}
@@ -18,12 +18,23 @@
 // OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use image::{DynamicImage, ImageBuffer, Rgb};
-use mupdf::{Colorspace, Device, IRect, Matrix, Page, Pixmap, Rect};
-use std::path::{Path, PathBuf};
+use mupdf::{Colorspace, Device, DocumentWriter, IRect, Matrix, Page, Pixmap, Rect};
+use std::{
+    cell::{Cell, OnceCell},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use crate::{
     backends::{
-        document::{pages, PageMode, Pages},
+        document::{
+            attachments::{attachments, Attachments},
+            is_cover,
+            labels::page_labels,
+            logical_page_count, pages, split_spreads, LogicalPage, PageMode, Pages,
+            COVER_DETECT_PAGES,
+        },
+        extract::enter_entry,
         Backend, ImageParams,
     },
     classification::FileType,
@@ -31,7 +42,7 @@ use crate::{
     error::MviewResult,
     file_view::{
         model::{BackendRef, ItemRef, Reference, Row},
-        Cursor,
+        Cursor, Target,
     },
     image::{draw::draw_error, provider::surface::SurfaceData, view::Zoom},
     mview6_error,
@@ -44,23 +55,36 @@ const MIN_DOC_HEIGHT: f32 = 32.0;
 pub struct DocMuPdf {
     path: PathBuf,
     document: MviewResult<mupdf::Document>,
-    store: Vec<Row>,
+    /// Pages followed by attachments, filled when first listed
+    store: OnceCell<Vec<Row>>,
+    /// Rows of the pages until the document is listed
+    pages: Cell<Vec<Row>>,
     last_page: i32,
+    split: bool,
+    /// Parsed when first asked for, the render thread only needs them to show one
+    attachments: OnceCell<Arc<Attachments>>,
+    /// Read when first asked for
+    page_labels: OnceCell<Vec<String>>,
 }
 
 impl DocMuPdf {
     pub fn new(filename: &Path) -> Self {
-        let (document, store, last_page) = Self::create_store(filename);
+        let split = split_spreads();
+        let (document, pages, last_page) = Self::create_store(filename, split);
         DocMuPdf {
             path: filename.into(),
             document,
-            store,
+            store: OnceCell::new(),
+            pages: Cell::new(pages),
             last_page,
+            split,
+            attachments: OnceCell::new(),
+            page_labels: OnceCell::new(),
         }
     }
 
-    fn create_store(filename: &Path) -> (MviewResult<mupdf::Document>, Vec<Row>, i32) {
-        match list_pages(filename) {
+    fn create_store(filename: &Path, split: bool) -> (MviewResult<mupdf::Document>, Vec<Row>, i32) {
+        match list_pages(filename, split) {
             Ok((document, store, last_page)) => (Ok(document), store, last_page),
             Err(e) => {
                 eprintln!("ERROR {e:?}");
@@ -69,6 +93,17 @@ impl DocMuPdf {
         }
     }
 
+    fn attachments(&self) -> &Attachments {
+        self.attachments.get_or_init(|| attachments(&self.path))
+    }
+
+    /// Rows after the last page are attachments
+    fn attachment_index(&self, index: u64) -> Option<usize> {
+        index
+            .checked_sub(self.last_page as u64 + 1)
+            .map(|index| index as usize)
+    }
+
     pub fn get_thumbnail(src: &Reference) -> MviewResult<DynamicImage> {
         if let (BackendRef::Mupdf(filename), ItemRef::Index(index)) = src.as_tuple() {
             let image = extract_thumb(filename, *index as i32)?;
@@ -90,10 +125,54 @@ impl Backend for DocMuPdf {
     }
 
     fn list(&self) -> &Vec<Row> {
-        &self.store
+        self.store.get_or_init(|| {
+            let mut store = self.pages.take();
+            store.extend(self.attachments().rows(self.last_page as u64 + 1));
+            store
+        })
+    }
+
+    fn enter(&self, cursor: &Cursor) -> Option<Box<dyn Backend>> {
+        let attachment = self.attachment_index(cursor.index())?;
+        enter_entry(
+            &self.backend_ref(),
+            cursor,
+            &Target::Index(cursor.index()),
+            || Ok(self.attachments().data(attachment)?.1),
+        )
+    }
+
+    fn page_count(&self) -> Option<u64> {
+        let document = self.document.as_ref().ok()?;
+        Some(document.page_count().ok()? as u64)
+    }
+
+    fn page_labels(&self) -> &[String] {
+        self.page_labels.get_or_init(|| page_labels(&self.path))
+    }
+
+    fn first_page_is_cover(&self) -> bool {
+        let Ok(document) = self.document.as_ref() else {
+            return false;
+        };
+        if self.split {
+            return false;
+        }
+        let sizes: Vec<SizeD> = (0..=self.last_page.min(COVER_DETECT_PAGES - 1))
+            .filter_map(|index| page_size_as_rect(&document.load_page(index).ok()?).ok())
+            .collect();
+        is_cover(&sizes)
+    }
+
+    fn attachment(&self, cursor: &Cursor) -> Option<(String, Vec<u8>)> {
+        let attachment = self.attachment_index(cursor.index())?;
+        self.attachments().data(attachment).ok()
     }
 
     fn content(&self, item: &ItemRef, params: &ImageParams) -> Content {
+        if let Some(attachment) = self.attachment_index(item.idx()) {
+            return self.attachments().content(attachment);
+        }
         (|| {
             let document = self.document.as_ref().map_err(|e| e.to_string())?;
             page_size(
@@ -105,6 +184,7 @@ impl Backend for DocMuPdf {
                 item.idx() as i32,
                 self.last_page,
                 params.page_mode,
+                self.split,
             )
             .map_err(|e| e.to_string())
         })()
@@ -132,6 +212,7 @@ impl Backend for DocMuPdf {
             item.idx() as i32,
             self.last_page,
             page_mode,
+            self.split,
             zoom,
             viewport,
         )
@@ -145,10 +226,11 @@ fn page_size(
     index: i32,
     last_page: i32,
     mode: &PageMode,
+    split: bool,
 ) -> MviewResult<Content> {
     match pages(index, last_page, mode) {
-        Pages::Single(page) => page_size_single(reference, mode, document, page),
-        Pages::Dual(left) => page_size_dual(reference, mode, document, left),
+        Pages::Single(page) => page_size_single(reference, mode, document, page, split),
+        Pages::Dual(left) => page_size_dual(reference, mode, document, left, split),
     }
 }
 
@@ -157,9 +239,10 @@ fn page_size_single(
     mode: &PageMode,
     document: &mupdf::Document,
     index: i32,
+    split: bool,
 ) -> MviewResult<Content> {
     let duration = Performance::start();
-    let size = page_size_as_rect(&document.load_page(index)?)?;
+    let size = logical_page_size(document, LogicalPage::new(index, split))?;
     let image = Content::new_doc(reference, *mode, size);
     duration.elapsed("mupdf single");
     Ok(image)
@@ -170,11 +253,12 @@ fn page_size_dual(
     mode: &PageMode,
     document: &mupdf::Document,
     index: i32,
+    split: bool,
 ) -> MviewResult<Content> {
     // The right page is scaled so its height is the same as the left page
     let duration = Performance::start();
-    let size_left = page_size_as_rect(&document.load_page(index)?)?;
-    let size_right = page_size_as_rect(&document.load_page(index + 1)?)?;
+    let size_left = logical_page_size(document, LogicalPage::new(index, split))?;
+    let size_right = logical_page_size(document, LogicalPage::new(index + 1, split))?;
     let scale_right = size_left.height() / size_right.height();
     let size = SizeD::new(
         size_left.width() + scale_right * size_right.width(),
@@ -187,8 +271,14 @@ fn page_size_dual(
 
 fn extract_thumb(filename: &Path, index: i32) -> MviewResult<DynamicImage> {
     let doc = open(filename)?;
+    let split = split_spreads();
+    let page_count = logical_page_count(doc.page_count()?, split);
+    if index >= page_count {
+        return attachments(filename).thumbnail((index - page_count) as usize);
+    }
 
-    let (page, bounds) = open_page(&doc, index)?;
+    let logical = LogicalPage::new(index, split);
+    let (page, bounds) = open_page(&doc, logical.page)?;
     let zoom = 350.0 / bounds.height();
     let matrix = Matrix::new_scale(zoom, zoom);
     let pixmap = page.to_pixmap(&matrix, &Colorspace::device_rgb(), false, false)?;
@@ -198,7 +288,17 @@ fn extract_thumb(filename: &Path, index: i32) -> MviewResult<DynamicImage> {
         pixmap.height(),
         pixmap.samples().to_vec(),
     ) {
-        Some(rgb_image) => Ok(DynamicImage::ImageRgb8(rgb_image)),
+        Some(rgb_image) => {
+            let image = DynamicImage::ImageRgb8(rgb_image);
+            let page_size = SizeD::new(bounds.width() as f64, bounds.height() as f64);
+            let zoom = zoom as f64;
+            Ok(image.crop_imm(
+                (logical.offset(page_size) * zoom) as u32,
+                0,
+                (logical.size(page_size).width() * zoom).ceil() as u32,
+                image.height(),
+            ))
+        }
         None => mview6_error!("Could not create ImageBuffer from pdf thumb data").into(),
     }
 }
@@ -208,29 +308,43 @@ fn page_size_as_rect(page: &Page) -> MviewResult<SizeD> {
     Ok(SizeD::new(bounds.width() as f64, bounds.height() as f64))
 }
 
+fn logical_page_size(document: &mupdf::Document, logical: LogicalPage) -> MviewResult<SizeD> {
+    Ok(logical.size(page_size_as_rect(&document.load_page(logical.page)?)?))
+}
+
 fn render(
     document: &mupdf::Document,
     index: i32,
     last_page: i32,
     mode: &PageMode,
+    split: bool,
     zoom: &Zoom,
     viewport: &RectD,
 ) -> MviewResult<SurfaceData> {
     match pages(index, last_page, mode) {
-        Pages::Single(page) => render_single(document, page, zoom, viewport),
-        Pages::Dual(left) => render_dual(document, left, zoom, viewport),
+        Pages::Single(page) => {
+            render_single(document, LogicalPage::new(page, split), zoom, viewport)
+        }
+        Pages::Dual(left) => render_dual(
+            document,
+            LogicalPage::new(left, split),
+            LogicalPage::new(left + 1, split),
+            zoom,
+            viewport,
+        ),
     }
 }
 
 fn render_single(
     document: &mupdf::Document,
-    index: i32,
+    logical: LogicalPage,
     zoom: &Zoom,
     viewport: &RectD,
 ) -> MviewResult<SurfaceData> {
     let duration = Performance::start();
-    let page = document.load_page(index)?;
-    let surface = if let Some(pixmap) = page_render(&page, zoom, viewport)? {
+    let page = document.load_page(logical.page)?;
+    let offset = logical.offset(page_size_as_rect(&page)?);
+    let surface = if let Some(pixmap) = page_render(&page, zoom, viewport, offset)? {
         Ok(SurfaceData::from_rgb(
             pixmap.width(),
             pixmap.height(),
@@ -245,26 +359,34 @@ fn render_single(
 
 fn render_dual(
     document: &mupdf::Document,
-    index: i32,
+    left: LogicalPage,
+    right: LogicalPage,
     zoom: &Zoom,
     viewport: &RectD,
 ) -> MviewResult<SurfaceData> {
     let duration = Performance::start();
 
-    let page_left = document.load_page(index)?;
-    let size_left = page_size_as_rect(&page_left)?;
+    let page_left = document.load_page(left.page)?;
+    let physical_left = page_size_as_rect(&page_left)?;
+    let size_left = left.size(physical_left);
     let mut zoom_left = zoom.clone();
     zoom_left.set_image_size(size_left);
-    let pixmap_left = page_render(&page_left, &zoom_left, viewport)?;
+    let pixmap_left = page_render(&page_left, &zoom_left, viewport, left.offset(physical_left))?;
 
-    let page_right = document.load_page(index + 1)?;
-    let size_right = page_size_as_rect(&page_right)?;
+    let page_right = document.load_page(right.page)?;
+    let physical_right = page_size_as_rect(&page_right)?;
+    let size_right = right.size(physical_right);
     let scale_right = size_left.height() / size_right.height();
     let mut zoom_right = zoom.clone();
     zoom_right.set_image_size(size_right);
     zoom_right.set_zoom_factor(zoom.scale() * scale_right);
     zoom_right.set_origin(zoom.image_to_screen(&VectorD::new(size_left.width(), 0.0)));
-    let pixmap_right = page_render(&page_right, &zoom_right, viewport)?;
+    let pixmap_right = page_render(
+        &page_right,
+        &zoom_right,
+        viewport,
+        right.offset(physical_right),
+    )?;
 
     let surface = match (pixmap_left, pixmap_right) {
         (None, None) => return mview6_error!("empty clip").into(),
@@ -305,8 +427,17 @@ fn open_page(doc: &mupdf::Document, page_no: i32) -> MviewResult<(Page, Rect)> {
     Ok((page, bounds))
 }
 
-fn page_render(page: &Page, zoom: &Zoom, viewport: &RectD) -> MviewResult<Option<mupdf::Pixmap>> {
-    let intersect = zoom.intersection(viewport);
+/// Render the visible part of a page, `offset` is the horizontal position in the page
+/// where the image starts
+fn page_render(
+    page: &Page,
+    zoom: &Zoom,
+    viewport: &RectD,
+    offset: f64,
+) -> MviewResult<Option<mupdf::Pixmap>> {
+    let intersect = zoom
+        .intersection(viewport)
+        .translate(VectorD::new(offset * zoom.scale(), 0.0));
 
     let (x0, y0, x1, y1) = intersect.round();
     let intersect_i = IRect::new(x0, y0, x1, y1);
@@ -324,6 +455,20 @@ fn page_render(page: &Page, zoom: &Zoom, viewport: &RectD) -> MviewResult<Option
     }
 }
 
+/// Write pages `first` to `last` (zero based, inclusive) of the document to a new PDF.
+/// The pages are replayed into the PDF writer, which keeps text as text.
+pub fn export_pages(source: &Path, first: i32, last: i32, target: &Path) -> MviewResult<()> {
+    let document = open(source)?;
+    let mut writer = DocumentWriter::new(&target.to_string_lossy(), "pdf", "")?;
+    for index in first..=last {
+        let page = document.load_page(index)?;
+        let device = writer.begin_page(page.bounds()?)?;
+        page.run(&device, &Matrix::IDENTITY)?;
+        writer.end_page(device)?;
+    }
+    Ok(())
+}
+
 fn open(path: &Path) -> Result<mupdf::Document, mupdf::Error> {
     #[cfg(windows)]
     {
@@ -336,16 +481,16 @@ fn open(path: &Path) -> Result<mupdf::Document, mupdf::Error> {
     }
 }
 
-fn list_pages(filename: &Path) -> MviewResult<(mupdf::Document, Vec<Row>, i32)> {
+fn list_pages(filename: &Path, split: bool) -> MviewResult<(mupdf::Document, Vec<Row>, i32)> {
     let duration = Performance::start();
     let doc = open(filename)?;
-    let page_count = doc.page_count()? as u32;
+    let page_count = logical_page_count(doc.page_count()?, split) as u32;
     let mut result = Vec::new();
     println!("Total pages: {page_count}");
     if page_count > 0 {
         let cat = FileType::Image.into();
         for i in 0..page_count {
-            let page = format!("Page {0:5}", i + 1);
+            let page = LogicalPage::new(i as i32, split).label();
             result.push(Row::new_index(cat, page, 0, 0, i as u64));
         }
         duration.elapsed("mupdf list");
@@ -19,11 +19,22 @@
 
 use image::DynamicImage;
 use pdfium::{PdfiumBitmap, PdfiumDocument, PdfiumPage, PdfiumRenderConfig};
-use std::path::{Path, PathBuf};
+use std::{
+    cell::{Cell, OnceCell},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use crate::{
     backends::{
-        document::{pages, PageMode, Pages},
+        document::{
+            attachments::{attachments, Attachments},
+            is_cover,
+            labels::page_labels,
+            logical_page_count, pages, split_spreads, LogicalPage, PageMode, Pages,
+            COVER_DETECT_PAGES,
+        },
+        extract::enter_entry,
         Backend, ImageParams,
     },
     classification::FileType,
@@ -31,7 +42,7 @@ use crate::{
     error::MviewResult,
     file_view::{
         model::{BackendRef, ItemRef, Reference, Row},
-        Cursor,
+        Cursor, Target,
     },
     image::{draw::draw_error, provider::surface::SurfaceData, view::Zoom},
     mview6_error,
@@ -42,23 +53,36 @@ use crate::{
 pub struct DocPdfium {
     path: PathBuf,
     document: MviewResult<PdfiumDocument>,
-    store: Vec<Row>,
+    /// Pages followed by attachments, filled when first listed
+    store: OnceCell<Vec<Row>>,
+    /// Rows of the pages until the document is listed
+    pages: Cell<Vec<Row>>,
     last_page: i32,
+    split: bool,
+    /// Parsed when first asked for, the render thread only needs them to show one
+    attachments: OnceCell<Arc<Attachments>>,
+    /// Read when first asked for
+    page_labels: OnceCell<Vec<String>>,
 }
 
 impl DocPdfium {
     pub fn new(filename: &Path) -> Self {
-        let (document, store, last_page) = Self::create_store(filename);
+        let split = split_spreads();
+        let (document, pages, last_page) = Self::create_store(filename, split);
         DocPdfium {
             path: filename.into(),
             document,
-            store,
+            store: OnceCell::new(),
+            pages: Cell::new(pages),
             last_page,
+            split,
+            attachments: OnceCell::new(),
+            page_labels: OnceCell::new(),
         }
     }
 
-    fn create_store(filename: &Path) -> (MviewResult<PdfiumDocument>, Vec<Row>, i32) {
-        match list_pages(filename) {
+    fn create_store(filename: &Path, split: bool) -> (MviewResult<PdfiumDocument>, Vec<Row>, i32) {
+        match list_pages(filename, split) {
             Ok((document, store, last_page)) => (Ok(document), store, last_page),
             Err(e) => {
                 eprintln!("ERROR {e:?}");
@@ -67,6 +91,17 @@ impl DocPdfium {
         }
     }
 
+    fn attachments(&self) -> &Attachments {
+        self.attachments.get_or_init(|| attachments(&self.path))
+    }
+
+    /// Rows after the last page are attachments
+    fn attachment_index(&self, index: u64) -> Option<usize> {
+        index
+            .checked_sub(self.last_page as u64 + 1)
+            .map(|index| index as usize)
+    }
+
     pub fn get_thumbnail(src: &Reference) -> MviewResult<DynamicImage> {
         if let (BackendRef::Pdfium(filename), ItemRef::Index(index)) = src.as_tuple() {
             let image = extract_thumb(filename, *index as i32)?;
@@ -88,10 +123,54 @@ impl Backend for DocPdfium {
     }
 
     fn list(&self) -> &Vec<Row> {
-        &self.store
+        self.store.get_or_init(|| {
+            let mut store = self.pages.take();
+            store.extend(self.attachments().rows(self.last_page as u64 + 1));
+            store
+        })
+    }
+
+    fn enter(&self, cursor: &Cursor) -> Option<Box<dyn Backend>> {
+        let attachment = self.attachment_index(cursor.index())?;
+        enter_entry(
+            &self.backend_ref(),
+            cursor,
+            &Target::Index(cursor.index()),
+            || Ok(self.attachments().data(attachment)?.1),
+        )
+    }
+
+    fn page_count(&self) -> Option<u64> {
+        let document = self.document.as_ref().ok()?;
+        Some(document.page_count() as u64)
+    }
+
+    fn page_labels(&self) -> &[String] {
+        self.page_labels.get_or_init(|| page_labels(&self.path))
+    }
+
+    fn first_page_is_cover(&self) -> bool {
+        let Ok(document) = self.document.as_ref() else {
+            return false;
+        };
+        if self.split {
+            return false;
+        }
+        let sizes: Vec<SizeD> = (0..=self.last_page.min(COVER_DETECT_PAGES - 1))
+            .filter_map(|index| page_size_as_rect(&document.page(index).ok()?).ok())
+            .collect();
+        is_cover(&sizes)
+    }
+
+    fn attachment(&self, cursor: &Cursor) -> Option<(String, Vec<u8>)> {
+        let attachment = self.attachment_index(cursor.index())?;
+        self.attachments().data(attachment).ok()
     }
 
     fn content(&self, item: &ItemRef, params: &ImageParams) -> Content {
+        if let Some(attachment) = self.attachment_index(item.idx()) {
+            return self.attachments().content(attachment);
+        }
         (|| {
             let document = self.document.as_ref().map_err(|e| e.to_string())?;
             page_size(
@@ -103,6 +182,7 @@ impl Backend for DocPdfium {
                 item.idx() as i32,
                 self.last_page,
                 params.page_mode,
+                self.split,
             )
             .map_err(|e| e.to_string())
         })()
@@ -130,6 +210,7 @@ impl Backend for DocPdfium {
             item.idx() as i32,
             self.last_page,
             page_mode,
+            self.split,
             zoom,
             viewport,
         )
@@ -143,10 +224,11 @@ fn page_size(
     index: i32,
     last_page: i32,
     mode: &PageMode,
+    split: bool,
 ) -> MviewResult<Content> {
     match pages(index, last_page, mode) {
-        Pages::Single(page) => page_size_single(reference, mode, document, page),
-        Pages::Dual(left) => page_size_dual(reference, mode, document, left),
+        Pages::Single(page) => page_size_single(reference, mode, document, page, split),
+        Pages::Dual(left) => page_size_dual(reference, mode, document, left, split),
     }
 }
 
@@ -155,9 +237,10 @@ fn page_size_single(
     mode: &PageMode,
     document: &PdfiumDocument,
     index: i32,
+    split: bool,
 ) -> MviewResult<Content> {
     let duration = Performance::start();
-    let size = page_size_as_rect(&document.page(index)?)?;
+    let size = logical_page_size(document, LogicalPage::new(index, split))?;
     let image = Content::new_doc(reference, *mode, size);
     duration.elapsed("pdfium single");
     Ok(image)
@@ -168,11 +251,12 @@ fn page_size_dual(
     mode: &PageMode,
     document: &PdfiumDocument,
     index: i32,
+    split: bool,
 ) -> MviewResult<Content> {
     // The right page is scaled so its height is the same as the left page
     let duration = Performance::start();
-    let size_left = page_size_as_rect(&document.page(index)?)?;
-    let size_right = page_size_as_rect(&document.page(index + 1)?)?;
+    let size_left = logical_page_size(document, LogicalPage::new(index, split))?;
+    let size_right = logical_page_size(document, LogicalPage::new(index + 1, split))?;
     let scale_right = size_left.height() / size_right.height();
     let size = SizeD::new(
         size_left.width() + scale_right * size_right.width(),
@@ -185,12 +269,21 @@ fn page_size_dual(
 
 fn extract_thumb(filename: &Path, index: i32) -> MviewResult<DynamicImage> {
     let document = PdfiumDocument::new_from_path(filename, None)?;
-    let page = document.page(index)?;
+    let split = split_spreads();
+    let page_count = logical_page_count(document.page_count(), split);
+    if index >= page_count {
+        return attachments(filename).thumbnail((index - page_count) as usize);
+    }
+    let logical = LogicalPage::new(index, split);
+    let page = document.page(logical.page)?;
+    let page_size = page_size_as_rect(&page)?;
+    let size = logical.size(page_size);
     let zoom = 350.0 / page.height();
-    let width = (page.width() * zoom) as i32;
+    let width = (size.width() as f32 * zoom) as i32;
     let config = PdfiumRenderConfig::new()
         .with_size(width, 350)
-        .with_scale(zoom);
+        .with_scale(zoom)
+        .with_pan(-(logical.offset(page_size) as f32 * zoom), 0.0);
     let bitmap = page.render(&config)?;
     Ok(bitmap.as_rgba8_image()?)
 }
@@ -199,29 +292,43 @@ fn page_size_as_rect(page: &PdfiumPage) -> MviewResult<SizeD> {
     Ok(SizeD::new(page.width() as f64, page.height() as f64))
 }
 
+fn logical_page_size(document: &PdfiumDocument, logical: LogicalPage) -> MviewResult<SizeD> {
+    Ok(logical.size(page_size_as_rect(&document.page(logical.page)?)?))
+}
+
 fn render(
     document: &PdfiumDocument,
     index: i32,
     last_page: i32,
     mode: &PageMode,
+    split: bool,
     zoom: &Zoom,
     viewport: &RectD,
 ) -> MviewResult<SurfaceData> {
     match pages(index, last_page, mode) {
-        Pages::Single(page) => render_single(document, page, zoom, viewport),
-        Pages::Dual(left) => render_dual(document, left, zoom, viewport),
+        Pages::Single(page) => {
+            render_single(document, LogicalPage::new(page, split), zoom, viewport)
+        }
+        Pages::Dual(left) => render_dual(
+            document,
+            LogicalPage::new(left, split),
+            LogicalPage::new(left + 1, split),
+            zoom,
+            viewport,
+        ),
     }
 }
 
 fn render_single(
     document: &PdfiumDocument,
-    index: i32,
+    logical: LogicalPage,
     zoom: &Zoom,
     viewport: &RectD,
 ) -> MviewResult<SurfaceData> {
     let duration = Performance::start();
-    let page = document.page(index)?;
-    let surface = if let Some(bitmap) = page_render(&page, zoom, viewport)? {
+    let page = document.page(logical.page)?;
+    let offset = logical.offset(page_size_as_rect(&page)?);
+    let surface = if let Some(bitmap) = page_render(&page, zoom, viewport, offset)? {
         Ok(SurfaceData::from_bgra8(
             bitmap.width() as u32,
             bitmap.height() as u32,
@@ -236,26 +343,34 @@ fn render_single(
 
 fn render_dual(
     document: &PdfiumDocument,
-    index: i32,
+    left: LogicalPage,
+    right: LogicalPage,
     zoom: &Zoom,
     viewport: &RectD,
 ) -> MviewResult<SurfaceData> {
     let duration = Performance::start();
 
-    let page_left = document.page(index)?;
-    let size_left = page_size_as_rect(&page_left)?;
+    let page_left = document.page(left.page)?;
+    let physical_left = page_size_as_rect(&page_left)?;
+    let size_left = left.size(physical_left);
     let mut zoom_left = zoom.clone();
     zoom_left.set_image_size(size_left);
-    let pixmap_left = page_render(&page_left, &zoom_left, viewport)?;
+    let pixmap_left = page_render(&page_left, &zoom_left, viewport, left.offset(physical_left))?;
 
-    let page_right = document.page(index + 1)?;
-    let size_right = page_size_as_rect(&page_right)?;
+    let page_right = document.page(right.page)?;
+    let physical_right = page_size_as_rect(&page_right)?;
+    let size_right = right.size(physical_right);
     let scale_right = size_left.height() / size_right.height();
     let mut zoom_right = zoom.clone();
     zoom_right.set_image_size(size_right);
     zoom_right.set_zoom_factor(zoom.scale() * scale_right);
     zoom_right.set_origin(zoom.image_to_screen(&VectorD::new(size_left.width(), 0.0)));
-    let pixmap_right = page_render(&page_right, &zoom_right, viewport)?;
+    let pixmap_right = page_render(
+        &page_right,
+        &zoom_right,
+        viewport,
+        right.offset(physical_right),
+    )?;
 
     let surface = match (pixmap_left, pixmap_right) {
         (None, None) => return mview6_error!("empty clip").into(),
@@ -288,10 +403,13 @@ fn render_dual(
     Ok(surface)
 }
 
+/// Render the visible part of a page, `offset` is the horizontal position in the page
+/// where the image starts
 fn page_render(
     page: &PdfiumPage,
     zoom: &Zoom,
     viewport: &RectD,
+    offset: f64,
 ) -> MviewResult<Option<PdfiumBitmap>> {
     let intersection = zoom.intersection(viewport);
     if intersection.is_empty() {
@@ -302,21 +420,24 @@ fn page_render(
         let config = PdfiumRenderConfig::new()
             .with_size(width, height)
             .with_scale(zoom.scale() as f32)
-            .with_pan(-intersection.x0 as f32, -intersection.y0 as f32);
+            .with_pan(
+                -(intersection.x0 + offset * zoom.scale()) as f32,
+                -intersection.y0 as f32,
+            );
         Ok(Some(page.render(&config)?))
     }
 }
 
-fn list_pages(filename: &Path) -> MviewResult<(PdfiumDocument, Vec<Row>, i32)> {
+fn list_pages(filename: &Path, split: bool) -> MviewResult<(PdfiumDocument, Vec<Row>, i32)> {
     let duration = Performance::start();
     let document = PdfiumDocument::new_from_path(filename, None)?;
-    let page_count = document.page_count();
+    let page_count = logical_page_count(document.page_count(), split);
     let mut result = Vec::new();
     println!("Total pages: {page_count}");
     if page_count > 0 {
         let cat = FileType::Image.into();
         for i in 0..page_count {
-            let page = format!("Page {0:5}", i + 1);
+            let page = LogicalPage::new(i, split).label();
             result.push(Row::new_index(cat, page, 0, 0, i as u64));
         }
         duration.elapsed("pdfium list");
@@ -0,0 +1,243 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Managed area for archive entries that have to be extracted to disk, e.g. videos
+//! played by an external player or archives inside archives.
+//!
+//! Extracted files are reference counted: a file is removed when the last handle is
+//! dropped. Each session uses its own directory, which is removed on exit. Directories
+//! left behind by a crashed session are removed on the next start. Other temporary
+//! files, like images dropped from other applications, use a folder in the same session
+//! directory.
+
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    process::{self, Child},
+    sync::{Arc, Mutex, OnceLock, Weak},
+    thread,
+};
+
+use sha2::{Digest, Sha256};
+
+use crate::{
+    backends::{filesystem::launch_video, Backend},
+    cache::CacheKind,
+    classification::FileType,
+    error::MviewResult,
+    file_view::{model::BackendRef, Cursor, Target},
+};
+
+#[derive(Debug)]
+struct Extracted {
+    path: PathBuf,
+    /// Archive the file was extracted from
+    source: BackendRef,
+    /// Entry of the archive
+    item: Target,
+}
+
+impl Drop for Extracted {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+        if let Some(dir) = self.path.parent() {
+            let _ = fs::remove_dir(dir);
+        }
+        let mut registry = registry().lock().unwrap();
+        // The entry may already belong to a new extraction of the same file
+        if registry
+            .get(&self.path)
+            .is_some_and(|weak| weak.strong_count() == 0)
+        {
+            registry.remove(&self.path);
+        }
+    }
+}
+
+/// Handle to an extracted file, the file is kept as long as there are handles
+#[derive(Debug, Clone)]
+pub struct ExtractedFile(Arc<Extracted>);
+
+impl ExtractedFile {
+    pub fn path(&self) -> &Path {
+        &self.0.path
+    }
+
+    /// Archive and entry the file was extracted from
+    pub fn source(&self) -> (BackendRef, Target) {
+        (self.0.source.clone(), self.0.item.clone())
+    }
+}
+
+fn registry() -> &'static Mutex<HashMap<PathBuf, Weak<Extracted>>> {
+    static REGISTRY: OnceLock<Mutex<HashMap<PathBuf, Weak<Extracted>>>> = OnceLock::new();
+    REGISTRY.get_or_init(Default::default)
+}
+
+fn session_dir() -> PathBuf {
+    CacheKind::Extraction
+        .directory()
+        .join(process::id().to_string())
+}
+
+/// Folder for temporary files of this session, removed on exit like the extracted files
+pub fn session_folder(name: &str) -> io::Result<PathBuf> {
+    CacheKind::Extraction.create_directory()?;
+    let folder = session_dir().join(name);
+    fs::create_dir_all(&folder)?;
+    Ok(folder)
+}
+
+/// Extract an archive entry (provided by `data`), or share the already extracted file
+pub fn extract(
+    source: &BackendRef,
+    item: &Target,
+    name: &str,
+    data: impl FnOnce() -> MviewResult<Vec<u8>>,
+) -> MviewResult<ExtractedFile> {
+    let mut hasher = Sha256::new();
+    hasher.update(source.path().as_bytes());
+    hasher.update(format!("{item:?}").as_bytes());
+    let key = format!("{:x}", hasher.finalize());
+    let file_name = Path::new(name).file_name().unwrap_or_default();
+    let path = session_dir().join(&key[..16]).join(file_name);
+
+    if let Some(extracted) = lookup(&path) {
+        return Ok(extracted);
+    }
+
+    let bytes = data()?;
+    CacheKind::Extraction.create_directory()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(&path, bytes)?;
+    let extracted = Arc::new(Extracted {
+        path: path.clone(),
+        source: source.clone(),
+        item: item.clone(),
+    });
+    registry()
+        .lock()
+        .unwrap()
+        .insert(path, Arc::downgrade(&extracted));
+    Ok(ExtractedFile(extracted))
+}
+
+/// Handle to an extracted file that is still in use
+pub fn lookup(path: &Path) -> Option<ExtractedFile> {
+    registry()
+        .lock()
+        .unwrap()
+        .get(path)
+        .and_then(Weak::upgrade)
+        .map(ExtractedFile)
+}
+
+/// Keep the file until the external program using it has finished
+fn keep_while_running(file: ExtractedFile, mut child: Child) {
+    thread::spawn(move || {
+        let _ = child.wait();
+        drop(file);
+    });
+}
+
+/// Keep the file until the end of the session, for backends that do not hold a handle
+fn keep_for_session(file: ExtractedFile) {
+    static SESSION: Mutex<Vec<ExtractedFile>> = Mutex::new(Vec::new());
+    SESSION.lock().unwrap().push(file);
+}
+
+/// Open an archive entry that cannot be shown inline: play a video or enter a nested
+/// archive or document
+pub fn enter_entry(
+    source: &BackendRef,
+    cursor: &Cursor,
+    item: &Target,
+    data: impl FnOnce() -> MviewResult<Vec<u8>>,
+) -> Option<Box<dyn Backend>> {
+    let content = cursor.content();
+    if !matches!(
+        content,
+        FileType::Video | FileType::Archive | FileType::Document
+    ) {
+        return None;
+    }
+    let extracted = match extract(source, item, &cursor.name(), data) {
+        Ok(extracted) => extracted,
+        Err(e) => {
+            eprintln!("Failed to extract {}: {e}", cursor.name());
+            return None;
+        }
+    };
+    match content {
+        FileType::Video => {
+            if let Some(child) = launch_video(extracted.path()) {
+                keep_while_running(extracted, child);
+            }
+            None
+        }
+        FileType::Archive => {
+            // The archive backend takes its own handle through `lookup`
+            Some(<dyn Backend>::new_from_path(extracted.path()))
+        }
+        _ => {
+            let backend = <dyn Backend>::new_from_path(extracted.path());
+            keep_for_session(extracted);
+            Some(backend)
+        }
+    }
+}
+
+/// Remove the files of this session
+pub fn cleanup() {
+    let _ = fs::remove_dir_all(session_dir());
+}
+
+/// Remove the files left behind by sessions that are no longer running
+pub fn recover() {
+    let Ok(entries) = fs::read_dir(CacheKind::Extraction.directory()) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let Some(pid) = entry
+            .file_name()
+            .to_str()
+            .and_then(|n| n.parse::<u32>().ok())
+        else {
+            continue;
+        };
+        if pid != process::id() && !is_running(pid) {
+            println!("Removing files extracted by previous session {pid}");
+            let _ = fs::remove_dir_all(entry.path());
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn is_running(pid: u32) -> bool {
+    Path::new("/proc").join(pid.to_string()).exists()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn is_running(_pid: u32) -> bool {
+    // Without a way to check, only clean up at the end of each session
+    true
+}
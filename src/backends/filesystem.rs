@@ -19,24 +19,31 @@
 
 use super::{Content, ImageParams};
 use crate::{
+    backends::archive_zip::{is_office_document, office_thumbnail},
+    cache::CacheKind,
     classification::{FileClassification, FileType, Preference},
+    config::DirectoryConfig,
     content::loader::ContentLoader,
     error::MviewResult,
     file_view::{
         model::{BackendRef, ItemRef, Reference, Row},
         Cursor, Direction,
     },
-    image::provider::{image_rs::RsImageLoader, internal::InternalImageLoader},
+    image::{
+        model,
+        provider::{image_rs::RsImageLoader, internal::InternalImageLoader},
+    },
     mview6_error,
     util::path_to_filename,
 };
 use image::DynamicImage;
 use regex::Regex;
+use sha2::{Digest, Sha256};
 use std::{
     fs::{metadata, read_dir, rename},
     io::{self},
     path::{Path, PathBuf},
-    process::{Command, Stdio},
+    process::{Child, Command, Stdio},
     time::UNIX_EPOCH,
 };
 
@@ -45,6 +52,7 @@ use super::{Backend, Target};
 pub struct FileSystem {
     directory: PathBuf,
     store: Vec<Row>,
+    config: DirectoryConfig,
 }
 
 impl FileSystem {
@@ -52,6 +60,7 @@ impl FileSystem {
         FileSystem {
             directory: directory.into(),
             store: Self::read_directory(directory).unwrap_or_default(),
+            config: DirectoryConfig::load(directory),
         }
     }
 
@@ -83,27 +92,37 @@ impl FileSystem {
             let size = metadata.len();
 
             let cat = FileClassification::determine(&path, metadata.is_dir());
-
             result.push(Row::new(cat, filename.to_string(), size, modified));
         }
         Ok(result)
     }
 
+    /// Full resolution image, for a preview larger than the thumbnail
+    pub fn get_preview(src: &Reference) -> MviewResult<DynamicImage> {
+        if let (BackendRef::FileSystem(directory), ItemRef::String(name)) = src.as_tuple() {
+            RsImageLoader::dynimg_from_file(&directory.join(name))
+        } else {
+            mview6_error!("invalid reference").into()
+        }
+    }
+
     pub fn get_thumbnail(src: &Reference) -> MviewResult<DynamicImage> {
         if let (BackendRef::FileSystem(directory), ItemRef::String(name)) = src.as_tuple() {
             let filename = directory.join(name);
-            if let Some(image) = InternalImageLoader::thumb_from_file(&filename) {
+            if model::is_model(&filename) {
+                model::thumbnail(&filename)
+            } else if is_office_document(&filename) {
+                RsImageLoader::dynimg_from_memory(&office_thumbnail(&filename)?)
+            } else if let Some(image) = InternalImageLoader::thumb_from_file(&filename) {
                 Ok(image)
             } else {
-                let thumb_filename = name.replace(".lo.", ".").replace(".hi.", ".") + ".mthumb";
-                let thumb_path = directory.join(".mview").join(thumb_filename);
-                if Path::new(&thumb_path).exists() {
+                let thumb_path = thumbnail_cache_path(&filename);
+                if thumb_path.exists() {
                     RsImageLoader::dynimg_from_file(&thumb_path)
                 } else {
-                    let path = directory.join(name);
-                    let image = RsImageLoader::dynimg_from_file(&path)?;
+                    let image = RsImageLoader::dynimg_from_file(&filename)?;
                     let image = image.resize(175, 175, image::imageops::FilterType::Lanczos3);
-                    // ImageSaver::save_thumbnail(&src.directory, &thumb_filename, &image);
+                    // ImageSaver::save_thumbnail(&thumb_path, &image);
                     Ok(image)
                 }
             }
@@ -113,6 +132,41 @@ impl FileSystem {
     }
 }
 
+/// Location of the cached thumbnail of a file, shared by its `.lo.` and `.hi.` variants
+fn thumbnail_cache_path(filename: &Path) -> PathBuf {
+    let name = path_to_filename(filename)
+        .replace(".lo.", ".")
+        .replace(".hi.", ".");
+    let mut hasher = Sha256::new();
+    hasher.update(filename.with_file_name(name).to_string_lossy().as_bytes());
+    let sha256sum = format!("{:x}", hasher.finalize());
+    CacheKind::Thumbnails.path(&format!("{sha256sum}.mthumb"))
+}
+
+/// Remove the cached thumbnail of a (modified) file
+pub fn invalidate_thumbnail(filename: &Path) {
+    let _ = std::fs::remove_file(thumbnail_cache_path(filename));
+}
+
+/// Play a video with the external player
+pub fn launch_video(path: &Path) -> Option<Child> {
+    println!("Launch video external {}", path.to_string_lossy());
+    let child = Command::new("mpv")
+        .arg(path)
+        .arg("--fullscreen=yes")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    match child {
+        Ok(child) => Some(child),
+        Err(error) => {
+            eprintln!("Failed to launch mpv {:?}", error);
+            None
+        }
+    }
+}
+
 impl Backend for FileSystem {
     fn class_name(&self) -> &str {
         "FileSystem"
@@ -129,18 +183,7 @@ impl Backend for FileSystem {
     fn enter(&self, cursor: &Cursor) -> Option<Box<dyn Backend>> {
         let content = cursor.content();
         if content == FileType::Video {
-            let full_path = self.directory.join(cursor.name());
-            println!("Launch video external {}", full_path.to_string_lossy());
-            let child = Command::new("mpv")
-                .arg(full_path)
-                .arg("--fullscreen=yes")
-                .stdin(Stdio::null())
-                .stdout(Stdio::null())
-                .stderr(Stdio::null())
-                .spawn();
-            if let Err(error) = child {
-                eprintln!("Failed to launch mpv {:?}", error);
-            };
+            launch_video(&self.directory.join(cursor.name()));
             None
         } else if content == FileType::Folder
             || content == FileType::Archive
@@ -243,8 +286,13 @@ impl Backend for FileSystem {
         Some(Box::new(FileSystem {
             directory: directory.into(),
             store: Self::read_directory(directory).unwrap_or_default(),
+            config: DirectoryConfig::load(directory),
         }))
     }
+
+    fn directory_config(&self) -> Option<&DirectoryConfig> {
+        Some(&self.config)
+    }
 }
 
 // fn _read_bytes(path: &Path) -> MviewResult<Vec<u8>> {
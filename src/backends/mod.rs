@@ -22,14 +22,18 @@ use std::{
     path::{Path, PathBuf},
 };
 
+pub use archive_mail::MailArchive;
 pub use archive_mar::MarArchive;
 pub use archive_rar::RarArchive;
 pub use archive_zip::ZipArchive;
 pub use async_channel::Sender;
 pub use bookmarks::Bookmarks;
+pub use collection::Collection;
 pub use filesystem::FileSystem;
 pub use none::NoneBackend;
+pub use staging::Staging;
 pub use thumbnail::{Message, Thumbnail};
+pub use usage::MostViewed;
 
 #[cfg(feature = "mupdf")]
 use crate::backends::document::{mupdf::DocMuPdf, PdfEngine};
@@ -39,6 +43,11 @@ use crate::{
         document::{pdf_engine, pdfium::DocPdfium, PageMode},
         thumbnail::model::TParent,
     },
+    classification::{
+        detect,
+        file_formats::{ArchiveFormat, DocumentFormat, FileFormat},
+    },
+    config::DirectoryConfig,
     content::Content,
     file_view::{
         model::{BackendRef, ItemRef, Reference, Row},
@@ -49,14 +58,20 @@ use crate::{
     util::path_to_filename,
 };
 
+pub mod archive_mail;
 mod archive_mar;
-mod archive_rar;
-mod archive_zip;
+pub mod archive_rar;
+pub mod archive_zip;
 mod bookmarks;
+pub mod collection;
 pub mod document;
+pub mod extract;
 pub mod filesystem;
 mod none;
+pub mod staging;
 pub mod thumbnail;
+pub mod usage;
+mod virtual_list;
 
 pub struct ImageParams<'a> {
     pub tn_sender: Option<&'a Sender<Message>>,
@@ -114,10 +129,34 @@ pub trait Backend {
             store: Column::empty_store(),
         }
     }
+    // Only implemented by document backends, dummy here
+    fn page_count(&self) -> Option<u64> {
+        None
+    }
+    // Only implemented by document backends, dummy here
+    fn page_labels(&self) -> &[String] {
+        &[]
+    }
+    // Only implemented by document backends, dummy here
+    fn first_page_is_cover(&self) -> bool {
+        false
+    }
+    // Only implemented by document backends, dummy here
+    fn attachment(&self, cursor: &Cursor) -> Option<(String, Vec<u8>)> {
+        None
+    }
+    // Only implemented by collection backend, dummy here
+    fn resolve(&self, cursor: &Cursor) -> Option<Reference> {
+        None
+    }
     // Only implemented by filesystem backend, dummy here
     fn reload(&self) -> Option<Box<dyn Backend>> {
         None
     }
+    // Only implemented by filesystem backend, dummy here
+    fn directory_config(&self) -> Option<&DirectoryConfig> {
+        None
+    }
     fn normalized_path(&self) -> PathBuf {
         let path = self.path();
         #[cfg(windows)]
@@ -153,22 +192,21 @@ impl Default for Box<dyn Backend> {
 
 impl dyn Backend {
     pub fn new_from_path(filename: &Path) -> Box<dyn Backend> {
-        let ext = filename
-            .extension()
-            .map(|ext| ext.to_str().unwrap_or_default());
-
-        match ext {
-            Some("zip") => Box::new(ZipArchive::new(filename)),
-            Some("rar") => Box::new(RarArchive::new(filename)),
-            Some("mar") => Box::new(MarArchive::new(filename)),
-            Some("pdf") => match pdf_engine() {
+        match detect::format_from_path(filename) {
+            FileFormat::Archive(ArchiveFormat::Zip | ArchiveFormat::Office) => {
+                Box::new(ZipArchive::new(filename))
+            }
+            FileFormat::Archive(ArchiveFormat::Rar) => Box::new(RarArchive::new(filename)),
+            FileFormat::Archive(ArchiveFormat::Mar) => Box::new(MarArchive::new(filename)),
+            FileFormat::Archive(ArchiveFormat::Mail) => Box::new(MailArchive::new(filename)),
+            FileFormat::Document(DocumentFormat::Pdf) => match pdf_engine() {
                 #[cfg(feature = "mupdf")]
                 PdfEngine::MuPdf => Box::new(DocMuPdf::new(filename)),
                 _ => Box::new(DocPdfium::new(filename)),
             },
             #[cfg(feature = "mupdf")]
-            Some("epub") => Box::new(DocMuPdf::new(filename)),
-            Some(_) | None => Box::new(FileSystem::new(filename)),
+            FileFormat::Document(DocumentFormat::Epub) => Box::new(DocMuPdf::new(filename)),
+            _ => Box::new(FileSystem::new(filename)),
         }
     }
 
@@ -176,6 +214,7 @@ impl dyn Backend {
         match reference {
             BackendRef::FileSystem(path_buf) => Box::new(FileSystem::new(path_buf)),
             BackendRef::MarArchive(path_buf) => Box::new(MarArchive::new(path_buf)),
+            BackendRef::MailArchive(path_buf) => Box::new(MailArchive::new(path_buf)),
             BackendRef::RarArchive(path_buf) => Box::new(RarArchive::new(path_buf)),
             BackendRef::ZipArchive(path_buf) => Box::new(ZipArchive::new(path_buf)),
             #[cfg(feature = "mupdf")]
@@ -192,6 +231,7 @@ impl dyn Backend {
         match reference {
             BackendRef::FileSystem(path_buf) => Box::new(FileSystem::new(path_buf)),
             BackendRef::MarArchive(path_buf) => Box::new(MarArchive::new(path_buf)),
+            BackendRef::MailArchive(path_buf) => Box::new(MailArchive::new(path_buf)),
             BackendRef::RarArchive(path_buf) => Box::new(RarArchive::new(path_buf)),
             BackendRef::ZipArchive(path_buf) => Box::new(ZipArchive::new(path_buf)),
             #[cfg(feature = "mupdf")]
@@ -208,6 +248,22 @@ impl dyn Backend {
         Box::new(Bookmarks::new(parent_backend, parent_target))
     }
 
+    pub fn collection(parent_backend: Box<dyn Backend>, parent_target: Target) -> Box<dyn Backend> {
+        Box::new(Collection::new(parent_backend, parent_target))
+    }
+
+    pub fn most_viewed(
+        entries: Vec<usage::FileUsage>,
+        parent_backend: Box<dyn Backend>,
+        parent_target: Target,
+    ) -> Box<dyn Backend> {
+        Box::new(MostViewed::new(entries, parent_backend, parent_target))
+    }
+
+    pub fn staging(parent_backend: Box<dyn Backend>, parent_target: Target) -> Box<dyn Backend> {
+        Box::new(Staging::new(parent_backend, parent_target))
+    }
+
     pub fn thumbnail(thumbnail: Thumbnail) -> Box<dyn Backend> {
         Box::new(thumbnail)
     }
@@ -241,6 +297,18 @@ impl dyn Backend {
         matches!(self.backend_ref(), BackendRef::Bookmarks)
     }
 
+    pub fn is_collection(&self) -> bool {
+        matches!(self.backend_ref(), BackendRef::Collection)
+    }
+
+    pub fn is_most_viewed(&self) -> bool {
+        matches!(self.backend_ref(), BackendRef::MostViewed)
+    }
+
+    pub fn is_staging(&self) -> bool {
+        matches!(self.backend_ref(), BackendRef::Staging)
+    }
+
     pub fn is_thumbnail(&self) -> bool {
         matches!(self.backend_ref(), BackendRef::Thumbnail)
     }
@@ -252,6 +320,19 @@ impl dyn Backend {
         )
     }
 
+    /// Archives and documents
+    pub fn is_container(&self) -> bool {
+        matches!(
+            self.backend_ref(),
+            BackendRef::MarArchive(_)
+                | BackendRef::MailArchive(_)
+                | BackendRef::RarArchive(_)
+                | BackendRef::ZipArchive(_)
+                | BackendRef::Mupdf(_)
+                | BackendRef::Pdfium(_)
+        )
+    }
+
     pub fn is_none(&self) -> bool {
         matches!(self.backend_ref(), BackendRef::None)
     }
@@ -259,7 +340,10 @@ impl dyn Backend {
     pub fn can_be_sorted(&self) -> bool {
         !matches!(
             self.backend_ref(),
-            BackendRef::Pdfium(_) | BackendRef::Mupdf(_) | BackendRef::Thumbnail
+            BackendRef::Pdfium(_)
+                | BackendRef::Mupdf(_)
+                | BackendRef::Thumbnail
+                | BackendRef::Collection
         )
     }
 }
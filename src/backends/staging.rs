@@ -0,0 +1,259 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Staging area for deleted files (review trash). Deleting moves a file, with its XMP
+//! sidecar, into a hidden folder next to it, which is instant and can be undone until the
+//! staging area is emptied.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::{virtual_list::VirtualList, Backend, Content, ImageParams, Target};
+use crate::{
+    classification::FileClassification,
+    config::JsonStore,
+    content::loader::ContentLoader,
+    error::MviewResult,
+    file_view::{
+        model::{BackendRef, ItemRef, Reference, Row},
+        Cursor,
+    },
+    metadata::keywords::{sidecar_path, with_xmp},
+    mview6_error,
+    util::{path_to_directory, path_to_filename},
+};
+
+/// Hidden folder, next to the deleted file, holding it until the staging area is emptied
+pub const STAGING_FOLDER: &str = ".mview-staged";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StagedFile {
+    pub original: PathBuf,
+    pub staged: PathBuf,
+    /// Time of deletion, seconds since the epoch
+    pub time: u64,
+    /// Original location of the sidecar that was staged along with the file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sidecar: Option<PathBuf>,
+}
+
+impl StagedFile {
+    pub fn name(&self) -> String {
+        path_to_filename(&self.original)
+    }
+
+    fn staged_sidecar(&self) -> PathBuf {
+        with_xmp(&self.staged)
+    }
+}
+
+const STAGING: JsonStore = JsonStore::new("staging.json");
+
+pub fn load_staging() -> Vec<StagedFile> {
+    STAGING.load()
+}
+
+pub fn save_staging(entries: &[StagedFile]) -> io::Result<()> {
+    STAGING.save(entries)
+}
+
+/// Unused location for `path` in the staging folder next to it. A file with the same name
+/// that was deleted before is not overwritten.
+pub fn staged_path(path: &Path) -> PathBuf {
+    let folder = path.parent().unwrap_or(Path::new("")).join(STAGING_FOLDER);
+    let name = path_to_filename(path);
+    let mut staged = folder.join(&name);
+    let mut counter = 1;
+    while staged.exists() || with_xmp(&staged).exists() {
+        staged = folder.join(format!("{counter}-{name}"));
+        counter += 1;
+    }
+    staged
+}
+
+/// Move `path` into the staging area
+pub fn stage(path: &Path) -> MviewResult<StagedFile> {
+    let staged = staged_path(path);
+    if let Some(folder) = staged.parent() {
+        fs::create_dir_all(folder)?;
+    }
+    fs::rename(path, &staged)?;
+    let sidecar = sidecar_path(path);
+    let sidecar =
+        (sidecar.exists() && fs::rename(&sidecar, with_xmp(&staged)).is_ok()).then_some(sidecar);
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_secs())
+        .unwrap_or_default();
+    Ok(StagedFile {
+        original: path.to_path_buf(),
+        staged,
+        time,
+        sidecar,
+    })
+}
+
+/// Move the file back to where it was deleted from
+pub fn restore(entry: &StagedFile) -> MviewResult<()> {
+    for original in [Some(&entry.original), entry.sidecar.as_ref()]
+        .into_iter()
+        .flatten()
+    {
+        if original.exists() {
+            return mview6_error!(format!("{} already exists", original.display())).into();
+        }
+    }
+    fs::rename(&entry.staged, &entry.original)?;
+    if let Some(sidecar) = &entry.sidecar {
+        fs::rename(entry.staged_sidecar(), sidecar)?;
+    }
+    remove_empty_folder(&entry.staged);
+    Ok(())
+}
+
+/// Delete the file for good
+pub fn purge(entry: &StagedFile) -> MviewResult<()> {
+    for staged in [entry.staged.clone(), entry.staged_sidecar()] {
+        match fs::remove_file(&staged) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e.into()),
+            _ => (),
+        }
+    }
+    remove_empty_folder(&entry.staged);
+    Ok(())
+}
+
+fn remove_empty_folder(staged: &Path) {
+    if let Some(folder) = staged.parent() {
+        // Fails, as intended, when other deleted files are still staged
+        let _ = fs::remove_dir(folder);
+    }
+}
+
+pub struct Staging {
+    list: VirtualList<StagedFile>,
+}
+
+impl Staging {
+    pub fn new(parent_backend: Box<dyn Backend>, parent_target: Target) -> Self {
+        let row = |index, entry: &StagedFile| {
+            let size = fs::metadata(&entry.staged)
+                .map(|metadata| metadata.len())
+                .unwrap_or_default();
+            Row::new_folder_index(
+                FileClassification::determine(&entry.original, false),
+                entry.name(),
+                size,
+                entry.time,
+                index,
+                path_to_directory(&entry.original),
+            )
+        };
+        Staging {
+            list: VirtualList::new(load_staging(), row, parent_backend, parent_target),
+        }
+    }
+}
+
+impl Backend for Staging {
+    fn class_name(&self) -> &str {
+        "Staging"
+    }
+
+    fn path(&self) -> PathBuf {
+        Path::new("staging").into()
+    }
+
+    fn list(&self) -> &Vec<Row> {
+        self.list.list()
+    }
+
+    fn leave(&self) -> Option<(Box<dyn Backend>, Target)> {
+        self.list.leave()
+    }
+
+    fn content(&self, item: &ItemRef, _: &ImageParams) -> Content {
+        match self.list.entry(item) {
+            Some(entry) => ContentLoader::content_from_file(&entry.staged),
+            None => Content::default(),
+        }
+    }
+
+    fn resolve(&self, cursor: &Cursor) -> Option<Reference> {
+        let entry = self.list.current(cursor)?;
+        Some(Reference {
+            backend: BackendRef::FileSystem(entry.staged.parent()?.to_path_buf()),
+            item: ItemRef::String(path_to_filename(&entry.staged)),
+        })
+    }
+
+    fn backend_ref(&self) -> BackendRef {
+        BackendRef::Staging
+    }
+
+    fn item_ref(&self, cursor: &Cursor) -> ItemRef {
+        ItemRef::Index(cursor.index())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+
+    #[test]
+    fn stage_and_restore() {
+        let directory = TestDir::new("staging");
+        let directory = directory.path();
+        let path = directory.join("a.jpg");
+        let sidecar = directory.join("a.jpg.xmp");
+
+        fs::write(&path, "first").unwrap();
+        fs::write(&sidecar, "first sidecar").unwrap();
+        let first = stage(&path).unwrap();
+        assert!(!path.exists());
+        assert!(!sidecar.exists());
+        assert_eq!(first.staged, directory.join(STAGING_FOLDER).join("a.jpg"));
+        assert_eq!(first.sidecar.as_ref(), Some(&sidecar));
+
+        fs::write(&path, "second").unwrap();
+        fs::write(&sidecar, "second sidecar").unwrap();
+        let second = stage(&path).unwrap();
+        assert_eq!(
+            second.staged,
+            directory.join(STAGING_FOLDER).join("1-a.jpg")
+        );
+        assert!(directory.join(STAGING_FOLDER).join("1-a.jpg.xmp").exists());
+
+        fs::write(&path, "third").unwrap();
+        assert!(restore(&first).is_err(), "must not overwrite");
+        fs::remove_file(&path).unwrap();
+
+        purge(&second).unwrap();
+        restore(&first).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "first");
+        assert_eq!(fs::read_to_string(&sidecar).unwrap(), "first sidecar");
+        assert!(!directory.join(STAGING_FOLDER).exists());
+    }
+}
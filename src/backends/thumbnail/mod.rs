@@ -34,6 +34,7 @@ use crate::{
         Cursor,
     },
     image::draw::thumbnail_sheet,
+    metadata::{keywords::read_keywords, label::file_label},
     rect::PointD,
 };
 use gtk4::{prelude::TreeModelExt, Allocation, ListStore};
@@ -44,6 +45,17 @@ const FOOTER: i32 = 50;
 const MARGIN: i32 = 15;
 const MIN_SEPARATOR: i32 = 5;
 
+/// Path of a file in a folder, its keywords and label are read when its thumbnail is shown
+fn file_path(entry: &Entry) -> Option<PathBuf> {
+    if entry.category.file_type == FileType::Folder {
+        return None;
+    }
+    match entry.reference.as_tuple() {
+        (BackendRef::FileSystem(directory), ItemRef::String(name)) => Some(directory.join(name)),
+        _ => None,
+    }
+}
+
 #[derive(Debug)]
 pub struct Thumbnail {
     dim: SheetDimensions,
@@ -151,7 +163,9 @@ impl Thumbnail {
                     let source = Entry {
                         category: FileClassification::new(cursor.content(), cursor.preference()),
                         name: cursor.name(),
-                        reference: backend.reference(&cursor),
+                        reference: backend
+                            .resolve(&cursor)
+                            .unwrap_or_else(|| backend.reference(&cursor)),
                     };
                     let x = self.dim.offset_x + col * (self.dim.size + self.dim.separator_x);
                     let y = self.dim.offset_y + row * (self.dim.size + self.dim.separator_y);
@@ -160,6 +174,11 @@ impl Thumbnail {
                         id,
                         position: TRect::new_i32(x, y, self.dim.size, self.dim.size),
                         entry: source.clone(),
+                        keywords: file_path(&source)
+                            .map(|path| read_keywords(&path))
+                            .unwrap_or_default(),
+                        progress: cursor.progress(),
+                        label: file_path(&source).and_then(|path| file_label(&path)),
                     };
                     let task = TTask::new(id, self.dim.size as u32, x, y, source, annotation);
                     res.push(task);
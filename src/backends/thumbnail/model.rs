@@ -17,15 +17,23 @@
 // STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
 // OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::time::SystemTime;
+use std::{
+    collections::VecDeque,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
 
 use gtk4::ListStore;
 use image::DynamicImage;
 
 use crate::{
     backends::Backend,
-    file_view::{model::Entry, Target},
+    file_view::{
+        model::{BackendRef, Entry, ItemRef, Progress},
+        Target,
+    },
     image::colors::Color,
+    metadata::label::ColorLabel,
     rect::PointD,
 };
 
@@ -45,6 +53,8 @@ pub struct TCommand {
     pub todo: usize,
     pub last_update: f64,
     pub dim: SheetDimensions,
+    /// Tasks to render again, after the file they show was modified
+    pub redo: VecDeque<usize>,
 }
 
 impl Default for TCommand {
@@ -57,6 +67,7 @@ impl Default for TCommand {
             todo: 0,
             last_update: 0.0,
             dim: Default::default(),
+            redo: Default::default(),
         }
     }
 }
@@ -72,6 +83,7 @@ impl TCommand {
             todo,
             last_update: 0.0,
             dim,
+            redo: Default::default(),
         }
     }
 
@@ -86,6 +98,20 @@ impl TCommand {
     pub fn needs_work(&self) -> bool {
         self.todo != 0
     }
+
+    /// Queue the tasks that show `path` to be rendered again, returns how many
+    pub fn refresh(&mut self, path: &Path) -> usize {
+        let affected: Vec<usize> = self
+            .tasks
+            .iter()
+            .enumerate()
+            .filter(|(_, task)| task.shows(path))
+            .map(|(index, _)| index)
+            .collect();
+        self.todo += affected.len();
+        self.redo.extend(&affected);
+        affected.len()
+    }
 }
 
 #[allow(dead_code)]
@@ -108,6 +134,19 @@ impl TTask {
             annotation,
         }
     }
+
+    /// The thumbnail is made from `path`, either the file itself or the archive or
+    /// document containing it
+    fn shows(&self, path: &Path) -> bool {
+        let reference = &self.source.reference;
+        match (&reference.backend, &reference.item) {
+            (BackendRef::FileSystem(directory), ItemRef::String(name)) => {
+                directory.join(name) == path
+            }
+            (BackendRef::FileSystem(_), _) => false,
+            (backend, _) => !backend.is_none() && Path::new(backend.path()) == path,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -162,6 +201,28 @@ impl TResult {
 pub enum Message {
     Command(Box<TCommand>),
     Result(Box<TResult>),
+    /// File that was modified on disk
+    Refresh(PathBuf),
+    /// Sharper version of a thumbnail wanted for the hovered cell: image id, task id and
+    /// size
+    Hover(u32, i32, u32),
+    HoverResult(Box<TResult>),
+}
+
+/// Largest hover preview, in pixels
+pub const MAX_PREVIEW_SIZE: u32 = 1024;
+
+/// Size of the hover preview for a thumbnail cell of `size` that is shown at `scale`
+/// screen pixels per sheet pixel: twice the thumbnail resolution, doubled further until
+/// it is sharp at the current zoom
+pub fn preview_size(size: i32, scale: f64) -> u32 {
+    let size = size.max(1) as u32;
+    let wanted = (size as f64 * scale).ceil() as u32;
+    let mut preview = 2 * size;
+    while preview < wanted && preview < MAX_PREVIEW_SIZE {
+        preview *= 2;
+    }
+    preview.min(MAX_PREVIEW_SIZE)
 }
 
 #[derive(Default, Debug, Clone)]
@@ -254,6 +315,9 @@ pub struct Annotation {
     pub id: i32,
     pub position: TRect,
     pub entry: Entry,
+    pub keywords: Vec<String>,
+    pub progress: Option<Progress>,
+    pub label: Option<ColorLabel>,
 }
 
 impl PartialEq for Annotation {
@@ -261,3 +325,16 @@ impl PartialEq for Annotation {
         self.id == other.id
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preview_size() {
+        assert_eq!(preview_size(175, 1.0), 350);
+        assert_eq!(preview_size(175, 2.5), 700);
+        assert_eq!(preview_size(175, 10.0), MAX_PREVIEW_SIZE);
+        assert_eq!(preview_size(0, 1.0), 2);
+    }
+}
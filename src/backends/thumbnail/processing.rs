@@ -27,13 +27,14 @@ use crate::backends::document::mupdf::DocMuPdf;
 
 use crate::{
     backends::{
-        archive_mar::MarArchive, archive_rar::RarArchive, archive_zip::ZipArchive,
-        document::pdfium::DocPdfium, filesystem::FileSystem,
+        archive_mail::MailArchive, archive_mar::MarArchive, archive_rar::RarArchive,
+        archive_zip::ZipArchive, document::pdfium::DocPdfium, filesystem::FileSystem,
     },
     classification::FileType,
     error::MviewResult,
     file_view::model::BackendRef,
     image::{draw::text_thumb, provider::image_rs::RsImageLoader, view::ImageView},
+    mview6_error,
 };
 
 use super::{
@@ -71,7 +72,7 @@ fn thumb_result(res: MviewResult<DynamicImage>, task: &TTask) -> TResultOption {
 pub fn start_thumbnail_task(
     sender: &Sender<Message>,
     image_view: &ImageView,
-    command: &TCommand,
+    command: &mut TCommand,
     current_task: &mut usize,
 ) {
     // let elapsed = command.elapsed();
@@ -80,8 +81,13 @@ pub fn start_thumbnail_task(
     if command.id == id {
         // println!("-- command id is ok: {id}");
         let sender_clone = sender.clone();
-        if let Some(task) = command.tasks.get(*current_task) {
+        let index = if *current_task < command.tasks.len() {
             *current_task += 1;
+            Some(*current_task - 1)
+        } else {
+            command.redo.pop_front()
+        };
+        if let Some(task) = index.and_then(|index| command.tasks.get(index)) {
             let task = task.clone();
             // let tid = task.tid;
             thread::spawn(move || {
@@ -96,6 +102,9 @@ pub fn start_thumbnail_task(
                         dbg!(&task.source.reference);
                         thumb_result(MarArchive::get_thumbnail(&task.source.reference), &task)
                     }
+                    BackendRef::MailArchive(_) => {
+                        thumb_result(MailArchive::get_thumbnail(&task.source.reference), &task)
+                    }
                     BackendRef::RarArchive(_) => {
                         thumb_result(RarArchive::get_thumbnail(&task.source.reference), &task)
                     }
@@ -123,6 +132,60 @@ pub fn start_thumbnail_task(
     }
 }
 
+/// Decode the image of `task` at full resolution for a hover preview. Only plain folders
+/// and zip archives (photos, comics) are supported.
+fn preview_image(task: &TTask) -> MviewResult<DynamicImage> {
+    match &task.source.reference.backend {
+        BackendRef::FileSystem(_) => FileSystem::get_preview(&task.source.reference),
+        BackendRef::ZipArchive(_) => ZipArchive::get_preview(&task.source.reference),
+        _ => mview6_error!("No preview").into(),
+    }
+}
+
+/// Start decoding a sharper version of the thumbnail of cell `task_id`, right away instead
+/// of after the thumbnails of the sheet that are still being made
+pub fn start_hover_task(
+    sender: &Sender<Message>,
+    image_view: &ImageView,
+    command: &TCommand,
+    task_id: i32,
+    size: u32,
+) {
+    if command.id != image_view.image_id() {
+        return;
+    }
+    let Some(task) = command.tasks.get(task_id as usize) else {
+        return;
+    };
+    if task.source.category.file_type != FileType::Image {
+        return;
+    }
+    let mut task = task.clone();
+    task.size = size;
+    let id = command.id;
+    let sender = sender.clone();
+    thread::spawn(move || {
+        let Ok(Ok(image)) = panic::catch_unwind(|| preview_image(&task)) else {
+            return;
+        };
+        let image = image.resize(size, size, image::imageops::FilterType::Lanczos3);
+        let result = TResult::new(id, task, TResultOption::Image(image));
+        let _ = sender.send_blocking(Message::HoverResult(result.into()));
+    });
+}
+
+pub fn handle_hover_result(image_view: &ImageView, command: &TCommand, result: Box<TResult>) {
+    if command.id != result.id || result.id != image_view.image_id() {
+        return;
+    }
+    if let TResultOption::Image(image) = &result.result {
+        match RsImageLoader::dynimg_to_surface(image) {
+            Ok(surface) => image_view.set_hover_preview(result.task.id, result.task.size, surface),
+            Err(error) => println!("Thumbnail: failed to convert preview {error:?}"),
+        }
+    }
+}
+
 pub fn handle_thumbnail_result(
     image_view: &ImageView,
     command: &mut TCommand,
@@ -158,6 +221,8 @@ pub fn handle_thumbnail_result(
                     let dest_x = x + (size - thumb_pb.width()) / 2;
                     let dest_y = y + (size - thumb_pb.height()) / 2;
 
+                    // The cell may hold an older thumbnail of a modified file
+                    image_view.clear_area(x, y, size, size);
                     image_view.draw_pixbuf(&thumb_pb, dest_x, dest_y);
                     // ongoing
                     if let Some(task) = command.tasks.get_mut(result.task.id as usize) {
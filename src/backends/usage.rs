@@ -0,0 +1,278 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Local, opt-in usage statistics: how often and how long each file was viewed, and the
+//! list of the most viewed files
+
+use std::{
+    collections::HashMap,
+    io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{
+    collection::CollectionEntry, virtual_list::VirtualList, Backend, Content, ImageParams, Target,
+};
+use crate::{
+    config::JsonStore,
+    file_view::{
+        model::{BackendRef, ItemRef, Reference, Row},
+        Cursor,
+    },
+};
+
+/// Length of the most viewed list
+const MOST_VIEWED: usize = 100;
+
+/// Number of files kept, beyond it the ones not viewed for the longest time are forgotten
+const MAX_FILES: usize = 10_000;
+
+/// Identifies a file: its path, or the archive or document and the entry in it
+type UsageKey = (PathBuf, String);
+
+fn key(entry: &CollectionEntry) -> UsageKey {
+    (entry.path.clone(), entry.item.clone())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileUsage {
+    #[serde(flatten)]
+    pub entry: CollectionEntry,
+    pub views: u32,
+    /// Total viewing time in seconds
+    pub seconds: u64,
+    /// Unix time of the last view
+    pub last_viewed: u64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct UsageStats {
+    /// Nothing is recorded unless the user opted in
+    #[serde(default)]
+    pub enabled: bool,
+    /// Stored as a list of files
+    #[serde(
+        default,
+        serialize_with = "serialize_files",
+        deserialize_with = "deserialize_files"
+    )]
+    files: HashMap<UsageKey, FileUsage>,
+}
+
+fn serialize_files<S: Serializer>(
+    files: &HashMap<UsageKey, FileUsage>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(files.values())
+}
+
+fn deserialize_files<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<HashMap<UsageKey, FileUsage>, D::Error> {
+    let files = Vec::<FileUsage>::deserialize(deserializer)?;
+    Ok(files
+        .into_iter()
+        .map(|usage| (key(&usage.entry), usage))
+        .collect())
+}
+
+const USAGE: JsonStore = JsonStore::new("usage.json");
+
+impl UsageStats {
+    pub fn load() -> Self {
+        USAGE.load()
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        USAGE.save(self)
+    }
+
+    pub fn get(&self, entry: &CollectionEntry) -> Option<&FileUsage> {
+        self.files.get(&key(entry))
+    }
+
+    /// Count a view of `entry` that started at unix time `start` and lasted `seconds`
+    pub fn record_view(&mut self, entry: &CollectionEntry, start: u64, seconds: u64) {
+        let usage = self.files.entry(key(entry)).or_insert_with(|| FileUsage {
+            entry: entry.clone(),
+            views: 0,
+            seconds: 0,
+            last_viewed: start,
+        });
+        usage.views += 1;
+        usage.seconds += seconds;
+        usage.last_viewed = start;
+        if self.files.len() > MAX_FILES {
+            self.forget_oldest();
+        }
+    }
+
+    /// Forget the file that was not viewed for the longest time
+    fn forget_oldest(&mut self) {
+        if let Some(oldest) = self
+            .files
+            .iter()
+            .min_by_key(|(_, usage)| usage.last_viewed)
+            .map(|(key, _)| key.clone())
+        {
+            self.files.remove(&oldest);
+        }
+    }
+
+    /// Most viewed files first, on a tie the one viewed longest
+    pub fn most_viewed(&self) -> Vec<FileUsage> {
+        let mut files: Vec<FileUsage> = self.files.values().cloned().collect();
+        files.sort_by(|a, b| b.views.cmp(&a.views).then(b.seconds.cmp(&a.seconds)));
+        files.truncate(MOST_VIEWED);
+        files
+    }
+}
+
+/// Viewing time as e.g. `2 h 05 m`, `3 m 12 s` or `40 s`
+pub fn format_duration(seconds: u64) -> String {
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{hours} h {minutes:02} m")
+    } else if minutes > 0 {
+        format!("{minutes} m {seconds:02} s")
+    } else {
+        format!("{seconds} s")
+    }
+}
+
+pub struct MostViewed {
+    list: VirtualList<FileUsage>,
+}
+
+impl MostViewed {
+    pub fn new(
+        entries: Vec<FileUsage>,
+        parent_backend: Box<dyn Backend>,
+        parent_target: Target,
+    ) -> Self {
+        let row = |index, usage: &FileUsage| {
+            Row::new_index(
+                usage.entry.category(),
+                usage.entry.name.clone(),
+                0,
+                usage.last_viewed,
+                index,
+            )
+        };
+        MostViewed {
+            list: VirtualList::new(entries, row, parent_backend, parent_target),
+        }
+    }
+}
+
+impl Backend for MostViewed {
+    fn class_name(&self) -> &str {
+        "MostViewed"
+    }
+
+    fn path(&self) -> PathBuf {
+        Path::new("most viewed").into()
+    }
+
+    fn list(&self) -> &Vec<Row> {
+        self.list.list()
+    }
+
+    fn leave(&self) -> Option<(Box<dyn Backend>, Target)> {
+        self.list.leave()
+    }
+
+    fn content(&self, item: &ItemRef, params: &ImageParams) -> Content {
+        match self.list.entry(item) {
+            Some(usage) => usage.entry.content(params),
+            None => Content::default(),
+        }
+    }
+
+    fn resolve(&self, cursor: &Cursor) -> Option<Reference> {
+        self.list
+            .current(cursor)
+            .map(|usage| usage.entry.reference())
+    }
+
+    fn backend_ref(&self) -> BackendRef {
+        BackendRef::MostViewed
+    }
+
+    fn item_ref(&self, cursor: &Cursor) -> ItemRef {
+        ItemRef::Index(cursor.index())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str) -> CollectionEntry {
+        CollectionEntry::new(
+            &Reference {
+                backend: BackendRef::FileSystem(PathBuf::from("/photos")),
+                item: ItemRef::String(name.to_string()),
+            },
+            name,
+        )
+    }
+
+    #[test]
+    fn most_viewed_first() {
+        let mut stats = UsageStats::default();
+        stats.record_view(&entry("a.jpg"), 10, 5);
+        stats.record_view(&entry("b.jpg"), 20, 30);
+        stats.record_view(&entry("c.jpg"), 30, 3);
+        stats.record_view(&entry("c.jpg"), 40, 3);
+
+        let names: Vec<_> = stats
+            .most_viewed()
+            .iter()
+            .map(|usage| usage.entry.name.clone())
+            .collect();
+        assert_eq!(names, ["c.jpg", "b.jpg", "a.jpg"]);
+        let c = stats.get(&entry("c.jpg")).unwrap();
+        assert_eq!((c.views, c.seconds, c.last_viewed), (2, 6, 40));
+
+        let json = serde_json::to_string(&stats).unwrap();
+        let loaded: UsageStats = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.get(&entry("b.jpg")).unwrap().seconds, 30);
+    }
+
+    #[test]
+    fn forget_oldest() {
+        let mut stats = UsageStats::default();
+        for i in 0..=MAX_FILES as u64 {
+            stats.record_view(&entry(&format!("{i}.jpg")), 100 + i, 5);
+        }
+        assert_eq!(stats.files.len(), MAX_FILES);
+        assert!(stats.get(&entry("0.jpg")).is_none());
+        assert!(stats.get(&entry("1.jpg")).is_some());
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(format_duration(40), "40 s");
+        assert_eq!(format_duration(192), "3 m 12 s");
+        assert_eq!(format_duration(7500), "2 h 05 m");
+    }
+}
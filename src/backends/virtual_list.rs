@@ -0,0 +1,81 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Common part of the backends that list files from elsewhere (collection, most viewed,
+//! review trash): the entries with their rows, and the backend to return to on leave
+
+use std::cell::RefCell;
+
+use super::{Backend, Target};
+use crate::file_view::{
+    model::{ItemRef, Row},
+    Cursor,
+};
+
+pub struct VirtualList<T> {
+    entries: Vec<T>,
+    store: Vec<Row>,
+    parent_backend: RefCell<Box<dyn Backend>>,
+    parent_target: Target,
+}
+
+impl<T> VirtualList<T> {
+    /// The rows are made by `row` from the position and the entry, their index is the
+    /// position of the entry
+    pub fn new(
+        entries: Vec<T>,
+        row: impl Fn(u64, &T) -> Row,
+        parent_backend: Box<dyn Backend>,
+        parent_target: Target,
+    ) -> Self {
+        let store = entries
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| row(index as u64, entry))
+            .collect();
+        VirtualList {
+            entries,
+            store,
+            parent_backend: parent_backend.into(),
+            parent_target,
+        }
+    }
+
+    pub fn list(&self) -> &Vec<Row> {
+        &self.store
+    }
+
+    pub fn entry(&self, item: &ItemRef) -> Option<&T> {
+        match item {
+            ItemRef::Index(index) => self.entries.get(*index as usize),
+            _ => None,
+        }
+    }
+
+    pub fn current(&self, cursor: &Cursor) -> Option<&T> {
+        self.entries.get(cursor.index() as usize)
+    }
+
+    pub fn leave(&self) -> Option<(Box<dyn Backend>, Target)> {
+        Some((
+            self.parent_backend.replace(<dyn Backend>::none()),
+            self.parent_target.clone(),
+        ))
+    }
+}
@@ -0,0 +1,221 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Central management of the on-disk caches: their locations, size limits and statistics
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use crate::config::{config, CacheSettings};
+
+/// Marks a directory as created by the cache, see <https://bford.info/cachedir/>. Only
+/// directories with this marker are ever cleared or trimmed.
+const MARKER: &str = "CACHEDIR.TAG";
+
+const MARKER_CONTENT: &str = "Signature: 8a477f597d28d172789f06886806bc55\n\
+# This file is a cache directory tag created by MView6.\n";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheKind {
+    /// Thumbnails of files and archive entries
+    Thumbnails,
+    /// Archive entries extracted to disk
+    Extraction,
+}
+
+impl CacheKind {
+    pub const ALL: [CacheKind; 2] = [Self::Thumbnails, Self::Extraction];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Thumbnails => "Thumbnails",
+            Self::Extraction => "Archive extraction",
+        }
+    }
+
+    fn settings(&self) -> &'static CacheSettings {
+        let cache = &config().config_file.cache;
+        match self {
+            Self::Thumbnails => &cache.thumbnails,
+            Self::Extraction => &cache.extraction,
+        }
+    }
+
+    /// Location of the cache. This is always a dedicated `mview6` directory, also inside a
+    /// configured directory, so the cache never shares a directory with other files.
+    pub fn directory(&self) -> PathBuf {
+        let base = match (&self.settings().directory, self) {
+            (Some(directory), _) => PathBuf::from(directory),
+            // Extracted files do not need to survive a reboot
+            (None, Self::Extraction) => std::env::temp_dir(),
+            (None, _) => dirs::cache_dir().unwrap_or_else(std::env::temp_dir),
+        };
+        base.join("mview6").join(match self {
+            Self::Thumbnails => "thumbnails",
+            Self::Extraction => "extract",
+        })
+    }
+
+    /// Create the directory of the cache, with the marker that allows clearing it
+    pub fn create_directory(&self) -> io::Result<PathBuf> {
+        let dir = self.directory();
+        fs::create_dir_all(&dir)?;
+        let marker = dir.join(MARKER);
+        if !marker.exists() {
+            fs::write(marker, MARKER_CONTENT)?;
+        }
+        Ok(dir)
+    }
+
+    /// Size limit the cache is trimmed to. Extracted files belong to running sessions and
+    /// may still be open, so they are not trimmed: directories of ended sessions are removed
+    /// by `extract::recover` instead.
+    pub fn max_size(&self) -> Option<u64> {
+        match self {
+            Self::Thumbnails => Some(self.settings().max_size_mb.unwrap_or(512) * 1024 * 1024),
+            Self::Extraction => None,
+        }
+    }
+
+    /// Path for an entry of the cache
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.directory().join(name)
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CacheStats {
+    pub files: usize,
+    pub size: u64,
+}
+
+fn is_cache_directory(dir: &Path) -> bool {
+    dir.join(MARKER).is_file()
+}
+
+/// All files in the cache with their size and last modification
+fn cache_files(dir: &Path, files: &mut Vec<(PathBuf, u64, SystemTime)>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if metadata.is_dir() {
+            cache_files(&entry.path(), files);
+        } else if entry.file_name() != MARKER {
+            let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            files.push((entry.path(), metadata.len(), modified));
+        }
+    }
+}
+
+pub fn stats(kind: CacheKind) -> CacheStats {
+    let mut files = Vec::new();
+    cache_files(&kind.directory(), &mut files);
+    CacheStats {
+        files: files.len(),
+        size: files.iter().map(|(_, size, _)| size).sum(),
+    }
+}
+
+pub fn clear(kind: CacheKind) -> io::Result<()> {
+    let dir = kind.directory();
+    if !dir.exists() {
+        return Ok(());
+    }
+    if !is_cache_directory(&dir) {
+        return Err(io::Error::other(format!(
+            "{} was not created by MView6",
+            dir.display()
+        )));
+    }
+    fs::remove_dir_all(&dir)
+}
+
+/// Files to remove to bring the total size within the limit, the oldest first
+fn excess_files(mut files: Vec<(PathBuf, u64, SystemTime)>, max_size: u64) -> Vec<PathBuf> {
+    let mut total: u64 = files.iter().map(|(_, size, _)| size).sum();
+    files.sort_by_key(|(_, _, modified)| *modified);
+    let mut excess = Vec::new();
+    for (path, size, _) in files {
+        if total <= max_size {
+            break;
+        }
+        total -= size;
+        excess.push(path);
+    }
+    excess
+}
+
+/// Remove the oldest entries of the cache until it is within its size limit
+pub fn trim(kind: CacheKind) {
+    let dir = kind.directory();
+    let Some(max_size) = kind.max_size() else {
+        return;
+    };
+    if !is_cache_directory(&dir) {
+        return;
+    }
+    let mut files = Vec::new();
+    cache_files(&dir, &mut files);
+    let excess = excess_files(files, max_size);
+    for path in &excess {
+        let _ = fs::remove_file(path);
+    }
+    if !excess.is_empty() {
+        println!(
+            "Removed {} files from the {} cache",
+            excess.len(),
+            kind.name()
+        );
+    }
+}
+
+pub fn trim_all() {
+    for kind in CacheKind::ALL {
+        trim(kind);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn excess() {
+        let time = |s| SystemTime::UNIX_EPOCH + Duration::from_secs(s);
+        let files = vec![
+            (PathBuf::from("new"), 400, time(30)),
+            (PathBuf::from("old"), 300, time(10)),
+            (PathBuf::from("mid"), 300, time(20)),
+        ];
+        assert!(excess_files(files.clone(), 1000).is_empty());
+        assert_eq!(excess_files(files.clone(), 700), [PathBuf::from("old")]);
+        assert_eq!(
+            excess_files(files, 400),
+            [PathBuf::from("old"), PathBuf::from("mid")]
+        );
+    }
+}
@@ -0,0 +1,423 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Content-type detection
+//!
+//! Every supported format is described by a [`Detector`]: its extensions and, when the
+//! format can be recognized from its first bytes, a magic check. The detectors are
+//! consulted in order of priority, so specific formats (EPUB, office documents) win
+//! over the generic ones they are built on (ZIP) and the weak text-based heuristics
+//! come last.
+//!
+//! Adding a format is a matter of adding an entry to [`DETECTORS`] and handling the
+//! [`FileFormat`] in the content loader.
+
+use std::{path::Path, sync::OnceLock};
+
+use crate::{
+    classification::{
+        file_formats::{ArchiveFormat, DocumentFormat, FileFormat, ImageFormat},
+        FileType,
+    },
+    image::provider::ico::is_icon,
+    util::path_to_extension,
+};
+
+/// Number of bytes the magic checks may look at
+pub const MAGIC_SIZE: usize = 4096;
+
+pub struct Detector {
+    pub name: &'static str,
+    /// Detectors with a higher priority are consulted first
+    pub priority: i32,
+    pub format: FileFormat,
+    /// How the format is presented in the file list
+    pub file_type: FileType,
+    pub extensions: &'static [&'static str],
+    pub magic: Option<fn(&[u8]) -> bool>,
+}
+
+// Notebooks, fonts and models can be viewed, but are neither images nor containers, so
+// they are listed as unsupported
+const DETECTORS: &[Detector] = &[
+    Detector {
+        name: "EPUB",
+        priority: 90,
+        format: FileFormat::Document(DocumentFormat::Epub),
+        file_type: FileType::Document,
+        extensions: &["epub"],
+        magic: Some(is_epub),
+    },
+    Detector {
+        name: "Office Open XML",
+        priority: 80,
+        format: FileFormat::Archive(ArchiveFormat::Office),
+        file_type: FileType::Archive,
+        extensions: &["docx", "xlsx", "pptx"],
+        magic: Some(is_office),
+    },
+    Detector {
+        name: "ZIP",
+        priority: 50,
+        format: FileFormat::Archive(ArchiveFormat::Zip),
+        file_type: FileType::Archive,
+        extensions: &["zip"],
+        magic: Some(|data| data.starts_with(b"PK")),
+    },
+    Detector {
+        name: "RAR",
+        priority: 50,
+        format: FileFormat::Archive(ArchiveFormat::Rar),
+        file_type: FileType::Archive,
+        extensions: &["rar"],
+        // "Rar!\x1A\x07\x00" (RAR 1.5-4.x) or "Rar!\x1A\x07\x01\x00" (RAR 5.0+)
+        magic: Some(|data| data.starts_with(b"Rar!\x1A\x07")),
+    },
+    Detector {
+        name: "MAR",
+        priority: 50,
+        format: FileFormat::Archive(ArchiveFormat::Mar),
+        file_type: FileType::Archive,
+        extensions: &["mar"],
+        magic: None,
+    },
+    Detector {
+        name: "PDF",
+        priority: 50,
+        format: FileFormat::Document(DocumentFormat::Pdf),
+        file_type: FileType::Document,
+        extensions: &["pdf"],
+        magic: Some(|data| data.starts_with(b"%PDF")),
+    },
+    Detector {
+        name: "GIF",
+        priority: 50,
+        format: FileFormat::Image(ImageFormat::Gif),
+        file_type: FileType::Image,
+        extensions: &["gif"],
+        magic: Some(|data| data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a")),
+    },
+    Detector {
+        name: "JPEG",
+        priority: 50,
+        format: FileFormat::Image(ImageFormat::Jpeg),
+        file_type: FileType::Image,
+        extensions: &["jpg", "jpeg", "jfif"],
+        magic: Some(|data| data.starts_with(b"\xFF\xD8\xFF")),
+    },
+    Detector {
+        name: "PNG",
+        priority: 50,
+        format: FileFormat::Image(ImageFormat::Png),
+        file_type: FileType::Image,
+        extensions: &["png"],
+        magic: Some(|data| data.starts_with(b"\x89PNG\r\n\x1A\n")),
+    },
+    Detector {
+        name: "WebP",
+        priority: 50,
+        format: FileFormat::Image(ImageFormat::Webp),
+        file_type: FileType::Image,
+        extensions: &["webp"],
+        magic: Some(|data| {
+            data.starts_with(b"RIFF") && data.get(8..12) == Some(b"WEBP".as_slice())
+        }),
+    },
+    Detector {
+        name: "HEIC",
+        priority: 50,
+        format: FileFormat::Image(ImageFormat::Heic),
+        file_type: FileType::Image,
+        extensions: &["heic"],
+        magic: Some(|data| has_brand(data, b"ftyphei")),
+    },
+    Detector {
+        name: "AVIF",
+        priority: 50,
+        format: FileFormat::Image(ImageFormat::Avif),
+        file_type: FileType::Image,
+        extensions: &["avif"],
+        magic: Some(|data| has_brand(data, b"ftypavif")),
+    },
+    // TODO: -1, jxl
+    Detector {
+        name: "PCX",
+        priority: 50,
+        format: FileFormat::Image(ImageFormat::Pcx),
+        file_type: FileType::Image,
+        extensions: &["pcx"],
+        magic: None,
+    },
+    Detector {
+        name: "Font",
+        priority: 40,
+        format: FileFormat::Font,
+        file_type: FileType::Unsupported,
+        extensions: &["ttf", "otf", "ttc", "woff", "woff2"],
+        magic: Some(is_font),
+    },
+    Detector {
+        name: "Video",
+        priority: 40,
+        format: FileFormat::Video,
+        file_type: FileType::Video,
+        extensions: &[
+            "webm", "mkv", "flv", "vob", "ogv", "ogg", "rrc", "gifv", "mng", "mov", "avi", "qt",
+            "wmv", "yuv", "rm", "asf", "amv", "mp4", "m4p", "m4v", "mpg", "mp2", "mpeg", "mpe",
+            "mpv", "svi", "3gp", "3g2", "mxf", "roq", "nsv", "f4v", "f4p", "f4a", "f4b", "mod",
+        ],
+        magic: None,
+    },
+    Detector {
+        name: "ICO/CUR",
+        priority: 20,
+        format: FileFormat::Image(ImageFormat::Ico),
+        file_type: FileType::Image,
+        extensions: &["ico", "cur"],
+        // the header is short and mostly zeros, so only checked after the others
+        magic: Some(is_icon),
+    },
+    Detector {
+        name: "Email/MHTML",
+        priority: 20,
+        format: FileFormat::Archive(ArchiveFormat::Mail),
+        file_type: FileType::Archive,
+        extensions: &["eml", "mht", "mhtml"],
+        magic: Some(is_mail),
+    },
+    Detector {
+        name: "Jupyter notebook",
+        priority: 20,
+        format: FileFormat::Notebook,
+        file_type: FileType::Unsupported,
+        extensions: &["ipynb"],
+        magic: Some(is_notebook),
+    },
+    Detector {
+        name: "3D model",
+        priority: 20,
+        format: FileFormat::Model,
+        file_type: FileType::Unsupported,
+        extensions: &["stl", "obj"],
+        // binary STL and OBJ have no signature
+        magic: Some(|data| data.starts_with(b"solid ") && contains(data, b"facet", 1024)),
+    },
+    Detector {
+        name: "SVG",
+        priority: 10,
+        format: FileFormat::Image(ImageFormat::Svg),
+        file_type: FileType::Image,
+        extensions: &["svg", "svgz"],
+        magic: Some(is_svg),
+    },
+];
+
+/// The detectors, highest priority first
+pub fn detectors() -> &'static [&'static Detector] {
+    static SORTED: OnceLock<Vec<&'static Detector>> = OnceLock::new();
+    SORTED.get_or_init(|| {
+        let mut sorted = DETECTORS.iter().collect::<Vec<_>>();
+        sorted.sort_by_key(|detector| -detector.priority);
+        sorted
+    })
+}
+
+fn by_extension(extension: &str) -> Option<&'static Detector> {
+    let ext = extension.to_lowercase();
+    detectors()
+        .iter()
+        .find(|detector| detector.extensions.contains(&ext.as_str()))
+        .copied()
+}
+
+fn by_magic(data: &[u8]) -> Option<&'static Detector> {
+    if data.len() < 4 {
+        return None; // Not enough bytes to identify
+    }
+    detectors()
+        .iter()
+        .find(|detector| detector.magic.is_some_and(|magic| magic(data)))
+        .copied()
+}
+
+pub fn format_from_extension(extension: &str) -> FileFormat {
+    by_extension(extension).map_or(FileFormat::Unknown, |detector| detector.format)
+}
+
+pub fn format_from_path(path: &Path) -> FileFormat {
+    format_from_extension(&path_to_extension(path))
+}
+
+pub fn format_from_data(data: &[u8]) -> FileFormat {
+    by_magic(data).map_or(FileFormat::Unknown, |detector| detector.format)
+}
+
+/// Extension first, then the content
+pub fn format_from_path_and_data(path: &Path, data: &[u8]) -> FileFormat {
+    match format_from_path(path) {
+        FileFormat::Unknown => format_from_data(data),
+        format => format,
+    }
+}
+
+/// Preferred extension for content of unknown origin, e.g. dragged from a browser
+pub fn extension_from_data(data: &[u8]) -> Option<&'static str> {
+    by_magic(data).and_then(|detector| detector.extensions.first().copied())
+}
+
+pub fn file_type_from_extension(extension: &str) -> FileType {
+    by_extension(extension).map_or(FileType::Unsupported, |detector| detector.file_type)
+}
+
+fn contains(data: &[u8], needle: &[u8], limit: usize) -> bool {
+    data[..data.len().min(limit)]
+        .windows(needle.len())
+        .any(|w| w == needle)
+}
+
+/// ISO base media file brand (HEIF family) near the start of the file
+fn has_brand(data: &[u8], brand: &[u8]) -> bool {
+    data.len() >= 12 && contains(data, brand, 256)
+}
+
+/// ZIP with the uncompressed "mimetype" entry first
+fn is_epub(data: &[u8]) -> bool {
+    data.starts_with(b"PK") && contains(data, b"mimetype", 1024)
+}
+
+/// ZIP with the "[Content_Types].xml" part, which office applications write first
+fn is_office(data: &[u8]) -> bool {
+    data.starts_with(b"PK") && contains(data, b"[Content_Types].xml", 1024)
+}
+
+fn is_font(data: &[u8]) -> bool {
+    [
+        b"\x00\x01\x00\x00".as_slice(),
+        b"OTTO",
+        b"ttcf",
+        b"wOFF",
+        b"wOF2",
+    ]
+    .iter()
+    .any(|magic| data.starts_with(magic))
+}
+
+/// "<svg" right at the start, or after an XML declaration (which may be followed by a
+/// doctype and comments)
+fn is_svg(data: &[u8]) -> bool {
+    contains(data, b"<svg", 100) || (data.starts_with(b"<?xml") && contains(data, b"<svg", 1024))
+}
+
+/// Message headers: the first line is a header and a MIME-Version header follows
+fn is_mail(data: &[u8]) -> bool {
+    let head = String::from_utf8_lossy(&data[..data.len().min(MAGIC_SIZE)]);
+    let first_line_is_header = head.lines().next().is_some_and(|line| {
+        line.split_once(':').is_some_and(|(name, _)| {
+            !name.is_empty() && name.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-')
+        })
+    });
+    first_line_is_header
+        && head
+            .lines()
+            .take_while(|line| !line.is_empty())
+            .any(|line| line.to_ascii_lowercase().starts_with("mime-version:"))
+}
+
+/// Jupyter writes the "cells" key first
+fn is_notebook(data: &[u8]) -> bool {
+    let head = String::from_utf8_lossy(&data[..data.len().min(256)]);
+    head.trim_start()
+        .strip_prefix('{')
+        .is_some_and(|rest| rest.trim_start().starts_with("\"cells\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_priority() {
+        let office = b"PK\x03\x04\x14\x00\x06\x00\x08\x00\x00\x00!\x00\x00\x00\x00\x00\x00\x00\
+                       \x00\x00\x00\x00\x00\x00\x13\x00\x00\x00[Content_Types].xml";
+        assert_eq!(
+            format_from_data(office),
+            FileFormat::Archive(ArchiveFormat::Office)
+        );
+        assert_eq!(
+            format_from_data(b"PK\x03\x04mimetypeapplication/epub+zip"),
+            FileFormat::Document(DocumentFormat::Epub)
+        );
+        assert_eq!(
+            format_from_data(b"PK\x03\x04\x14\x00"),
+            FileFormat::Archive(ArchiveFormat::Zip)
+        );
+        let priorities = detectors()
+            .iter()
+            .map(|detector| detector.priority)
+            .collect::<Vec<_>>();
+        assert!(priorities.windows(2).all(|w| w[0] >= w[1]));
+    }
+
+    #[test]
+    fn test_extension() {
+        assert_eq!(format_from_extension("STL"), FileFormat::Model);
+        assert_eq!(
+            format_from_path(Path::new("/tmp/report.docx")),
+            FileFormat::Archive(ArchiveFormat::Office)
+        );
+        assert_eq!(file_type_from_extension("mkv"), FileType::Video);
+        assert_eq!(file_type_from_extension("xlsx"), FileType::Archive);
+        assert_eq!(file_type_from_extension("ipynb"), FileType::Unsupported);
+        assert_eq!(format_from_extension("txt"), FileFormat::Unknown);
+    }
+
+    #[test]
+    fn test_extension_from_data() {
+        assert_eq!(
+            extension_from_data(b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR"),
+            Some("png")
+        );
+        assert_eq!(extension_from_data(b"plain text"), None);
+    }
+
+    #[test]
+    fn test_heuristics() {
+        assert_eq!(
+            format_from_data(b"From: a@example.com\r\nMIME-Version: 1.0\r\n\r\nHello"),
+            FileFormat::Archive(ArchiveFormat::Mail)
+        );
+        assert_eq!(
+            format_from_data(b"Dear reader: MIME-Version: 1.0\n"),
+            FileFormat::Unknown
+        );
+        assert_eq!(
+            format_from_data(b"{\n \"cells\": [\n  {\n"),
+            FileFormat::Notebook
+        );
+        assert_eq!(
+            format_from_data(b"solid cube\n  facet normal 0 0 1\n"),
+            FileFormat::Model
+        );
+        assert_eq!(format_from_data(b"OTTO\x00\x0a"), FileFormat::Font);
+        assert_eq!(
+            format_from_path_and_data(Path::new("x.svg"), b"PK\x03\x04"),
+            FileFormat::Image(ImageFormat::Svg)
+        );
+    }
+}
@@ -17,11 +17,14 @@
 // STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
 // OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use crate::classification::detect;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ImageFormat {
     Avif,
     Gif,
     Heic,
+    Ico,
     Jpeg,
     Pcx,
     Png,
@@ -33,7 +36,9 @@ pub enum ImageFormat {
 pub enum ArchiveFormat {
     Zip,
     Rar,
-    Mar, // Mozilla Archive Format
+    Mar,    // Mozilla Archive Format
+    Mail,   // Email message or MHTML web archive
+    Office, // Office Open XML document (zip based)
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -48,112 +53,20 @@ pub enum FileFormat {
     Image(ImageFormat),
     Archive(ArchiveFormat),
     Document(DocumentFormat),
+    Notebook,
+    Font,
+    Model,
+    Video,
     Unknown,
 }
 
 impl FileFormat {
     pub fn determine(data: &[u8]) -> Self {
-        if data.len() < 4 {
-            return Self::Unknown; // Not enough bytes to identify
-        }
-
-        // ZIP: "PK" (50 4B)
-        if data.starts_with(&[0x50, 0x4B]) {
-            // Check if it's EPUB (ZIP-based format)
-            // Look for "mimetype" string within the first 1024 bytes (common in EPUB)
-            if let Some(slice) = data.get(0..std::cmp::min(1024, data.len())) {
-                if str::from_utf8(slice)
-                    .map(|s| s.contains("mimetype"))
-                    .unwrap_or(false)
-                {
-                    return Self::Document(DocumentFormat::Epub);
-                }
-            }
-            return Self::Archive(ArchiveFormat::Zip);
-        }
-
-        // RAR: Starts with "Rar!\x1A\x07\x00" (RAR 1.5-4.x) or "Rar!\x1A\x07\x01\x00" (RAR 5.0+)
-        if data.starts_with(b"Rar!\x1A\x07") {
-            return Self::Archive(ArchiveFormat::Rar);
-        }
-
-        // PDF: Starts with "%PDF"
-        if data.starts_with(b"%PDF") {
-            return Self::Document(DocumentFormat::Pdf);
-        }
-
-        // GIF: Starts with "GIF87a" or "GIF89a"
-        if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
-            return Self::Image(ImageFormat::Gif);
-        }
-
-        // JPEG: Starts with "\xFF\xD8\xFF"
-        if data.starts_with(b"\xFF\xD8\xFF") {
-            return Self::Image(ImageFormat::Jpeg);
-        }
-
-        // PNG: Starts with "\x89PNG\r\n\x1A\n"
-        if data.starts_with(b"\x89PNG\r\n\x1A\n") {
-            return Self::Image(ImageFormat::Png);
-        }
-
-        if data.len() >= 12 {
-            // WebP: Starts with "RIFF" followed by length and "WEBP" (at offset 8)
-            if data.starts_with(b"RIFF") && &data[8..12] == b"WEBP" {
-                return Self::Image(ImageFormat::Webp);
-            }
-
-            // HEIC: Contains "ftyphei[cxms]" within first 12 bytes
-            if data[..data.len().min(256)]
-                .windows(7)
-                .any(|w| w == b"ftyphei")
-            {
-                return Self::Image(ImageFormat::Heic);
-            }
-
-            // AVIF: Contains "ftypavif" within first 12 bytes
-            if data[..data.len().min(256)]
-                .windows(8)
-                .any(|w| w == b"ftypavif")
-            {
-                return Self::Image(ImageFormat::Avif);
-            }
-        }
-
-        // SVG: Look for "<svg" within the first 100 bytes (SVG is text-based)
-        if let Some(slice) = data.get(0..std::cmp::min(100, data.len())) {
-            if str::from_utf8(slice)
-                .map(|s| s.contains("<svg"))
-                .unwrap_or(false)
-            {
-                return Self::Image(ImageFormat::Svg);
-            }
-        }
-
-        Self::Unknown
+        detect::format_from_data(data)
     }
 
     pub fn from_extension(extension: &str) -> Self {
-        let ext_low = extension.to_lowercase();
-        match ext_low.as_str() {
-            "zip" => Self::Archive(ArchiveFormat::Zip),
-            "rar" => Self::Archive(ArchiveFormat::Rar),
-            "mar" => Self::Archive(ArchiveFormat::Mar),
-            "pdf" => Self::Document(DocumentFormat::Pdf),
-            "epub" => Self::Document(DocumentFormat::Epub),
-            "jpg" => Self::Image(ImageFormat::Jpeg),
-            "jpeg" => Self::Image(ImageFormat::Jpeg),
-            "jfif" => Self::Image(ImageFormat::Jpeg),
-            "gif" => Self::Image(ImageFormat::Gif),
-            "svg" => Self::Image(ImageFormat::Svg),
-            "svgz" => Self::Image(ImageFormat::Svg),
-            "webp" => Self::Image(ImageFormat::Webp),
-            "heic" => Self::Image(ImageFormat::Heic),
-            "avif" => Self::Image(ImageFormat::Avif),
-            "pcx" => Self::Image(ImageFormat::Pcx),
-            "png" => Self::Image(ImageFormat::Png),
-            _ => Self::Unknown,
-        }
+        detect::format_from_extension(extension)
     }
 }
 
@@ -314,6 +227,24 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_ico_detection() {
+        let ico_header = vec![0x00, 0x00, 0x01, 0x00, 0x02, 0x00];
+        let cur_header = vec![0x00, 0x00, 0x02, 0x00, 0x01, 0x00];
+        assert_eq!(
+            FileFormat::determine(&ico_header),
+            FileFormat::Image(ImageFormat::Ico)
+        );
+        assert_eq!(
+            FileFormat::determine(&cur_header),
+            FileFormat::Image(ImageFormat::Ico)
+        );
+        assert_eq!(
+            FileFormat::from_extension("CUR"),
+            FileFormat::Image(ImageFormat::Ico)
+        );
+    }
+
     #[test]
     fn test_unknown_format() {
         let unknown_data = vec![0x00, 0x01, 0x02, 0x03];
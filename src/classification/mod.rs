@@ -17,24 +17,13 @@
 // STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
 // OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+pub mod detect;
 pub mod file_formats;
 
 use std::{collections::HashSet, path::Path};
 
 use crate::image::colors::Color;
 
-const ARCHIVE_EXT: &[&str] = &["zip", "rar", "mar"];
-const DOC_EXT: &[&str] = &["pdf", "epub"];
-// TODO: -1, jxl
-const IMAGE_EXT: &[&str] = &[
-    "jpg", "jpeg", "jfif", "gif", "svg", "svgz", "webp", "heic", "avif", "pcx", "png",
-];
-const VIDEO_EXT: &[&str] = &[
-    "webm", "mkv", "flv", "vob", "ogv", "ogg", "rrc", "gifv", "mng", "mov", "avi", "qt", "wmv",
-    "yuv", "rm", "asf", "amv", "mp4", "m4p", "m4v", "mpg", "mp2", "mpeg", "mpe", "mpv", "m4v",
-    "svi", "3gp", "3g2", "mxf", "roq", "nsv", "flv", "f4v", "f4p", "f4a", "f4b", "mod",
-];
-
 #[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
 #[repr(u32)]
 pub enum FileType {
@@ -129,20 +118,7 @@ impl FileType {
     }
 
     pub fn from_extension(extension: &str) -> Self {
-        let ext_low = extension.to_lowercase();
-        if ARCHIVE_EXT.contains(&ext_low.as_str()) {
-            return Self::Archive;
-        }
-        if DOC_EXT.contains(&ext_low.as_str()) {
-            return Self::Document;
-        }
-        if IMAGE_EXT.contains(&ext_low.as_str()) {
-            return Self::Image;
-        }
-        if VIDEO_EXT.contains(&ext_low.as_str()) {
-            return Self::Video;
-        }
-        Self::Unsupported
+        detect::file_type_from_extension(extension)
     }
 }
 
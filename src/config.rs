@@ -18,16 +18,18 @@
 // OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use std::{
-    fs::{create_dir_all, File},
-    io::{BufWriter, Result, Write},
+    collections::BTreeMap,
+    fs::{self, create_dir_all, File},
+    io::{BufReader, BufWriter, Error, ErrorKind, Result, Write},
     path::{Path, PathBuf},
     sync::{
         atomic::{AtomicI32, Ordering},
         OnceLock,
     },
+    time::Duration,
 };
 
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use syntect::{highlighting::ThemeSet, parsing::SyntaxSet};
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -36,11 +38,86 @@ pub struct Bookmark {
     pub folder: String,
 }
 
+/// Color lookup table (.cube) to apply when the window is shown on a specific monitor
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MonitorLut {
+    /// Monitor connector (e.g. "DP-1") or model name
+    pub monitor: String,
+    pub file: String,
+}
+
+/// Location and size limit of a cache, unset values use the defaults
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct CacheSettings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub directory: Option<String>,
+    /// Not used for the extraction cache, which only holds files of running sessions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_size_mb: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct CacheConfig {
+    #[serde(default)]
+    pub thumbnails: CacheSettings,
+    #[serde(default)]
+    pub extraction: CacheSettings,
+}
+
+impl CacheConfig {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Layout of two images side by side (dual page mode)
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct DualPageConfig {
+    /// Space between the pages, in pixels of the left page
+    #[serde(default)]
+    pub gap: u32,
+    /// Draw a subtle line at the fold between the pages
+    #[serde(default)]
+    pub fold_line: bool,
+}
+
+impl DualPageConfig {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ConfigFile {
     pub bookmarks: Vec<Bookmark>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub contrast: Option<i32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub monitor_luts: Vec<MonitorLut>,
+    /// ICC profile used for soft-proofing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub printer_profile: Option<String>,
+    /// Terminal emulator command, parsed like a shell command line. `{dir}` is replaced by
+    /// the directory to open.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub terminal: Option<String>,
+    /// Image editor command, parsed like a shell command line. `{file}` is replaced by
+    /// the image to edit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub editor: Option<String>,
+    #[serde(default, skip_serializing_if = "CacheConfig::is_default")]
+    pub cache: CacheConfig,
+    #[serde(default, skip_serializing_if = "DualPageConfig::is_default")]
+    pub dual_page: DualPageConfig,
+    /// Seconds a high quality render may take before the preview is shown instead
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub render_timeout: Option<u64>,
+    /// Buttons shown in the header bar, e.g. `["rotate-left", "zoom-fit", "fullscreen"]`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub toolbar: Option<Vec<String>>,
+    /// Address of the preview server, e.g. `0.0.0.0:6060` to mirror the view on the LAN
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preview_server: Option<String>,
 }
 
 #[derive(Debug)]
@@ -48,6 +125,8 @@ pub struct Config {
     pub config_file: ConfigFile,
     pub ps: SyntaxSet,
     pub ts: ThemeSet,
+    /// No configuration file existed when MView6 was started
+    pub first_run: bool,
 }
 
 fn pathbuf_to_string(pathbuf: &Path) -> String {
@@ -110,6 +189,15 @@ impl Default for ConfigFile {
         let config = Self {
             bookmarks,
             contrast: None,
+            monitor_luts: Vec::new(),
+            printer_profile: None,
+            terminal: None,
+            editor: None,
+            cache: CacheConfig::default(),
+            dual_page: DualPageConfig::default(),
+            render_timeout: None,
+            toolbar: None,
+            preview_server: None,
         };
 
         match config.save() {
@@ -123,6 +211,34 @@ impl Default for ConfigFile {
     }
 }
 
+/// A JSON file in the configuration folder, for data kept between sessions
+pub struct JsonStore(&'static str);
+
+impl JsonStore {
+    pub const fn new(name: &'static str) -> Self {
+        Self(name)
+    }
+
+    pub fn path(&self) -> PathBuf {
+        ConfigFile::config_dir().join(self.0)
+    }
+
+    /// The stored value, the default when the file is missing or cannot be read
+    pub fn load<T: DeserializeOwned + Default>(&self) -> T {
+        File::open(self.path())
+            .ok()
+            .and_then(|file| serde_json::from_reader(BufReader::new(file)).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save<T: Serialize + ?Sized>(&self, value: &T) -> Result<()> {
+        create_dir_all(ConfigFile::config_dir())?;
+        let mut writer = BufWriter::new(File::create(self.path())?);
+        serde_json::to_writer_pretty(&mut writer, value)?;
+        writer.flush()
+    }
+}
+
 fn read_config() -> Result<ConfigFile> {
     println!("Config file location {:?}", ConfigFile::config_file());
     let file = File::open(ConfigFile::config_file())?;
@@ -133,13 +249,83 @@ fn read_config() -> Result<ConfigFile> {
 
 pub fn config<'a>() -> &'a Config {
     static CONFIG: OnceLock<Config> = OnceLock::new();
-    CONFIG.get_or_init(|| Config {
-        config_file: read_config().unwrap_or_default(),
-        ps: SyntaxSet::load_defaults_nonewlines(),
-        ts: ThemeSet::load_defaults(),
+    CONFIG.get_or_init(|| {
+        let first_run = !ConfigFile::config_file().exists();
+        Config {
+            config_file: read_config().unwrap_or_default(),
+            ps: SyntaxSet::load_defaults_nonewlines(),
+            ts: ThemeSet::load_defaults(),
+            first_run,
+        }
     })
 }
 
+/// Store the printer profile for the next sessions. The configuration file is read again
+/// so only this setting changes.
+pub fn save_printer_profile(profile: &Path) -> Result<()> {
+    let mut config_file = if ConfigFile::config_file().exists() {
+        read_config()?
+    } else {
+        ConfigFile::default()
+    };
+    config_file.printer_profile = Some(pathbuf_to_string(profile));
+    config_file.save()
+}
+
+/// Store the color LUT of a monitor for the next sessions, replacing the one it had
+pub fn save_monitor_lut(monitor: &str, file: &Path) -> Result<()> {
+    let mut config_file = if ConfigFile::config_file().exists() {
+        read_config()?
+    } else {
+        ConfigFile::default()
+    };
+    let file = pathbuf_to_string(file);
+    match config_file
+        .monitor_luts
+        .iter_mut()
+        .find(|m| m.monitor == monitor)
+    {
+        Some(lut) => lut.file = file,
+        None => config_file.monitor_luts.push(MonitorLut {
+            monitor: monitor.to_string(),
+            file,
+        }),
+    }
+    config_file.save()
+}
+
+/// Command line of the terminal emulator: from the configuration file, the `TERMINAL`
+/// environment variable or the Debian/Ubuntu alternatives default
+pub fn terminal_command() -> String {
+    config()
+        .config_file
+        .terminal
+        .clone()
+        .or_else(|| std::env::var("TERMINAL").ok())
+        .unwrap_or_else(|| "x-terminal-emulator".to_string())
+}
+
+/// Command line of the image editor: from the configuration file, or GIMP
+pub fn editor_command() -> String {
+    config()
+        .config_file
+        .editor
+        .clone()
+        .unwrap_or_else(|| "gimp {file}".to_string())
+}
+
+const DEFAULT_RENDER_TIMEOUT: u64 = 15;
+
+/// Time after which a stuck render is abandoned
+pub fn render_timeout() -> Duration {
+    Duration::from_secs(
+        config()
+            .config_file
+            .render_timeout
+            .unwrap_or(DEFAULT_RENDER_TIMEOUT),
+    )
+}
+
 static CONTRAST: AtomicI32 = AtomicI32::new(0);
 
 pub fn contrast_delta(delta: i32) {
@@ -153,3 +339,206 @@ pub fn contrast() -> u8 {
     }
     contrast as u8
 }
+
+/// Name of the optional configuration file in a directory
+pub const DIRECTORY_CONFIG: &str = ".mview6";
+
+/// Overrides for a directory and its children, read from `.mview6` files. Settings that are
+/// not present fall back to the nearest parent directory and then to the global settings.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct DirectoryConfig {
+    /// Sort column: `type`, `name`, `size` or `modified`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sort: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sort_descending: Option<bool>,
+    /// Zoom mode, same values as the zoom action (`nozoom`, `fit`, `fill`, `max`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zoom: Option<String>,
+    /// Transparency background (`checkerboard`, `white`, `black`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transparency: Option<String>,
+    /// Page mode (`single`, `deo`, `doe`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_mode: Option<String>,
+    /// Slideshow interval in seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slideshow_interval: Option<i32>,
+    /// Content types and preferences to show, e.g. `["image", "folder", "liked"]`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<Vec<String>>,
+}
+
+impl DirectoryConfig {
+    /// Combine the configuration files from the root down to `directory`
+    pub fn load(directory: &Path) -> Self {
+        let mut config = Self::default();
+        let ancestors: Vec<&Path> = directory.ancestors().collect();
+        for dir in ancestors.into_iter().rev() {
+            let file = dir.join(DIRECTORY_CONFIG);
+            if !file.is_file() {
+                continue;
+            }
+            match Self::read(&file) {
+                Ok(local) => config.merge(local),
+                Err(e) => eprintln!("Ignoring {file:?}: {e}"),
+            }
+        }
+        config
+    }
+
+    fn read(file: &Path) -> Result<Self> {
+        let file = File::open(file)?;
+        Ok(serde_json::from_reader(file)?)
+    }
+
+    /// Settings present in `other` take precedence
+    fn merge(&mut self, other: Self) {
+        self.sort = other.sort.or(self.sort.take());
+        self.sort_descending = other.sort_descending.or(self.sort_descending);
+        self.zoom = other.zoom.or(self.zoom.take());
+        self.transparency = other.transparency.or(self.transparency.take());
+        self.page_mode = other.page_mode.or(self.page_mode.take());
+        self.slideshow_interval = other.slideshow_interval.or(self.slideshow_interval);
+        self.filter = other.filter.or(self.filter.take());
+    }
+}
+
+/// Files in the configuration folder that make up the preferences: the settings with the
+/// bookmarks, the settings per document and the collection
+const PREFERENCE_FILES: &[&str] = &["mview6.json", "documents.json", "collection.json"];
+
+const PREFERENCES_VERSION: u32 = 1;
+
+/// All preferences in a single file, to move a setup to another computer
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PreferencesBundle {
+    pub mview6_preferences: u32,
+    pub files: BTreeMap<String, serde_json::Value>,
+}
+
+impl PreferencesBundle {
+    /// Read the preference files present in `dir`
+    fn collect(dir: &Path) -> Self {
+        let mut files = BTreeMap::new();
+        for &name in PREFERENCE_FILES {
+            let Ok(file) = File::open(dir.join(name)) else {
+                continue;
+            };
+            match serde_json::from_reader(file) {
+                Ok(value) => {
+                    files.insert(name.to_string(), value);
+                }
+                Err(e) => eprintln!("Not exporting {name}: {e}"),
+            }
+        }
+        Self {
+            mview6_preferences: PREFERENCES_VERSION,
+            files,
+        }
+    }
+
+    /// Write the preference files of the bundle to `dir`, returns their names. Nothing is
+    /// written when the bundle is not valid.
+    fn install(&self, dir: &Path) -> Result<Vec<String>> {
+        if self.mview6_preferences != PREFERENCES_VERSION {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "unsupported preferences version {}",
+                    self.mview6_preferences
+                ),
+            ));
+        }
+        if let Some(settings) = self.files.get("mview6.json") {
+            serde_json::from_value::<ConfigFile>(settings.clone())?;
+        }
+        create_dir_all(dir)?;
+        let mut installed = Vec::new();
+        // Only the known files, the names come from outside
+        for &name in PREFERENCE_FILES {
+            if let Some(value) = self.files.get(name) {
+                fs::write(dir.join(name), serde_json::to_vec_pretty(value)?)?;
+                installed.push(name.to_string());
+            }
+        }
+        Ok(installed)
+    }
+}
+
+/// Save all preferences to `target`
+pub fn export_preferences(target: &Path) -> Result<()> {
+    let bundle = PreferencesBundle::collect(&ConfigFile::config_dir());
+    let file = File::create(target)?;
+    serde_json::to_writer_pretty(BufWriter::new(file), &bundle)?;
+    Ok(())
+}
+
+/// Replace the preferences by the ones saved in `source`, returns the names of the files
+/// that were replaced
+pub fn import_preferences(source: &Path) -> Result<Vec<String>> {
+    let file = File::open(source)?;
+    let bundle: PreferencesBundle = serde_json::from_reader(file)?;
+    bundle.install(&ConfigFile::config_dir())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+
+    #[test]
+    fn preferences_round_trip() {
+        let root = TestDir::new("preferences");
+        let (from, to) = (root.join("from"), root.join("to"));
+        create_dir_all(&from).unwrap();
+        std::fs::write(from.join("mview6.json"), r#"{"bookmarks": []}"#).unwrap();
+        std::fs::write(
+            from.join("documents.json"),
+            r#"{"/a.pdf": {"page_mode": "doe"}}"#,
+        )
+        .unwrap();
+        std::fs::write(from.join("navigation.json"), "{}").unwrap();
+
+        let mut bundle = PreferencesBundle::collect(&from);
+        assert_eq!(
+            bundle.files.keys().collect::<Vec<_>>(),
+            ["documents.json", "mview6.json"]
+        );
+        bundle
+            .files
+            .insert("../escape.json".to_string(), serde_json::Value::Null);
+        assert_eq!(
+            bundle.install(&to).unwrap(),
+            ["mview6.json", "documents.json"]
+        );
+        assert!(to.join("documents.json").exists());
+        assert!(!root.join("escape.json").exists());
+
+        bundle.files.insert(
+            "mview6.json".to_string(),
+            serde_json::json!({"contrast": 1}),
+        );
+        assert!(bundle.install(&to).is_err(), "settings without bookmarks");
+    }
+
+    #[test]
+    fn nearest_directory_config_wins() {
+        let root = TestDir::new("dirconfig");
+        let child = root.join("child");
+        create_dir_all(&child).unwrap();
+        std::fs::write(
+            root.join(DIRECTORY_CONFIG),
+            r#"{"sort": "modified", "sort_descending": true, "zoom": "fit"}"#,
+        )
+        .unwrap();
+        std::fs::write(child.join(DIRECTORY_CONFIG), r#"{"zoom": "fill"}"#).unwrap();
+
+        let config = DirectoryConfig::load(&child);
+
+        assert_eq!(config.sort.as_deref(), Some("modified"));
+        assert_eq!(config.sort_descending, Some(true));
+        assert_eq!(config.zoom.as_deref(), Some("fill"));
+        assert_eq!(config.filter, None);
+    }
+}
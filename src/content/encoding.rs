@@ -0,0 +1,113 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Character encoding of text content
+//!
+//! The encoding is taken from the byte order mark if present. Valid UTF-8 is used as is,
+//! otherwise the encoding is guessed from the byte statistics (chardetng). The user can
+//! override the result from the menu.
+
+use chardetng::EncodingDetector;
+use encoding_rs::{Encoding, UTF_8};
+
+/// Encodings offered for manual selection: (name, description)
+pub const ENCODINGS: &[(&str, &str)] = &[
+    ("UTF-8", "Unicode (UTF-8)"),
+    ("UTF-16LE", "Unicode (UTF-16LE)"),
+    ("UTF-16BE", "Unicode (UTF-16BE)"),
+    ("windows-1252", "Western (Latin-1)"),
+    ("ISO-8859-2", "Central European (Latin-2)"),
+    ("windows-1251", "Cyrillic (Windows)"),
+    ("KOI8-R", "Cyrillic (KOI8-R)"),
+    ("Shift_JIS", "Japanese (Shift-JIS)"),
+    ("EUC-JP", "Japanese (EUC-JP)"),
+    ("GBK", "Chinese Simplified (GBK)"),
+    ("Big5", "Chinese Traditional (Big5)"),
+    ("EUC-KR", "Korean (EUC-KR)"),
+];
+
+/// Detect the encoding of `data`, `None` if it does not look like text
+pub fn detect(data: &[u8]) -> Option<&'static Encoding> {
+    if let Some((encoding, _)) = Encoding::for_bom(data) {
+        return Some(encoding);
+    }
+    if data.contains(&0) {
+        return None;
+    }
+    match std::str::from_utf8(data) {
+        Ok(_) => return Some(UTF_8),
+        // Data might be truncated in the middle of a character
+        Err(e) if e.error_len().is_none() => return Some(UTF_8),
+        Err(_) => (),
+    }
+    let mut detector = EncodingDetector::new();
+    detector.feed(data, true);
+    let encoding = detector.guess(None, true);
+    let (text, _, had_errors) = encoding.decode(data);
+    if had_errors || is_binary(&text) {
+        None
+    } else {
+        Some(encoding)
+    }
+}
+
+/// Decode `data` with `encoding`, a matching byte order mark is removed
+pub fn decode(data: &[u8], encoding: &'static Encoding) -> String {
+    encoding.decode_with_bom_removal(data).0.into_owned()
+}
+
+/// Text with more than 1% control characters is considered binary
+fn is_binary(text: &str) -> bool {
+    let control = text
+        .chars()
+        .filter(|c| c.is_control() && !matches!(c, '\t' | '\n' | '\r' | '\x0c'))
+        .count();
+    control * 100 > text.chars().count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encoding_rs::UTF_16LE;
+
+    #[test]
+    fn detection() {
+        assert_eq!(detect(b"plain ascii"), Some(UTF_8));
+        assert_eq!(detect("caf\u{e9} cr\u{e8}me".as_bytes()), Some(UTF_8));
+        assert_eq!(detect(&[0xff, 0xfe, b'h', 0, b'i', 0]), Some(UTF_16LE));
+        assert_eq!(detect(&[0x00, 0x01, 0x02, 0xff]), None);
+
+        let latin1 =
+            b"Le caf\xe9 \xe9tait d\xe9j\xe0 ferm\xe9 quand nous sommes arriv\xe9s \xe0 la gare.";
+        let encoding = detect(latin1).unwrap();
+        assert_eq!(encoding.name(), "windows-1252");
+        assert_eq!(
+            decode(latin1, encoding),
+            "Le caf\u{e9} \u{e9}tait d\u{e9}j\u{e0} ferm\u{e9} quand nous sommes arriv\u{e9}s \u{e0} la gare."
+        );
+    }
+
+    #[test]
+    fn encodings() {
+        for (name, _) in ENCODINGS {
+            let encoding = Encoding::for_label(name.as_bytes()).unwrap();
+            assert_eq!(encoding.name(), *name);
+        }
+    }
+}
@@ -0,0 +1,195 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Specimen sheet for font files: metadata, character set and text in a range of sizes
+
+use std::{path::Path, sync::Arc};
+
+use resvg::usvg::Tree;
+use ttf_parser::{fonts_in_collection, name_id, Face};
+
+use crate::{
+    content::Content,
+    error::MviewResult,
+    image::{
+        colors::Color,
+        svg::text_sheet::{svg_options, TextSheet},
+        view::{data::TransparencyMode, ZoomMode},
+    },
+    mview6_error,
+    util::ellipsis_middle,
+};
+
+const SAMPLE: &str = "The quick brown fox jumps over the lazy dog";
+const SAMPLE_SIZES: &[u32] = &[12, 18, 24, 36, 48, 72];
+const CHARSET: &[&str] = &[
+    "ABCDEFGHIJKLMNOPQRSTUVWXYZ",
+    "abcdefghijklmnopqrstuvwxyz",
+    "0123456789 .,;:!?&@#%*()[]{}<>/\\\"'",
+];
+const CHARSET_SIZE: u32 = 32;
+
+/// Usable width of the sheet in pixels
+const WIDTH: f64 = 1140.0;
+
+/// Average advance of a character relative to the font size, used to shorten samples
+const CHAR_WIDTH: f64 = 0.55;
+
+#[derive(Debug, Default, PartialEq)]
+pub struct FontInfo {
+    pub family: String,
+    pub style: String,
+    pub version: String,
+    pub designer: String,
+    pub copyright: String,
+    pub glyphs: u16,
+    pub units_per_em: u16,
+    pub weight: u16,
+    pub monospaced: bool,
+    pub variable: bool,
+    pub faces: u32,
+}
+
+impl FontInfo {
+    pub fn parse(data: &[u8]) -> MviewResult<Self> {
+        if data.starts_with(b"wOFF") || data.starts_with(b"wOF2") {
+            return Err(mview6_error!("Compressed WOFF fonts are not supported"));
+        }
+        let face = Face::parse(data, 0).map_err(|e| mview6_error!(format!("{e}")))?;
+        let name = |ids: &[u16]| {
+            ids.iter()
+                .find_map(|&id| {
+                    face.names()
+                        .into_iter()
+                        .filter(|name| name.name_id == id)
+                        .find_map(|name| name.to_string())
+                })
+                .unwrap_or_default()
+        };
+        Ok(Self {
+            family: name(&[name_id::TYPOGRAPHIC_FAMILY, name_id::FAMILY]),
+            style: name(&[name_id::TYPOGRAPHIC_SUBFAMILY, name_id::SUBFAMILY]),
+            version: name(&[name_id::VERSION]),
+            designer: name(&[name_id::DESIGNER, name_id::MANUFACTURER]),
+            copyright: name(&[name_id::COPYRIGHT_NOTICE]),
+            glyphs: face.number_of_glyphs(),
+            units_per_em: face.units_per_em(),
+            weight: face.weight().to_number(),
+            monospaced: face.is_monospaced(),
+            variable: face.is_variable(),
+            faces: fonts_in_collection(data).unwrap_or(1),
+        })
+    }
+
+    fn details(&self) -> String {
+        let mut details = format!(
+            "{} glyphs, {} units per em, weight {}",
+            self.glyphs, self.units_per_em, self.weight
+        );
+        if self.monospaced {
+            details.push_str(", monospaced");
+        }
+        if self.variable {
+            details.push_str(", variable");
+        }
+        if self.faces > 1 {
+            details.push_str(&format!(", collection of {} fonts", self.faces));
+        }
+        details
+    }
+}
+
+/// Render the specimen sheet, the font itself is added to the font database of the sheet
+pub fn font_specimen(path: &Path, data: Vec<u8>) -> MviewResult<Content> {
+    let info = FontInfo::parse(&data)?;
+    // the family name ends up in an svg attribute
+    let family: String = info
+        .family
+        .chars()
+        .filter(|c| !matches!(c, '"' | '<' | '>' | '&'))
+        .collect();
+
+    let mut sheet = TextSheet::new(1200, 800, 14);
+    sheet.header(path, 24, 81);
+
+    let meta = sheet.base_style().font_family("Liberation Sans");
+    sheet.add_line(
+        &format!("{} {}", info.family, info.style),
+        meta.clone().font_size(18).color(Color::White),
+    );
+    sheet.delta_y(0.3);
+    for text in [&info.version, &info.designer, &info.copyright] {
+        if !text.is_empty() {
+            sheet.add_line(&ellipsis_middle(text, 140), meta.clone());
+        }
+    }
+    sheet.add_line(&info.details(), meta);
+    sheet.delta_y(0.5);
+
+    let specimen = sheet.base_style().font_family(&family).color(Color::White);
+    for line in CHARSET {
+        sheet.delta_y(CHARSET_SIZE as f64 * 1.3 / 14.0);
+        sheet.add_fragment(line, specimen.clone().font_size(CHARSET_SIZE));
+    }
+    sheet.delta_y(0.8);
+
+    let label = sheet.base_style().color(Color::Gray);
+    for &size in SAMPLE_SIZES {
+        sheet.delta_y(size as f64 * 1.3 / 14.0);
+        sheet.add_fragment(&format!("{size}"), label.clone());
+        let start = sheet.pos();
+        sheet.delta_x(3.0);
+        let max_chars = ((WIDTH - 3.0 * 14.0) / (size as f64 * CHAR_WIDTH)) as usize;
+        let sample: String = SAMPLE.chars().take(max_chars).collect();
+        sheet.add_fragment(sample.trim_end(), specimen.clone().font_size(size));
+        sheet.set_pos(start);
+    }
+
+    let mut options = svg_options();
+    Arc::make_mut(&mut options.fontdb).load_font_data(data);
+    let svg_content = sheet.finish().render();
+    let tree = Tree::from_str(&svg_content, &options)?;
+    Ok(Content::new_svg(
+        tree,
+        None,
+        ZoomMode::NotSpecified,
+        TransparencyMode::Black,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn info() {
+        let info = FontInfo::parse(include_bytes!(
+            "../../resources/fonts/LiberationSans-Bold.ttf"
+        ))
+        .unwrap();
+        assert_eq!(info.family, "Liberation Sans");
+        assert_eq!(info.style, "Bold");
+        assert_eq!(info.weight, 700);
+        assert!(!info.monospaced);
+        assert_eq!(info.faces, 1);
+        assert!(info.glyphs > 100);
+        assert!(FontInfo::parse(b"wOF2....").is_err());
+        assert!(FontInfo::parse(b"not a font").is_err());
+    }
+}
@@ -18,23 +18,43 @@
 // OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use crate::{
-    backends::{filesystem::FileSystem, Backend, MarArchive, RarArchive, ZipArchive},
-    classification::file_formats::{ArchiveFormat, FileFormat, ImageFormat},
-    content::{paginated::PaginatedContent, Content},
+    backends::{
+        archive_mail::{message_content, read_mail},
+        archive_zip::office_thumbnail,
+        filesystem::FileSystem,
+        Backend, MarArchive, RarArchive, ZipArchive,
+    },
+    classification::{
+        detect,
+        file_formats::{ArchiveFormat, FileFormat, ImageFormat},
+        FileType,
+    },
+    content::{
+        encoding,
+        font::font_specimen,
+        notebook::{Notebook, MAX_NOTEBOOK_SIZE},
+        paginated::PaginatedContent,
+        sheet::SheetContent,
+        Content,
+    },
     error::MviewResult,
+    file_reader::FileReader,
     file_view::model::BackendRef,
     image::{
         draw::{draw_error, draw_text},
-        provider::{gdk::GdkImageLoader, image_rs::RsImageLoader, internal::InternalImageLoader},
+        model::read_model,
+        provider::{
+            gdk::GdkImageLoader, ico::IcoImageLoader, image_rs::RsImageLoader,
+            internal::InternalImageLoader,
+        },
         view::{data::TransparencyMode, ZoomMode},
     },
     profile::performance::Performance,
-    util::path_to_extension,
 };
 use resvg::usvg::{self, fontdb::Database, Options, Tree};
 use std::{
     fs,
-    io::{BufReader, Cursor, Read, Seek},
+    io::{Cursor, Read, Seek},
     path::Path,
 };
 
@@ -61,8 +81,7 @@ impl ContentLoader {
             return Content::new_list(path, BackendRef::FileSystem(path.into()), list);
         }
 
-        let ext = path_to_extension(path);
-        let file_format = FileFormat::from_extension(&ext);
+        let file_format = detect::format_from_path(path);
         // dbg!(content_type);
         if file_format != FileFormat::Unknown {
             return Self::load_file(file_format, path);
@@ -73,26 +92,19 @@ impl ContentLoader {
             Err(e) => return draw_error(path, e),
         };
 
-        let file_format = FileFormat::determine(&data);
+        let file_format = detect::format_from_data(&data);
         if file_format != FileFormat::Unknown {
             return Self::load_file(file_format, path);
         }
 
-        // is it text? FIXME: handle utf16
-        Content::new_paginated(if data.contains(&0) {
-            PaginatedContent::new_raw(path, data)
-        } else {
-            match str::from_utf8(&data) {
-                Ok(text) => {
-                    let lines: Vec<String> = text.lines().map(|line| line.to_string()).collect();
-                    // if lines.iter().any(|line| line.len() > 200) {
-                    //     PaginatedContent::new_raw(path, data)
-                    // } else {
-                    PaginatedContent::new_text(path, lines)
-                    // }
-                }
-                Err(_) => PaginatedContent::new_raw(path, data),
-            }
+        Self::content_from_text(data, path)
+    }
+
+    /// Show data as text, or as raw bytes if it is not text
+    pub fn content_from_text(data: Vec<u8>, path: &Path) -> Content {
+        Content::new_paginated(match encoding::detect(&data) {
+            Some(encoding) => PaginatedContent::new_text(path, data, encoding),
+            None => PaginatedContent::new_raw(path, data),
         })
     }
 
@@ -106,6 +118,18 @@ impl ContentLoader {
                 let list = MarArchive::new(path).list().clone();
                 Content::new_list(path, BackendRef::MarArchive(path.into()), list)
             }
+            FileFormat::Archive(ArchiveFormat::Mail) => match read_mail(path) {
+                Ok(message) => message_content(path, &message),
+                Err(error) => draw_error(path, error),
+            },
+            FileFormat::Archive(ArchiveFormat::Office) => match office_thumbnail(path) {
+                Ok(bytes) => Self::content_from_memory(bytes, path),
+                Err(_) => {
+                    // no usable thumbnail: show the structure of the document
+                    let list = ZipArchive::new(path).list().clone();
+                    Content::new_list(path, BackendRef::ZipArchive(path.into()), list)
+                }
+            },
             FileFormat::Archive(ArchiveFormat::Rar) => {
                 let list = RarArchive::new(path).list().clone();
                 Content::new_list(path, BackendRef::RarArchive(path.into()), list)
@@ -123,12 +147,15 @@ impl ContentLoader {
                 ),
                 Err(error) => draw_error(path, error),
             },
+            FileFormat::Image(ImageFormat::Ico) => match IcoImageLoader::image_from_file(path) {
+                Ok(content) => content,
+                Err(error) => draw_error(path, error),
+            },
             FileFormat::Image(_) => {
-                let input = match std::fs::File::open(path) {
-                    Ok(file) => file,
+                let mut reader = match FileReader::read_into_memory(path) {
+                    Ok(reader) => reader,
                     Err(error) => return draw_error(path, error.into()),
                 };
-                let mut reader = BufReader::new(input);
 
                 if let Ok(im) = GdkImageLoader::image_from_reader(&mut reader) {
                     im
@@ -145,10 +172,27 @@ impl ContentLoader {
                     }
                 }
             }
+            FileFormat::Notebook => match Self::read_notebook(path) {
+                Ok(sheet) => Content::new_paginated(PaginatedContent::new_sheet(sheet)),
+                Err(error) => draw_error(path, error),
+            },
+            FileFormat::Font => match fs::read(path) {
+                Ok(data) => {
+                    font_specimen(path, data).unwrap_or_else(|error| draw_error(path, error))
+                }
+                Err(error) => draw_error(path, error.into()),
+            },
+            FileFormat::Model => match read_model(path).and_then(Content::new_model) {
+                Ok(content) => content,
+                Err(error) => draw_error(path, error),
+            },
+            FileFormat::Video => {
+                draw_text("Video", "Press enter to play", FileType::Video.colors())
+            }
             FileFormat::Unknown => draw_text(
                 "Unknown",
                 "Content not recognized",
-                crate::classification::FileType::Unsupported.colors(),
+                FileType::Unsupported.colors(),
             ),
         }
     }
@@ -159,7 +203,10 @@ impl ContentLoader {
     pub fn content_from_memory(buf: Vec<u8>, path: &Path) -> Content {
         let duration = Performance::start();
 
-        if buf.starts_with(&[0x3c, 0x3f]) || buf.starts_with(&[0x1f, 0x8b]) {
+        let file_format = detect::format_from_data(&buf);
+
+        // svgz is gzip compressed, which is not recognized by the detector
+        if file_format == FileFormat::Image(ImageFormat::Svg) || buf.starts_with(&[0x1f, 0x8b]) {
             let svg_options = usvg::Options::default();
             if let Ok(tree) = Tree::from_data(&buf, &svg_options) {
                 duration.elapsed("decode svg (mem)");
@@ -172,6 +219,13 @@ impl ContentLoader {
             }
         }
 
+        if file_format == FileFormat::Image(ImageFormat::Ico) {
+            if let Ok(content) = IcoImageLoader::image_from_memory(&buf) {
+                duration.elapsed("decode ico (mem)");
+                return content;
+            }
+        }
+
         let mut reader = Cursor::new(buf);
 
         let image = if let Ok(im) = GdkImageLoader::image_from_reader(&mut reader) {
@@ -225,6 +279,13 @@ impl ContentLoader {
         Ok(Tree::from_data(&svg_data, &svg_options)?)
     }
 
+    fn read_notebook(path: &Path) -> MviewResult<SheetContent> {
+        let file = std::fs::File::open(path)?;
+        let mut buffer = Vec::new();
+        file.take(MAX_NOTEBOOK_SIZE).read_to_end(&mut buffer)?;
+        Ok(SheetContent::new(path, Notebook::parse(&buffer)?.rows()))
+    }
+
     fn read_file<P: AsRef<Path>>(path: P) -> MviewResult<Vec<u8>> {
         let file = std::fs::File::open(path)?;
         let mut buffer = Vec::new();
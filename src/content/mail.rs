@@ -0,0 +1,111 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Email message (.eml, .mhtml) laid out as a sheet: headers, text body and attachments
+
+use human_bytes::human_bytes;
+
+use crate::{
+    content::{markdown::wrap, mime::Message, paginated::MAX_LINE_LENGTH, sheet::Row},
+    image::{colors::Color, svg::creator::FontWeight},
+    util::ellipsis_middle,
+};
+
+/// Headers shown above the body, with their label
+const HEADERS: &[(&str, &str)] = &[
+    ("from", "From:"),
+    ("to", "To:"),
+    ("cc", "Cc:"),
+    ("date", "Date:"),
+];
+
+/// Width of the header labels in characters
+const LABEL_WIDTH: usize = 7;
+
+pub fn rows(message: &Message) -> Vec<Row> {
+    let mut rows = Vec::new();
+
+    let subject = message
+        .headers
+        .get("subject")
+        .unwrap_or_else(|| "(no subject)".to_string());
+    rows.push(Row::Spans(
+        vec![(
+            ellipsis_middle(&subject, MAX_LINE_LENGTH),
+            Color::Yellow.into(),
+        )],
+        FontWeight::Bold,
+    ));
+    for (name, label) in HEADERS {
+        if let Some(value) = message.headers.get(name) {
+            rows.push(Row::Spans(
+                vec![
+                    (format!("{label:<LABEL_WIDTH$}"), Color::Glaucous.into()),
+                    (
+                        ellipsis_middle(&value, MAX_LINE_LENGTH - LABEL_WIDTH),
+                        Color::Gainsboro.into(),
+                    ),
+                ],
+                FontWeight::Normal,
+            ));
+        }
+    }
+    rows.push(Row::blank());
+
+    match message.text_body() {
+        Some(body) => {
+            for line in body.lines() {
+                let color = if line.starts_with('>') {
+                    Color::Gray
+                } else {
+                    Color::Silver
+                };
+                for line in wrap(line, MAX_LINE_LENGTH) {
+                    rows.push(Row::text(&line, color));
+                }
+            }
+        }
+        None => rows.push(Row::text("(no text)", Color::Gray)),
+    }
+
+    let attachments: Vec<String> = message
+        .parts
+        .iter()
+        .filter_map(|part| {
+            let name = part.filename()?;
+            Some(format!(
+                "{}  {}  {}",
+                ellipsis_middle(&name, 80),
+                part.content_type,
+                human_bytes(part.body.len() as f64)
+            ))
+        })
+        .collect();
+    if !attachments.is_empty() {
+        rows.push(Row::blank());
+        rows.push(Row::Spans(
+            vec![("Attachments".to_string(), Color::FolderTitle.into())],
+            FontWeight::Bold,
+        ));
+        for attachment in attachments {
+            rows.push(Row::text(&attachment, Color::Gainsboro));
+        }
+    }
+    rows
+}
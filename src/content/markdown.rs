@@ -0,0 +1,252 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Markdown rendered as lines for a text sheet
+//!
+//! Block structure (headings, lists, quotes, code blocks and rules) is kept, inline markup
+//! is reduced to plain text and paragraphs are wrapped to the width of the sheet.
+
+use regex::Regex;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Block {
+    Heading(u8),
+    Paragraph,
+    Bullet,
+    Quote,
+    Code,
+    Rule,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Line {
+    pub block: Block,
+    pub text: String,
+}
+
+impl Line {
+    fn new(block: Block, text: String) -> Self {
+        Self { block, text }
+    }
+}
+
+struct Renderer {
+    width: usize,
+    lines: Vec<Line>,
+    paragraph: Vec<String>,
+    inline: [(Regex, &'static str); 6],
+}
+
+impl Renderer {
+    fn new(width: usize) -> Self {
+        Self {
+            width,
+            lines: Vec::new(),
+            paragraph: Vec::new(),
+            inline: [
+                (Regex::new(r"!\[([^\]]*)\]\([^)]*\)").unwrap(), "[$1]"),
+                (Regex::new(r"\[([^\]]+)\]\([^)]*\)").unwrap(), "$1"),
+                (Regex::new(r"\*\*(.+?)\*\*").unwrap(), "$1"),
+                (Regex::new(r"\b__(.+?)__\b").unwrap(), "$1"),
+                (Regex::new(r"\*(\S[^*]*?)\*").unwrap(), "$1"),
+                (Regex::new(r"`([^`]*)`").unwrap(), "$1"),
+            ],
+        }
+    }
+
+    fn inline(&self, text: &str) -> String {
+        self.inline
+            .iter()
+            .fold(text.trim().to_string(), |text, (regex, replacement)| {
+                regex.replace_all(&text, *replacement).into_owned()
+            })
+    }
+
+    /// Add `text` wrapped to the width, continuation lines are indented like the first line
+    /// after its `prefix`
+    fn wrapped(&mut self, block: Block, prefix: &str, text: &str) {
+        let indent = " ".repeat(prefix.chars().count());
+        for (i, line) in wrap(text, self.width.saturating_sub(indent.len()))
+            .into_iter()
+            .enumerate()
+        {
+            let lead = if i == 0 { prefix } else { &indent };
+            self.lines.push(Line::new(block, format!("{lead}{line}")));
+        }
+    }
+
+    fn flush(&mut self) {
+        if !self.paragraph.is_empty() {
+            let text = self.inline(&self.paragraph.join(" "));
+            self.paragraph.clear();
+            self.wrapped(Block::Paragraph, "", &text);
+        }
+    }
+
+    fn blank(&mut self) {
+        self.flush();
+        if self.lines.last().is_some_and(|line| !line.text.is_empty()) {
+            self.lines.push(Line::new(Block::Paragraph, String::new()));
+        }
+    }
+
+    fn finish(mut self) -> Vec<Line> {
+        self.flush();
+        while self.lines.last().is_some_and(|line| line.text.is_empty()) {
+            self.lines.pop();
+        }
+        self.lines
+    }
+}
+
+/// Render markdown `source` as lines of at most `width` characters (code is not wrapped)
+pub fn render(source: &str, width: usize) -> Vec<Line> {
+    let heading = Regex::new(r"^(#{1,6})\s+(.*?)\s*#*\s*$").unwrap();
+    let bullet = Regex::new(r"^(\s*)([-*+]|\d+[.)])\s+(.*)$").unwrap();
+    let rule = Regex::new(r"^\s*([-*_])(\s*[-*_]){2,}\s*$").unwrap();
+
+    let mut r = Renderer::new(width);
+    let mut fence: Option<&str> = None;
+    for line in source.lines() {
+        let trimmed = line.trim_start();
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
+                r.blank();
+            } else {
+                r.lines.push(Line::new(Block::Code, line.to_string()));
+            }
+        } else if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            r.blank();
+            fence = Some(&trimmed[..3]);
+        } else if trimmed.is_empty() {
+            r.blank();
+        } else if let Some(caps) = heading.captures(trimmed) {
+            r.blank();
+            let text = r.inline(&caps[2]);
+            r.lines
+                .push(Line::new(Block::Heading(caps[1].len() as u8), text));
+            r.blank();
+        } else if rule.is_match(line) && trimmed.starts_with('-') && !r.paragraph.is_empty() {
+            // setext heading
+            let text = r.inline(&r.paragraph.join(" "));
+            r.paragraph.clear();
+            r.lines.push(Line::new(Block::Heading(2), text));
+            r.blank();
+        } else if rule.is_match(line) {
+            r.flush();
+            r.lines
+                .push(Line::new(Block::Rule, "─".repeat(width.min(40))));
+        } else if let Some(caps) = bullet.captures(line) {
+            r.flush();
+            let marker = match &caps[2] {
+                "-" | "*" | "+" => "•".to_string(),
+                number => number.to_string(),
+            };
+            let prefix = format!("{}{marker} ", &caps[1]);
+            let text = r.inline(&caps[3]);
+            r.wrapped(Block::Bullet, &prefix, &text);
+        } else if let Some(quote) = trimmed.strip_prefix('>') {
+            r.flush();
+            let text = r.inline(quote);
+            r.wrapped(Block::Quote, "│ ", &text);
+        } else {
+            r.paragraph.push(trimmed.to_string());
+        }
+    }
+    r.finish()
+}
+
+/// Greedy word wrap, words longer than `width` are split
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let mut word: Vec<char> = word.chars().collect();
+        let len = line.chars().count();
+        if len > 0 && len + 1 + word.len() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        while word.len() > width {
+            lines.push(word.drain(..width).collect());
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.extend(word);
+    }
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(lines: &[Line]) -> Vec<&str> {
+        lines.iter().map(|line| line.text.as_str()).collect()
+    }
+
+    #[test]
+    fn blocks() {
+        let lines = render(
+            "# Title\n\nSome **bold** and `code`,\nsee [docs](http://x).\n\n- one\n- two\n\n```python\nx = 1\n```\n> quoted\n\n---",
+            80,
+        );
+        assert_eq!(
+            texts(&lines),
+            vec![
+                "Title",
+                "",
+                "Some bold and code, see docs.",
+                "",
+                "• one",
+                "• two",
+                "",
+                "x = 1",
+                "",
+                "│ quoted",
+                "",
+                &"─".repeat(40),
+            ]
+        );
+        assert_eq!(lines[0].block, Block::Heading(1));
+        assert_eq!(lines[4].block, Block::Bullet);
+        assert_eq!(lines[7].block, Block::Code);
+        assert_eq!(lines[11].block, Block::Rule);
+    }
+
+    #[test]
+    fn wrapping() {
+        assert_eq!(wrap("aaa bbb ccc", 7), vec!["aaa bbb", "ccc"]);
+        assert_eq!(wrap("abcdefghij", 4), vec!["abcd", "efgh", "ij"]);
+        assert_eq!(wrap("", 4), vec![""]);
+        assert_eq!(
+            texts(&render("1. first item that wraps", 12)),
+            vec!["1. first", "   item that", "   wraps"]
+        );
+        assert_eq!(
+            texts(&render("snake_case_name", 80)),
+            vec!["snake_case_name"]
+        );
+    }
+}
@@ -0,0 +1,441 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Minimal MIME parser for email (.eml) and MHTML files
+//!
+//! Multipart bodies are flattened into their leaf parts. Transfer encodings (base64 and
+//! quoted-printable) are undone, encoded words in headers are decoded.
+
+use base64::{
+    alphabet,
+    engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig},
+    Engine,
+};
+use encoding_rs::{Encoding, UTF_8};
+use regex::{Captures, Regex};
+
+/// Base64 as found in mail: padding is optional
+const BASE64: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+#[derive(Debug, Default)]
+pub struct Headers(Vec<(String, String)>);
+
+impl Headers {
+    fn parse(data: &[u8]) -> Self {
+        let text = String::from_utf8_lossy(data);
+        let mut headers: Vec<(String, String)> = Vec::new();
+        for line in text.lines() {
+            if line.starts_with([' ', '\t']) {
+                // folded continuation of the previous header
+                if let Some((_, value)) = headers.last_mut() {
+                    value.push(' ');
+                    value.push_str(line.trim());
+                }
+            } else if let Some((name, value)) = line.split_once(':') {
+                headers.push((name.trim().to_lowercase(), value.trim().to_string()));
+            }
+        }
+        Self(headers)
+    }
+
+    /// Raw value of the first header called `name`
+    fn raw(&self, name: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Value of the first header called `name` with encoded words decoded
+    pub fn get(&self, name: &str) -> Option<String> {
+        self.raw(name).map(decode_words)
+    }
+}
+
+#[derive(Debug)]
+pub struct Part {
+    pub headers: Headers,
+    /// Lowercase media type without parameters, e.g. `text/plain`
+    pub content_type: String,
+    /// Body with the transfer encoding removed
+    pub body: Vec<u8>,
+}
+
+impl Part {
+    /// Filename from the content disposition, the content type or the content location
+    pub fn filename(&self) -> Option<String> {
+        let disposition = self.headers.raw("content-disposition").unwrap_or_default();
+        let content_type = self.headers.raw("content-type").unwrap_or_default();
+        param(disposition, "filename")
+            .or_else(|| param(content_type, "name"))
+            .map(|name| decode_words(&name))
+            .or_else(|| {
+                self.headers.raw("content-location").and_then(|location| {
+                    location
+                        .trim_end_matches('/')
+                        .rsplit('/')
+                        .next()
+                        .filter(|name| !name.is_empty())
+                        .map(|name| name.to_string())
+                })
+            })
+    }
+
+    pub fn is_image(&self) -> bool {
+        self.content_type.starts_with("image/")
+    }
+
+    /// Body decoded with the charset of the part
+    pub fn text(&self) -> String {
+        let charset = param(
+            self.headers.raw("content-type").unwrap_or_default(),
+            "charset",
+        );
+        let encoding = charset
+            .and_then(|charset| Encoding::for_label(charset.as_bytes()))
+            .unwrap_or(UTF_8);
+        encoding
+            .decode_without_bom_handling(&self.body)
+            .0
+            .into_owned()
+    }
+}
+
+#[derive(Debug)]
+pub struct Message {
+    pub headers: Headers,
+    pub parts: Vec<Part>,
+}
+
+impl Message {
+    pub fn parse(data: &[u8]) -> Self {
+        let (headers, body) = split_header(data);
+        let mut parts = Vec::new();
+        collect_parts(Headers::parse(headers), body, &mut parts, 0);
+        Self {
+            headers: Headers::parse(headers),
+            parts,
+        }
+    }
+
+    /// Plain text of the message: the first text/plain part, or the first html part as text
+    pub fn text_body(&self) -> Option<String> {
+        let attachment = |part: &&Part| {
+            part.headers
+                .raw("content-disposition")
+                .is_some_and(|d| d.trim_start().to_lowercase().starts_with("attachment"))
+        };
+        self.parts
+            .iter()
+            .filter(|part| !attachment(part))
+            .find(|part| part.content_type == "text/plain")
+            .map(Part::text)
+            .or_else(|| {
+                self.parts
+                    .iter()
+                    .filter(|part| !attachment(part))
+                    .find(|part| part.content_type == "text/html")
+                    .map(|part| html_to_text(&part.text()))
+            })
+    }
+
+    pub fn images(&self) -> impl Iterator<Item = &Part> {
+        self.parts.iter().filter(|part| part.is_image())
+    }
+}
+
+/// Nesting limit for multipart bodies
+const MAX_DEPTH: usize = 8;
+
+fn collect_parts(headers: Headers, body: &[u8], parts: &mut Vec<Part>, depth: usize) {
+    let content_type_header = headers
+        .raw("content-type")
+        .unwrap_or("text/plain")
+        .to_string();
+    let content_type = content_type_header
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_lowercase();
+    if content_type.starts_with("multipart/") && depth < MAX_DEPTH {
+        if let Some(boundary) = param(&content_type_header, "boundary") {
+            for part in split_multipart(body, &boundary) {
+                let (part_headers, part_body) = split_header(part);
+                collect_parts(Headers::parse(part_headers), part_body, parts, depth + 1);
+            }
+            return;
+        }
+    }
+    let encoding = headers
+        .raw("content-transfer-encoding")
+        .unwrap_or_default()
+        .trim()
+        .to_lowercase();
+    let body = match encoding.as_str() {
+        "base64" => {
+            let data: Vec<u8> = body
+                .iter()
+                .copied()
+                .filter(|b| !b.is_ascii_whitespace())
+                .collect();
+            BASE64.decode(data).unwrap_or_default()
+        }
+        "quoted-printable" => decode_quoted_printable(body, false),
+        _ => body.to_vec(),
+    };
+    parts.push(Part {
+        headers,
+        content_type,
+        body,
+    });
+}
+
+/// Split at the first empty line into header and body
+fn split_header(data: &[u8]) -> (&[u8], &[u8]) {
+    let mut start = 0;
+    for line in data.split_inclusive(|&b| b == b'\n') {
+        let end = start + line.len();
+        if line.iter().all(|b| b.is_ascii_whitespace()) {
+            return (&data[..start], &data[end..]);
+        }
+        start = end;
+    }
+    (data, &[])
+}
+
+/// Bodies between the `--boundary` delimiter lines
+fn split_multipart<'a>(body: &'a [u8], boundary: &str) -> Vec<&'a [u8]> {
+    let delimiter = format!("--{boundary}");
+    let mut parts = Vec::new();
+    let mut part_start: Option<usize> = None;
+    let mut start = 0;
+    for line in body.split_inclusive(|&b| b == b'\n') {
+        let end = start + line.len();
+        let trimmed = line.trim_ascii_end();
+        if trimmed.starts_with(delimiter.as_bytes()) {
+            if let Some(part_start) = part_start {
+                // the line break before the delimiter belongs to the delimiter
+                let mut part_end = start;
+                if body[..part_end].ends_with(b"\n") {
+                    part_end -= 1;
+                }
+                if body[..part_end].ends_with(b"\r") {
+                    part_end -= 1;
+                }
+                parts.push(&body[part_start..part_end.max(part_start)]);
+            }
+            if trimmed[delimiter.len()..].starts_with(b"--") {
+                return parts;
+            }
+            part_start = Some(end);
+        }
+        start = end;
+    }
+    if let Some(part_start) = part_start {
+        parts.push(&body[part_start..]);
+    }
+    parts
+}
+
+/// Decode quoted-printable, in encoded words (`header`) an underscore is a space
+fn decode_quoted_printable(data: &[u8], header: bool) -> Vec<u8> {
+    let mut result = Vec::with_capacity(data.len());
+    let mut i = 0;
+    while i < data.len() {
+        match data[i] {
+            b'=' => {
+                let rest = &data[i + 1..];
+                if rest.starts_with(b"\r\n") {
+                    i += 3;
+                    continue;
+                } else if rest.starts_with(b"\n") {
+                    i += 2;
+                    continue;
+                }
+                let hex = rest
+                    .get(..2)
+                    .and_then(|hex| std::str::from_utf8(hex).ok())
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                match hex {
+                    Some(byte) => {
+                        result.push(byte);
+                        i += 3;
+                        continue;
+                    }
+                    None => result.push(b'='),
+                }
+            }
+            b'_' if header => result.push(b' '),
+            byte => result.push(byte),
+        }
+        i += 1;
+    }
+    result
+}
+
+/// Decode RFC 2047 encoded words like `=?utf-8?B?...?=`
+fn decode_words(value: &str) -> String {
+    if !value.contains("=?") {
+        return value.to_string();
+    }
+    // whitespace between two encoded words is not part of the text
+    let adjacent = Regex::new(r"\?=\s+=\?").unwrap();
+    let value = adjacent.replace_all(value, "?==?");
+    let word = Regex::new(r"=\?([^?]+)\?([bBqQ])\?([^?]*)\?=").unwrap();
+    word.replace_all(&value, |caps: &Captures| {
+        let bytes = match &caps[2] {
+            "b" | "B" => BASE64.decode(&caps[3]).unwrap_or_default(),
+            _ => decode_quoted_printable(caps[3].as_bytes(), true),
+        };
+        // strip the language from a charset like utf-8*en
+        let charset = caps[1].split('*').next().unwrap_or_default();
+        let encoding = Encoding::for_label(charset.as_bytes()).unwrap_or(UTF_8);
+        encoding.decode_without_bom_handling(&bytes).0.into_owned()
+    })
+    .into_owned()
+}
+
+/// Parameter `name` of a header value like `text/plain; charset="utf-8"`
+fn param(value: &str, name: &str) -> Option<String> {
+    value.split(';').skip(1).find_map(|param| {
+        let (key, value) = param.split_once('=')?;
+        let key = key.trim();
+        if key.eq_ignore_ascii_case(name) {
+            Some(value.trim().trim_matches('"').to_string())
+        } else if key.eq_ignore_ascii_case(&format!("{name}*")) {
+            // RFC 2231: charset'language'percent-encoded
+            let encoded = value.trim().splitn(3, '\'').nth(2)?;
+            Some(percent_decode(encoded))
+        } else {
+            None
+        }
+    })
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut result = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            if let Some(byte) = bytes
+                .get(i + 1..i + 3)
+                .and_then(|hex| std::str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                result.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        result.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&result).into_owned()
+}
+
+/// Readable text from html: scripts and styles removed, block elements on their own line
+pub fn html_to_text(html: &str) -> String {
+    let hidden = Regex::new(r"(?is)<(script|style|head)\b.*?</(script|style|head)\s*>").unwrap();
+    let breaks = Regex::new(r"(?i)<br\s*/?>|</(p|div|tr|li|h[1-6]|table|blockquote)\s*>").unwrap();
+    let tags = Regex::new(r"(?s)<[^>]*>").unwrap();
+    let blank_lines = Regex::new(r"\n\s*\n\s*\n+").unwrap();
+    let text = hidden.replace_all(html, "");
+    let text = breaks.replace_all(&text, "\n");
+    let text = tags.replace_all(&text, "");
+    let text: String = text
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let text = text
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+    blank_lines.replace_all(text.trim(), "\n\n").into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAIL: &[u8] = b"From: =?utf-8?Q?Ren=C3=A9?= <rene@example.com>\r\n\
+Subject: =?UTF-8?B?SGVsbG8=?=\r\n =?UTF-8?B?IHdvcmxk?=\r\n\
+Content-Type: multipart/mixed; boundary=\"outer\"\r\n\
+\r\n\
+preamble\r\n\
+--outer\r\n\
+Content-Type: multipart/alternative; boundary=inner\r\n\
+\r\n\
+--inner\r\n\
+Content-Type: text/plain; charset=iso-8859-1\r\n\
+Content-Transfer-Encoding: quoted-printable\r\n\
+\r\n\
+Caf=E9 au lait, long line =\r\n\
+continued\r\n\
+--inner\r\n\
+Content-Type: text/html\r\n\
+\r\n\
+<p>Caf&eacute;</p>\r\n\
+--inner--\r\n\
+--outer\r\n\
+Content-Type: image/png; name=\"dot.png\"\r\n\
+Content-Disposition: attachment; filename*=utf-8''d%C3%B6t.png\r\n\
+Content-Transfer-Encoding: base64\r\n\
+\r\n\
+iVBO\r\n\
+Rw\r\n\
+--outer--\r\n";
+
+    #[test]
+    fn message() {
+        let message = Message::parse(MAIL);
+        assert_eq!(
+            message.headers.get("from").unwrap(),
+            "René <rene@example.com>"
+        );
+        assert_eq!(message.headers.get("Subject").unwrap(), "Hello world");
+        assert_eq!(message.parts.len(), 3);
+        assert_eq!(
+            message.text_body().unwrap(),
+            "Café au lait, long line continued"
+        );
+        let images: Vec<&Part> = message.images().collect();
+        assert_eq!(images.len(), 1);
+        assert_eq!(images[0].body, vec![0x89, 0x50, 0x4e, 0x47]);
+        assert_eq!(images[0].filename().unwrap(), "döt.png");
+    }
+
+    #[test]
+    fn html() {
+        assert_eq!(
+            html_to_text(
+                "<html><head><title>t</title></head><body><h1>Title</h1>\n<p>a &amp;  b<br>c</p></body></html>"
+            ),
+            "Title\n\na & b\nc"
+        );
+    }
+}
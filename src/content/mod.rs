@@ -17,12 +17,20 @@
 // STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
 // OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-pub mod analyze_text;
+pub mod encoding;
+pub mod font;
 pub mod loader;
+pub mod mail;
+pub mod markdown;
+pub mod mime;
+pub mod notebook;
 pub mod paginated;
 pub mod preview;
+pub mod sheet;
+pub mod table;
 
 use cairo::ImageSurface;
+use encoding_rs::Encoding;
 use exif::Exif;
 use gdk_pixbuf::Pixbuf;
 use resvg::usvg::Tree;
@@ -36,16 +44,16 @@ use std::{
 
 use crate::{
     backends::document::PageMode,
-    content::{
-        paginated::{PaginatedContent, PaginatedContentData},
-        preview::PreviewContent,
-    },
+    content::{paginated::PaginatedContent, preview::PreviewContent},
+    error::MviewResult,
     file_view::model::{BackendRef, Reference, Row},
     image::{
         animation::{Animation, AnimationImage},
+        model::{Mesh, ModelImage},
         provider::gdk::GdkImageLoader,
+        scan::ScanFilters,
         view::{data::TransparencyMode, Zoom, ZoomMode},
-        DualImage, SingleImage,
+        DualImage, Frame, FramesImage, SingleImage,
     },
     rect::{PointD, RectD, SizeD},
     render_thread::model::RenderCommand,
@@ -82,6 +90,7 @@ pub struct DocContent {
     pub page_mode: PageMode,
     pub size: SizeD,
     pub reference: Reference,
+    pub scan_filters: ScanFilters,
 }
 
 impl DocContent {
@@ -117,6 +126,8 @@ pub enum ContentData {
     Single(SingleImage),
     Dual(DualImage),
     Animation(AnimationImage),
+    Frames(FramesImage),
+    Model(ModelImage),
     Svg(SvgContent),
     Doc(DocContent),
     Paginated(PaginatedContent),
@@ -245,6 +256,28 @@ impl Content {
         }
     }
 
+    pub fn new_frames(frames: Vec<Frame>, current: usize) -> Self {
+        Content {
+            id: get_content_id(),
+            data: ContentData::Frames(FramesImage::new(frames, current)),
+            exif: None,
+            zoom_mode: ZoomMode::NotSpecified,
+            transparency_mode: TransparencyMode::NotSpecified,
+            tag: None,
+        }
+    }
+
+    pub fn new_model(mesh: Mesh) -> MviewResult<Self> {
+        Ok(Content {
+            id: get_content_id(),
+            data: ContentData::Model(ModelImage::new(mesh)?),
+            exif: None,
+            zoom_mode: ZoomMode::NotSpecified,
+            transparency_mode: TransparencyMode::NotSpecified,
+            tag: None,
+        })
+    }
+
     pub fn new_svg(
         tree: Tree,
         tag: Option<String>,
@@ -270,6 +303,7 @@ impl Content {
                 page_mode,
                 size,
                 reference,
+                scan_filters: ScanFilters::default(),
             }),
             exif: None,
             zoom_mode: ZoomMode::NotSpecified,
@@ -321,6 +355,8 @@ impl Content {
             ContentData::Single(image) => image.size(),
             ContentData::Dual(image) => image.size(),
             ContentData::Animation(image) => image.size(),
+            ContentData::Frames(image) => image.size(),
+            ContentData::Model(image) => image.size(),
             ContentData::Paginated(image) => image.size(),
             ContentData::Preview(image) => image.size(),
         }
@@ -332,6 +368,8 @@ impl Content {
             ContentData::Single(single) => single.has_alpha(),
             ContentData::Dual(dual) => dual.has_alpha(),
             ContentData::Animation(animation) => animation.has_alpha(),
+            ContentData::Frames(frames) => frames.has_alpha(),
+            ContentData::Model(model) => model.has_alpha(),
             ContentData::Svg(svg) => svg.has_alpha(),
             ContentData::Doc(doc) => doc.has_alpha(),
             ContentData::Paginated(paginated) => paginated.has_alpha(),
@@ -404,6 +442,12 @@ impl Content {
         }
     }
 
+    pub fn clear_area(&self, x: i32, y: i32, width: i32, height: i32) {
+        if let ContentData::Single(single) = &self.data {
+            single.clear_area(x, y, width, height);
+        }
+    }
+
     /// Double click handling depends on content
     ///
     /// List
@@ -417,7 +461,7 @@ impl Content {
     ///
     /// None: do nothing
     ///
-    /// Image (Single, Dual, Animation, Svg): do nothing
+    /// Image (Single, Dual, Animation, Frames, Model, Svg): do nothing
     ///
     /// Doc: do nothing,
     pub fn double_click(&self, position: PointD) -> Reference {
@@ -430,15 +474,47 @@ impl Content {
         }
     }
 
+    /// Returns `true` if the content needs to be redrawn
     pub fn sort(&mut self, sort: &str) -> bool {
         if let ContentData::Paginated(paginated) = &mut self.data {
-            if let PaginatedContentData::List(list) = &mut paginated.data {
-                list.sort(sort);
-                paginated.prepare();
-                return true;
-            }
+            paginated.sort(sort)
+        } else {
+            false
+        }
+    }
+
+    /// Name of the encoding used to decode text content
+    pub fn text_encoding(&self) -> Option<&'static str> {
+        match &self.data {
+            ContentData::Paginated(paginated) => paginated.text_encoding(),
+            _ => None,
+        }
+    }
+
+    pub fn set_text_encoding(&mut self, encoding: &'static Encoding) -> bool {
+        if let ContentData::Paginated(paginated) = &mut self.data {
+            paginated.set_text_encoding(encoding)
+        } else {
+            false
+        }
+    }
+
+    pub fn set_table_view(&mut self, show_table: bool) -> bool {
+        if let ContentData::Paginated(paginated) = &mut self.data {
+            paginated.set_table_view(show_table)
+        } else {
+            false
+        }
+    }
+
+    pub fn is_doc(&self) -> bool {
+        matches!(self.data, ContentData::Doc(_))
+    }
+
+    pub fn set_scan_filters(&mut self, scan_filters: ScanFilters) {
+        if let ContentData::Doc(doc) = &mut self.data {
+            doc.scan_filters = scan_filters;
         }
-        false
     }
 
     pub fn can_enter(&self) -> bool {
@@ -454,4 +530,6 @@ impl Content {
     }
 
     content_getter!(animation, animation_mut, Animation, AnimationImage);
+    content_getter!(frames, frames_mut, Frames, FramesImage);
+    content_getter!(model, model_mut, Model, ModelImage);
 }
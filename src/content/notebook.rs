@@ -0,0 +1,336 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Jupyter notebooks (.ipynb) rendered as text sheets
+//!
+//! Markdown cells go through the markdown renderer, code cells are highlighted with the
+//! kernel language and outputs are shown below their cell. Images in the outputs are decoded
+//! from base64 and placed inline.
+
+use std::io::Cursor;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use image::ImageReader;
+use regex::Regex;
+use serde_json::Value;
+use syntect::{easy::HighlightLines, highlighting::Style};
+
+use crate::{
+    config::config,
+    content::{
+        markdown::{self, Block},
+        paginated::{limit_string, LINES_PER_PAGE, MAX_LINE_LENGTH},
+        sheet::{Row, LINE_HEIGHT},
+    },
+    error::MviewResult,
+    image::{colors::Color, svg::creator::FontWeight},
+    mview6_error,
+    rect::SizeD,
+};
+
+/// Maximum size of a notebook file, images make notebooks much larger than plain text
+pub const MAX_NOTEBOOK_SIZE: u64 = 32 * 1024 * 1024;
+
+/// Width of the `In [n]:` prompt in characters
+const PROMPT_WIDTH: usize = 9;
+
+/// Maximum width of an output image in pixels
+const IMAGE_MAX_WIDTH: f64 = 1000.0;
+
+#[derive(Debug, PartialEq)]
+pub enum Output {
+    Text(String),
+    Error(String),
+    Image { mime: String, data: Vec<u8> },
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Cell {
+    Markdown(String),
+    Code {
+        source: String,
+        execution_count: Option<u64>,
+        outputs: Vec<Output>,
+    },
+    Raw(String),
+}
+
+pub struct Notebook {
+    pub language: String,
+    pub cells: Vec<Cell>,
+}
+
+impl Notebook {
+    pub fn parse(data: &[u8]) -> MviewResult<Self> {
+        let json: Value = serde_json::from_slice(data)
+            .map_err(|e| mview6_error!(format!("Invalid notebook: {e}")))?;
+        let metadata = &json["metadata"];
+        let language = [
+            &metadata["language_info"]["name"],
+            &metadata["kernelspec"]["language"],
+        ]
+        .into_iter()
+        .find_map(Value::as_str)
+        .unwrap_or("python")
+        .to_string();
+        let cells = json["cells"]
+            .as_array()
+            .ok_or_else(|| mview6_error!("Notebook has no cells"))?
+            .iter()
+            .map(Self::cell)
+            .collect();
+        Ok(Self { language, cells })
+    }
+
+    fn cell(cell: &Value) -> Cell {
+        let source = text(&cell["source"]);
+        match cell["cell_type"].as_str() {
+            Some("markdown") => Cell::Markdown(source),
+            Some("code") => Cell::Code {
+                source,
+                execution_count: cell["execution_count"].as_u64(),
+                outputs: cell["outputs"]
+                    .as_array()
+                    .map(|outputs| outputs.iter().filter_map(Self::output).collect())
+                    .unwrap_or_default(),
+            },
+            _ => Cell::Raw(source),
+        }
+    }
+
+    fn output(output: &Value) -> Option<Output> {
+        match output["output_type"].as_str()? {
+            "stream" => Some(Output::Text(text(&output["text"]))),
+            "error" => {
+                let traceback = text_lines(&output["traceback"]);
+                Some(Output::Error(if traceback.is_empty() {
+                    format!("{}: {}", text(&output["ename"]), text(&output["evalue"]))
+                } else {
+                    traceback
+                }))
+            }
+            "execute_result" | "display_data" => {
+                let data = &output["data"];
+                ["image/png", "image/jpeg", "image/gif"]
+                    .into_iter()
+                    .find_map(|mime| {
+                        let encoded: String = text(data.get(mime)?)
+                            .chars()
+                            .filter(|c| !c.is_whitespace())
+                            .collect();
+                        STANDARD.decode(encoded).ok().map(|data| Output::Image {
+                            mime: mime.to_string(),
+                            data,
+                        })
+                    })
+                    .or_else(|| {
+                        data.get("text/plain")
+                            .map(|plain| Output::Text(text(plain)))
+                    })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Notebook text is either a string or an array of lines including their line breaks
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Array(lines) => lines.iter().filter_map(Value::as_str).collect(),
+        _ => String::new(),
+    }
+}
+
+/// Lines without line breaks (tracebacks), ANSI color codes removed
+fn text_lines(value: &Value) -> String {
+    let ansi = Regex::new(r"\x1b\[[0-9;]*m").unwrap();
+    match value {
+        Value::Array(lines) => lines
+            .iter()
+            .filter_map(Value::as_str)
+            .map(|line| ansi.replace_all(line, "").into_owned())
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+impl Notebook {
+    /// Lay out the cells as rows for a sheet
+    pub fn rows(&self) -> Vec<Row> {
+        let ps = &config().ps;
+        let syntax = ps
+            .find_syntax_by_token(&self.language)
+            .unwrap_or_else(|| ps.find_syntax_plain_text());
+        let theme = config().ts.themes.get("base16-mocha.dark").unwrap();
+
+        let mut rows = Vec::new();
+        for cell in &self.cells {
+            if !rows.is_empty() {
+                rows.push(Row::blank());
+            }
+            match cell {
+                Cell::Markdown(source) => {
+                    for line in markdown::render(source, MAX_LINE_LENGTH) {
+                        let (color, weight) = match line.block {
+                            Block::Heading(1) => (Color::Yellow, FontWeight::Bold),
+                            Block::Heading(_) => (Color::FolderTitle, FontWeight::Bold),
+                            Block::Paragraph | Block::Bullet => {
+                                (Color::Gainsboro, FontWeight::Normal)
+                            }
+                            Block::Quote => (Color::Gray, FontWeight::Normal),
+                            Block::Code => (Color::Cyan, FontWeight::Normal),
+                            Block::Rule => (Color::DimGray, FontWeight::Normal),
+                        };
+                        rows.push(Row::Spans(
+                            vec![(limit_string(&line.text), color.into())],
+                            weight,
+                        ));
+                    }
+                }
+                Cell::Code {
+                    source,
+                    execution_count,
+                    outputs,
+                } => {
+                    let mut h = HighlightLines::new(syntax, theme);
+                    let prompt = match execution_count {
+                        Some(count) => format!("In [{count}]:"),
+                        None => "In [ ]:".to_string(),
+                    };
+                    for (i, line) in source.lines().enumerate() {
+                        let lead = if i == 0 { prompt.as_str() } else { "" };
+                        let line = line
+                            .chars()
+                            .take(MAX_LINE_LENGTH - PROMPT_WIDTH)
+                            .collect::<String>();
+                        let ranges: Vec<(Style, &str)> =
+                            h.highlight_line(&line, ps).unwrap_or_default();
+                        let mut spans =
+                            vec![(format!("{lead:<PROMPT_WIDTH$}"), Color::Glaucous.into())];
+                        spans.extend(
+                            ranges
+                                .iter()
+                                .map(|(style, text)| (text.to_string(), style.foreground.into())),
+                        );
+                        rows.push(Row::Spans(spans, FontWeight::Normal));
+                    }
+                    for output in outputs {
+                        Self::output_rows(&mut rows, output);
+                    }
+                }
+                Cell::Raw(source) => {
+                    rows.extend(
+                        source
+                            .lines()
+                            .map(|line| Row::text(&limit_string(line), Color::DarkGray)),
+                    );
+                }
+            }
+        }
+        rows
+    }
+
+    fn output_rows(rows: &mut Vec<Row>, output: &Output) {
+        let indent = " ".repeat(PROMPT_WIDTH);
+        match output {
+            Output::Text(text) | Output::Error(text) => {
+                let color = match output {
+                    Output::Error(_) => Color::Red,
+                    _ => Color::Silver,
+                };
+                rows.extend(text.lines().map(|line| {
+                    let line: String = line.chars().take(MAX_LINE_LENGTH - PROMPT_WIDTH).collect();
+                    Row::text(&format!("{indent}{line}"), color)
+                }));
+            }
+            Output::Image { mime, data } => {
+                let dimensions = ImageReader::new(Cursor::new(data))
+                    .with_guessed_format()
+                    .ok()
+                    .and_then(|reader| reader.into_dimensions().ok());
+                match dimensions {
+                    Some((width, height)) if width > 0 && height > 0 => {
+                        let max_height = (LINES_PER_PAGE - 2) as f64 * LINE_HEIGHT;
+                        let scale = (IMAGE_MAX_WIDTH / width as f64)
+                            .min(max_height / height as f64)
+                            .min(1.0);
+                        rows.push(Row::Image {
+                            href: format!("data:{mime};base64,{}", STANDARD.encode(data)),
+                            size: SizeD::new(width as f64 * scale, height as f64 * scale),
+                            indent: PROMPT_WIDTH,
+                        });
+                    }
+                    _ => rows.push(Row::text(&format!("{indent}[{mime}]"), Color::Silver)),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let notebook = Notebook::parse(
+            br##"{
+                "metadata": {"kernelspec": {"language": "python"}},
+                "cells": [
+                    {"cell_type": "markdown", "source": ["# Title\n", "text"]},
+                    {"cell_type": "code", "execution_count": 3, "source": "print(1)",
+                     "outputs": [
+                        {"output_type": "stream", "name": "stdout", "text": ["1\n"]},
+                        {"output_type": "display_data",
+                         "data": {"image/png": "iVBO\nRw==", "text/plain": "<Figure>"}},
+                        {"output_type": "execute_result", "data": {"text/plain": ["42"]}},
+                        {"output_type": "error", "ename": "E", "evalue": "v",
+                         "traceback": ["\u001b[0;31mE\u001b[0m: v"]}
+                     ]},
+                    {"cell_type": "raw", "source": "raw"}
+                ]
+            }"##,
+        )
+        .unwrap();
+        assert_eq!(notebook.language, "python");
+        assert_eq!(
+            notebook.cells,
+            vec![
+                Cell::Markdown("# Title\ntext".into()),
+                Cell::Code {
+                    source: "print(1)".into(),
+                    execution_count: Some(3),
+                    outputs: vec![
+                        Output::Text("1\n".into()),
+                        Output::Image {
+                            mime: "image/png".into(),
+                            data: vec![0x89, 0x50, 0x4e, 0x47],
+                        },
+                        Output::Text("42".into()),
+                        Output::Error("E: v".into()),
+                    ],
+                },
+                Cell::Raw("raw".into()),
+            ]
+        );
+        assert!(Notebook::parse(b"{}").is_err());
+    }
+}
@@ -18,6 +18,7 @@
 // OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use std::{
+    cmp::Ordering,
     path::{Path, PathBuf},
     sync::Arc,
 };
@@ -27,9 +28,12 @@ use human_bytes::human_bytes;
 use resvg::usvg::Tree;
 use syntect::{easy::HighlightLines, highlighting::Style};
 
+use encoding_rs::Encoding;
+
 use crate::{
     classification::FileType,
     config::config,
+    content::{encoding::decode, sheet::SheetContent, table::Table},
     error::MviewResult,
     file_view::{
         model::{BackendRef, ItemRef, Reference, Row},
@@ -37,7 +41,10 @@ use crate::{
     },
     image::{
         colors::{Color, MViewColor},
-        svg::text_sheet::{svg_options, TextSheet},
+        svg::{
+            creator::FontWeight,
+            text_sheet::{svg_options, TextSheet},
+        },
     },
     profile::performance::Performance,
     rect::{PointD, SizeD},
@@ -55,6 +62,9 @@ pub const WIDTH_ASCII: f64 = 5.4;
 
 pub const MAX_LINE_LENGTH: usize = 142;
 
+/// Table rows per page, the header and its underline take two lines
+pub const ROWS_PER_PAGE: usize = LINES_PER_PAGE - 2;
+
 pub struct RawContent {
     pub path: PathBuf,
     pub data: Arc<Vec<u8>>,
@@ -150,31 +160,86 @@ pub struct TextContent {
     pub path: PathBuf,
     pub syntax_ext: String,
     pub text: Arc<Vec<String>>,
+    pub data: Arc<Vec<u8>>,
+    pub encoding: &'static Encoding,
+    pub table: Option<Table>,
+    pub show_table: bool,
 }
 
 impl TextContent {
-    pub fn new<P: AsRef<Path>>(path: P, text: Vec<String>) -> Self {
+    pub fn new<P: AsRef<Path>>(path: P, data: Vec<u8>, encoding: &'static Encoding) -> Self {
         let extension = path_to_extension(&path);
         let syntax_ext = match config().ps.find_syntax_by_extension(&extension) {
             Some(_) => extension,
             None => "txt".to_string(),
         };
+        let text = Self::decode(&data, encoding);
         Self {
             path: path.as_ref().into(),
+            table: Table::from_text(&extension, &text),
+            show_table: true,
             text: text.into(),
+            data: data.into(),
+            encoding,
             syntax_ext,
         }
     }
 
+    fn decode(data: &[u8], encoding: &'static Encoding) -> Vec<String> {
+        decode(data, encoding)
+            .lines()
+            .map(|line| line.to_string())
+            .collect()
+    }
+
+    /// Decode the text again with a different encoding
+    pub fn set_encoding(&mut self, encoding: &'static Encoding) {
+        let text = Self::decode(&self.data, encoding);
+        self.table = Table::from_text(&path_to_extension(&self.path), &text);
+        self.text = text.into();
+        self.encoding = encoding;
+    }
+
+    fn shown_table(&self) -> Option<&Table> {
+        self.table.as_ref().filter(|_| self.show_table)
+    }
+
     pub fn size(&self) -> SizeD {
         SizeD::new(1200.0, 800.0)
     }
 
     pub fn num_pages(&self) -> usize {
-        1 + (self.text.len().saturating_sub(1) / LINES_PER_PAGE)
+        match self.shown_table() {
+            Some(table) => 1 + (table.body().len().saturating_sub(1) / ROWS_PER_PAGE),
+            None => 1 + (self.text.len().saturating_sub(1) / LINES_PER_PAGE),
+        }
+    }
+
+    pub fn word_count(&self, page: usize) -> usize {
+        match self.shown_table() {
+            Some(table) => table
+                .body()
+                .iter()
+                .skip(page * ROWS_PER_PAGE)
+                .take(ROWS_PER_PAGE)
+                .flatten()
+                .map(|cell| cell.split_whitespace().count())
+                .sum(),
+            None => self
+                .text
+                .iter()
+                .skip(page * LINES_PER_PAGE)
+                .take(LINES_PER_PAGE)
+                .map(|line| line.split_whitespace().count())
+                .sum(),
+        }
     }
 
     pub fn prepare(&self, page: usize) -> MviewResult<Tree> {
+        if let Some(table) = self.shown_table() {
+            return self.prepare_table(table, page);
+        }
+
         let syntax = config()
             .ps
             .find_syntax_by_extension(&self.syntax_ext)
@@ -206,6 +271,52 @@ impl TextContent {
         let svg_content = sheet.finish().render();
         Ok(Tree::from_str(&svg_content, &svg_options())?)
     }
+
+    fn prepare_table(&self, table: &Table, page: usize) -> MviewResult<Tree> {
+        let mut sheet = TextSheet::new(1200, 800, FONT_SIZE);
+        sheet.header(&self.path, FONT_SIZE_TITLE, 81);
+
+        sheet.delta_y(1.5);
+        let header = table.format_row(table.header(), MAX_LINE_LENGTH);
+        sheet.add_mulit_color_fragment(
+            Self::table_spans(&header, Color::Yellow, Color::Cyan),
+            sheet.base_style().font_weight(FontWeight::Bold),
+        );
+        sheet.add_line(
+            &"─".repeat(table.width(MAX_LINE_LENGTH)),
+            sheet.base_style().color(Color::DimGray),
+        );
+
+        for row in table
+            .body()
+            .iter()
+            .skip(page * ROWS_PER_PAGE)
+            .take(ROWS_PER_PAGE)
+        {
+            let cells = table.format_row(row, MAX_LINE_LENGTH);
+            sheet.delta_y(1.5);
+            sheet.add_mulit_color_fragment(
+                Self::table_spans(&cells, Color::White, Color::Silver),
+                sheet.base_style(),
+            );
+        }
+
+        sheet.show_page_no(page, self.num_pages());
+        let svg_content = sheet.finish().render();
+        Ok(Tree::from_str(&svg_content, &svg_options())?)
+    }
+
+    /// Alternate the color of the columns
+    fn table_spans(cells: &[String], even: Color, odd: Color) -> Vec<(&str, MViewColor)> {
+        cells
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| {
+                let color = if i % 2 == 0 { even } else { odd };
+                (cell.as_str(), color.into())
+            })
+            .collect()
+    }
 }
 
 impl From<syntect::highlighting::Color> for MViewColor {
@@ -214,7 +325,7 @@ impl From<syntect::highlighting::Color> for MViewColor {
     }
 }
 
-fn limit_string(s: &str) -> String {
+pub fn limit_string(s: &str) -> String {
     if s.chars().count() <= MAX_LINE_LENGTH {
         s.to_string()
     } else {
@@ -316,37 +427,60 @@ impl ListContent {
         self.list.get(page * LINES_PER_PAGE + n as usize)
     }
 
-    pub fn sort(&mut self, sort: &str) {
-        let mut list = self.list.as_ref().clone();
-        match sort {
-            // "0a" => x.sort_by_key(|r| r.category),          // Ascending
-            // "0d" => x.sort_by_key(|r| Reverse(r.category)), // Descending
-            "0a" => list.sort_by(|a, b| {
-                a.content_type
-                    .cmp(&b.content_type)
-                    .then(a.name.cmp(&b.name))
-            }), // Ascending
-            "0d" => list.sort_by(|a, b| {
-                b.content_type
-                    .cmp(&a.content_type)
-                    .then(b.name.cmp(&a.name))
-            }), // Descending
-            "1a" => list.sort_by(|a, b| a.name.cmp(&b.name)), // Ascending
-            "1d" => list.sort_by(|a, b| b.name.cmp(&a.name)), // Descending
-            "2a" => list.sort_by(|a, b| a.size.cmp(&b.size)), // Ascending
-            "2d" => list.sort_by(|a, b| b.size.cmp(&a.size)), // Descending
-            "3a" => list.sort_by(|a, b| a.modified.cmp(&b.modified)), // Ascending
-            "3d" => list.sort_by(|a, b| b.modified.cmp(&a.modified)), // Descending
-            _ => (),
+    /// Names of the rows shown on `page`
+    fn page_names(&self, page: usize) -> Vec<String> {
+        self.list
+            .iter()
+            .skip(page * LINES_PER_PAGE)
+            .take(LINES_PER_PAGE)
+            .map(|row| row.name.clone())
+            .collect()
+    }
+
+    /// Stable sort in place: the rows are not copied (unless the list is still shared
+    /// with a previous rendering) and the sort is close to linear when the list is
+    /// already (reverse) sorted. Returns `false` if the order did not change.
+    pub fn sort(&mut self, sort: &str) -> bool {
+        let Some(order) = row_order(sort) else {
+            return false;
         };
-        self.list = list.into();
+        if self.list.is_sorted_by(|a, b| order(a, b).is_le()) {
+            return false;
+        }
+        Arc::make_mut(&mut self.list).sort_by(order);
+        true
     }
 }
 
+/// Order of the rows for a sort as in [`crate::file_view::Sort::str_repr`]: column and
+/// (a)scending or (d)escending
+fn row_order(sort: &str) -> Option<fn(&Row, &Row) -> Ordering> {
+    Some(match sort {
+        "0a" => |a, b| {
+            a.content_type
+                .cmp(&b.content_type)
+                .then(a.name.cmp(&b.name))
+        },
+        "0d" => |a, b| {
+            b.content_type
+                .cmp(&a.content_type)
+                .then(b.name.cmp(&a.name))
+        },
+        "1a" => |a, b| a.name.cmp(&b.name),
+        "1d" => |a, b| b.name.cmp(&a.name),
+        "2a" => |a, b| a.size.cmp(&b.size),
+        "2d" => |a, b| b.size.cmp(&a.size),
+        "3a" => |a, b| a.modified.cmp(&b.modified),
+        "3d" => |a, b| b.modified.cmp(&a.modified),
+        _ => return None,
+    })
+}
+
 pub enum PaginatedContentData {
     Raw(RawContent),
     Text(TextContent),
     List(ListContent),
+    Sheet(SheetContent),
 }
 
 pub struct PaginatedContent {
@@ -356,9 +490,9 @@ pub struct PaginatedContent {
 }
 
 impl PaginatedContent {
-    pub fn new_text<P: AsRef<Path>>(path: P, lines: Vec<String>) -> Self {
+    pub fn new_text<P: AsRef<Path>>(path: P, data: Vec<u8>, encoding: &'static Encoding) -> Self {
         Self {
-            data: PaginatedContentData::Text(TextContent::new(path, lines)),
+            data: PaginatedContentData::Text(TextContent::new(path, data, encoding)),
             page: 0,
             rendered: None,
         }
@@ -375,6 +509,14 @@ impl PaginatedContent {
         }
     }
 
+    pub fn new_sheet(sheet: SheetContent) -> Self {
+        Self {
+            data: PaginatedContentData::Sheet(sheet),
+            page: 0,
+            rendered: None,
+        }
+    }
+
     pub fn new_list<P: AsRef<Path>>(path: P, reference: BackendRef, list: Vec<Row>) -> Self {
         Self {
             data: PaginatedContentData::List(ListContent {
@@ -387,6 +529,29 @@ impl PaginatedContent {
         }
     }
 
+    /// Sort a list, staying on the page that shows the row that was at the top. Returns
+    /// `true` if the page has to be rendered again.
+    pub fn sort(&mut self, sort: &str) -> bool {
+        let PaginatedContentData::List(list) = &mut self.data else {
+            return false;
+        };
+        let shown = list.page_names(self.page);
+        if !list.sort(sort) {
+            return false;
+        }
+        if let Some(index) = shown
+            .first()
+            .and_then(|first| list.list.iter().position(|row| row.name == *first))
+        {
+            self.page = index / LINES_PER_PAGE;
+        }
+        if list.page_names(self.page) == shown {
+            return false;
+        }
+        self.prepare();
+        true
+    }
+
     pub fn is_list(&self) -> bool {
         matches!(self.data, PaginatedContentData::List(_))
     }
@@ -407,6 +572,7 @@ impl PaginatedContent {
             PaginatedContentData::Raw(content) => content.prepare(self.page),
             PaginatedContentData::Text(content) => content.prepare(self.page),
             PaginatedContentData::List(content) => content.prepare(self.page),
+            PaginatedContentData::Sheet(content) => content.prepare(self.page),
         };
         if let Err(e) = &rendered {
             eprintln!("Content:prepare failed {e:#?}");
@@ -420,6 +586,47 @@ impl PaginatedContent {
             PaginatedContentData::Raw(content) => content.num_pages(),
             PaginatedContentData::Text(content) => content.num_pages(),
             PaginatedContentData::List(content) => content.num_pages(),
+            PaginatedContentData::Sheet(content) => content.num_pages(),
+        }
+    }
+
+    pub fn text_encoding(&self) -> Option<&'static str> {
+        match &self.data {
+            PaginatedContentData::Text(content) => Some(content.encoding.name()),
+            _ => None,
+        }
+    }
+
+    /// Re-decode text with `encoding`, returns `false` if the content is not text
+    pub fn set_text_encoding(&mut self, encoding: &'static Encoding) -> bool {
+        if let PaginatedContentData::Text(content) = &mut self.data {
+            content.set_encoding(encoding);
+            self.page = self.page.min(self.num_pages() - 1);
+            self.prepare();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Show delimited text as a table or as plain text, returns `false` if there is no table
+    pub fn set_table_view(&mut self, show_table: bool) -> bool {
+        if let PaginatedContentData::Text(content) = &mut self.data {
+            if content.table.is_some() && content.show_table != show_table {
+                content.show_table = show_table;
+                self.page = self.page.min(self.num_pages() - 1);
+                self.prepare();
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Number of words on the current page, only for text
+    pub fn page_word_count(&self) -> Option<usize> {
+        match &self.data {
+            PaginatedContentData::Text(content) => Some(content.word_count(self.page)),
+            _ => None,
         }
     }
 
@@ -466,3 +673,33 @@ impl PaginatedContent {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::classification::FileClassification;
+
+    fn row(name: &str, size: u64) -> Row {
+        let cat = FileClassification::determine(Path::new(name), false);
+        Row::new(cat, name.to_string(), size, 0)
+    }
+
+    fn names(list: &ListContent) -> Vec<&str> {
+        list.list.iter().map(|row| row.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_list_sort() {
+        let rows = vec![row("b.jpg", 2), row("a.jpg", 2), row("c.jpg", 1)];
+        let mut list = ListContent::new("/tmp".into(), BackendRef::FileSystem("/tmp".into()), rows);
+        assert!(!list.sort("unknown"));
+        // Stable: rows of equal size keep their order
+        assert!(list.sort("2a"));
+        assert_eq!(names(&list), ["c.jpg", "b.jpg", "a.jpg"]);
+        assert!(!list.sort("2a"));
+        // Already in this order
+        assert!(!list.sort("1d"));
+        assert!(list.sort("1a"));
+        assert_eq!(names(&list), ["a.jpg", "b.jpg", "c.jpg"]);
+    }
+}
@@ -0,0 +1,162 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Paginated sheet built from prepared rows of colored text and images
+//!
+//! Used for content that is laid out once when loaded, like notebooks and email messages.
+
+use std::{
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use resvg::usvg::Tree;
+
+use crate::{
+    content::paginated::{FONT_SIZE, FONT_SIZE_TITLE, LINES_PER_PAGE},
+    error::MviewResult,
+    image::{
+        colors::{Color, MViewColor},
+        svg::{
+            creator::FontWeight,
+            text_sheet::{svg_options, TextSheet},
+        },
+    },
+    rect::SizeD,
+};
+
+/// Advance of a monospace character relative to the font size
+const CHAR_WIDTH: f64 = 0.6;
+
+/// Height of a line on the sheet in pixels
+pub const LINE_HEIGHT: f64 = FONT_SIZE as f64 * 1.5;
+
+pub enum Row {
+    Spans(Vec<(String, MViewColor)>, FontWeight),
+    /// Image given as (data) url, indented by a number of characters
+    Image {
+        href: String,
+        size: SizeD,
+        indent: usize,
+    },
+}
+
+impl Row {
+    pub fn text(text: &str, color: Color) -> Self {
+        Self::Spans(vec![(text.to_string(), color.into())], FontWeight::Normal)
+    }
+
+    pub fn blank() -> Self {
+        Self::text("", Color::Black)
+    }
+
+    fn is_blank(&self) -> bool {
+        matches!(self, Row::Spans(spans, _) if spans.iter().all(|(text, _)| text.is_empty()))
+    }
+
+    /// Number of lines taken on the sheet
+    fn lines(&self) -> usize {
+        match self {
+            Row::Spans(..) => 1,
+            Row::Image { size, .. } => (size.height() / LINE_HEIGHT).ceil() as usize + 1,
+        }
+    }
+}
+
+pub struct SheetContent {
+    pub path: PathBuf,
+    rows: Vec<Row>,
+    pages: Vec<Range<usize>>,
+}
+
+impl SheetContent {
+    pub fn new<P: AsRef<Path>>(path: P, rows: Vec<Row>) -> Self {
+        let pages = Self::paginate(&rows);
+        Self {
+            path: path.as_ref().into(),
+            rows,
+            pages,
+        }
+    }
+
+    pub fn num_pages(&self) -> usize {
+        self.pages.len().max(1)
+    }
+
+    /// Fill pages with rows, a page never starts with a blank row
+    fn paginate(rows: &[Row]) -> Vec<Range<usize>> {
+        let mut pages = Vec::new();
+        let mut start = 0;
+        let mut used = 0;
+        for (i, row) in rows.iter().enumerate() {
+            let lines = row.lines();
+            if used + lines > LINES_PER_PAGE && i > start {
+                pages.push(start..i);
+                start = i;
+                used = 0;
+            }
+            if used == 0 && row.is_blank() {
+                start = i + 1;
+                continue;
+            }
+            used += lines;
+        }
+        if start < rows.len() {
+            pages.push(start..rows.len());
+        }
+        pages
+    }
+
+    pub fn prepare(&self, page: usize) -> MviewResult<Tree> {
+        let mut sheet = TextSheet::new(1200, 800, FONT_SIZE);
+        sheet.header(&self.path, FONT_SIZE_TITLE, 81);
+
+        if let Some(range) = self.pages.get(page) {
+            for row in &self.rows[range.clone()] {
+                match row {
+                    Row::Spans(spans, weight) => {
+                        sheet.delta_y(1.5);
+                        let spans = spans
+                            .iter()
+                            .map(|(text, color)| (text.as_str(), *color))
+                            .collect();
+                        sheet.add_mulit_color_fragment(
+                            spans,
+                            sheet.base_style().font_weight(weight.clone()),
+                        );
+                    }
+                    Row::Image { href, size, indent } => {
+                        // top of the image just below the previous line
+                        sheet.delta_y(0.5);
+                        let top = sheet.pos();
+                        sheet
+                            .set_pos(top + sheet.base_style().delta_x(*indent as f64 * CHAR_WIDTH));
+                        sheet.add_image(*size, href);
+                        sheet.set_pos(top);
+                        sheet.delta_y(1.5 * (row.lines() - 1) as f64);
+                    }
+                }
+            }
+        }
+
+        sheet.show_page_no(page, self.num_pages());
+        let svg_content = sheet.finish().render();
+        Ok(Tree::from_str(&svg_content, &svg_options())?)
+    }
+}
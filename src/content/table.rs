@@ -0,0 +1,255 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Delimited text (CSV and TSV) shown as a table
+//!
+//! Columns are sized to their widest cell up to `MAX_COLUMN_WIDTH` characters; longer cells
+//! are truncated with an ellipsis. Columns that only contain numbers are right aligned.
+
+/// Maximum width of a column in characters
+pub const MAX_COLUMN_WIDTH: usize = 30;
+
+/// Spaces between two columns
+const COLUMN_GAP: usize = 2;
+
+/// Number of lines inspected to guess the delimiter of a CSV file
+const SNIFF_LINES: usize = 20;
+
+pub struct Table {
+    pub rows: Vec<Vec<String>>,
+    widths: Vec<usize>,
+    numeric: Vec<bool>,
+}
+
+impl Table {
+    /// Parse text as a table when the extension indicates delimited data
+    pub fn from_text(extension: &str, lines: &[String]) -> Option<Self> {
+        let delimiter = match extension {
+            "tsv" | "tab" => '\t',
+            "csv" => sniff_delimiter(lines),
+            _ => return None,
+        };
+        let rows = parse(lines, delimiter);
+        if rows.is_empty() {
+            None
+        } else {
+            Some(Self::new(rows))
+        }
+    }
+
+    fn new(rows: Vec<Vec<String>>) -> Self {
+        let columns = rows.iter().map(|row| row.len()).max().unwrap_or_default();
+        let mut widths = vec![1; columns];
+        let mut numeric = vec![true; columns];
+        for (row_index, row) in rows.iter().enumerate() {
+            for (column, cell) in row.iter().enumerate() {
+                widths[column] = widths[column].max(cell.chars().count().min(MAX_COLUMN_WIDTH));
+                if row_index > 0 && !cell.is_empty() && cell.trim().parse::<f64>().is_err() {
+                    numeric[column] = false;
+                }
+            }
+        }
+        Self {
+            rows,
+            widths,
+            numeric,
+        }
+    }
+
+    /// The first row, shown on top of every page
+    pub fn header(&self) -> &[String] {
+        &self.rows[0]
+    }
+
+    /// All rows except the header
+    pub fn body(&self) -> &[Vec<String>] {
+        &self.rows[1..]
+    }
+
+    /// Width in characters of the columns that fit in `max_len`
+    pub fn width(&self, max_len: usize) -> usize {
+        self.layout(max_len)
+            .last()
+            .map_or(0, |(start, width)| start + width)
+    }
+
+    /// Start and width of the columns that fit in `max_len`
+    fn layout(&self, max_len: usize) -> Vec<(usize, usize)> {
+        let mut start = 0;
+        let mut layout = Vec::new();
+        for &width in &self.widths {
+            if start + width > max_len {
+                break;
+            }
+            layout.push((start, width));
+            start += width + COLUMN_GAP;
+        }
+        layout
+    }
+
+    /// Cells of `row` padded and truncated to their column width, columns that do not fit
+    /// in `max_len` are left out
+    pub fn format_row(&self, row: &[String], max_len: usize) -> Vec<String> {
+        let layout = self.layout(max_len);
+        let last = layout.len().saturating_sub(1);
+        layout
+            .iter()
+            .enumerate()
+            .map(|(column, &(_, width))| {
+                let cell = row.get(column).map(String::as_str).unwrap_or_default();
+                let mut text = fit(cell, width, self.numeric[column]);
+                if column != last {
+                    text.push_str(&" ".repeat(COLUMN_GAP));
+                }
+                text
+            })
+            .collect()
+    }
+}
+
+/// Pad or truncate `cell` to exactly `width` characters
+fn fit(cell: &str, width: usize, align_right: bool) -> String {
+    let cell = cell.replace(['\t', '\n', '\r'], " ");
+    let len = cell.chars().count();
+    if len > width {
+        let mut text: String = cell.chars().take(width - 1).collect();
+        text.push('…');
+        text
+    } else if align_right {
+        format!("{cell:>width$}")
+    } else {
+        format!("{cell:<width$}")
+    }
+}
+
+/// Guess the delimiter of a CSV file: the candidate that splits the first lines into the
+/// same number of fields most consistently
+fn sniff_delimiter(lines: &[String]) -> char {
+    let lines = &lines[..lines.len().min(SNIFF_LINES)];
+    // max_by_key returns the last maximum, so the comma wins a tie
+    ['|', '\t', ';', ',']
+        .into_iter()
+        .max_by_key(|&delimiter| {
+            let count = |line: &String| line.matches(delimiter).count();
+            match lines.first().map(count) {
+                Some(0) | None => 0,
+                Some(first) => lines.iter().filter(|line| count(line) == first).count(),
+            }
+        })
+        .unwrap_or(',')
+}
+
+/// Split lines into fields, quoted fields may contain delimiters, doubled quotes and line breaks
+fn parse(lines: &[String], delimiter: char) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    for line in lines {
+        let mut chars = line.chars().peekable();
+        while let Some(c) = chars.next() {
+            if quoted {
+                if c == '"' {
+                    if chars.peek() == Some(&'"') {
+                        chars.next();
+                        field.push('"');
+                    } else {
+                        quoted = false;
+                    }
+                } else {
+                    field.push(c);
+                }
+            } else if c == '"' && field.is_empty() {
+                quoted = true;
+            } else if c == delimiter {
+                row.push(std::mem::take(&mut field));
+            } else {
+                field.push(c);
+            }
+        }
+        if quoted {
+            field.push('\n');
+        } else {
+            row.push(std::mem::take(&mut field));
+            if !(row.len() == 1 && row[0].is_empty()) {
+                rows.push(std::mem::take(&mut row));
+            }
+            row.clear();
+        }
+    }
+    if quoted {
+        row.push(field);
+        rows.push(row);
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn delimiter() {
+        assert_eq!(sniff_delimiter(&lines("a,b,c\n1,2,3")), ',');
+        assert_eq!(sniff_delimiter(&lines("a;b;c\n1,5;2,5;3")), ';');
+        assert_eq!(sniff_delimiter(&lines("a\tb\n1\t2")), '\t');
+        assert_eq!(sniff_delimiter(&lines("single column")), ',');
+    }
+
+    #[test]
+    fn quoting() {
+        let rows = parse(
+            &lines("name,remark\n\"Doe, John\",\"says \"\"hi\"\"\"\n\n\"multi\nline\",x"),
+            ',',
+        );
+        assert_eq!(
+            rows,
+            vec![
+                vec!["name", "remark"],
+                vec!["Doe, John", "says \"hi\""],
+                vec!["multi\nline", "x"],
+            ]
+        );
+    }
+
+    #[test]
+    fn layout() {
+        let table = Table::from_text(
+            "csv",
+            &lines("item,count,description\napple,12,round and red\npear,7,a rather long description of a pear"),
+        )
+        .unwrap();
+        assert_eq!(table.body().len(), 2);
+        assert_eq!(table.header()[2], "description");
+        assert_eq!(
+            table.format_row(&table.rows[1], 100),
+            vec!["apple  ", "   12  ", "round and red                 "]
+        );
+        let row = table.format_row(&table.rows[2], 100);
+        assert_eq!(row[2].chars().count(), MAX_COLUMN_WIDTH);
+        assert!(row[2].ends_with('…'));
+        assert_eq!(table.format_row(&table.rows[1], 20).len(), 2);
+        assert_eq!(table.width(20), 12);
+        assert!(Table::from_text("txt", &lines("a,b")).is_none());
+    }
+}
@@ -86,6 +86,8 @@ pub enum MviewError {
 
     Pdfium(PdfiumError),
 
+    Pdf(lopdf::Error),
+
     Svg(resvg::usvg::Error),
 }
 
@@ -175,6 +177,12 @@ impl From<PdfiumError> for MviewError {
     }
 }
 
+impl From<lopdf::Error> for MviewError {
+    fn from(err: lopdf::Error) -> MviewError {
+        MviewError::Pdf(err)
+    }
+}
+
 impl From<resvg::usvg::Error> for MviewError {
     fn from(err: resvg::usvg::Error) -> MviewError {
         MviewError::Svg(err)
@@ -196,6 +204,7 @@ impl fmt::Display for MviewError {
             #[cfg(feature = "mupdf")]
             MviewError::MuPdf(err) => err.fmt(fmt),
             MviewError::Pdfium(err) => err.fmt(fmt),
+            MviewError::Pdf(err) => err.fmt(fmt),
             MviewError::Svg(err) => err.fmt(fmt),
         }
     }
@@ -0,0 +1,156 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Reading of (potentially very large) files: archives and images
+//!
+//! Archives above [`MMAP_THRESHOLD`] are memory mapped. The archive readers then read
+//! straight from the page cache instead of copying everything through a read buffer,
+//! which makes sequential browsing of multi-gigabyte archives a lot faster. Smaller files,
+//! and files that cannot be mapped (e.g. on some network file systems), are read through a
+//! regular buffered reader.
+//!
+//! Images are never mapped but read into memory. The app rewrites them (metadata, date
+//! shift, external editor) while they are shown, and an animation keeps its reader for as
+//! long as it plays.
+
+use std::{
+    fs::{self, File},
+    io::{self, BufRead, BufReader, Cursor, Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use memmap2::Mmap;
+
+/// Files of at least this size are memory mapped
+pub const MMAP_THRESHOLD: u64 = 4 * 1024 * 1024;
+
+pub enum FileReader {
+    Mapped(Cursor<Mmap>),
+    Buffered(BufReader<File>),
+    Memory(Cursor<Vec<u8>>),
+}
+
+impl FileReader {
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::open_with_threshold(path.as_ref(), MMAP_THRESHOLD)
+    }
+
+    fn open_with_threshold(path: &Path, threshold: u64) -> io::Result<Self> {
+        let file = File::open(path)?;
+        if file.metadata()?.len() >= threshold {
+            // SAFETY: the map is read-only. When another process truncates the file while
+            // it is mapped, reading the missing part faults, which is the same risk every
+            // memory mapping viewer takes.
+            match unsafe { Mmap::map(&file) } {
+                Ok(map) => return Ok(Self::Mapped(Cursor::new(map))),
+                Err(e) => eprintln!("Cannot map {}, reading instead: {e}", path.display()),
+            }
+        }
+        Ok(Self::Buffered(BufReader::new(file)))
+    }
+
+    /// Read the whole file into memory, for files that may change while being decoded
+    pub fn read_into_memory<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(Self::Memory(Cursor::new(fs::read(path)?)))
+    }
+
+    pub fn is_mapped(&self) -> bool {
+        matches!(self, Self::Mapped(_))
+    }
+}
+
+impl Read for FileReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Self::Mapped(reader) => reader.read(buf),
+            Self::Buffered(reader) => reader.read(buf),
+            Self::Memory(reader) => reader.read(buf),
+        }
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        match self {
+            Self::Mapped(reader) => reader.read_exact(buf),
+            Self::Buffered(reader) => reader.read_exact(buf),
+            Self::Memory(reader) => reader.read_exact(buf),
+        }
+    }
+}
+
+impl BufRead for FileReader {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        match self {
+            Self::Mapped(reader) => reader.fill_buf(),
+            Self::Buffered(reader) => reader.fill_buf(),
+            Self::Memory(reader) => reader.fill_buf(),
+        }
+    }
+
+    fn consume(&mut self, amt: usize) {
+        match self {
+            Self::Mapped(reader) => reader.consume(amt),
+            Self::Buffered(reader) => reader.consume(amt),
+            Self::Memory(reader) => reader.consume(amt),
+        }
+    }
+}
+
+impl Seek for FileReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        match self {
+            Self::Mapped(reader) => reader.seek(pos),
+            Self::Buffered(reader) => reader.seek(pos),
+            Self::Memory(reader) => reader.seek(pos),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+
+    #[test]
+    fn mapped_and_buffered() {
+        let directory = TestDir::new("file-reader");
+        let path = directory.join("data");
+        let data = (0..=255u8).cycle().take(10_000).collect::<Vec<_>>();
+        std::fs::write(&path, &data).unwrap();
+
+        for threshold in [1_000, 1_000_000] {
+            let mut reader = FileReader::open_with_threshold(&path, threshold).unwrap();
+            assert_eq!(reader.is_mapped(), threshold == 1_000);
+            reader.seek(SeekFrom::Start(300)).unwrap();
+            let mut buf = [0u8; 4];
+            reader.read_exact(&mut buf).unwrap();
+            assert_eq!(buf, [44, 45, 46, 47]);
+            reader.rewind().unwrap();
+            let mut all = Vec::new();
+            reader.read_to_end(&mut all).unwrap();
+            assert_eq!(all, data);
+        }
+
+        let mut reader = FileReader::read_into_memory(&path).unwrap();
+        assert!(!reader.is_mapped());
+        std::fs::write(&path, b"rewritten").unwrap();
+        let mut all = Vec::new();
+        reader.read_to_end(&mut all).unwrap();
+        assert_eq!(all, data);
+    }
+}
@@ -25,7 +25,7 @@ use gtk4::{
 
 use crate::classification::{FileClassification, FileType, Preference};
 
-use super::model::{Column, Direction, Filter};
+use super::model::{Column, Direction, Filter, Progress};
 
 pub struct Cursor {
     pub store: ListStore,
@@ -62,6 +62,11 @@ impl Cursor {
         self.store.folder(&self.iter)
     }
 
+    /// Reading progress of a container row, if it was opened before
+    pub fn progress(&self) -> Option<Progress> {
+        self.store.progress(&self.iter)
+    }
+
     /// Value of the category field of the row (as u32)
     pub fn content_id(&self) -> u32 {
         self.store.content_id(&self.iter)
@@ -128,6 +133,7 @@ pub trait TreeModelMviewExt: IsA<TreeModel> {
     fn index(&self, iter: &TreeIter) -> u64;
     fn modified(&self, iter: &TreeIter) -> u64;
     fn size(&self, iter: &TreeIter) -> u64;
+    fn progress(&self, iter: &TreeIter) -> Option<Progress>;
 }
 
 impl<O: IsA<TreeModel>> TreeModelMviewExt for O {
@@ -180,4 +186,11 @@ impl<O: IsA<TreeModel>> TreeModelMviewExt for O {
             .get::<u64>()
             .unwrap_or(0)
     }
+    fn progress(&self, iter: &TreeIter) -> Option<Progress> {
+        self.get_value(iter, Column::Progress as i32)
+            .get::<String>()
+            .ok()?
+            .parse()
+            .ok()
+    }
 }
@@ -17,14 +17,22 @@
 // STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
 // OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::cell::OnceCell;
+use std::{
+    cell::{OnceCell, RefCell},
+    path::PathBuf,
+};
 
-use crate::file_view;
+use crate::{
+    classification::FileType,
+    file_view,
+    metadata::label::{file_label, ColorLabel},
+};
 use chrono::{
     offset::LocalResult,
     {Local, TimeZone},
 };
 use glib::{
+    clone,
     object::ObjectExt,
     subclass::{
         object::{ObjectImpl, ObjectImplExt},
@@ -35,7 +43,8 @@ use gtk4::{
     glib,
     prelude::{CellRendererExt, TreeViewExt},
     subclass::{prelude::TreeViewImpl, widget::WidgetImpl},
-    CellRendererPixbuf, CellRendererText, TreeView, TreeViewColumn, TreeViewColumnSizing,
+    CellRendererPixbuf, CellRendererText, TreeIter, TreeModel, TreeView, TreeViewColumn,
+    TreeViewColumnSizing,
 };
 use human_bytes::human_bytes;
 
@@ -49,11 +58,13 @@ struct FileViewColumns {
     name: TreeViewColumn,
     size: TreeViewColumn,
     date: TreeViewColumn,
+    label: TreeViewColumn,
 }
 
 #[derive(Default)]
 pub struct FileViewImp {
     columns: OnceCell<FileViewColumns>,
+    pub(super) directory: RefCell<Option<PathBuf>>,
 }
 
 #[glib::object_subclass]
@@ -71,6 +82,15 @@ impl FileViewImp {
             columns.date.set_visible(extended);
         }
     }
+
+    /// Color label of a row, read when the row is shown
+    fn label(&self, model: &TreeModel, iter: &TreeIter) -> Option<ColorLabel> {
+        if model.content(iter) == FileType::Folder {
+            return None;
+        }
+        let directory = self.directory.borrow();
+        file_label(&directory.as_ref()?.join(model.name(iter)))
+    }
 }
 
 impl ObjectImpl for FileViewImp {
@@ -93,13 +113,19 @@ impl ObjectImpl for FileViewImp {
         let renderer_txt = CellRendererText::new();
         let renderer_icon = CellRendererPixbuf::new();
         renderer_icon.set_padding(2, 0);
+        // Reading progress of archives and documents, e.g. "12/40"
+        let renderer_progress = CellRendererText::new();
+        renderer_progress.set_property("foreground", "#9497c2");
+        renderer_progress.set_property("scale", 0.8_f64);
         let col_name = TreeViewColumn::new();
         col_name.pack_start(&renderer_icon, false);
         col_name.pack_start(&renderer_txt, true);
+        col_name.pack_start(&renderer_progress, false);
         col_name.set_title("Name");
         col_name.add_attribute(&renderer_icon, "icon-name", Column::PrefIcon as i32);
         col_name.add_attribute(&renderer_icon, "visible", Column::ShowPrefIcon as i32);
         col_name.add_attribute(&renderer_txt, "text", Column::Name as i32);
+        col_name.add_attribute(&renderer_progress, "text", Column::Progress as i32);
         col_name.set_sizing(TreeViewColumnSizing::Fixed);
         col_name.set_fixed_width(300);
         col_name.set_sort_column_id(Column::Name as i32);
@@ -154,12 +180,35 @@ impl ObjectImpl for FileViewImp {
         });
         instance.append_column(&col_date);
 
+        // Column for color label, not sortable
+        let renderer = CellRendererText::new();
+        let col_label = TreeViewColumn::new();
+        col_label.pack_start(&renderer, true);
+        col_label.set_sizing(TreeViewColumnSizing::Fixed);
+        col_label.set_fixed_width(12);
+        col_label.set_cell_data_func(
+            &renderer,
+            clone!(
+                #[weak(rename_to = this)]
+                self,
+                move |_col, renderer, model, iter| {
+                    let label = this.label(model, iter);
+                    if let Some(label) = label {
+                        renderer.set_property("cell-background", label.hex());
+                    }
+                    renderer.set_property("cell-background-set", label.is_some());
+                }
+            ),
+        );
+        instance.append_column(&col_label);
+
         self.columns
             .set(FileViewColumns {
                 category: col_category,
                 name: col_name,
                 size: col_size,
                 date: col_date,
+                label: col_label,
             })
             .expect("Failed to store file list columns");
     }
@@ -22,6 +22,8 @@ mod imp;
 pub mod model;
 mod sort;
 
+use std::path::PathBuf;
+
 pub use cursor::{Cursor, TreeModelMviewExt};
 use glib::{
     clone, idle_add_local, object::Cast, subclass::types::ObjectSubclassIsExt, ControlFlow,
@@ -196,7 +198,8 @@ impl FileView {
 
     pub fn set_sortable(&self, sortable: bool) {
         self.set_headers_clickable(sortable);
-        for (i, column) in self.columns().iter().enumerate() {
+        // Only the first 4 columns (see `Column`) can be sorted on
+        for (i, column) in self.columns().iter().take(4).enumerate() {
             column.set_clickable(sortable);
             column.set_sort_column_id(if sortable { i as i32 } else { -1 });
         }
@@ -206,6 +209,12 @@ impl FileView {
         self.imp().set_extended(extended);
     }
 
+    /// Folder of the listed files, their color labels are shown when it is set
+    pub fn set_directory(&self, directory: Option<PathBuf>) {
+        self.imp().directory.replace(directory);
+        self.queue_draw();
+    }
+
     pub fn change_sort(&self, sort_col: Column) {
         if let Some(store) = self.store() {
             let new_sort_column = SortColumn::Index(sort_col as u32);
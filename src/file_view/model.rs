@@ -64,6 +64,19 @@ impl Filter {
             }
         }
     }
+
+    /// The images that pass this filter
+    pub fn images_only(&self) -> Self {
+        let images = HashSet::from([FileType::Image]);
+        match self {
+            Self::Liked => Self::Set((images, HashSet::from([Preference::Liked]))),
+            Self::Set((c_set, f_set)) if c_set.contains(&FileType::Image) => {
+                Self::Set((images, f_set.clone()))
+            }
+            Self::Set(_) => Self::Set((HashSet::new(), HashSet::new())),
+            _ => Self::Image,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -79,6 +92,7 @@ pub enum Column {
     PrefIcon,
     ShowPrefIcon,
     Folder,
+    Progress,
 }
 
 #[derive(Debug, Clone)]
@@ -150,7 +164,7 @@ impl Row {
 
 impl Column {
     pub fn empty_store() -> ListStore {
-        let col_types: [glib::Type; 9] = [
+        let col_types: [glib::Type; 10] = [
             glib::Type::U32,
             glib::Type::STRING,
             glib::Type::U64,
@@ -160,6 +174,7 @@ impl Column {
             glib::Type::STRING,
             glib::Type::BOOL,
             glib::Type::STRING,
+            glib::Type::STRING,
         ];
         let store = ListStore::new(&col_types);
         store.set_sort_func(
@@ -188,6 +203,60 @@ impl Column {
         }
         store
     }
+
+    /// Store with the folders and the rows that are kept by `keep`
+    pub fn store_filtered(index: &[Row], keep: impl Fn(&Row) -> bool) -> ListStore {
+        let store = Self::empty_store();
+        for row in index
+            .iter()
+            .filter(|row| row.content_type == FileType::Folder.id() || keep(row))
+        {
+            row.push(&store);
+        }
+        store
+    }
+}
+
+/// How far a container was read: the last viewed entry (counting from 1) of all entries
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Progress {
+    pub position: u32,
+    pub total: u32,
+}
+
+impl Progress {
+    pub fn new(position: u32, total: u32) -> Option<Self> {
+        if total > 0 && position > 0 && position <= total {
+            Some(Self { position, total })
+        } else {
+            None
+        }
+    }
+
+    pub fn fraction(&self) -> f64 {
+        self.position as f64 / self.total as f64
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.position == self.total
+    }
+}
+
+impl fmt::Display for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.position, self.total)
+    }
+}
+
+impl FromStr for Progress {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (position, total) = s.split_once('/').ok_or(())?;
+        let position = position.parse().map_err(|_| ())?;
+        let total = total.parse().map_err(|_| ())?;
+        Self::new(position, total).ok_or(())
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -203,10 +272,14 @@ impl From<Reference> for Target {
         match value.take_tuple() {
             (BackendRef::FileSystem(_), ItemRef::String(name)) => Target::Name(name),
             (BackendRef::MarArchive(_), ItemRef::Index(index)) => Target::Index(index),
+            (BackendRef::MailArchive(_), ItemRef::Index(index)) => Target::Index(index),
             (BackendRef::RarArchive(_), ItemRef::String(name)) => Target::Name(name),
             (BackendRef::ZipArchive(_), ItemRef::Index(index)) => Target::Index(index),
             (BackendRef::Mupdf(_), ItemRef::Index(index)) => Target::Index(index),
             (BackendRef::Pdfium(_), ItemRef::Index(index)) => Target::Index(index),
+            (BackendRef::Collection, ItemRef::Index(index)) => Target::Index(index),
+            (BackendRef::Staging, ItemRef::Index(index)) => Target::Index(index),
+            (BackendRef::MostViewed, ItemRef::Index(index)) => Target::Index(index),
             (_, _) => Target::First,
         }
     }
@@ -249,12 +322,16 @@ impl Default for Reference {
 pub enum BackendRef {
     FileSystem(PathBuf),
     MarArchive(PathBuf),
+    MailArchive(PathBuf),
     RarArchive(PathBuf),
     ZipArchive(PathBuf),
     Mupdf(PathBuf),
     Pdfium(PathBuf),
     Thumbnail, //(Box<Reference>),
     Bookmarks,
+    Collection,
+    Staging,
+    MostViewed,
     None,
 }
 
@@ -263,12 +340,16 @@ impl BackendRef {
         match name {
             "FileSystem" => BackendRef::FileSystem(path),
             "MarArchive" => BackendRef::MarArchive(path),
+            "MailArchive" => BackendRef::MailArchive(path),
             "RarArchive" => BackendRef::RarArchive(path),
             "ZipArchive" => BackendRef::ZipArchive(path),
             "Mupdf" => BackendRef::Mupdf(path),
             "Pdfium" => BackendRef::Pdfium(path),
             "Thumbnail" => BackendRef::Thumbnail,
             "Bookmarks" => BackendRef::Bookmarks,
+            "Collection" => BackendRef::Collection,
+            "Staging" => BackendRef::Staging,
+            "MostViewed" => BackendRef::MostViewed,
             _ => BackendRef::None,
         }
     }
@@ -277,12 +358,16 @@ impl BackendRef {
         match self {
             BackendRef::FileSystem(_) => "FileSystem",
             BackendRef::MarArchive(_) => "MarArchive",
+            BackendRef::MailArchive(_) => "MailArchive",
             BackendRef::RarArchive(_) => "RarArchive",
             BackendRef::ZipArchive(_) => "ZipArchive",
             BackendRef::Mupdf(_) => "Mupdf",
             BackendRef::Pdfium(_) => "Pdfium",
             BackendRef::Thumbnail => "Thumbnail",
             BackendRef::Bookmarks => "Bookmarks",
+            BackendRef::Collection => "Collection",
+            BackendRef::Staging => "Staging",
+            BackendRef::MostViewed => "MostViewed",
             BackendRef::None => "None",
         }
     }
@@ -291,12 +376,16 @@ impl BackendRef {
         let p = match self {
             BackendRef::FileSystem(path_buf) => path_buf.to_str(),
             BackendRef::MarArchive(path_buf) => path_buf.to_str(),
+            BackendRef::MailArchive(path_buf) => path_buf.to_str(),
             BackendRef::RarArchive(path_buf) => path_buf.to_str(),
             BackendRef::ZipArchive(path_buf) => path_buf.to_str(),
             BackendRef::Mupdf(path_buf) => path_buf.to_str(),
             BackendRef::Pdfium(path_buf) => path_buf.to_str(),
             BackendRef::Thumbnail => None,
             BackendRef::Bookmarks => None,
+            BackendRef::Collection => None,
+            BackendRef::Staging => None,
+            BackendRef::MostViewed => None,
             BackendRef::None => None,
         };
         p.unwrap_or_default()
@@ -307,6 +396,7 @@ impl BackendRef {
             self,
             BackendRef::FileSystem(_)
                 | BackendRef::MarArchive(_)
+                | BackendRef::MailArchive(_)
                 | BackendRef::RarArchive(_)
                 | BackendRef::ZipArchive(_)
                 | BackendRef::Mupdf(_)
@@ -332,12 +422,16 @@ impl ItemRef {
         match backend {
             BackendRef::FileSystem(_) => ItemRef::String(row.name.clone()),
             BackendRef::MarArchive(_) => ItemRef::Index(row.index),
+            BackendRef::MailArchive(_) => ItemRef::Index(row.index),
             BackendRef::RarArchive(_) => ItemRef::String(row.name.clone()),
             BackendRef::ZipArchive(_) => ItemRef::Index(row.index),
             BackendRef::Mupdf(_) => ItemRef::Index(row.index),
             BackendRef::Pdfium(_) => ItemRef::Index(row.index),
             BackendRef::Thumbnail => ItemRef::Index(row.index),
             BackendRef::Bookmarks => ItemRef::String(row.folder.clone()),
+            BackendRef::Collection => ItemRef::Index(row.index),
+            BackendRef::Staging => ItemRef::Index(row.index),
+            BackendRef::MostViewed => ItemRef::Index(row.index),
             BackendRef::None => ItemRef::None,
         }
     }
@@ -494,4 +588,31 @@ mod tests {
         assert!(ItemRef::from_str("invalid").is_err());
         assert!(ItemRef::from_str("i:not_a_number").is_err());
     }
+
+    #[test]
+    fn test_progress_round_trip() {
+        let progress = Progress::new(12, 40).unwrap();
+        assert_eq!(progress.to_string(), "12/40");
+        assert_eq!("12/40".parse::<Progress>(), Ok(progress));
+        assert!(!progress.is_complete());
+        assert_eq!("".parse::<Progress>(), Err(()));
+        assert_eq!("41/40".parse::<Progress>(), Err(()));
+        assert_eq!(Progress::new(0, 0), None);
+    }
+
+    #[test]
+    fn test_filter_images_only() {
+        let liked = FileClassification::new(FileType::Image, Preference::Liked);
+        let disliked = FileClassification::new(FileType::Image, Preference::Disliked);
+        let folder = FileClassification::new(FileType::Folder, Preference::Normal);
+
+        let filter = Filter::full_set().images_only();
+        assert!(filter.matches(liked) && filter.matches(disliked) && !filter.matches(folder));
+
+        let filter = Filter::Liked.images_only();
+        assert!(filter.matches(liked) && !filter.matches(disliked));
+
+        let filter = Filter::Set((HashSet::from([FileType::Video]), Preference::all()));
+        assert!(!filter.images_only().matches(liked));
+    }
 }
@@ -18,8 +18,7 @@
 // OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use std::{
-    fs::File,
-    io::{BufRead, BufReader, Cursor, Seek},
+    io::{BufRead, Cursor, Seek},
     time::{Duration, SystemTime},
 };
 
@@ -29,6 +28,7 @@ use image_webp::WebPDecoder;
 
 use crate::{
     error::MviewResult,
+    file_reader::FileReader,
     image::{provider::gdk::GdkImageLoader, view::Zoom},
     rect::SizeD,
 };
@@ -37,7 +37,7 @@ use super::provider::webp::WebP;
 
 pub enum Animation {
     Gdk(PixbufAnimationIter),
-    WebPFile(Box<WebPAnimation<BufReader<File>>>),
+    WebPFile(Box<WebPAnimation<FileReader>>),
     WebPMemory(Box<WebPAnimation<Cursor<Vec<u8>>>>),
 }
 
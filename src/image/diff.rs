@@ -0,0 +1,489 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Visual diff of two images (typically screenshots).
+//!
+//! Before comparing, the translation between both images is estimated using phase
+//! correlation. This way slightly shifted screenshots (scrolled a few pixels, different
+//! crop) can still be compared meaningfully. The comparison itself is done on blocks of
+//! pixels; blocks that differ are highlighted on top of the second image.
+
+use std::f64::consts::PI;
+
+use cairo::{Context, Format, ImageSurface};
+
+use crate::{
+    error::MviewResult,
+    image::colors::{CairoColorExt, Color},
+    mview6_error,
+    profile::performance::Performance,
+    rect::{RectI, VectorI},
+};
+
+/// Size (width and height) of the grid used for phase correlation. Must be a power of two.
+const CORRELATION_SIZE: usize = 256;
+/// Size of the blocks that are compared after alignment
+const BLOCK_SIZE: i32 = 16;
+/// Mean absolute luma difference (0-255) above which a block is considered changed
+const BLOCK_THRESHOLD: f32 = 8.0;
+
+/// Grayscale copy of an image used for the diff calculations
+pub struct LumaImage {
+    width: i32,
+    height: i32,
+    data: Vec<f32>,
+}
+
+impl LumaImage {
+    pub fn new(width: i32, height: i32, data: Vec<f32>) -> Self {
+        assert_eq!(data.len(), (width * height) as usize);
+        Self {
+            width,
+            height,
+            data,
+        }
+    }
+
+    pub fn from_surface(surface: &ImageSurface) -> MviewResult<Self> {
+        let width = surface.width();
+        let height = surface.height();
+        let stride = surface.stride() as usize;
+        let mut data = Vec::with_capacity((width * height) as usize);
+        surface
+            .with_data(|bytes| {
+                for row in bytes.chunks_exact(stride).take(height as usize) {
+                    // Cairo ARGB32/RGB24 is stored as native endian u32, so BGRA on little endian
+                    for pixel in row.chunks_exact(4).take(width as usize) {
+                        data.push(
+                            0.114 * pixel[0] as f32
+                                + 0.587 * pixel[1] as f32
+                                + 0.299 * pixel[2] as f32,
+                        );
+                    }
+                }
+            })
+            .map_err(|e| mview6_error!(format!("Cannot access surface data: {e}")))?;
+        Ok(Self::new(width, height, data))
+    }
+
+    fn get(&self, x: i32, y: i32) -> Option<f32> {
+        if x >= 0 && y >= 0 && x < self.width && y < self.height {
+            Some(self.data[(y * self.width + x) as usize])
+        } else {
+            None
+        }
+    }
+
+    /// Downscale by averaging `step` x `step` pixels into a `CORRELATION_SIZE` square grid,
+    /// mean-subtracted and windowed to reduce edge effects
+    fn correlation_grid(&self, step: i32) -> Vec<f64> {
+        let n = CORRELATION_SIZE;
+        let mut grid = vec![0.0; n * n];
+        let cols = (self.width / step).min(n as i32) as usize;
+        let rows = (self.height / step).min(n as i32) as usize;
+        if cols == 0 || rows == 0 {
+            return grid;
+        }
+        for gy in 0..rows {
+            for gx in 0..cols {
+                let mut sum = 0.0;
+                for y in 0..step {
+                    let row = (gy as i32 * step + y) * self.width;
+                    for x in 0..step {
+                        sum += self.data[(row + gx as i32 * step + x) as usize] as f64;
+                    }
+                }
+                grid[gy * n + gx] = sum / (step * step) as f64;
+            }
+        }
+        let mean = grid.iter().sum::<f64>() / (rows * cols) as f64;
+        for gy in 0..rows {
+            let wy = hann(gy, rows);
+            for gx in 0..cols {
+                let v = &mut grid[gy * n + gx];
+                *v = (*v - mean) * wy * hann(gx, cols);
+            }
+        }
+        grid
+    }
+}
+
+fn hann(i: usize, len: usize) -> f64 {
+    if len < 2 {
+        1.0
+    } else {
+        0.5 - 0.5 * (2.0 * PI * i as f64 / (len - 1) as f64).cos()
+    }
+}
+
+/// In-place iterative radix-2 FFT. The length of `re` and `im` must be a power of two.
+fn fft(re: &mut [f64], im: &mut [f64], inverse: bool) {
+    let n = re.len();
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+    let sign = if inverse { 1.0 } else { -1.0 };
+    let mut len = 2;
+    while len <= n {
+        let angle = sign * 2.0 * PI / len as f64;
+        let (w_im, w_re) = angle.sin_cos();
+        for start in (0..n).step_by(len) {
+            let (mut cur_re, mut cur_im) = (1.0, 0.0);
+            for k in 0..len / 2 {
+                let a = start + k;
+                let b = a + len / 2;
+                let t_re = re[b] * cur_re - im[b] * cur_im;
+                let t_im = re[b] * cur_im + im[b] * cur_re;
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+                let next_re = cur_re * w_re - cur_im * w_im;
+                cur_im = cur_re * w_im + cur_im * w_re;
+                cur_re = next_re;
+            }
+        }
+        len <<= 1;
+    }
+    if inverse {
+        for i in 0..n {
+            re[i] /= n as f64;
+            im[i] /= n as f64;
+        }
+    }
+}
+
+/// Two dimensional FFT on a square `n` x `n` grid (rows first, then columns)
+fn fft_2d(re: &mut [f64], im: &mut [f64], n: usize, inverse: bool) {
+    for row in 0..n {
+        let range = row * n..(row + 1) * n;
+        fft(&mut re[range.clone()], &mut im[range], inverse);
+    }
+    let mut col_re = vec![0.0; n];
+    let mut col_im = vec![0.0; n];
+    for col in 0..n {
+        for row in 0..n {
+            col_re[row] = re[row * n + col];
+            col_im[row] = im[row * n + col];
+        }
+        fft(&mut col_re, &mut col_im, inverse);
+        for row in 0..n {
+            re[row * n + col] = col_re[row];
+            im[row * n + col] = col_im[row];
+        }
+    }
+}
+
+/// Estimate the translation of `b` relative to `a` using phase correlation.
+///
+/// Returns the offset such that pixel `(x, y)` in `a` corresponds to pixel
+/// `(x + offset.x, y + offset.y)` in `b`.
+pub fn estimate_translation(a: &LumaImage, b: &LumaImage) -> VectorI {
+    let n = CORRELATION_SIZE;
+    let max_dim = a.width.max(a.height).max(b.width).max(b.height);
+    let step = ((max_dim + n as i32 - 1) / n as i32).max(1);
+
+    let mut a_re = a.correlation_grid(step);
+    let mut a_im = vec![0.0; n * n];
+    let mut b_re = b.correlation_grid(step);
+    let mut b_im = vec![0.0; n * n];
+    fft_2d(&mut a_re, &mut a_im, n, false);
+    fft_2d(&mut b_re, &mut b_im, n, false);
+
+    // Normalized cross power spectrum: B * conj(A) / |B * conj(A)|
+    for i in 0..n * n {
+        let re = b_re[i] * a_re[i] + b_im[i] * a_im[i];
+        let im = b_im[i] * a_re[i] - b_re[i] * a_im[i];
+        let magnitude = (re * re + im * im).sqrt();
+        if magnitude > 1e-9 {
+            a_re[i] = re / magnitude;
+            a_im[i] = im / magnitude;
+        } else {
+            a_re[i] = 0.0;
+            a_im[i] = 0.0;
+        }
+    }
+    fft_2d(&mut a_re, &mut a_im, n, true);
+
+    let (peak, _) =
+        a_re.iter().enumerate().fold(
+            (0, f64::MIN),
+            |best, (i, &v)| if v > best.1 { (i, v) } else { best },
+        );
+    let wrap = |v: usize| {
+        if v > n / 2 {
+            v as i32 - n as i32
+        } else {
+            v as i32
+        }
+    };
+    let coarse = VectorI::new(wrap(peak % n) * step, wrap(peak / n) * step);
+    if step == 1 {
+        coarse
+    } else {
+        refine_translation(a, b, coarse, step)
+    }
+}
+
+/// Refine a coarse (downscaled) translation estimate at full resolution by trying all
+/// offsets within one grid step
+fn refine_translation(a: &LumaImage, b: &LumaImage, coarse: VectorI, step: i32) -> VectorI {
+    let sample = (a.width.min(a.height) / 64).max(1);
+    let mut best = coarse;
+    let mut best_score = f32::MAX;
+    for dy in -step..=step {
+        for dx in -step..=step {
+            let offset = VectorI::new(coarse.x() + dx, coarse.y() + dy);
+            let mut sum = 0.0;
+            let mut count = 0;
+            for y in (0..a.height).step_by(sample as usize) {
+                for x in (0..a.width).step_by(sample as usize) {
+                    if let (Some(va), Some(vb)) =
+                        (a.get(x, y), b.get(x + offset.x(), y + offset.y()))
+                    {
+                        sum += (va - vb).abs();
+                        count += 1;
+                    }
+                }
+            }
+            if count > 0 && sum / (count as f32) < best_score {
+                best_score = sum / count as f32;
+                best = offset;
+            }
+        }
+    }
+    best
+}
+
+pub struct DiffResult {
+    /// Translation of the second image relative to the first one
+    pub offset: VectorI,
+    /// Changed blocks in coordinates of the second image
+    pub blocks: Vec<RectI>,
+    /// Bounding boxes of connected changed blocks in coordinates of the second image
+    pub regions: Vec<RectI>,
+}
+
+impl DiffResult {
+    pub fn is_identical(&self) -> bool {
+        self.blocks.is_empty()
+    }
+}
+
+/// Compare `b` to `a` after compensating for the translation between both images
+pub fn compare(a: &LumaImage, b: &LumaImage) -> DiffResult {
+    let offset = estimate_translation(a, b);
+
+    let cols = (b.width + BLOCK_SIZE - 1) / BLOCK_SIZE;
+    let rows = (b.height + BLOCK_SIZE - 1) / BLOCK_SIZE;
+    let mut changed = vec![false; (cols * rows) as usize];
+    let mut blocks = Vec::new();
+
+    for row in 0..rows {
+        for col in 0..cols {
+            let mut sum = 0.0;
+            let mut count = 0;
+            for y in row * BLOCK_SIZE..((row + 1) * BLOCK_SIZE).min(b.height) {
+                for x in col * BLOCK_SIZE..((col + 1) * BLOCK_SIZE).min(b.width) {
+                    // Parts of b that are not covered by a (due to the shift) are not compared
+                    if let (Some(vb), Some(va)) =
+                        (b.get(x, y), a.get(x - offset.x(), y - offset.y()))
+                    {
+                        sum += (va - vb).abs();
+                        count += 1;
+                    }
+                }
+            }
+            if count > 0 && sum / count as f32 > BLOCK_THRESHOLD {
+                changed[(row * cols + col) as usize] = true;
+                blocks.push(block_rect(col, row, b));
+            }
+        }
+    }
+
+    DiffResult {
+        offset,
+        blocks,
+        regions: connected_regions(&mut changed, cols, rows, b),
+    }
+}
+
+fn block_rect(col: i32, row: i32, image: &LumaImage) -> RectI {
+    RectI::new(
+        col * BLOCK_SIZE,
+        row * BLOCK_SIZE,
+        ((col + 1) * BLOCK_SIZE).min(image.width),
+        ((row + 1) * BLOCK_SIZE).min(image.height),
+    )
+}
+
+/// Group the changed blocks (8-connected) and return their bounding boxes.
+/// Consumes the `changed` flags.
+fn connected_regions(changed: &mut [bool], cols: i32, rows: i32, image: &LumaImage) -> Vec<RectI> {
+    let mut regions = Vec::new();
+    let mut stack = Vec::new();
+    for start in 0..changed.len() {
+        if !changed[start] {
+            continue;
+        }
+        changed[start] = false;
+        stack.push(start as i32);
+        let (mut c0, mut r0, mut c1, mut r1) = (cols, rows, 0, 0);
+        while let Some(index) = stack.pop() {
+            let (col, row) = (index % cols, index / cols);
+            c0 = c0.min(col);
+            r0 = r0.min(row);
+            c1 = c1.max(col);
+            r1 = r1.max(row);
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    let (c, r) = (col + dx, row + dy);
+                    if c >= 0 && r >= 0 && c < cols && r < rows {
+                        let neighbour = (r * cols + c) as usize;
+                        if changed[neighbour] {
+                            changed[neighbour] = false;
+                            stack.push(neighbour as i32);
+                        }
+                    }
+                }
+            }
+        }
+        let top_left = block_rect(c0, r0, image);
+        let bottom_right = block_rect(c1, r1, image);
+        regions.push(RectI::new(
+            top_left.x0,
+            top_left.y0,
+            bottom_right.x1,
+            bottom_right.y1,
+        ));
+    }
+    regions
+}
+
+/// Create the diff image: the second image with the changed regions highlighted
+pub fn diff_surface(first: &ImageSurface, second: &ImageSurface) -> MviewResult<ImageSurface> {
+    let duration = Performance::start();
+
+    let a = LumaImage::from_surface(first)?;
+    let b = LumaImage::from_surface(second)?;
+    let result = compare(&a, &b);
+
+    let surface = ImageSurface::create(Format::ARgb32, b.width, b.height)?;
+    let context = Context::new(&surface)?;
+    context.set_source_surface(second, 0.0, 0.0)?;
+    context.paint()?;
+
+    context.set_source_rgba(1.0, 0.0, 0.0, 0.3);
+    for block in &result.blocks {
+        context.rectangle(
+            block.x0 as f64,
+            block.y0 as f64,
+            block.width() as f64,
+            block.height() as f64,
+        );
+    }
+    context.fill()?;
+
+    context.color(Color::Red);
+    context.set_line_width(2.0);
+    for region in &result.regions {
+        context.rectangle(
+            region.x0 as f64 + 1.0,
+            region.y0 as f64 + 1.0,
+            region.width() as f64 - 2.0,
+            region.height() as f64 - 2.0,
+        );
+    }
+    context.stroke()?;
+
+    duration.elapsed_suffix(
+        "diff",
+        &format!(
+            "(offset {},{}, {} regions)",
+            result.offset.x(),
+            result.offset.y(),
+            result.regions.len()
+        ),
+    );
+
+    Ok(surface)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern(width: i32, height: i32, dx: i32, dy: i32) -> LumaImage {
+        let mut data = Vec::with_capacity((width * height) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let (sx, sy) = (x - dx, y - dy);
+                // Simple integer hash to get a non-periodic pattern
+                let h = (sx.wrapping_mul(374761393) ^ sy.wrapping_mul(668265263)) as u32;
+                let h = (h ^ (h >> 13)).wrapping_mul(1274126177);
+                data.push((h >> 24) as f32);
+            }
+        }
+        LumaImage::new(width, height, data)
+    }
+
+    #[test]
+    fn translation_small() {
+        let a = pattern(200, 150, 0, 0);
+        let b = pattern(200, 150, 5, -3);
+        assert_eq!(estimate_translation(&a, &b), VectorI::new(5, -3));
+    }
+
+    #[test]
+    fn translation_downscaled() {
+        let a = pattern(700, 400, 0, 0);
+        let b = pattern(700, 400, -11, 20);
+        assert_eq!(estimate_translation(&a, &b), VectorI::new(-11, 20));
+    }
+
+    #[test]
+    fn compare_shifted_with_change() {
+        let a = pattern(160, 160, 0, 0);
+        let mut b = pattern(160, 160, 4, 4);
+        for y in 64..80 {
+            for x in 96..128 {
+                b.data[(y * 160 + x) as usize] = 255.0;
+            }
+        }
+        let result = compare(&a, &b);
+        assert_eq!(result.offset, VectorI::new(4, 4));
+        assert_eq!(result.regions, vec![RectI::new(96, 64, 128, 80)]);
+    }
+
+    #[test]
+    fn compare_identical() {
+        let a = pattern(100, 80, 0, 0);
+        assert!(compare(&a, &a).is_identical());
+    }
+}
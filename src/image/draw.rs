@@ -17,7 +17,7 @@
 // STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
 // OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::path::Path;
+use std::{f64::consts::PI, path::Path};
 
 use cairo::{Context, FontSlant, FontWeight, Format, ImageSurface, Operator};
 use gdk_pixbuf::Pixbuf;
@@ -140,6 +140,42 @@ fn logo(context: &Context, x_right: i32, y: i32, size: f64, draw: bool) -> Mview
     Ok(extends.width())
 }
 
+/// Draw keywords as chips along the bottom edge of a thumbnail, as far as they fit
+pub fn keyword_chips(
+    context: &Context,
+    x: f64,
+    y_bottom: f64,
+    max_width: f64,
+    keywords: &[String],
+) -> MviewResult<()> {
+    const HEIGHT: f64 = 14.0;
+    const PADDING: f64 = 4.0;
+    const SPACING: f64 = 3.0;
+    context.select_font_face("Liberation Sans", FontSlant::Normal, FontWeight::Normal);
+    context.set_font_size(10.0);
+    let y = y_bottom - HEIGHT - SPACING;
+    let mut offset = SPACING;
+    for keyword in keywords {
+        let extents = context.text_extents(keyword)?;
+        let width = extents.x_advance() + 2.0 * PADDING;
+        if offset + width > max_width {
+            break;
+        }
+        context.set_source_rgba(0.1, 0.1, 0.1, 0.75);
+        context.new_sub_path();
+        let r = HEIGHT / 2.0;
+        context.arc(x + offset + r, y + r, r, 0.5 * PI, 1.5 * PI);
+        context.arc(x + offset + width - r, y + r, r, 1.5 * PI, 0.5 * PI);
+        context.close_path();
+        context.fill()?;
+        context.set_source_rgb(0.9, 0.9, 0.9);
+        context.move_to(x + offset + PADDING, y + HEIGHT - 4.0);
+        context.show_text(keyword)?;
+        offset += width + SPACING;
+    }
+    Ok(())
+}
+
 pub fn text_thumb(message: TMessage) -> MviewResult<Pixbuf> {
     let (color_back, color_title, color_msg) = message.colors;
     let surface: ImageSurface = ImageSurface::create(Format::ARgb32, 175, 175)?;
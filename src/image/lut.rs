@@ -0,0 +1,277 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! 3D color lookup tables (Adobe/Resolve `.cube` format)
+//!
+//! Used to correct the output for uncalibrated displays. The LUT is applied to the drawn
+//! content just before it is put on the screen, the overlays are drawn on top of it.
+
+use std::{fs, path::Path};
+
+use cairo::ImageSurface;
+
+use crate::{error::MviewResult, mview6_error};
+
+const MAX_LUT_SIZE: usize = 256;
+
+#[derive(Debug, Clone)]
+pub struct Lut3D {
+    title: String,
+    size: usize,
+    domain_min: [f32; 3],
+    domain_max: [f32; 3],
+    /// RGB entries, red changes fastest, then green, then blue
+    table: Vec<[f32; 3]>,
+}
+
+impl Lut3D {
+    pub fn load(path: &Path) -> MviewResult<Self> {
+        let text = fs::read_to_string(path)?;
+        let mut lut = Self::parse(&text)?;
+        if lut.title.is_empty() {
+            lut.title = path
+                .file_stem()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string();
+        }
+        Ok(lut)
+    }
+
+    pub fn parse(text: &str) -> MviewResult<Self> {
+        let mut title = String::new();
+        let mut size = 0;
+        let mut domain_min = [0.0; 3];
+        let mut domain_max = [1.0; 3];
+        let mut table = Vec::new();
+
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut parts = line.split_whitespace();
+            let keyword = parts.next().unwrap_or_default();
+            match keyword {
+                "TITLE" => {
+                    title = line[5..].trim().trim_matches('"').to_string();
+                }
+                "LUT_3D_SIZE" => {
+                    size = parts.next().and_then(|s| s.parse().ok()).unwrap_or(0);
+                    if !(2..=MAX_LUT_SIZE).contains(&size) {
+                        return mview6_error!(format!("Invalid LUT_3D_SIZE in line '{line}'"))
+                            .into();
+                    }
+                    table.reserve(size * size * size);
+                }
+                "LUT_1D_SIZE" => {
+                    return mview6_error!("1D LUTs are not supported").into();
+                }
+                "DOMAIN_MIN" => domain_min = parse_triplet(parts, line)?,
+                "DOMAIN_MAX" => domain_max = parse_triplet(parts, line)?,
+                _ if keyword.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '.') => {
+                    table.push(parse_triplet(line.split_whitespace(), line)?);
+                }
+                _ => (), // ignore unknown keywords (LUT_3D_INPUT_RANGE, etc)
+            }
+        }
+
+        if size == 0 {
+            return mview6_error!("Missing LUT_3D_SIZE").into();
+        }
+        if table.len() != size * size * size {
+            return mview6_error!(format!(
+                "Expected {} LUT entries, got {}",
+                size * size * size,
+                table.len()
+            ))
+            .into();
+        }
+        if (0..3).any(|c| domain_max[c] <= domain_min[c]) {
+            return mview6_error!("Invalid LUT domain").into();
+        }
+
+        Ok(Self {
+            title,
+            size,
+            domain_min,
+            domain_max,
+            table,
+        })
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    fn entry(&self, r: usize, g: usize, b: usize) -> &[f32; 3] {
+        &self.table[r + self.size * (g + self.size * b)]
+    }
+
+    /// Look up a color (components 0.0 - 1.0) using trilinear interpolation
+    pub fn lookup(&self, rgb: [f32; 3]) -> [f32; 3] {
+        let max = (self.size - 1) as f32;
+        let mut index = [0; 3];
+        let mut fraction = [0.0; 3];
+        for c in 0..3 {
+            let v = (rgb[c] - self.domain_min[c]) / (self.domain_max[c] - self.domain_min[c]);
+            let v = v.clamp(0.0, 1.0) * max;
+            let i = (v.floor() as usize).min(self.size - 2);
+            index[c] = i;
+            fraction[c] = v - i as f32;
+        }
+        let [r, g, b] = index;
+        let [fr, fg, fb] = fraction;
+        let mut result = [0.0; 3];
+        for (c, value) in result.iter_mut().enumerate() {
+            let c00 = lerp(self.entry(r, g, b)[c], self.entry(r + 1, g, b)[c], fr);
+            let c10 = lerp(
+                self.entry(r, g + 1, b)[c],
+                self.entry(r + 1, g + 1, b)[c],
+                fr,
+            );
+            let c01 = lerp(
+                self.entry(r, g, b + 1)[c],
+                self.entry(r + 1, g, b + 1)[c],
+                fr,
+            );
+            let c11 = lerp(
+                self.entry(r, g + 1, b + 1)[c],
+                self.entry(r + 1, g + 1, b + 1)[c],
+                fr,
+            );
+            *value = lerp(lerp(c00, c10, fg), lerp(c01, c11, fg), fb);
+        }
+        result
+    }
+
+    /// Apply the LUT to cairo ARGB32 pixel data (premultiplied alpha, native endian)
+    pub fn apply(&self, data: &mut [u8]) {
+        for pixel in data.chunks_exact_mut(4) {
+            let (b, g, r, a) = pixel_bgra(pixel);
+            if a == 0 {
+                continue;
+            }
+            let alpha = a as f32 / 255.0;
+            let rgb = self.lookup([
+                r as f32 / 255.0 / alpha,
+                g as f32 / 255.0 / alpha,
+                b as f32 / 255.0 / alpha,
+            ]);
+            let to_u8 = |v: f32| (v.clamp(0.0, 1.0) * alpha * 255.0).round() as u8;
+            set_pixel_bgra(pixel, to_u8(rgb[2]), to_u8(rgb[1]), to_u8(rgb[0]), a);
+        }
+    }
+
+    pub fn apply_to_surface(&self, surface: &mut ImageSurface) -> MviewResult<()> {
+        surface.flush();
+        let mut data = surface
+            .data()
+            .map_err(|e| mview6_error!(format!("Cannot access surface data: {e}")))?;
+        self.apply(&mut data);
+        Ok(())
+    }
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+fn parse_triplet<'a>(
+    mut parts: impl Iterator<Item = &'a str>,
+    line: &str,
+) -> MviewResult<[f32; 3]> {
+    let mut triplet = [0.0; 3];
+    for value in triplet.iter_mut() {
+        *value = match parts.next().map(str::parse::<f32>) {
+            Some(Ok(v)) => v,
+            _ => return mview6_error!(format!("Invalid line '{line}'")).into(),
+        };
+    }
+    Ok(triplet)
+}
+
+#[cfg(target_endian = "little")]
+fn pixel_bgra(pixel: &[u8]) -> (u8, u8, u8, u8) {
+    (pixel[0], pixel[1], pixel[2], pixel[3])
+}
+
+#[cfg(target_endian = "little")]
+fn set_pixel_bgra(pixel: &mut [u8], b: u8, g: u8, r: u8, a: u8) {
+    pixel.copy_from_slice(&[b, g, r, a]);
+}
+
+#[cfg(target_endian = "big")]
+fn pixel_bgra(pixel: &[u8]) -> (u8, u8, u8, u8) {
+    (pixel[3], pixel[2], pixel[1], pixel[0])
+}
+
+#[cfg(target_endian = "big")]
+fn set_pixel_bgra(pixel: &mut [u8], b: u8, g: u8, r: u8, a: u8) {
+    pixel.copy_from_slice(&[a, r, g, b]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn identity(size: usize) -> String {
+        let mut text = format!("TITLE \"identity\"\n# comment\nLUT_3D_SIZE {size}\n");
+        let max = (size - 1) as f32;
+        for b in 0..size {
+            for g in 0..size {
+                for r in 0..size {
+                    text.push_str(&format!(
+                        "{} {} {}\n",
+                        r as f32 / max,
+                        g as f32 / max,
+                        b as f32 / max
+                    ));
+                }
+            }
+        }
+        text
+    }
+
+    #[test]
+    fn parse_identity() {
+        let lut = Lut3D::parse(&identity(5)).unwrap();
+        assert_eq!(lut.title(), "identity");
+        let rgb = lut.lookup([0.3, 0.6, 0.9]);
+        for (v, expected) in rgb.iter().zip([0.3, 0.6, 0.9]) {
+            assert!((v - expected).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn apply_identity() {
+        let lut = Lut3D::parse(&identity(17)).unwrap();
+        let original = vec![10, 128, 250, 255, 0, 0, 0, 0, 40, 20, 60, 128];
+        let mut data = original.clone();
+        lut.apply(&mut data);
+        assert_eq!(data, original);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(Lut3D::parse("LUT_3D_SIZE 2\n0 0 0\n").is_err());
+        assert!(Lut3D::parse("LUT_1D_SIZE 16\n").is_err());
+        assert!(Lut3D::parse("0 0 0\n").is_err());
+    }
+}
@@ -19,8 +19,13 @@
 
 pub mod animation;
 pub mod colors;
+pub mod diff;
 pub mod draw;
+pub mod lut;
+pub mod model;
+pub mod proof;
 pub mod provider;
+pub mod scan;
 pub mod svg;
 pub mod view;
 
@@ -30,6 +35,7 @@ use gtk4::gdk::prelude::GdkCairoContextExt;
 use std::cmp::max;
 
 use crate::{
+    config::config,
     image::{animation::AnimationImage, view::Zoom},
     rect::{SizeD, VectorD},
 };
@@ -121,6 +127,15 @@ impl SingleImage {
             let _ = ctx.paint();
         }
     }
+
+    /// Paint an area black, the background of thumbnail sheets
+    pub fn clear_area(&self, x: i32, y: i32, width: i32, height: i32) {
+        if let Ok(ctx) = Context::new(&self.surface) {
+            ctx.set_source_rgb(0.0, 0.0, 0.0);
+            ctx.rectangle(x as f64, y as f64, width as f64, height as f64);
+            let _ = ctx.fill();
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -130,17 +145,20 @@ pub struct DualImage {
     offset_y_left: f64,
     offset_x_right: f64,
     offset_y_right: f64,
+    fold_line: bool,
 }
 
 impl DualImage {
     pub fn new(surface_left: ImageSurface, surface_right: ImageSurface) -> Self {
+        let dual_page = &config().config_file.dual_page;
         let width_left = surface_left.width() as f64;
         let height_left = surface_left.height() as f64;
         let height_right = surface_right.height() as f64;
-        let (offset_y_left, offset_x_right, offset_y_right) = if height_left > height_right {
-            (0.0, width_left, (height_left - height_right) / 2.0)
+        let offset_x_right = width_left + dual_page.gap as f64;
+        let (offset_y_left, offset_y_right) = if height_left > height_right {
+            (0.0, (height_left - height_right) / 2.0)
         } else {
-            ((height_right - height_left) / 2.0, width_left, 0.0)
+            ((height_right - height_left) / 2.0, 0.0)
         };
         Self {
             surface_left,
@@ -148,9 +166,29 @@ impl DualImage {
             offset_y_left,
             offset_x_right,
             offset_y_right,
+            fold_line: dual_page.fold_line,
         }
     }
 
+    /// Center of the gap between the pages
+    fn fold_x(&self) -> f64 {
+        (self.surface_left.width() as f64 + self.offset_x_right) / 2.0
+    }
+
+    /// One screen pixel wide, independent of the zoom
+    fn draw_fold_line(&self, context: &Context) {
+        let line_width = context
+            .device_to_user_distance(1.0, 0.0)
+            .map(|(dx, _)| dx.abs())
+            .unwrap_or(1.0);
+        let x = self.fold_x();
+        context.set_source_rgba(0.5, 0.5, 0.5, 0.6);
+        context.set_line_width(line_width);
+        context.move_to(x, 0.0);
+        context.line_to(x, self.size().height());
+        let _ = context.stroke();
+    }
+
     pub fn draw(&self, context: &Context, quality: Filter) {
         let size = self.size();
 
@@ -167,11 +205,15 @@ impl DualImage {
         );
         context.source().set_filter(quality);
         let _ = context.fill();
+
+        if self.fold_line {
+            self.draw_fold_line(context);
+        }
     }
 
     pub fn size(&self) -> SizeD {
         SizeD::new(
-            (self.surface_left.width() + self.surface_right.width()).into(),
+            self.offset_x_right + self.surface_right.width() as f64,
             max(self.surface_left.height(), self.surface_right.height()).into(),
         )
     }
@@ -186,6 +228,54 @@ impl DualImage {
     }
 }
 
+/// One of the images in a file with multiple images (e.g. the sizes in an icon)
+#[derive(Debug, Clone)]
+pub struct Frame {
+    pub image: SingleImage,
+    pub info: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct FramesImage {
+    frames: Vec<Frame>,
+    current: usize,
+}
+
+impl FramesImage {
+    pub fn new(frames: Vec<Frame>, current: usize) -> Self {
+        Self { frames, current }
+    }
+
+    pub fn current(&self) -> &SingleImage {
+        &self.frames[self.current].image
+    }
+
+    pub fn index(&self) -> usize {
+        self.current
+    }
+
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    pub fn select(&mut self, index: usize) -> bool {
+        if index < self.frames.len() && index != self.current {
+            self.current = index;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn size(&self) -> SizeD {
+        self.current().size()
+    }
+
+    pub fn has_alpha(&self) -> bool {
+        self.current().has_alpha()
+    }
+}
+
 pub enum Image<'a> {
     Single(&'a SingleImage),
     Dual(&'a DualImage),
@@ -0,0 +1,445 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Shaded preview of 3D models (STL and OBJ) using a small software rasterizer
+//!
+//! The model is centered, scaled to fit any orientation and drawn with an orthographic
+//! projection, a z-buffer and two-sided diffuse lighting.
+
+use std::{fs::File, io::Read, path::Path, sync::Arc};
+
+use image::{DynamicImage, Rgb, RgbImage};
+
+use crate::{
+    classification::{detect, file_formats::FileFormat},
+    error::MviewResult,
+    image::{provider::image_rs::RsImageLoader, SingleImage},
+    mview6_error,
+    profile::performance::Performance,
+    rect::{SizeD, VectorD},
+    util::path_to_extension,
+};
+
+pub const MAX_MODEL_SIZE: u64 = 256 * 1024 * 1024;
+
+/// Size of the rendered view in pixels (square)
+const RENDER_SIZE: u32 = 1024;
+
+/// Size of the rendered thumbnail, downscaled afterwards for smooth edges
+const THUMBNAIL_SIZE: u32 = 350;
+
+/// Rotation in radians per pixel of mouse movement
+const ORBIT_SPEED: f64 = 0.01;
+
+const INITIAL_YAW: f64 = -0.6;
+const INITIAL_PITCH: f64 = 0.45;
+
+const BACKGROUND: Rgb<u8> = Rgb([40, 40, 46]);
+const MODEL_COLOR: [f32; 3] = [0.62, 0.74, 0.90];
+const AMBIENT: f32 = 0.18;
+
+type Vec3 = [f32; 3];
+
+#[derive(Debug, Default, Clone)]
+pub struct Mesh {
+    triangles: Vec<[Vec3; 3]>,
+}
+
+impl Mesh {
+    /// Parse a model, the format is taken from the extension (`stl` or `obj`)
+    pub fn parse(extension: &str, data: &[u8]) -> MviewResult<Self> {
+        let mesh = match extension.to_lowercase().as_str() {
+            "stl" => Self::parse_stl(data)?,
+            "obj" => Self::parse_obj(data)?,
+            _ => return mview6_error!("Unsupported model format").into(),
+        };
+        if mesh.triangles.is_empty() {
+            mview6_error!("Model contains no triangles").into()
+        } else {
+            Ok(mesh)
+        }
+    }
+
+    /// Binary or ASCII STL. Binary files may also start with "solid", so the size of the
+    /// file is checked against the triangle count first.
+    fn parse_stl(data: &[u8]) -> MviewResult<Self> {
+        let count = data
+            .get(80..84)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize);
+        let triangles = if count.is_some_and(|count| data.len() == 84 + 50 * count) {
+            data[84..]
+                .chunks_exact(50)
+                .map(|facet| {
+                    // skip the normal (12 bytes), ignore the attribute (2 bytes)
+                    let vertex = |i: usize| {
+                        let coord = |j: usize| {
+                            let o = 12 + 12 * i + 4 * j;
+                            f32::from_le_bytes([facet[o], facet[o + 1], facet[o + 2], facet[o + 3]])
+                        };
+                        z_up_to_y_up([coord(0), coord(1), coord(2)])
+                    };
+                    [vertex(0), vertex(1), vertex(2)]
+                })
+                .collect()
+        } else if data.starts_with(b"solid") {
+            let text = String::from_utf8_lossy(data);
+            let vertices = text
+                .lines()
+                .filter_map(|line| line.trim().strip_prefix("vertex"))
+                .map(|coords| parse_vec3(coords.split_whitespace()).map(z_up_to_y_up))
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| mview6_error!("Invalid vertex in ASCII STL"))?;
+            vertices
+                .chunks_exact(3)
+                .map(|v| [v[0], v[1], v[2]])
+                .collect()
+        } else {
+            return mview6_error!("Not a valid STL file").into();
+        };
+        Ok(Self { triangles })
+    }
+
+    /// Wavefront OBJ: only vertices and faces are used, polygons are split into a fan
+    fn parse_obj(data: &[u8]) -> MviewResult<Self> {
+        let text = String::from_utf8_lossy(data);
+        let mut vertices = Vec::new();
+        let mut triangles = Vec::new();
+        for line in text.lines() {
+            let mut parts = line.split_whitespace();
+            match parts.next() {
+                Some("v") => vertices
+                    .push(parse_vec3(parts).ok_or_else(|| mview6_error!("Invalid vertex in OBJ"))?),
+                Some("f") => {
+                    // "f v", "f v/vt", "f v//vn" or "f v/vt/vn", indices are 1-based or
+                    // negative (relative to the end of the vertex list)
+                    let face = parts
+                        .map(|part| {
+                            let index = part.split('/').next()?.parse::<i64>().ok()?;
+                            let index = if index < 0 {
+                                vertices.len() as i64 + index
+                            } else {
+                                index - 1
+                            };
+                            vertices.get(usize::try_from(index).ok()?).copied()
+                        })
+                        .collect::<Option<Vec<_>>>()
+                        .ok_or_else(|| mview6_error!("Invalid face in OBJ"))?;
+                    for i in 1..face.len().saturating_sub(1) {
+                        triangles.push([face[0], face[i], face[i + 1]]);
+                    }
+                }
+                _ => (),
+            }
+        }
+        Ok(Self { triangles })
+    }
+
+    pub fn num_triangles(&self) -> usize {
+        self.triangles.len()
+    }
+
+    /// Center of the bounding box and the radius of a sphere that contains it
+    fn bounds(&self) -> (Vec3, f32) {
+        let mut min = [f32::MAX; 3];
+        let mut max = [f32::MIN; 3];
+        for vertex in self.triangles.iter().flatten() {
+            for axis in 0..3 {
+                min[axis] = min[axis].min(vertex[axis]);
+                max[axis] = max[axis].max(vertex[axis]);
+            }
+        }
+        let center = [0, 1, 2].map(|axis| (min[axis] + max[axis]) / 2.0);
+        let radius = length(sub(max, min)) / 2.0;
+        (center, if radius > 0.0 { radius } else { 1.0 })
+    }
+
+    /// Render the model rotated by `yaw` (around the vertical axis) and `pitch` (tilting
+    /// towards the viewer)
+    pub fn render(&self, width: u32, height: u32, yaw: f64, pitch: f64) -> RgbImage {
+        let mut image = RgbImage::from_pixel(width, height, BACKGROUND);
+        let mut depth = vec![f32::MIN; (width * height) as usize];
+
+        let (center, radius) = self.bounds();
+        let scale = 0.45 * width.min(height) as f32 / radius;
+        let (cx, cy) = (width as f32 / 2.0, height as f32 / 2.0);
+        let (sin_yaw, cos_yaw) = (yaw as f32).sin_cos();
+        let (sin_pitch, cos_pitch) = (pitch as f32).sin_cos();
+        let light = normalize([-0.4, 0.6, 0.7]);
+
+        // to screen coordinates: x right, y down, z towards the viewer
+        let project = |v: Vec3| -> Vec3 {
+            let [x, y, z] = sub(v, center);
+            let (x, z) = (x * cos_yaw + z * sin_yaw, z * cos_yaw - x * sin_yaw);
+            let (y, z) = (y * cos_pitch - z * sin_pitch, z * cos_pitch + y * sin_pitch);
+            [cx + x * scale, cy - y * scale, z * scale]
+        };
+
+        for triangle in &self.triangles {
+            let [a, b, c] = triangle.map(project);
+            let normal = cross(sub(b, a), sub(c, a));
+            let area = normal[2];
+            if area.abs() < f32::EPSILON {
+                continue; // seen edge-on
+            }
+            // screen y points down, so flip it back for the lighting
+            let normal = normalize([normal[0], -normal[1], normal[2]]);
+            let intensity = AMBIENT + (1.0 - AMBIENT) * dot(normal, light).abs();
+            let color = Rgb(MODEL_COLOR.map(|c| (255.0 * c * intensity).min(255.0) as u8));
+
+            let x0 = a[0].min(b[0]).min(c[0]).floor().max(0.0) as u32;
+            let y0 = a[1].min(b[1]).min(c[1]).floor().max(0.0) as u32;
+            let x1 = (a[0].max(b[0]).max(c[0]).ceil() as u32).min(width);
+            let y1 = (a[1].max(b[1]).max(c[1]).ceil() as u32).min(height);
+            for py in y0..y1 {
+                for px in x0..x1 {
+                    let p = [px as f32 + 0.5, py as f32 + 0.5, 0.0];
+                    // barycentric weights, all with the sign of the area when inside
+                    let wa = edge(b, c, p) / area;
+                    let wb = edge(c, a, p) / area;
+                    let wc = edge(a, b, p) / area;
+                    if wa < 0.0 || wb < 0.0 || wc < 0.0 {
+                        continue;
+                    }
+                    let z = wa * a[2] + wb * b[2] + wc * c[2];
+                    let index = (py * width + px) as usize;
+                    if z > depth[index] {
+                        depth[index] = z;
+                        image.put_pixel(px, py, color);
+                    }
+                }
+            }
+        }
+        image
+    }
+}
+
+/// Model with the current orientation and its rendering
+#[derive(Debug, Clone)]
+pub struct ModelImage {
+    mesh: Arc<Mesh>,
+    yaw: f64,
+    pitch: f64,
+    image: SingleImage,
+}
+
+impl ModelImage {
+    pub fn new(mesh: Mesh) -> MviewResult<Self> {
+        let image = Self::render(&mesh, INITIAL_YAW, INITIAL_PITCH)?;
+        Ok(Self {
+            mesh: Arc::new(mesh),
+            yaw: INITIAL_YAW,
+            pitch: INITIAL_PITCH,
+            image,
+        })
+    }
+
+    fn render(mesh: &Mesh, yaw: f64, pitch: f64) -> MviewResult<SingleImage> {
+        let duration = Performance::start();
+        let image = mesh.render(RENDER_SIZE, RENDER_SIZE, yaw, pitch);
+        let surface = RsImageLoader::rgb8_image_to_surface(&image)?;
+        duration.elapsed_suffix(
+            "render model",
+            &format!("({} triangles)", mesh.num_triangles()),
+        );
+        Ok(SingleImage::new(surface))
+    }
+
+    /// Orbit around the model, horizontal movement turns it, vertical movement tilts it
+    pub fn rotate(&mut self, delta: VectorD) -> bool {
+        let yaw = self.yaw + delta.x() * ORBIT_SPEED;
+        let pitch = (self.pitch + delta.y() * ORBIT_SPEED)
+            .clamp(-std::f64::consts::FRAC_PI_2, std::f64::consts::FRAC_PI_2);
+        if yaw == self.yaw && pitch == self.pitch {
+            return false;
+        }
+        match Self::render(&self.mesh, yaw, pitch) {
+            Ok(image) => {
+                self.yaw = yaw;
+                self.pitch = pitch;
+                self.image = image;
+                true
+            }
+            Err(e) => {
+                eprintln!("Failed to render model: {e}");
+                false
+            }
+        }
+    }
+
+    pub fn image(&self) -> &SingleImage {
+        &self.image
+    }
+
+    pub fn num_triangles(&self) -> usize {
+        self.mesh.num_triangles()
+    }
+
+    pub fn size(&self) -> SizeD {
+        self.image.size()
+    }
+
+    pub fn has_alpha(&self) -> bool {
+        false
+    }
+}
+
+pub fn is_model(path: &Path) -> bool {
+    detect::format_from_path(path) == FileFormat::Model
+}
+
+pub fn read_model(path: &Path) -> MviewResult<Mesh> {
+    let mut data = Vec::new();
+    File::open(path)?
+        .take(MAX_MODEL_SIZE)
+        .read_to_end(&mut data)?;
+    Mesh::parse(&path_to_extension(path), &data)
+}
+
+pub fn thumbnail(path: &Path) -> MviewResult<DynamicImage> {
+    let mesh = read_model(path)?;
+    Ok(DynamicImage::ImageRgb8(mesh.render(
+        THUMBNAIL_SIZE,
+        THUMBNAIL_SIZE,
+        INITIAL_YAW,
+        INITIAL_PITCH,
+    )))
+}
+
+/// STL models are usually Z-up (3D printing), the renderer is Y-up
+fn z_up_to_y_up([x, y, z]: Vec3) -> Vec3 {
+    [x, z, -y]
+}
+
+fn parse_vec3<'a>(mut parts: impl Iterator<Item = &'a str>) -> Option<Vec3> {
+    let mut next = || parts.next()?.parse::<f32>().ok();
+    Some([next()?, next()?, next()?])
+}
+
+fn sub(a: Vec3, b: Vec3) -> Vec3 {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn dot(a: Vec3, b: Vec3) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: Vec3, b: Vec3) -> Vec3 {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn length(a: Vec3) -> f32 {
+    dot(a, a).sqrt()
+}
+
+fn normalize(a: Vec3) -> Vec3 {
+    let len = length(a);
+    if len > 0.0 {
+        a.map(|c| c / len)
+    } else {
+        a
+    }
+}
+
+/// Twice the signed area of triangle (a, b, p) in the screen plane
+fn edge(a: Vec3, b: Vec3, p: Vec3) -> f32 {
+    (b[0] - a[0]) * (p[1] - a[1]) - (b[1] - a[1]) * (p[0] - a[0])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CUBE_OBJ: &str = "\
+# unit cube
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+v 0 0 1
+v 1 0 1
+v 1 1 1
+v 0 1 1
+f 1 2 3 4
+f 5/1 6/2 7/3 8/4
+f 1//1 2//1 6//1 5//1
+f -5 -6 -2 -1
+f 1 5 8 4
+f 2 3 7 6
+";
+
+    #[test]
+    fn test_parse_obj() {
+        let mesh = Mesh::parse("OBJ", CUBE_OBJ.as_bytes()).unwrap();
+        assert_eq!(mesh.num_triangles(), 12);
+        assert!(Mesh::parse("obj", b"v 0 0 0\nf 1 2 3\n").is_err());
+        assert!(Mesh::parse("obj", b"v 0 0 0\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_stl() {
+        let ascii = "solid test\n facet normal 0 0 1\n  outer loop\n   vertex 0 0 0\n   \
+                     vertex 1 0 0\n   vertex 0 1 0\n  endloop\n endfacet\nendsolid test\n";
+        let mesh = Mesh::parse("stl", ascii.as_bytes()).unwrap();
+        assert_eq!(
+            mesh.triangles,
+            vec![[[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, -1.0]]]
+        );
+
+        // binary with a header that starts with "solid" as some exporters do
+        let mut binary = b"solid binary".to_vec();
+        binary.resize(80, 0);
+        binary.extend_from_slice(&2u32.to_le_bytes());
+        for _ in 0..2 {
+            for value in [
+                0.0f32, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0,
+            ] {
+                binary.extend_from_slice(&value.to_le_bytes());
+            }
+            binary.extend_from_slice(&[0, 0]);
+        }
+        let mesh = Mesh::parse("stl", &binary).unwrap();
+        assert_eq!(mesh.num_triangles(), 2);
+        assert_eq!(mesh.triangles[0][2], [0.0, 1.0, 0.0]);
+
+        assert!(Mesh::parse("stl", b"garbage").is_err());
+    }
+
+    #[test]
+    fn test_render() {
+        let mesh = Mesh::parse("obj", CUBE_OBJ.as_bytes()).unwrap();
+        let image = mesh.render(64, 48, INITIAL_YAW, INITIAL_PITCH);
+        assert_eq!(image.dimensions(), (64, 48));
+        assert_eq!(*image.get_pixel(0, 0), BACKGROUND);
+        assert_ne!(*image.get_pixel(32, 24), BACKGROUND);
+
+        // any orientation fits in the image
+        for step in 0..16 {
+            let angle = step as f64 * 0.4;
+            let image = mesh.render(32, 32, angle, angle / 2.0);
+            for x in 0..32 {
+                assert_eq!(*image.get_pixel(x, 0), BACKGROUND);
+                assert_eq!(*image.get_pixel(x, 31), BACKGROUND);
+            }
+        }
+    }
+}
@@ -0,0 +1,105 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Soft-proofing: simulate on screen how an image will look when printed
+//!
+//! The display (assumed sRGB) is converted through a printer ICC profile and back. When the
+//! gamut warning is enabled, colors that the printer cannot reproduce are shown in magenta.
+
+use std::path::Path;
+
+use cairo::ImageSurface;
+use lcms2::{Flags, InfoType, Intent, Locale, PixelFormat, Profile, ThreadContext, Transform};
+
+use crate::{error::MviewResult, mview6_error};
+
+/// Gamut alarm color (magenta, 16 bit per channel)
+const GAMUT_ALARM: [u16; 16] = [0xffff, 0, 0xffff, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+
+pub struct SoftProof {
+    // NOTE: the transform must be dropped before the context it was created in
+    transform: Transform<u8, u8, ThreadContext>,
+    _context: ThreadContext,
+    name: String,
+    gamut_check: bool,
+}
+
+impl SoftProof {
+    pub fn new(printer_profile: &Path, gamut_check: bool) -> MviewResult<Self> {
+        let mut context = ThreadContext::new();
+        context.set_alarm_codes(GAMUT_ALARM);
+
+        let printer = Profile::new_file_context(&context, printer_profile)?;
+        let name = printer
+            .info(InfoType::Description, Locale::none())
+            .unwrap_or_else(|| {
+                printer_profile
+                    .file_stem()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_string()
+            });
+        let srgb = Profile::new_srgb_context(&context);
+
+        let flags = if gamut_check {
+            Flags::SOFT_PROOFING | Flags::GAMUT_CHECK
+        } else {
+            Flags::SOFT_PROOFING
+        };
+
+        // Cairo ARGB32 is native endian, so BGRA in memory on little endian machines
+        let transform = Transform::new_proofing_context(
+            &context,
+            &srgb,
+            PixelFormat::BGRA_8,
+            &srgb,
+            PixelFormat::BGRA_8,
+            &printer,
+            Intent::Perceptual,
+            Intent::RelativeColorimetric,
+            flags,
+        )
+        .map_err(|e| mview6_error!(format!("Cannot create soft-proof transform: {e}")))?;
+
+        Ok(Self {
+            transform,
+            _context: context,
+            name,
+            gamut_check,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn gamut_check(&self) -> bool {
+        self.gamut_check
+    }
+
+    /// Convert an opaque cairo ARGB32 surface in place
+    pub fn apply_to_surface(&self, surface: &mut ImageSurface) -> MviewResult<()> {
+        surface.flush();
+        let mut data = surface
+            .data()
+            .map_err(|e| mview6_error!(format!("Cannot access surface data: {e}")))?;
+        self.transform.transform_in_place(&mut data);
+        Ok(())
+    }
+}
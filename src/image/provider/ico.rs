@@ -0,0 +1,262 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Windows icon (.ico) and cursor (.cur) files. These contain several images, usually
+//! the same picture at different sizes and color depths.
+
+use std::{fs, io::Cursor, path::Path};
+
+use image::{ImageFormat, ImageReader};
+
+use crate::{
+    content::Content,
+    error::MviewResult,
+    image::{provider::image_rs::RsImageLoader, Frame, SingleImage},
+    mview6_error,
+};
+
+const ICON_DIR_SIZE: usize = 6;
+const ICON_ENTRY_SIZE: usize = 16;
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1A\n";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IconEntry {
+    pub width: u32,
+    pub height: u32,
+    pub bit_count: u16,
+    /// Hotspot of a cursor
+    pub hotspot: Option<(u16, u16)>,
+    pub png: bool,
+    offset: usize,
+    size: usize,
+}
+
+impl IconEntry {
+    pub fn info(&self) -> String {
+        let mut info = format!("{} × {}", self.width, self.height);
+        if self.bit_count > 0 {
+            info.push_str(&format!(", {} bpp", self.bit_count));
+        }
+        if self.png {
+            info.push_str(", png");
+        }
+        if let Some((x, y)) = self.hotspot {
+            info.push_str(&format!(", hotspot {x},{y}"));
+        }
+        info
+    }
+}
+
+fn u16_at(data: &[u8], pos: usize) -> u16 {
+    u16::from_le_bytes([data[pos], data[pos + 1]])
+}
+
+fn u32_at(data: &[u8], pos: usize) -> u32 {
+    u32::from_le_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]])
+}
+
+/// Check for the icon (type 1) or cursor (type 2) file header
+pub fn is_icon(data: &[u8]) -> bool {
+    data.len() >= ICON_DIR_SIZE
+        && (data.starts_with(&[0, 0, 1, 0]) || data.starts_with(&[0, 0, 2, 0]))
+        && u16_at(data, 4) > 0
+}
+
+/// Read the directory of an icon or cursor file
+pub fn icon_entries(data: &[u8]) -> MviewResult<Vec<IconEntry>> {
+    if !is_icon(data) {
+        return mview6_error!("Not an icon or cursor file").into();
+    }
+    let cursor = data[2] == 2;
+    let count = u16_at(data, 4) as usize;
+    if data.len() < ICON_DIR_SIZE + count * ICON_ENTRY_SIZE {
+        return mview6_error!("Icon directory truncated").into();
+    }
+    let mut entries = Vec::with_capacity(count);
+    for i in 0..count {
+        let e = ICON_DIR_SIZE + i * ICON_ENTRY_SIZE;
+        let size = u32_at(data, e + 8) as usize;
+        let offset = u32_at(data, e + 12) as usize;
+        if offset.checked_add(size).is_none_or(|end| end > data.len()) {
+            continue;
+        }
+        let image = &data[offset..offset + size];
+        let png = image.starts_with(PNG_SIGNATURE);
+        // Cursors store the hotspot where icons store planes and bit count
+        let bit_count = if png {
+            if image.len() >= 26 {
+                png_bit_count(image[24], image[25])
+            } else {
+                0
+            }
+        } else if cursor || u16_at(data, e + 6) == 0 {
+            if image.len() >= 16 {
+                u16_at(image, 14)
+            } else {
+                0
+            }
+        } else {
+            u16_at(data, e + 6)
+        };
+        entries.push(IconEntry {
+            width: if data[e] == 0 { 256 } else { data[e] as u32 },
+            height: if data[e + 1] == 0 {
+                256
+            } else {
+                data[e + 1] as u32
+            },
+            bit_count,
+            hotspot: cursor.then(|| (u16_at(data, e + 4), u16_at(data, e + 6))),
+            png,
+            offset,
+            size,
+        });
+    }
+    Ok(entries)
+}
+
+/// Bits per pixel from the bit depth and color type in the PNG header
+fn png_bit_count(depth: u8, color_type: u8) -> u16 {
+    let channels = match color_type {
+        2 => 3,
+        4 => 2,
+        6 => 4,
+        _ => 1,
+    };
+    depth as u16 * channels
+}
+
+/// Single image icon file for one entry, which can be handled by the ico decoder
+pub fn frame_data(data: &[u8], entry: &IconEntry) -> Vec<u8> {
+    let offset = ICON_DIR_SIZE + ICON_ENTRY_SIZE;
+    let mut frame = Vec::with_capacity(offset + entry.size);
+    frame.extend_from_slice(&[0, 0, 1, 0, 1, 0]);
+    frame.push(entry.width as u8); // 256 wraps to 0
+    frame.push(entry.height as u8);
+    frame.extend_from_slice(&[0, 0]);
+    frame.extend_from_slice(&1u16.to_le_bytes());
+    frame.extend_from_slice(&entry.bit_count.to_le_bytes());
+    frame.extend_from_slice(&(entry.size as u32).to_le_bytes());
+    frame.extend_from_slice(&(offset as u32).to_le_bytes());
+    frame.extend_from_slice(&data[entry.offset..entry.offset + entry.size]);
+    frame
+}
+
+/// Index of the frame to show by default: the largest one with the most colors
+pub fn largest(entries: &[IconEntry]) -> usize {
+    entries
+        .iter()
+        .enumerate()
+        .max_by_key(|(_, e)| (e.width * e.height, e.bit_count))
+        .map(|(i, _)| i)
+        .unwrap_or_default()
+}
+
+pub struct IcoImageLoader {}
+
+impl IcoImageLoader {
+    pub fn image_from_file(path: &Path) -> MviewResult<Content> {
+        Self::image_from_memory(&fs::read(path)?)
+    }
+
+    pub fn image_from_memory(data: &[u8]) -> MviewResult<Content> {
+        let entries = icon_entries(data)?;
+        let start = largest(&entries);
+        let mut frames = Vec::with_capacity(entries.len());
+        let mut current = 0;
+        for (index, entry) in entries.into_iter().enumerate() {
+            let reader =
+                ImageReader::with_format(Cursor::new(frame_data(data, &entry)), ImageFormat::Ico);
+            match RsImageLoader::surface(reader) {
+                Ok(surface) => {
+                    if index == start {
+                        current = frames.len();
+                    }
+                    frames.push(Frame {
+                        image: SingleImage::new(surface),
+                        info: entry.info(),
+                    });
+                }
+                Err(e) => eprintln!("Skipping icon frame {}: {e}", entry.info()),
+            }
+        }
+        if frames.is_empty() {
+            return mview6_error!("No decodable images in icon").into();
+        }
+        Ok(Content::new_frames(frames, current))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn icon(kind: u8, entries: &[(u8, u16, u16, &[u8])]) -> Vec<u8> {
+        let mut data = vec![0, 0, kind, 0, entries.len() as u8, 0];
+        let mut offset = ICON_DIR_SIZE + entries.len() * ICON_ENTRY_SIZE;
+        for (size, planes, bit_count, image) in entries {
+            data.extend_from_slice(&[*size, *size, 0, 0]);
+            data.extend_from_slice(&planes.to_le_bytes());
+            data.extend_from_slice(&bit_count.to_le_bytes());
+            data.extend_from_slice(&(image.len() as u32).to_le_bytes());
+            data.extend_from_slice(&(offset as u32).to_le_bytes());
+            offset += image.len();
+        }
+        for (_, _, _, image) in entries {
+            data.extend_from_slice(image);
+        }
+        data
+    }
+
+    #[test]
+    fn entries() {
+        let mut dib = vec![0u8; 40];
+        dib[14] = 8;
+        let mut png = PNG_SIGNATURE.to_vec();
+        png.extend_from_slice(&[0; 16]);
+        png.extend_from_slice(&[8, 6]);
+        let data = icon(1, &[(16, 1, 8, &dib), (0, 1, 0, &png)]);
+        assert!(is_icon(&data));
+
+        let entries = icon_entries(&data).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!((entries[0].width, entries[0].bit_count), (16, 8));
+        assert_eq!((entries[1].width, entries[1].bit_count), (256, 32));
+        assert!(entries[1].png && entries[1].hotspot.is_none());
+        assert_eq!(largest(&entries), 1);
+
+        let frame = frame_data(&data, &entries[0]);
+        assert_eq!(frame.len(), ICON_DIR_SIZE + ICON_ENTRY_SIZE + dib.len());
+        assert_eq!(icon_entries(&frame).unwrap()[0].bit_count, 8);
+    }
+
+    #[test]
+    fn cursor() {
+        let mut dib = vec![0u8; 40];
+        dib[14] = 32;
+        let data = icon(2, &[(32, 3, 5, &dib)]);
+        let entries = icon_entries(&data).unwrap();
+        assert_eq!(entries[0].hotspot, Some((3, 5)));
+        assert_eq!(entries[0].bit_count, 32);
+        // The decoder only accepts icons
+        let frame = frame_data(&data, &entries[0]);
+        assert_eq!(&frame[..4], &[0, 0, 1, 0]);
+        assert!(!is_icon(&data[..4]));
+    }
+}
@@ -18,8 +18,7 @@
 // OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use std::{
-    fs::File,
-    io::{BufRead, BufReader, Cursor, Seek},
+    io::{BufRead, Cursor, Seek},
     path::Path,
 };
 
@@ -30,7 +29,8 @@ use glib::Bytes;
 use image::{DynamicImage, GenericImageView, ImageReader, RgbImage, RgbaImage};
 
 use crate::{
-    content::Content, error::MviewResult, image::provider::surface::SurfaceData, mview6_error,
+    content::Content, error::MviewResult, file_reader::FileReader,
+    image::provider::surface::SurfaceData, mview6_error,
 };
 
 use super::{webp::WebP, ExifReader};
@@ -48,7 +48,7 @@ impl RsImageLoader {
 }
 
 impl RsImageLoader {
-    pub fn image_from_file(mut reader: BufReader<File>) -> MviewResult<Content> {
+    pub fn image_from_file(mut reader: FileReader) -> MviewResult<Content> {
         let exif = reader.exif();
         let image_reader = ImageReader::new(reader);
         let image_reader = image_reader.with_guessed_format()?;
@@ -18,16 +18,16 @@
 // OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 pub mod gdk;
+pub mod ico;
 pub mod image_rs;
 pub mod internal;
 pub mod surface;
 pub mod webp;
 
-use crate::profile::performance::Performance;
+use crate::{cache::CacheKind, profile::performance::Performance};
 use exif::Exif;
 use image::DynamicImage;
 use std::{
-    fs,
     io::{BufRead, Seek},
     path::Path,
 };
@@ -36,13 +36,9 @@ pub struct ImageSaver {}
 
 impl ImageSaver {
     pub fn save_thumbnail(thumbnail_path: &Path, image: &DynamicImage) {
-        if let Some(thumbnail_dir) = thumbnail_path.parent() {
-            if !thumbnail_dir.exists() {
-                if let Err(error) = fs::create_dir_all(thumbnail_dir) {
-                    println!("Failed to create thumbnail directory: {error:?}");
-                    return;
-                }
-            }
+        if let Err(error) = CacheKind::Thumbnails.create_directory() {
+            println!("Failed to create thumbnail directory: {error:?}");
+            return;
         }
 
         let image = match image.color() {
@@ -51,6 +51,11 @@ impl SurfaceData {
         )?)
     }
 
+    /// Pixel data with its width, height and stride
+    pub fn pixels_mut(&mut self) -> (&mut [u8], i32, i32, i32) {
+        (&mut self.data, self.width, self.height, self.stride)
+    }
+
     pub fn from_rgba8(width: u32, height: u32, rgba8: &[u8]) -> SurfaceData {
         let stride = 4 * width as usize;
         let mut surface_data = vec![0; stride * height as usize];
@@ -228,6 +233,41 @@ impl SurfaceData {
     }
 }
 
+/// Straight (not premultiplied) RGBA pixels of an ARGB32 surface, e.g. for the clipboard
+pub fn rgba8_from_surface(surface: &mut ImageSurface) -> MviewResult<Vec<u8>> {
+    let width = surface.width() as usize;
+    let height = surface.height() as usize;
+    let stride = surface.stride() as usize;
+    surface.flush();
+    let data = surface
+        .data()
+        .map_err(|e| mview6_error!(format!("Cannot access surface data: {e}")))?;
+    Ok(rgba8_from_argb32(&data, width, height, stride))
+}
+
+/// Straight RGBA pixels from the data of an ARGB32 surface
+pub fn rgba8_from_argb32(data: &[u8], width: usize, height: usize, stride: usize) -> Vec<u8> {
+    let mut rgba8 = Vec::with_capacity(4 * width * height);
+    for row in data.chunks_exact(stride).take(height) {
+        for pixel in row.chunks_exact(4).take(width) {
+            // native endian u32, so BGRA on little endian
+            let alpha = pixel[3] as u16;
+            let unpremultiply = |value: u8| match alpha {
+                0 => 0,
+                255 => value,
+                _ => ((value as u16 * 255) / alpha).min(255) as u8,
+            };
+            rgba8.extend([
+                unpremultiply(pixel[2]),
+                unpremultiply(pixel[1]),
+                unpremultiply(pixel[0]),
+                pixel[3],
+            ]);
+        }
+    }
+    rgba8
+}
+
 #[inline]
 pub fn convert_rgba_pixel(src: &[u8], dst: &mut [u8]) {
     if src[3] == 255 {
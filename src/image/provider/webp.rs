@@ -17,10 +17,7 @@
 // STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
 // OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::{
-    fs::File,
-    io::{BufRead, BufReader, Cursor, Seek},
-};
+use std::io::{BufRead, Cursor, Seek};
 
 use cairo::ImageSurface;
 use exif::Exif;
@@ -30,6 +27,7 @@ use image_webp::WebPDecoder;
 use crate::{
     content::Content,
     error::MviewResult,
+    file_reader::FileReader,
     image::{
         animation::{Animation, WebPAnimation},
         provider::image_rs::RsImageLoader,
@@ -39,11 +37,11 @@ use crate::{
 pub struct WebP {}
 
 impl WebP {
-    pub fn image_from_file(reader: BufReader<File>, exif: Option<Exif>) -> MviewResult<Content> {
+    pub fn image_from_file(reader: FileReader, exif: Option<Exif>) -> MviewResult<Content> {
         let mut decoder = WebPDecoder::new(reader)?;
         if decoder.is_animated() {
             Ok(Content::new_animation(Animation::WebPFile(Box::new(
-                WebPAnimation::<BufReader<File>>::new(decoder)?,
+                WebPAnimation::<FileReader>::new(decoder)?,
             ))))
         } else {
             Ok(Content::new_surface(Self::read_image(&mut decoder)?, exif))
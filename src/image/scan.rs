@@ -0,0 +1,360 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Display filters for scanned documents
+//!
+//! The filters work on the rendered (visible part of the) page on the render thread, so
+//! the document itself is never modified. Deskew estimates the skew angle of the text
+//! lines with a projection profile and rotates the page back.
+
+use crate::image::provider::surface::SurfaceData;
+
+/// Byte offsets of the color channels in a cairo (A)RGB32 pixel (native endian)
+#[cfg(target_endian = "little")]
+const COLOR: [usize; 3] = [0, 1, 2];
+#[cfg(target_endian = "big")]
+const COLOR: [usize; 3] = [1, 2, 3];
+
+/// Largest skew angle (degrees) deskew corrects
+const MAX_SKEW: f64 = 5.0;
+
+/// Step (degrees) between the skew angles that are tried
+const SKEW_STEP: f64 = 0.1;
+
+/// Pages are analysed at (at most) this width for deskew
+const SKEW_ANALYSIS_WIDTH: i32 = 800;
+
+/// Pixels with a luma below this value count as ink
+const INK_THRESHOLD: u8 = 128;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScanFilter {
+    Deskew,
+    Despeckle,
+    Normalize,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ScanFilters {
+    pub deskew: bool,
+    pub despeckle: bool,
+    pub normalize: bool,
+}
+
+impl ScanFilters {
+    pub fn is_active(&self) -> bool {
+        self.deskew || self.despeckle || self.normalize
+    }
+
+    pub fn toggle(&mut self, filter: ScanFilter) {
+        match filter {
+            ScanFilter::Deskew => self.deskew = !self.deskew,
+            ScanFilter::Despeckle => self.despeckle = !self.despeckle,
+            ScanFilter::Normalize => self.normalize = !self.normalize,
+        }
+    }
+
+    pub fn apply_to_surface(&self, surface: &mut SurfaceData) {
+        let (data, width, height, stride) = surface.pixels_mut();
+        self.apply(data, width, height, stride);
+    }
+
+    /// Apply the enabled filters to cairo (A)RGB32 pixel data
+    pub fn apply(&self, data: &mut [u8], width: i32, height: i32, stride: i32) {
+        let mut page = Page {
+            data,
+            width: width as usize,
+            height: height as usize,
+            stride: stride as usize,
+        };
+        if self.normalize {
+            page.normalize();
+        }
+        if self.despeckle {
+            page.despeckle();
+        }
+        if self.deskew {
+            let angle = page.skew_angle();
+            if angle.abs() >= SKEW_STEP / 2.0 {
+                page.rotate(angle);
+            }
+        }
+    }
+}
+
+struct Page<'a> {
+    data: &'a mut [u8],
+    width: usize,
+    height: usize,
+    stride: usize,
+}
+
+impl Page<'_> {
+    fn offset(&self, x: usize, y: usize) -> usize {
+        y * self.stride + 4 * x
+    }
+
+    fn luma(&self, x: usize, y: usize) -> u8 {
+        let o = self.offset(x, y);
+        let sum: u32 = COLOR.iter().map(|c| self.data[o + c] as u32).sum();
+        (sum / 3) as u8
+    }
+
+    /// Stretch the levels so the darkest and lightest 1% of the pixels become black and white
+    fn normalize(&mut self) {
+        let mut histogram = [0usize; 256];
+        for y in 0..self.height {
+            for x in 0..self.width {
+                histogram[self.luma(x, y) as usize] += 1;
+            }
+        }
+        let clip = self.width * self.height / 100;
+        let low = percentile(&histogram, clip, 0..256);
+        let high = percentile(&histogram, clip, (0..256).rev());
+        if high <= low + 16 {
+            return;
+        }
+        let mut table = [0u8; 256];
+        for (level, value) in table.iter_mut().enumerate() {
+            *value = ((level as f64 - low as f64) * 255.0 / (high - low) as f64)
+                .round()
+                .clamp(0.0, 255.0) as u8;
+        }
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let o = self.offset(x, y);
+                for c in COLOR {
+                    self.data[o + c] = table[self.data[o + c] as usize];
+                }
+            }
+        }
+    }
+
+    /// Remove isolated dark or light pixels: a pixel that is on the other side of the ink
+    /// threshold than all of its neighbours gets their average color
+    fn despeckle(&mut self) {
+        if self.width < 3 || self.height < 3 {
+            return;
+        }
+        let ink: Vec<bool> = (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| (x, y)))
+            .map(|(x, y)| self.luma(x, y) < INK_THRESHOLD)
+            .collect();
+        for y in 1..self.height - 1 {
+            for x in 1..self.width - 1 {
+                let is_ink = ink[y * self.width + x];
+                let isolated = NEIGHBOURS.iter().all(|(dx, dy)| {
+                    let n = (y as isize + dy) as usize * self.width + (x as isize + dx) as usize;
+                    ink[n] != is_ink
+                });
+                if isolated {
+                    let o = self.offset(x, y);
+                    for c in COLOR {
+                        let sum: u32 = NEIGHBOURS
+                            .iter()
+                            .map(|(dx, dy)| {
+                                let n = self
+                                    .offset((x as isize + dx) as usize, (y as isize + dy) as usize);
+                                self.data[n + c] as u32
+                            })
+                            .sum();
+                        self.data[o + c] = (sum / 8) as u8;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Estimate the skew angle (degrees, clockwise) of the text lines
+    ///
+    /// For every candidate angle the ink pixels are projected along lines of that slope.
+    /// When the angle matches the text lines, the projection has high peaks (lines) and
+    /// deep valleys (gaps), which maximizes the sum of squares.
+    fn skew_angle(&self) -> f64 {
+        let step = (self.width as i32 / SKEW_ANALYSIS_WIDTH + 1) as usize;
+        let ink: Vec<(f64, f64)> = (0..self.height)
+            .step_by(step)
+            .flat_map(|y| (0..self.width).step_by(step).map(move |x| (x, y)))
+            .filter(|&(x, y)| self.luma(x, y) < INK_THRESHOLD)
+            .map(|(x, y)| (x as f64, y as f64))
+            .collect();
+        if ink.is_empty() {
+            return 0.0;
+        }
+        let margin = self.width as f64 * MAX_SKEW.to_radians().tan();
+        let rows = self.height + 2 * margin.ceil() as usize + 1;
+        let mut profile = vec![0u32; rows];
+        let steps = (MAX_SKEW / SKEW_STEP).round() as i32;
+        let mut best = (0.0, 0u64);
+        for i in -steps..=steps {
+            let angle = i as f64 * SKEW_STEP;
+            let slope = angle.to_radians().tan();
+            profile.fill(0);
+            for (x, y) in &ink {
+                profile[(y - x * slope + margin) as usize] += 1;
+            }
+            let score = profile.iter().map(|&n| (n as u64) * (n as u64)).sum();
+            if score > best.1 || (score == best.1 && angle.abs() < f64::abs(best.0)) {
+                best = (angle, score);
+            }
+        }
+        best.0
+    }
+
+    /// Rotate the page counter-clockwise by `angle` degrees around its center, the
+    /// uncovered corners become white
+    fn rotate(&mut self, angle: f64) {
+        let source = self.data.to_vec();
+        let (sin, cos) = angle.to_radians().sin_cos();
+        let cx = self.width as f64 / 2.0;
+        let cy = self.height as f64 / 2.0;
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let dx = x as f64 + 0.5 - cx;
+                let dy = y as f64 + 0.5 - cy;
+                let sx = cx + dx * cos - dy * sin - 0.5;
+                let sy = cy + dx * sin + dy * cos - 0.5;
+                let o = self.offset(x, y);
+                for c in COLOR {
+                    self.data[o + c] = self.sample(&source, sx, sy, c);
+                }
+            }
+        }
+    }
+
+    /// Bilinear sample of channel `c` of `source` at (x, y)
+    fn sample(&self, source: &[u8], x: f64, y: f64, c: usize) -> u8 {
+        let x0 = x.floor();
+        let y0 = y.floor();
+        let (fx, fy) = (x - x0, y - y0);
+        let pixel = |x: f64, y: f64| {
+            if x < 0.0 || y < 0.0 || x >= self.width as f64 || y >= self.height as f64 {
+                255.0
+            } else {
+                source[self.offset(x as usize, y as usize) + c] as f64
+            }
+        };
+        let top = pixel(x0, y0) * (1.0 - fx) + pixel(x0 + 1.0, y0) * fx;
+        let bottom = pixel(x0, y0 + 1.0) * (1.0 - fx) + pixel(x0 + 1.0, y0 + 1.0) * fx;
+        (top * (1.0 - fy) + bottom * fy).round() as u8
+    }
+}
+
+/// First level (in the order of `levels`) where more than `clip` pixels have been counted
+fn percentile(histogram: &[usize; 256], clip: usize, levels: impl Iterator<Item = usize>) -> usize {
+    let mut count = 0;
+    for level in levels {
+        count += histogram[level];
+        if count > clip {
+            return level;
+        }
+    }
+    0
+}
+
+const NEIGHBOURS: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WIDTH: usize = 400;
+    const HEIGHT: usize = 300;
+
+    /// White page with horizontal "text lines" rotated clockwise by `angle` degrees
+    fn page(angle: f64) -> Vec<u8> {
+        let mut data = vec![255u8; WIDTH * HEIGHT * 4];
+        let slope = angle.to_radians().tan();
+        for line in (40..HEIGHT - 40).step_by(20) {
+            for x in 20..WIDTH - 20 {
+                for thickness in 0..3 {
+                    let y = (line as f64 + thickness as f64 + x as f64 * slope).round() as usize;
+                    data[(y * WIDTH + x) * 4..][..3].fill(0);
+                }
+            }
+        }
+        data
+    }
+
+    fn skew(data: &mut [u8]) -> f64 {
+        Page {
+            data,
+            width: WIDTH,
+            height: HEIGHT,
+            stride: WIDTH * 4,
+        }
+        .skew_angle()
+    }
+
+    #[test]
+    fn deskew() {
+        let mut data = page(2.0);
+        assert!((skew(&mut data) - 2.0).abs() < 0.15);
+        assert!(skew(&mut page(-3.0)).abs() > 2.8);
+
+        let filters = ScanFilters {
+            deskew: true,
+            ..Default::default()
+        };
+        filters.apply(&mut data, WIDTH as i32, HEIGHT as i32, WIDTH as i32 * 4);
+        assert!(skew(&mut data).abs() < 0.15);
+    }
+
+    #[test]
+    fn despeckle() {
+        let mut data = vec![255u8; WIDTH * HEIGHT * 4];
+        data[(10 * WIDTH + 10) * 4..][..3].fill(0);
+        data[(20 * WIDTH + 20) * 4..][..3].fill(0);
+        data[(20 * WIDTH + 21) * 4..][..3].fill(0);
+        let filters = ScanFilters {
+            despeckle: true,
+            ..Default::default()
+        };
+        filters.apply(&mut data, WIDTH as i32, HEIGHT as i32, WIDTH as i32 * 4);
+        assert_eq!(data[(10 * WIDTH + 10) * 4], 255);
+        assert_eq!(data[(20 * WIDTH + 20) * 4], 0);
+    }
+
+    #[test]
+    fn normalize() {
+        let mut data: Vec<u8> = (0..WIDTH * HEIGHT)
+            .flat_map(|i| {
+                let v = (60 + i % 120) as u8;
+                [v, v, v, 255]
+            })
+            .collect();
+        let filters = ScanFilters {
+            normalize: true,
+            ..Default::default()
+        };
+        filters.apply(&mut data, WIDTH as i32, HEIGHT as i32, WIDTH as i32 * 4);
+        assert_eq!(data.iter().step_by(4).min(), Some(&0));
+        assert_eq!(data.iter().step_by(4).max(), Some(&255));
+        assert_eq!(data[3], 255);
+    }
+}
@@ -219,6 +219,12 @@ enum SvgElement {
         spans: Vec<(String, MViewColor)>,
         style: TextStyle,
     },
+    Image {
+        position: PointD,
+        width: f64,
+        height: f64,
+        href: String,
+    },
 }
 
 /// Main SVG Canvas for programmatic SVG creation
@@ -264,6 +270,23 @@ impl SvgCanvas {
         self
     }
 
+    /// Add an image to the canvas, `href` is usually a data url
+    pub fn add_image(
+        &mut self,
+        position: PointD,
+        width: f64,
+        height: f64,
+        href: &str,
+    ) -> &mut Self {
+        self.elements.push(SvgElement::Image {
+            position,
+            width,
+            height,
+            href: escape_xml(href),
+        });
+        self
+    }
+
     /// Add a line to the canvas
     pub fn add_line(&mut self, start: PointD, end: PointD, style: LineStyle) -> &mut Self {
         self.elements.push(SvgElement::Line { start, end, style });
@@ -456,6 +479,21 @@ impl SvgCanvas {
 
                     svg.push_str("</text>");
                 }
+                SvgElement::Image {
+                    position,
+                    width,
+                    height,
+                    href,
+                } => {
+                    svg.push_str(&format!(
+                        r#"<image x="{}" y="{}" width="{}" height="{}" href="{}"/>"#,
+                        position.x(),
+                        position.y(),
+                        width,
+                        height,
+                        href
+                    ));
+                }
             }
         }
 
@@ -29,7 +29,7 @@ use crate::{
         svg::creator::{FontWeight, LineStyle, SvgCanvas, TextAnchor, TextStyle},
         view::{data::TransparencyMode, ZoomMode},
     },
-    rect::{PointD, RectD, VectorD},
+    rect::{PointD, RectD, SizeD, VectorD},
     util::{ellipsis_middle, path_to_directory, path_to_filename},
 };
 
@@ -71,6 +71,12 @@ impl TextSheet {
         self.canvas.add_multicolor_text(self.pos, spans, style);
     }
 
+    /// Add an image with its top left corner at the current position
+    pub fn add_image(&mut self, size: SizeD, href: &str) {
+        self.canvas
+            .add_image(self.pos, size.width(), size.height(), href);
+    }
+
     pub fn delta_x(&mut self, delta: f64) {
         self.pos += self.style.delta_x(delta);
     }
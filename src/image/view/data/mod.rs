@@ -20,14 +20,15 @@
 pub mod redraw;
 pub mod zoom;
 
+use async_channel::Sender;
 use cairo::{Filter, ImageSurface};
 use glib::SourceId;
 use gtk4::prelude::WidgetExt;
 
 use crate::{
-    backends::thumbnail::model::Annotations,
+    backends::thumbnail::model::{Annotations, Message},
     content::{Content, ContentData},
-    image::{Image, RenderedImage},
+    image::{lut::Lut3D, proof::SoftProof, Image, RenderedImage},
     rect::{PointD, RectD},
     render_thread::{model::RenderCommand, RenderThreadSender},
 };
@@ -68,6 +69,45 @@ impl From<TransparencyMode> for &str {
     }
 }
 
+/// Paper tint for document pages, for more comfortable (e-)reading
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PageTint {
+    #[default]
+    None,
+    Sepia,
+    Solarized,
+    Dark,
+}
+
+impl From<&str> for PageTint {
+    fn from(value: &str) -> Self {
+        match value {
+            "sepia" => Self::Sepia,
+            "solarized" => Self::Solarized,
+            "dark" => Self::Dark,
+            _ => Self::None,
+        }
+    }
+}
+
+impl From<PageTint> for &str {
+    fn from(value: PageTint) -> Self {
+        match value {
+            PageTint::None => "none",
+            PageTint::Sepia => "sepia",
+            PageTint::Solarized => "solarized",
+            PageTint::Dark => "dark",
+        }
+    }
+}
+
+/// Sharper version of the thumbnail in the hovered cell of a thumbnail sheet
+pub struct HoverPreview {
+    pub id: i32,
+    pub size: u32,
+    pub surface: ImageSurface,
+}
+
 pub struct ImageViewData {
     pub content: Content,
     pub zoom: Zoom,
@@ -78,11 +118,22 @@ pub struct ImageViewData {
     pub view: Option<ImageView>,
     pub mouse_position: PointD,
     pub drag: Option<PointD>,
+    pub orbit: Option<PointD>,
     pub quality: Filter,
     pub annotations: Option<Annotations>,
     pub hover: Option<i32>,
+    pub hover_preview: Option<HoverPreview>,
     pub shown: bool,
     pub rb_sender: Option<RenderThreadSender>,
+    pub tn_sender: Option<Sender<Message>>,
+    pub lut: Option<Lut3D>,
+    pub lut_enabled: bool,
+    pub soft_proof: Option<SoftProof>,
+    pub page_tint: PageTint,
+    /// High quality rendering of the content was abandoned, only the preview is shown
+    pub render_timed_out: bool,
+    /// Changes whenever the content has to be drawn again, not when only the overlays do
+    pub generation: u64,
     hq_redraw_timeout_id: Option<SourceId>,
 }
 
@@ -98,11 +149,20 @@ impl Default for ImageViewData {
             view: None,
             mouse_position: PointD::default(),
             drag: None,
+            orbit: None,
             quality: QUALITY_HIGH,
             annotations: Default::default(),
             hover: None,
+            hover_preview: None,
             shown: false,
             rb_sender: None,
+            tn_sender: None,
+            lut: None,
+            lut_enabled: false,
+            soft_proof: None,
+            page_tint: PageTint::None,
+            render_timed_out: false,
+            generation: 0,
             hq_redraw_timeout_id: None,
         }
     }
@@ -122,19 +182,46 @@ impl ImageViewData {
                 allocation.width() as f64,
                 allocation.height() as f64,
             );
-            let size = self.content.size();
-            let zoom_mode = if size.width() < 0.1 || size.height() < 0.1 {
+            let zoom_mode = self.effective_zoom_mode();
+            self.zoom
+                .apply_zoom(zoom_mode, self.content.size(), viewport);
+        }
+    }
+
+    /// Zoom mode of the content, or the one chosen by the user when it has none
+    fn effective_zoom_mode(&self) -> ZoomMode {
+        let size = self.content.size();
+        if size.width() < 0.1 || size.height() < 0.1 {
+            ZoomMode::NoZoom
+        } else if self.content.zoom_mode == ZoomMode::NotSpecified {
+            if self.zoom_mode == ZoomMode::NotSpecified {
                 ZoomMode::NoZoom
-            } else if self.content.zoom_mode == ZoomMode::NotSpecified {
-                if self.zoom_mode == ZoomMode::NotSpecified {
-                    ZoomMode::NoZoom
-                } else {
-                    self.zoom_mode
-                }
             } else {
-                self.content.zoom_mode
-            };
-            self.zoom.apply_zoom(zoom_mode, size, viewport);
+                self.zoom_mode
+            }
+        } else {
+            self.content.zoom_mode
+        }
+    }
+
+    /// Zoom that places the content in `viewport` the way the view would after loading it:
+    /// zoom mode and rotation are kept, interactive zooming and panning are not
+    pub fn zoom_for_viewport(&self, viewport: RectD) -> Zoom {
+        let mut zoom = Zoom::new();
+        zoom.set_rotation(self.zoom.rotation_degrees());
+        zoom.apply_zoom(self.effective_zoom_mode(), self.content.size(), viewport);
+        zoom
+    }
+
+    /// Image of the content itself, without the high quality rendering of the view
+    pub fn content_image(&'_ self) -> Image<'_> {
+        match &self.content.data {
+            ContentData::Single(single) => Image::Single(single),
+            ContentData::Dual(dual) => Image::Dual(dual),
+            ContentData::Animation(animation) => Image::Animation(animation),
+            ContentData::Frames(frames) => Image::Single(frames.current()),
+            ContentData::Model(model) => Image::Single(model.image()),
+            _ => Image::None,
         }
     }
 
@@ -146,21 +233,34 @@ impl ImageViewData {
     }
 
     pub fn rb_send(&self, command: RenderCommand) {
+        // Do not get stuck on the same content again
+        if self.render_timed_out {
+            return;
+        }
         if let Some(sender) = &self.rb_sender {
             sender.send_blocking(command);
         }
     }
 
+    pub fn active_lut(&self) -> Option<&Lut3D> {
+        if self.lut_enabled {
+            self.lut.as_ref()
+        } else {
+            None
+        }
+    }
+
+    /// Color transforms (soft-proof, monitor LUT) need the view to be drawn on an
+    /// intermediate surface first
+    pub fn needs_color_transform(&self) -> bool {
+        self.active_lut().is_some() || self.soft_proof.is_some()
+    }
+
     pub fn image(&'_ self) -> Image<'_> {
         if let Some(rendered) = &self.zoom_overlay {
             Image::Rendered(rendered)
         } else {
-            match &self.content.data {
-                ContentData::Single(single) => Image::Single(single),
-                ContentData::Dual(dual) => Image::Dual(dual),
-                ContentData::Animation(animation) => Image::Animation(animation),
-                _ => Image::None,
-            }
+            self.content_image()
         }
     }
 }
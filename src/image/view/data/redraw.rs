@@ -58,6 +58,12 @@ pub enum RedrawReason {
     ThumbnailSheetUpdated = 11,
     TransparencyBackgroundChanged = 12,
     ZoomSettingChanged = 13,
+    ColorLutChanged = 14,
+    SoftProofChanged = 15,
+    ReadingRuler = 16,
+    PageTintChanged = 17,
+    ModelOrbit = 18,
+    ZoomInfo = 19,
 }
 
 impl RedrawReason {
@@ -65,6 +71,14 @@ impl RedrawReason {
         matches!(self, Self::InteractiveDrag | Self::InteractiveZoom)
     }
 
+    /// Only the overlays on top of the content change, e.g. the reading ruler
+    pub fn overlay_only(&self) -> bool {
+        matches!(
+            self,
+            Self::Measurement | Self::ReadingRuler | Self::ZoomInfo
+        )
+    }
+
     pub fn quality(&self) -> Filter {
         if matches!(self, Self::AnimationCallback) {
             QUALITY_LOW
@@ -97,6 +111,12 @@ impl From<i32> for RedrawReason {
             11 => RedrawReason::ThumbnailSheetUpdated,
             12 => RedrawReason::TransparencyBackgroundChanged,
             13 => RedrawReason::ZoomSettingChanged,
+            14 => RedrawReason::ColorLutChanged,
+            15 => RedrawReason::SoftProofChanged,
+            16 => RedrawReason::ReadingRuler,
+            17 => RedrawReason::PageTintChanged,
+            18 => RedrawReason::ModelOrbit,
+            19 => RedrawReason::ZoomInfo,
             _ => RedrawReason::Unknown,
         }
     }
@@ -106,9 +126,15 @@ impl ImageViewData {
     fn redraw_quality(&mut self, quality: Filter, reason: RedrawReason) {
         // println!("-- redraw  reason={reason:?}");
         self.quality = quality;
+        if !reason.overlay_only() {
+            self.generation += 1;
+        }
         if let Some(view) = &self.view {
             if quality == QUALITY_HIGH
                 && reason != RedrawReason::RenderDone
+                && reason != RedrawReason::ReadingRuler
+                && reason != RedrawReason::ZoomInfo
+                && reason != RedrawReason::PageTintChanged
                 && self.content.needs_render()
             {
                 let a = view.allocation();
@@ -205,6 +231,26 @@ impl ImageViewData {
             self.redraw(RedrawReason::RenderDone);
         }
     }
+
+    /// High quality rendering failed, the content keeps being shown at preview quality.
+    /// Returns whether the failure concerns the current image.
+    pub fn event_render_failed(&mut self, image_id: u32) -> bool {
+        if self.content.id() != image_id {
+            return false;
+        }
+        self.zoom_overlay = None;
+        self.redraw(RedrawReason::RenderDone);
+        true
+    }
+
+    pub fn event_render_timed_out(&mut self, image_id: u32) {
+        if self.content.id() != image_id {
+            return;
+        }
+        self.render_timed_out = true;
+        self.zoom_overlay = None;
+        self.redraw(RedrawReason::RenderDone);
+    }
 }
 
 #[cfg(test)]
@@ -227,6 +273,11 @@ mod tests {
             RedrawReason::ThumbnailSheetUpdated,
             RedrawReason::TransparencyBackgroundChanged,
             RedrawReason::ZoomSettingChanged,
+            RedrawReason::ColorLutChanged,
+            RedrawReason::SoftProofChanged,
+            RedrawReason::ReadingRuler,
+            RedrawReason::PageTintChanged,
+            RedrawReason::ZoomInfo,
             RedrawReason::Unknown,
         ];
 
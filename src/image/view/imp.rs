@@ -25,24 +25,33 @@ use std::{
 
 use super::{data::ImageViewData, ImageView, ViewCursor};
 use crate::{
+    backends::thumbnail::model::{preview_size, Message, TRect},
     classification::Preference,
     content::Content,
+    error::MviewResult,
+    file_view::model::Progress,
     image::{
         colors::{CairoColorExt, Color},
-        draw::transparency_background,
+        draw::{keyword_chips, transparency_background},
         view::{
             data::{
                 zoom::{ZOOM_MULTIPLIER, ZOOM_MULTIPLIER_FAST},
-                TransparencyMode,
+                PageTint, TransparencyMode,
             },
             measure::{MeasureTool, MeasurementState},
-            RedrawReason, SIGNAL_CANVAS_RESIZED, SIGNAL_NAVIGATE, SIGNAL_SHOWN,
+            ruler::ReadingRuler,
+            RedrawReason, Zoom, SIGNAL_CANVAS_RESIZED, SIGNAL_NAVIGATE, SIGNAL_SHOWN,
         },
+        Image,
     },
-    rect::{PointD, RectD, SizeI},
+    metadata::resolution::PrintResolution,
+    rect::{PointD, RectD, SizeD, SizeI},
     util::remove_source_id,
 };
-use cairo::{Context, Extend, FillRule, SurfacePattern};
+use cairo::{
+    Context, Extend, FillRule, Filter, FontSlant, FontWeight, Format, ImageSurface, Operator,
+    SurfacePattern,
+};
 use gio::prelude::StaticType;
 use glib::{clone, object::ObjectExt, subclass::Signal, ControlFlow, Propagation, SourceId};
 use gtk4::{
@@ -58,6 +67,10 @@ pub struct ImageViewImp {
     animation_timeout_id: RefCell<Option<SourceId>>,
     pub(super) window_size: Cell<SizeI>,
     pub(super) measure_tool: MeasureTool,
+    pub(super) reading_ruler: ReadingRuler,
+    pub(super) zoom_info: Cell<bool>,
+    /// Content with the color transforms applied and the generation it was drawn for
+    color_transformed: RefCell<Option<(u64, ImageSurface)>>,
 }
 
 #[glib::object_subclass]
@@ -113,11 +126,110 @@ impl ImageViewImp {
     }
 
     fn draw(&self, context: &Context) {
+        if self.data.borrow().needs_color_transform() {
+            if let Err(e) = self.draw_color_transformed(context) {
+                eprintln!("Failed to apply color transform: {e}");
+                self.draw_view(context);
+            }
+        } else {
+            self.color_transformed.take();
+            self.draw_view(context);
+        }
+    }
+
+    /// Draw the content on an intermediate surface, apply the color transforms (soft-proof
+    /// first, monitor color LUT last) to it and put the result on screen with the overlays
+    /// on top. The transformed content is reused until the content changes, so moving the
+    /// reading ruler or measuring does not transform the whole view again.
+    fn draw_color_transformed(&self, context: &Context) -> MviewResult<()> {
+        let size = self.window_size.get();
+        let scale = self.obj().scale_factor();
+        let (width, height) = (size.width() * scale, size.height() * scale);
+        let generation = self.data.borrow().generation;
+        let mut cache = self.color_transformed.borrow_mut();
+        let surface = match cache.take() {
+            Some((cached, surface))
+                if cached == generation
+                    && surface.width() == width
+                    && surface.height() == height =>
+            {
+                surface
+            }
+            _ => {
+                let mut surface = ImageSurface::create(Format::ARgb32, width, height)?;
+                surface.set_device_scale(scale as f64, scale as f64);
+                let p = self.data.borrow();
+                {
+                    let surface_context = Context::new(&surface)?;
+                    self.paint(&surface_context, &p, &p.image(), &p.zoom, p.quality);
+                }
+                if let Some(soft_proof) = &p.soft_proof {
+                    soft_proof.apply_to_surface(&mut surface)?;
+                }
+                if let Some(lut) = p.active_lut() {
+                    lut.apply_to_surface(&mut surface)?;
+                }
+                surface
+            }
+        };
+        context.set_source_surface(&surface, 0.0, 0.0)?;
+        context.paint()?;
+        cache.replace((generation, surface));
+        self.draw_overlays(context);
+        Ok(())
+    }
+
+    fn draw_view(&self, context: &Context) {
+        {
+            let p = self.data.borrow();
+            self.paint(context, &p, &p.image(), &p.zoom, p.quality);
+        }
+        self.draw_overlays(context);
+    }
+
+    /// Reading ruler, measurement and badges, drawn on top of the content
+    fn draw_overlays(&self, context: &Context) {
         let p = self.data.borrow();
         let z = &p.zoom;
+        let viewport = clip_extents_to_rect(context);
+
+        if self.reading_ruler.is_enabled() {
+            self.reading_ruler.draw(context, &viewport);
+        }
+
+        if self.measure_tool.state() != MeasurementState::Idle {
+            self.measure_tool.draw(context, z, &self.mouse_position());
+        }
+
+        if p.render_timed_out {
+            draw_badge(
+                context,
+                "render timed out",
+                BadgeCorner::TopLeft,
+                (0.6, 0.1, 0.1, 0.85),
+            );
+        }
 
-        let image = p.image();
+        if self.zoom_info.get() {
+            draw_badge(
+                context,
+                &zoom_info_text(&p, &viewport),
+                BadgeCorner::BottomLeft(&viewport),
+                (0.0, 0.0, 0.0, 0.6),
+            );
+        }
+    }
 
+    /// Draw the content on the whole clip area of the context, shared by the view and
+    /// offscreen snapshots. Interactive overlays (ruler, measurements) are not included.
+    pub(super) fn paint(
+        &self,
+        context: &Context,
+        p: &ImageViewData,
+        image: &Image,
+        z: &Zoom,
+        quality: Filter,
+    ) {
         let _ = context.save();
 
         context.set_fill_rule(FillRule::EvenOdd);
@@ -184,13 +296,13 @@ impl ImageViewImp {
 
         // Viewport offset is handled in the transformation matrix so drawing here happens
         // at the virtual origin (0.0, 0.0)
-        context.transform(image.transform_matrix(&p.zoom));
-        image.draw(context, p.quality);
+        context.transform(image.transform_matrix(z));
+        image.draw(context, quality);
         self.draw_annotations(context);
+        let _ = context.restore();
 
-        if self.measure_tool.state() != MeasurementState::Idle {
-            let _ = context.restore();
-            self.measure_tool.draw(context, z, &self.mouse_position());
+        if p.content.is_doc() {
+            draw_page_tint(context, p.page_tint, &intersect);
         }
     }
 
@@ -199,6 +311,11 @@ impl ImageViewImp {
         if let Some(annotations) = &p.annotations {
             let hover = annotations.get(p.hover);
             if let Some(hover) = hover {
+                if let Some(preview) = &p.hover_preview {
+                    if preview.id == hover.id {
+                        draw_hover_preview(context, &preview.surface, &hover.position);
+                    }
+                }
                 context.set_source_rgba(1.0, 1.0, 1.0, 0.1);
                 context.rectangle(
                     hover.position.x,
@@ -211,6 +328,36 @@ impl ImageViewImp {
                 context.set_line_width(3.0);
                 let _ = context.stroke();
             }
+            for annotation in &annotations.annotations {
+                if !annotation.keywords.is_empty() {
+                    let _ = keyword_chips(
+                        context,
+                        annotation.position.x,
+                        annotation.position.y + annotation.position.height,
+                        annotation.position.width,
+                        &annotation.keywords,
+                    );
+                }
+            }
+            for annotation in &annotations.annotations {
+                if let Some(label) = annotation.label {
+                    let (r, g, b) = label.rgb();
+                    context.set_source_rgb(r, g, b);
+                    context.set_line_width(3.0);
+                    context.rectangle(
+                        annotation.position.x - 3.0,
+                        annotation.position.y - 3.0,
+                        annotation.position.width + 6.0,
+                        annotation.position.height + 6.0,
+                    );
+                    let _ = context.stroke();
+                }
+            }
+            for annotation in &annotations.annotations {
+                if let Some(progress) = annotation.progress {
+                    draw_progress_bar(context, &annotation.position, progress);
+                }
+            }
             for annotation in &annotations.annotations {
                 match annotation.entry.preference() {
                     Preference::Liked => context.set_source_rgb(0.0, 1.0, 0.0),
@@ -238,6 +385,10 @@ impl ImageViewImp {
                 self.measure_tool
                     .set_point(p.zoom.screen_to_image(&position));
                 p.redraw(RedrawReason::Measurement);
+            } else if p.content.model().is_some() {
+                // dragging a 3D model orbits around it instead of moving it
+                p.orbit = Some(position);
+                self.obj().set_view_cursor(ViewCursor::Drag);
             } else if p.drag.is_none() && p.content.is_movable() {
                 p.drag = Some(position - p.zoom.origin());
                 self.obj().set_view_cursor(ViewCursor::Drag);
@@ -260,8 +411,9 @@ impl ImageViewImp {
 
     fn button_release_event(&self) {
         let mut p = self.data.borrow_mut();
-        if p.drag.is_some() {
+        if p.drag.is_some() || p.orbit.is_some() {
             p.drag = None;
+            p.orbit = None;
             self.obj().set_view_cursor(ViewCursor::Normal);
         }
     }
@@ -269,20 +421,51 @@ impl ImageViewImp {
     fn motion_notify_event(&self, position: PointD) {
         let mut p = self.data.borrow_mut();
         p.mouse_position = position;
+        if self.reading_ruler.is_enabled() {
+            self.reading_ruler.set_position(position.y());
+            p.redraw(RedrawReason::ReadingRuler);
+        }
         if self.measure_tool.is_tracking() {
             p.redraw(RedrawReason::Measurement);
         } else if let Some(annotations) = &p.annotations {
             let index = annotations.index_at(position - p.zoom.origin());
             if index != p.hover {
+                if let Some(index) = index {
+                    self.request_hover_preview(&p, index);
+                }
                 p.hover = index;
                 p.redraw(RedrawReason::AnnotationChanged);
             }
+        } else if let Some(orbit) = p.orbit {
+            p.orbit = Some(position);
+            if let Some(model) = p.content.model_mut() {
+                if model.rotate(position - orbit) {
+                    p.redraw(RedrawReason::ModelOrbit);
+                }
+            }
         } else if let Some(drag) = p.drag {
             p.zoom.set_origin(position - drag);
             p.redraw(RedrawReason::InteractiveDrag);
         }
     }
 
+    /// Ask for a sharper version of the hovered thumbnail, with a resolution that matches
+    /// the current zoom
+    fn request_hover_preview(&self, p: &ImageViewData, index: i32) {
+        let (Some(sender), Some(annotations)) = (&p.tn_sender, &p.annotations) else {
+            return;
+        };
+        let scale = p.zoom.scale() * self.obj().scale_factor() as f64;
+        let size = preview_size(annotations.dim.size, scale);
+        if p.hover_preview
+            .as_ref()
+            .is_some_and(|preview| preview.id == index && preview.size >= size)
+        {
+            return;
+        }
+        let _ = sender.send_blocking(Message::Hover(p.content.id(), index, size));
+    }
+
     fn motion_leave_event(&self) {
         let mut p = self.data.borrow_mut();
         if p.hover.is_some() {
@@ -423,6 +606,29 @@ impl DrawingAreaImpl for ImageViewImp {
     }
 }
 
+/// Tint the (visible part of the) page like paper: multiplying keeps the text dark while
+/// white becomes the paper color. The dark tint inverts the page first.
+fn draw_page_tint(context: &Context, page_tint: PageTint, rect: &RectD) {
+    let (r, g, b) = match page_tint {
+        PageTint::None => return,
+        PageTint::Sepia => (0.957, 0.886, 0.741),
+        PageTint::Solarized => (0.992, 0.965, 0.890),
+        PageTint::Dark => (0.85, 0.85, 0.85),
+    };
+    let _ = context.save();
+    context.rectangle(rect.x0, rect.y0, rect.width(), rect.height());
+    context.clip();
+    if page_tint == PageTint::Dark {
+        context.set_operator(Operator::Difference);
+        context.set_source_rgb(1.0, 1.0, 1.0);
+        let _ = context.paint();
+    }
+    context.set_operator(Operator::Multiply);
+    context.set_source_rgb(r, g, b);
+    let _ = context.paint();
+    let _ = context.restore();
+}
+
 /// Utility to convert clip_extents to rectangle
 pub fn clip_extents_to_rect(context: &Context) -> RectD {
     if let Ok((x1, y1, x2, y2)) = context.clip_extents() {
@@ -432,3 +638,90 @@ pub fn clip_extents_to_rect(context: &Context) -> RectD {
         Default::default() // Should not happen
     }
 }
+
+/// Draw the preview scaled into the cell
+fn draw_hover_preview(context: &Context, surface: &ImageSurface, position: &TRect) {
+    if surface.width() <= 0 || surface.height() <= 0 {
+        return;
+    }
+    let _ = context.save();
+    context.rectangle(position.x, position.y, position.width, position.height);
+    context.clip();
+    context.translate(position.x, position.y);
+    context.scale(
+        position.width / surface.width() as f64,
+        position.height / surface.height() as f64,
+    );
+    if context.set_source_surface(surface, 0.0, 0.0).is_ok() {
+        context.source().set_filter(Filter::Good);
+        let _ = context.paint();
+    }
+    let _ = context.restore();
+}
+
+/// Thin bar along the bottom of a thumbnail showing how far a container was read
+fn draw_progress_bar(context: &Context, position: &TRect, progress: Progress) {
+    let height = 4.0;
+    let y = position.y + position.height - height;
+    context.set_source_rgba(0.0, 0.0, 0.0, 0.6);
+    context.rectangle(position.x, y, position.width, height);
+    let _ = context.fill();
+    if progress.is_complete() {
+        context.set_source_rgb(0.0, 0.8, 0.0);
+    } else {
+        context.set_source_rgb(0.58, 0.59, 0.76);
+    }
+    context.rectangle(position.x, y, position.width * progress.fraction(), height);
+    let _ = context.fill();
+}
+
+/// Zoom percentage and, when the image specifies its resolution, the print size of the
+/// image and of the part that is visible
+fn zoom_info_text(p: &ImageViewData, viewport: &RectD) -> String {
+    let z = &p.zoom;
+    let mut text = format!("{:.0}%", 100.0 * z.scale());
+    if let Some(resolution) = p.content.exif().and_then(PrintResolution::from_exif) {
+        let image_size = z.image_size();
+        text.push_str(&format!(" · {}", resolution.describe(image_size)));
+        let visible = z.intersection_image_coord(viewport);
+        if visible.width() < image_size.width() - 0.5
+            || visible.height() < image_size.height() - 0.5
+        {
+            let visible = SizeD::new(visible.width(), visible.height());
+            text.push_str(&format!(" · visible {}", resolution.size_text(visible)));
+        }
+    }
+    text
+}
+
+enum BadgeCorner<'a> {
+    TopLeft,
+    /// Lower left corner of the viewport
+    BottomLeft(&'a RectD),
+}
+
+/// Small label in a corner of the view
+fn draw_badge(
+    context: &Context,
+    text: &str,
+    corner: BadgeCorner,
+    (red, green, blue, alpha): (f64, f64, f64, f64),
+) {
+    context.select_font_face("Liberation Sans", FontSlant::Normal, FontWeight::Normal);
+    context.set_font_size(13.0);
+    let Ok(extents) = context.text_extents(text) else {
+        return;
+    };
+    let padding = 6.0;
+    let height = extents.height() + 2.0 * padding;
+    let (x, y) = match corner {
+        BadgeCorner::TopLeft => (10.0, 10.0),
+        BadgeCorner::BottomLeft(viewport) => (viewport.x0 + 10.0, viewport.y1 - 10.0 - height),
+    };
+    context.set_source_rgba(red, green, blue, alpha);
+    context.rectangle(x, y, extents.width() + 2.0 * padding, height);
+    let _ = context.fill();
+    context.color(Color::White);
+    context.move_to(x + padding, y + padding - extents.y_bearing());
+    let _ = context.show_text(text);
+}
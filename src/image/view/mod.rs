@@ -20,9 +20,12 @@
 pub mod data;
 mod imp;
 mod measure;
+mod ruler;
 
 use std::time::SystemTime;
 
+use cairo::{Context, Format, ImageSurface};
+use encoding_rs::Encoding;
 use gdk_pixbuf::Pixbuf;
 use gio::Menu;
 use glib::{object::Cast, subclass::types::ObjectSubclassIsExt};
@@ -39,15 +42,21 @@ use gtk4::{
 use crate::{
     backends::thumbnail::model::Annotations,
     content::{Content, ContentData},
+    error::MviewResult,
     file_view::Direction,
     image::{
+        lut::Lut3D,
+        proof::SoftProof,
         provider::surface::SurfaceData,
         view::{
-            data::{zoom::ZOOM_MULTIPLIER, TransparencyMode},
+            data::{zoom::ZOOM_MULTIPLIER, HoverPreview, PageTint, TransparencyMode},
             measure::MeasurementState,
         },
+        Image, RenderedImage,
     },
-    rect::{PointD, RectD, SizeD},
+    info_view::InfoView,
+    rect::{PointD, RectD, SizeD, SizeI},
+    render_thread::render_now,
     window::imp::MViewWidgets,
 };
 
@@ -88,6 +97,7 @@ impl ImageView {
     pub fn init(&self, widgets: &MViewWidgets) {
         let mut p = self.imp().data.borrow_mut();
         p.rb_sender = Some(widgets.rt_sender.clone());
+        p.tn_sender = Some(widgets.tn_sender.clone());
     }
 
     pub fn set_content(&self, content: Content) {
@@ -104,8 +114,10 @@ impl ImageView {
         p.content = content;
         p.zoom.set_rotation(0);
         p.zoom_overlay = None;
+        p.render_timed_out = false;
         p.annotations = None;
         p.hover = None;
+        p.hover_preview = None;
         p.shown = false;
     }
 
@@ -117,6 +129,14 @@ impl ImageView {
         p.redraw(RedrawReason::ContentPost);
     }
 
+    pub fn set_hover_preview(&self, id: i32, size: u32, surface: ImageSurface) {
+        let mut p = self.imp().data.borrow_mut();
+        p.hover_preview = Some(HoverPreview { id, size, surface });
+        if p.hover == Some(id) {
+            p.redraw(RedrawReason::AnnotationChanged);
+        }
+    }
+
     pub fn thumbnail_sheet_updated(&self) {
         let mut p = self.imp().data.borrow_mut();
         p.apply_zoom();
@@ -129,6 +149,12 @@ impl ImageView {
         p.redraw(RedrawReason::TransparencyBackgroundChanged);
     }
 
+    pub fn set_page_tint(&self, page_tint: PageTint) {
+        let mut p = self.imp().data.borrow_mut();
+        p.page_tint = page_tint;
+        p.redraw(RedrawReason::PageTintChanged);
+    }
+
     pub fn event_render_done(
         &self,
         image_id: u32,
@@ -140,6 +166,16 @@ impl ImageView {
         p.event_render_done(image_id, surface_data, zoom, viewport);
     }
 
+    pub fn event_render_failed(&self, image_id: u32) -> bool {
+        let mut p = self.imp().data.borrow_mut();
+        p.event_render_failed(image_id)
+    }
+
+    pub fn event_render_timed_out(&self, image_id: u32) {
+        let mut p = self.imp().data.borrow_mut();
+        p.event_render_timed_out(image_id);
+    }
+
     pub fn set_view_cursor(&self, view_cursor: ViewCursor) {
         match view_cursor {
             ViewCursor::Normal => self.set_cursor_from_name(Some("default")),
@@ -238,6 +274,73 @@ impl ImageView {
         self.imp().measure_tool.state() != MeasurementState::Idle
     }
 
+    pub fn reading_ruler_enable(&self, enabled: bool) {
+        let imp = self.imp();
+        if enabled && !imp.reading_ruler.is_enabled() {
+            let mouse = imp.mouse_position();
+            let y = if mouse.y() > 0.0 {
+                mouse.y()
+            } else {
+                imp.window_size.get().height() as f64 / 2.0
+            };
+            imp.reading_ruler.set_position(y);
+        }
+        imp.reading_ruler.set_enabled(enabled);
+        imp.data.borrow_mut().redraw(RedrawReason::ReadingRuler);
+    }
+
+    pub fn reading_ruler_active(&self) -> bool {
+        self.imp().reading_ruler.is_enabled()
+    }
+
+    /// Move the reading ruler `lines` steps down (negative is up)
+    pub fn reading_ruler_step(&self, lines: i32) {
+        let imp = self.imp();
+        let height = imp.window_size.get().height() as f64;
+        imp.reading_ruler.step(lines, height);
+        imp.data.borrow_mut().redraw(RedrawReason::ReadingRuler);
+    }
+
+    pub fn zoom_info_enable(&self, enabled: bool) {
+        let imp = self.imp();
+        imp.zoom_info.set(enabled);
+        imp.data.borrow_mut().redraw(RedrawReason::ZoomInfo);
+    }
+
+    pub fn zoom_info_active(&self) -> bool {
+        self.imp().zoom_info.get()
+    }
+
+    pub fn set_lut(&self, lut: Option<Lut3D>) {
+        let mut p = self.imp().data.borrow_mut();
+        p.lut = lut;
+        p.redraw(RedrawReason::ColorLutChanged);
+    }
+
+    pub fn lut_enable(&self, enabled: bool) {
+        let mut p = self.imp().data.borrow_mut();
+        p.lut_enabled = enabled;
+        p.redraw(RedrawReason::ColorLutChanged);
+    }
+
+    pub fn lut_enabled(&self) -> bool {
+        self.imp().data.borrow().lut_enabled
+    }
+
+    pub fn has_lut(&self) -> bool {
+        self.imp().data.borrow().lut.is_some()
+    }
+
+    pub fn set_soft_proof(&self, soft_proof: Option<SoftProof>) {
+        let mut p = self.imp().data.borrow_mut();
+        p.soft_proof = soft_proof;
+        p.redraw(RedrawReason::SoftProofChanged);
+    }
+
+    pub fn soft_proof_active(&self) -> bool {
+        self.imp().data.borrow().soft_proof.is_some()
+    }
+
     // Operations on image
 
     pub fn image_id(&self) -> u32 {
@@ -248,11 +351,63 @@ impl ImageView {
         self.imp().data.borrow().content.size()
     }
 
+    /// Render the current content offscreen at `size`, placed as the view shows it right
+    /// after loading. Documents and svg are rendered at full quality on the calling thread,
+    /// so the result depends neither on the widget being realized nor on render timing.
+    ///
+    /// This is synchronous: it blocks until the render is done and is not guarded by the
+    /// render thread's timeout. When the render thread keeps the renderers busy, the content
+    /// is painted without a high quality render. Only use it for an explicit user action; to
+    /// follow the view, use [`Self::capture_view`].
+    pub fn snapshot_to_surface(&self, size: SizeI) -> MviewResult<ImageSurface> {
+        let imp = self.imp();
+        let p = imp.data.borrow();
+        let surface = ImageSurface::create(Format::ARgb32, size.width(), size.height())?;
+        let viewport = RectD::new(0.0, 0.0, size.width() as f64, size.height() as f64);
+        let zoom = p.zoom_for_viewport(viewport);
+        let rendered = p
+            .content
+            .render(zoom.clone(), viewport)
+            .and_then(render_now)
+            .and_then(|surface_data| surface_data.surface().ok())
+            .map(|rendered| {
+                let rect = zoom.intersection_screen_coord(&viewport);
+                RenderedImage::new(rendered, zoom.top_left(&rect), zoom.clone())
+            });
+        let image = match &rendered {
+            Some(rendered) => Image::Rendered(rendered),
+            None => p.content_image(),
+        };
+        {
+            let context = Context::new(&surface)?;
+            imp.paint(&context, &p, &image, &zoom, QUALITY_HIGH);
+        }
+        Ok(surface)
+    }
+
+    /// Draw the view as it is shown: at the current zoom and position, with the high quality
+    /// render the render thread delivered for it. Overlays are not included.
+    pub fn capture_view(&self) -> MviewResult<ImageSurface> {
+        let imp = self.imp();
+        let p = imp.data.borrow();
+        let surface = ImageSurface::create(Format::ARgb32, self.width(), self.height())?;
+        {
+            let context = Context::new(&surface)?;
+            imp.paint(&context, &p, &p.image(), &p.zoom, p.quality);
+        }
+        Ok(surface)
+    }
+
     pub fn draw_pixbuf(&self, pixbuf: &Pixbuf, dest_x: i32, dest_y: i32) {
         let p = self.imp().data.borrow();
         p.content.draw_pixbuf(pixbuf, dest_x, dest_y);
     }
 
+    pub fn clear_area(&self, x: i32, y: i32, width: i32, height: i32) {
+        let p = self.imp().data.borrow();
+        p.content.clear_area(x, y, width, height);
+    }
+
     pub fn rotate(&self, angle: i32) {
         let mut p = self.imp().data.borrow_mut();
         p.zoom.add_rotation(angle);
@@ -266,6 +421,13 @@ impl ImageView {
     }
 
     pub fn navigate_page(&self, direction: Direction, count: u32) -> bool {
+        if let Some(index) = self.frame_index() {
+            let index = match direction {
+                Direction::Up => index.checked_sub(count as usize),
+                Direction::Down => Some(index + count as usize),
+            };
+            return index.is_some_and(|index| self.select_frame(index));
+        }
         let mut p = self.imp().data.borrow_mut();
         if let ContentData::Paginated(paginated) = &mut p.content.data {
             let page_changed = paginated.navigate_page(direction, count as usize);
@@ -278,6 +440,62 @@ impl ImageView {
         }
     }
 
+    /// Number of words on the current page of text content
+    pub fn page_word_count(&self) -> Option<usize> {
+        match &self.imp().data.borrow().content.data {
+            ContentData::Paginated(paginated) => paginated.page_word_count(),
+            _ => None,
+        }
+    }
+
+    /// Current frame of content with multiple frames (icons)
+    pub fn frame_index(&self) -> Option<usize> {
+        self.imp().data.borrow().content.frames().map(|f| f.index())
+    }
+
+    /// Description of each frame of content with multiple frames
+    pub fn frame_infos(&self) -> Vec<String> {
+        self.imp()
+            .data
+            .borrow()
+            .content
+            .frames()
+            .map(|f| f.frames().iter().map(|frame| frame.info.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    pub fn select_frame(&self, index: usize) -> bool {
+        let mut p = self.imp().data.borrow_mut();
+        let changed = p
+            .content
+            .frames_mut()
+            .is_some_and(|frames| frames.select(index));
+        if changed {
+            // Frames differ in size
+            p.apply_zoom();
+            p.redraw(RedrawReason::PageChanged);
+        }
+        changed
+    }
+
+    pub fn update_info(&self, info_view: &InfoView) {
+        info_view.update(&self.imp().data.borrow().content);
+    }
+
+    pub fn set_text_encoding(&self, encoding: &'static Encoding) {
+        let mut p = self.imp().data.borrow_mut();
+        if p.content.set_text_encoding(encoding) {
+            p.redraw(RedrawReason::PageChanged);
+        }
+    }
+
+    pub fn set_table_view(&self, show_table: bool) {
+        let mut p = self.imp().data.borrow_mut();
+        if p.content.set_table_view(show_table) {
+            p.redraw(RedrawReason::PageChanged);
+        }
+    }
+
     pub fn on_sort_changed(&self, new_sort: &str) {
         dbg!(new_sort);
         let mut p = self.imp().data.borrow_mut();
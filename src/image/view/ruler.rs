@@ -0,0 +1,84 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Reading ruler: a horizontal band that follows the mouse (or the arrow keys) while the
+//! rest of the view is dimmed, to keep track of the current line in long texts
+
+use std::cell::Cell;
+
+use cairo::Context;
+
+use crate::rect::RectD;
+
+/// Height of the band in screen pixels
+const BAND_HEIGHT: f64 = 48.0;
+
+/// Distance the band moves for an arrow key
+const STEP: f64 = BAND_HEIGHT / 2.0;
+
+/// Opacity of the dimming outside the band
+const DIM: f64 = 0.55;
+
+#[derive(Default)]
+pub struct ReadingRuler {
+    enabled: Cell<bool>,
+    center: Cell<f64>,
+}
+
+impl ReadingRuler {
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.get()
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.set(enabled);
+    }
+
+    pub fn set_position(&self, y: f64) {
+        self.center.set(y);
+    }
+
+    /// Move the band `lines` steps down (negative is up), keeping it within `height`
+    pub fn step(&self, lines: i32, height: f64) {
+        let center = self.center.get() + lines as f64 * STEP;
+        self.center.set(
+            center
+                .min(height - BAND_HEIGHT / 2.0)
+                .max(BAND_HEIGHT / 2.0),
+        );
+    }
+
+    pub fn draw(&self, context: &Context, viewport: &RectD) {
+        let top = self.center.get() - BAND_HEIGHT / 2.0;
+        let bottom = self.center.get() + BAND_HEIGHT / 2.0;
+        context.set_source_rgba(0.0, 0.0, 0.0, DIM);
+        if top > viewport.y0 {
+            context.rectangle(
+                viewport.x0,
+                viewport.y0,
+                viewport.width(),
+                top - viewport.y0,
+            );
+        }
+        if bottom < viewport.y1 {
+            context.rectangle(viewport.x0, bottom, viewport.width(), viewport.y1 - bottom);
+        }
+        let _ = context.fill();
+    }
+}
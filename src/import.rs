@@ -0,0 +1,247 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Import of photos and videos from a camera or memory card into a date based folder
+//! structure
+
+use std::{
+    collections::HashSet,
+    env,
+    fmt::Write as _,
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Local, NaiveDateTime};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    classification::{FileClassification, FileType},
+    metadata::writer::{capture_time, read_date_time_fields},
+};
+
+pub const DEFAULT_FOLDER_PATTERN: &str = "%Y/%Y-%m-%d";
+
+/// Camera raw formats, which are not viewable but should be imported
+const RAW_EXTENSIONS: &[&str] = &[
+    "arw", "cr2", "cr3", "crw", "dng", "nef", "nrw", "orf", "pef", "raf", "rw2", "srw",
+];
+
+#[derive(Debug, Clone)]
+pub struct ImportItem {
+    pub source: PathBuf,
+    pub target: PathBuf,
+    /// An identical file is already present in the target folder
+    pub duplicate: bool,
+}
+
+/// Mounted removable media: the user's directories in `/media` and `/run/media`
+pub fn removable_media() -> Vec<PathBuf> {
+    let Ok(user) = env::var("USER") else {
+        return Vec::new();
+    };
+    let mut media = Vec::new();
+    for base in [Path::new("/media"), Path::new("/run/media")] {
+        if let Ok(entries) = fs::read_dir(base.join(&user)) {
+            media.extend(entries.flatten().map(|e| e.path()).filter(|p| p.is_dir()));
+        }
+    }
+    media.sort();
+    media
+}
+
+fn is_importable(path: &Path) -> bool {
+    let file_type = FileClassification::determine(path, false).file_type;
+    file_type == FileType::Image
+        || file_type == FileType::Video
+        || path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| RAW_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+}
+
+/// Files to import, skipping hidden files and directories
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        if path.is_dir() {
+            collect_files(&path, files);
+        } else if is_importable(&path) {
+            files.push(path);
+        }
+    }
+}
+
+/// Capture time from EXIF, falling back to the modification time
+fn file_time(path: &Path) -> NaiveDateTime {
+    read_date_time_fields(path)
+        .ok()
+        .and_then(|fields| capture_time(&fields))
+        .unwrap_or_else(|| {
+            let modified = fs::metadata(path)
+                .and_then(|m| m.modified())
+                .unwrap_or(std::time::UNIX_EPOCH);
+            DateTime::<Local>::from(modified).naive_local()
+        })
+}
+
+/// Expand a pattern with strftime fields (`%Y`, `%m`, ...) and `{name}` for the original
+/// file name without extension. Returns `None` for an invalid pattern.
+pub fn expand_pattern(pattern: &str, time: &NaiveDateTime, name: &str) -> Option<String> {
+    let mut expanded = String::new();
+    write!(expanded, "{}", time.format(pattern)).ok()?;
+    Some(expanded.replace("{name}", name))
+}
+
+pub fn checksum(path: &Path) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Check if a file with the same content exists in the directory
+fn has_duplicate(path: &Path, dir: &Path) -> bool {
+    let Ok(size) = fs::metadata(path).map(|m| m.len()) else {
+        return false;
+    };
+    let Ok(entries) = fs::read_dir(dir) else {
+        return false;
+    };
+    let mut source_sum = None;
+    for entry in entries.flatten() {
+        if entry.metadata().map(|m| m.len()).ok() != Some(size) {
+            continue;
+        }
+        if source_sum.is_none() {
+            source_sum = checksum(path).ok();
+        }
+        if source_sum.is_some() && checksum(&entry.path()).ok() == source_sum {
+            return true;
+        }
+    }
+    false
+}
+
+/// Target path that is not used yet, by adding a counter to the name when needed
+pub fn unique_target(dir: &Path, stem: &str, extension: &str, taken: &HashSet<PathBuf>) -> PathBuf {
+    let mut counter = 0;
+    loop {
+        let name = match counter {
+            0 => format!("{stem}{extension}"),
+            n => format!("{stem}_{n}{extension}"),
+        };
+        let target = dir.join(name);
+        if !taken.contains(&target) && !target.exists() {
+            return target;
+        }
+        counter += 1;
+    }
+}
+
+/// Determine where each file of the source goes. An empty rename pattern keeps the
+/// original file name.
+pub fn plan_import(
+    source: &Path,
+    destination: &Path,
+    folder_pattern: &str,
+    rename_pattern: &str,
+) -> Option<Vec<ImportItem>> {
+    let mut files = Vec::new();
+    collect_files(source, &mut files);
+    files.sort();
+
+    let mut taken = HashSet::new();
+    let mut plan = Vec::new();
+    for path in files {
+        let time = file_time(&path);
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        let extension = path
+            .extension()
+            .map(|e| format!(".{}", e.to_string_lossy()))
+            .unwrap_or_default();
+        let dir = destination.join(expand_pattern(folder_pattern, &time, &stem)?);
+        let duplicate = has_duplicate(&path, &dir);
+        let stem = if rename_pattern.is_empty() {
+            stem.to_string()
+        } else {
+            expand_pattern(rename_pattern, &time, &stem)?
+        };
+        let target = unique_target(&dir, &stem, &extension, &taken);
+        taken.insert(target.clone());
+        plan.push(ImportItem {
+            source: path,
+            target,
+            duplicate,
+        });
+    }
+    Some(plan)
+}
+
+/// Copy a file, keeping its modification time
+pub fn import_file(item: &ImportItem) -> io::Result<()> {
+    if let Some(parent) = item.target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::copy(&item.source, &item.target)?;
+    let modified = fs::metadata(&item.source)?.modified()?;
+    File::options()
+        .write(true)
+        .open(&item.target)?
+        .set_modified(modified)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn patterns() {
+        let time =
+            NaiveDateTime::parse_from_str("2025-06-01 14:03:09", "%Y-%m-%d %H:%M:%S").unwrap();
+        assert_eq!(
+            expand_pattern(DEFAULT_FOLDER_PATTERN, &time, "IMG_0001").as_deref(),
+            Some("2025/2025-06-01")
+        );
+        assert_eq!(
+            expand_pattern("%Y%m%d_%H%M%S_{name}", &time, "IMG_0001").as_deref(),
+            Some("20250601_140309_IMG_0001")
+        );
+        assert_eq!(expand_pattern("%Q", &time, "IMG_0001"), None);
+    }
+
+    #[test]
+    fn unique_targets() {
+        let dir = Path::new("/nonexistent/import");
+        let mut taken = HashSet::new();
+        let first = unique_target(dir, "photo", ".jpg", &taken);
+        assert_eq!(first, dir.join("photo.jpg"));
+        taken.insert(first);
+        assert_eq!(
+            unique_target(dir, "photo", ".jpg", &taken),
+            dir.join("photo_1.jpg")
+        );
+    }
+}
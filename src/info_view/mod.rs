@@ -21,9 +21,16 @@ mod imp;
 
 use convert_case::{Case, Casing};
 use exif::In;
-use gtk4::{glib, prelude::TreeViewExt, ListStore};
+use gtk4::{
+    glib,
+    prelude::{Cast, TreeViewExt},
+    ListStore,
+};
 
-use crate::content::Content;
+use crate::{
+    backends::usage::{format_duration, FileUsage},
+    content::Content,
+};
 
 glib::wrapper! {
 pub struct InfoView(ObjectSubclass<imp::InfoViewImp>)
@@ -77,6 +84,21 @@ impl InfoView {
             if image.has_alpha() { "yes" } else { "no" },
         );
 
+        if let Some(frames) = image.frames() {
+            insert(
+                &store,
+                "frame",
+                &format!("{} of {}", frames.index() + 1, frames.frames().len()),
+            );
+            for (i, frame) in frames.frames().iter().enumerate() {
+                insert(&store, &format!("frame {}", i + 1), &frame.info);
+            }
+        }
+
+        if let Some(model) = image.model() {
+            insert(&store, "triangles", &model.num_triangles().to_string());
+        }
+
         match &image.exif {
             Some(exif) => {
                 for f in exif.fields() {
@@ -96,4 +118,16 @@ impl InfoView {
         }
         self.set_model(Some(&store));
     }
+
+    /// Add the usage statistics of the shown file to the information
+    pub fn add_usage(&self, usage: &FileUsage) {
+        let Some(store) = self
+            .model()
+            .and_then(|model| model.downcast::<ListStore>().ok())
+        else {
+            return;
+        };
+        insert(&store, "views", &usage.views.to_string());
+        insert(&store, "viewing time", &format_duration(usage.seconds));
+    }
 }
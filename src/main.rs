@@ -21,16 +21,24 @@
 
 mod application;
 mod backends;
+mod cache;
 mod classification;
 mod config;
 mod content;
 mod error;
+mod file_reader;
 mod file_view;
 mod image;
+mod import;
 mod info_view;
+mod metadata;
+mod preview_server;
 mod profile;
 mod rect;
 mod render_thread;
+#[cfg(test)]
+mod test_dir;
+mod transfer;
 mod util;
 mod window;
 
@@ -43,6 +51,16 @@ use gtk4::{
 };
 
 fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() > 2 && args[1] == "--replay-render" {
+        pdfium::set_library_location("/usr/lib/mview6");
+        if let Err(e) = render_thread::trace::replay(std::path::Path::new(&args[2])) {
+            eprintln!("Failed to replay {}: {e}", args[2]);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     gtk4::init().expect("Failed to initialize gtk");
 
     gio::resources_register_include!("mview6.gresource").unwrap();
@@ -62,6 +80,8 @@ fn main() {
 
     pdfium::set_library_location("/usr/lib/mview6");
 
+    std::thread::spawn(cache::trim_all);
+
     let app = application::MviewApplication::new();
 
     app.run();
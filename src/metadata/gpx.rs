@@ -0,0 +1,217 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Minimal GPX track reader, used for geotagging photos
+
+use std::{fs, path::Path};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use regex::Regex;
+
+use crate::{error::MviewResult, mview6_error};
+
+/// Photos taken longer than this before the start or after the end of the track are not
+/// matched. The same applies to gaps in the track.
+const MAX_TIME_GAP: i64 = 300;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GpsPosition {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub elevation: Option<f64>,
+}
+
+#[derive(Debug, Clone)]
+struct TrackPoint {
+    time: DateTime<Utc>,
+    position: GpsPosition,
+}
+
+#[derive(Debug, Default)]
+pub struct Track {
+    points: Vec<TrackPoint>,
+}
+
+impl Track {
+    pub fn load(path: &Path) -> MviewResult<Self> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn parse(text: &str) -> MviewResult<Self> {
+        let trkpt = Regex::new(r"(?s)<trkpt\s([^>]*)>(.*?)</trkpt>").unwrap();
+        let lat = Regex::new(r#"lat\s*=\s*["']([-+0-9.eE]+)["']"#).unwrap();
+        let lon = Regex::new(r#"lon\s*=\s*["']([-+0-9.eE]+)["']"#).unwrap();
+        let ele = Regex::new(r"<ele>\s*([-+0-9.eE]+)\s*</ele>").unwrap();
+        let time = Regex::new(r"<time>\s*([^<\s]+)\s*</time>").unwrap();
+
+        let mut points = Vec::new();
+        for captures in trkpt.captures_iter(text) {
+            let attributes = &captures[1];
+            let body = &captures[2];
+            let number = |re: &Regex, text: &str| -> Option<f64> {
+                re.captures(text).and_then(|c| c[1].parse::<f64>().ok())
+            };
+            let (Some(latitude), Some(longitude)) =
+                (number(&lat, attributes), number(&lon, attributes))
+            else {
+                continue;
+            };
+            let Some(time) = time
+                .captures(body)
+                .and_then(|c| DateTime::parse_from_rfc3339(&c[1]).ok())
+            else {
+                continue;
+            };
+            points.push(TrackPoint {
+                time: time.with_timezone(&Utc),
+                position: GpsPosition {
+                    latitude,
+                    longitude,
+                    elevation: number(&ele, body),
+                },
+            });
+        }
+
+        if points.is_empty() {
+            return mview6_error!("No track points with time found in GPX").into();
+        }
+        points.sort_by_key(|p| p.time);
+        Ok(Self { points })
+    }
+
+    pub fn point_count(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn time_range(&self) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        Some((self.points.first()?.time, self.points.last()?.time))
+    }
+
+    /// Position at the given time, either of the nearest track point or interpolated
+    /// linearly between the surrounding track points
+    pub fn position_at(&self, time: DateTime<Utc>, interpolate: bool) -> Option<GpsPosition> {
+        let max_gap = TimeDelta::seconds(MAX_TIME_GAP);
+        let index = self.points.partition_point(|p| p.time <= time);
+        let before = index.checked_sub(1).map(|i| &self.points[i]);
+        let after = self.points.get(index);
+        match (before, after) {
+            (Some(before), Some(after)) => {
+                if after.time - before.time > max_gap * 2 {
+                    // Gap in the track: only accept if close to one of the points
+                    if time - before.time <= max_gap {
+                        return Some(before.position);
+                    } else if after.time - time <= max_gap {
+                        return Some(after.position);
+                    }
+                    return None;
+                }
+                if interpolate {
+                    let span = (after.time - before.time).num_milliseconds() as f64;
+                    let t = if span > 0.0 {
+                        (time - before.time).num_milliseconds() as f64 / span
+                    } else {
+                        0.0
+                    };
+                    Some(interpolate_position(&before.position, &after.position, t))
+                } else if time - before.time <= after.time - time {
+                    Some(before.position)
+                } else {
+                    Some(after.position)
+                }
+            }
+            (Some(last), None) if time - last.time <= max_gap => Some(last.position),
+            (None, Some(first)) if first.time - time <= max_gap => Some(first.position),
+            _ => None,
+        }
+    }
+}
+
+fn interpolate_position(a: &GpsPosition, b: &GpsPosition, t: f64) -> GpsPosition {
+    let lerp = |a: f64, b: f64| a + (b - a) * t;
+    GpsPosition {
+        latitude: lerp(a.latitude, b.latitude),
+        longitude: lerp(a.longitude, b.longitude),
+        elevation: match (a.elevation, b.elevation) {
+            (Some(ea), Some(eb)) => Some(lerp(ea, eb)),
+            (ea, eb) => ea.or(eb),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GPX: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<gpx version="1.1" creator="test">
+  <trk><trkseg>
+    <trkpt lat="52.0" lon="4.0"><ele>10</ele><time>2024-06-01T10:00:00Z</time></trkpt>
+    <trkpt lon="4.2" lat="52.2">
+      <ele>30</ele>
+      <time>2024-06-01T10:01:40Z</time>
+    </trkpt>
+    <trkpt lat="60.0" lon="5.0"><time>2024-06-01T12:00:00Z</time></trkpt>
+  </trkseg></trk>
+</gpx>"#;
+
+    fn utc(text: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(text)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn parse() {
+        let track = Track::parse(GPX).unwrap();
+        assert_eq!(track.point_count(), 3);
+        assert!(Track::parse("<gpx></gpx>").is_err());
+    }
+
+    #[test]
+    fn position() {
+        let track = Track::parse(GPX).unwrap();
+        let p = track
+            .position_at(utc("2024-06-01T10:00:25Z"), true)
+            .unwrap();
+        assert!((p.latitude - 52.05).abs() < 1e-9);
+        assert!((p.longitude - 4.05).abs() < 1e-9);
+        assert!((p.elevation.unwrap() - 15.0).abs() < 1e-9);
+
+        let p = track
+            .position_at(utc("2024-06-01T10:01:00Z"), false)
+            .unwrap();
+        assert_eq!(p.latitude, 52.2);
+
+        // in the gap between 10:01:40 and 12:00:00
+        assert!(track
+            .position_at(utc("2024-06-01T11:00:00Z"), true)
+            .is_none());
+        // before the start of the track
+        assert_eq!(
+            track
+                .position_at(utc("2024-06-01T09:58:00Z"), true)
+                .unwrap()
+                .latitude,
+            52.0
+        );
+        assert!(track
+            .position_at(utc("2024-06-01T09:50:00Z"), true)
+            .is_none());
+    }
+}
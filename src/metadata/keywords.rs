@@ -0,0 +1,211 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Keywords (dc:subject) stored in XMP sidecar files
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use regex::Regex;
+
+use crate::error::MviewResult;
+
+pub(super) const XMP_TEMPLATE: &str = r#"<?xpacket begin="" id="W5M0MpCehiHzreSzNTczkc9d"?>
+<x:xmpmeta xmlns:x="adobe:ns:meta/">
+ <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+ </rdf:RDF>
+</x:xmpmeta>
+<?xpacket end="w"?>
+"#;
+
+/// `path` with `.xmp` added, the sidecar name that keeps the extension
+pub fn with_xmp(path: &Path) -> PathBuf {
+    let mut full = path.as_os_str().to_owned();
+    full.push(".xmp");
+    full.into()
+}
+
+/// Sidecar of a file: an existing `photo.jpg.xmp` or `photo.xmp`, otherwise `photo.jpg.xmp`
+pub fn sidecar_path(path: &Path) -> PathBuf {
+    let full = with_xmp(path);
+    if !full.exists() {
+        let stem = path.with_extension("xmp");
+        if stem != path && stem.exists() {
+            return stem;
+        }
+    }
+    full
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+fn subject_regex() -> Regex {
+    Regex::new(r"(?s)[ \t]*<dc:subject>.*?</dc:subject>[ \t]*\n?").unwrap()
+}
+
+/// Keywords in the dc:subject bag of an XMP packet
+pub fn parse_keywords(xmp: &str) -> Vec<String> {
+    let item = Regex::new(r"(?s)<rdf:li[^>]*>(.*?)</rdf:li>").unwrap();
+    subject_regex()
+        .find(xmp)
+        .map(|subject| {
+            item.captures_iter(subject.as_str())
+                .map(|c| unescape(c[1].trim()))
+                .filter(|k| !k.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Replace the dc:subject bag in an XMP packet (or create a new packet), leaving all
+/// other metadata untouched
+pub fn update_keywords(xmp: Option<&str>, keywords: &[String]) -> String {
+    let xmp = subject_regex().replace(xmp.unwrap_or(XMP_TEMPLATE), "");
+    if keywords.is_empty() {
+        return xmp.into_owned();
+    }
+    let mut subject = String::from("   <dc:subject>\n    <rdf:Bag>\n");
+    for keyword in keywords {
+        subject.push_str(&format!("     <rdf:li>{}</rdf:li>\n", escape(keyword)));
+    }
+    subject.push_str("    </rdf:Bag>\n   </dc:subject>\n");
+
+    // Add the bag to a description that declares the dc namespace, a self-closing one is
+    // expanded into an element with an end tag
+    let description = Regex::new(r#"<rdf:Description[^>]*xmlns:dc="[^"]*"[^>]*?(/?>)\n?"#).unwrap();
+    if let Some(captures) = description.captures(&xmp) {
+        let (Some(all), Some(close)) = (captures.get(0), captures.get(1)) else {
+            return xmp.into_owned();
+        };
+        return if close.as_str() == "/>" {
+            format!(
+                "{}>\n{subject}  </rdf:Description>\n{}",
+                &xmp[..close.start()],
+                &xmp[all.end()..]
+            )
+        } else {
+            format!("{}{subject}{}", &xmp[..all.end()], &xmp[all.end()..])
+        };
+    }
+    let description = format!(
+        "  <rdf:Description rdf:about=\"\"\n    xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n{subject}  </rdf:Description>\n"
+    );
+    match xmp.find(" </rdf:RDF>").or_else(|| xmp.find("</rdf:RDF>")) {
+        Some(pos) => format!("{}{description}{}", &xmp[..pos], &xmp[pos..]),
+        None => xmp.into_owned(),
+    }
+}
+
+/// Split a comma separated list of keywords, dropping empty and duplicate entries
+pub fn split_keywords(text: &str) -> Vec<String> {
+    let mut keywords: Vec<String> = Vec::new();
+    for keyword in text.split(',').map(str::trim) {
+        if !keyword.is_empty() && !keywords.iter().any(|k| k == keyword) {
+            keywords.push(keyword.to_string());
+        }
+    }
+    keywords
+}
+
+pub fn read_keywords(path: &Path) -> Vec<String> {
+    fs::read_to_string(sidecar_path(path))
+        .map(|xmp| parse_keywords(&xmp))
+        .unwrap_or_default()
+}
+
+pub fn write_keywords(path: &Path, keywords: &[String]) -> MviewResult<()> {
+    let sidecar = sidecar_path(path);
+    let existing = fs::read_to_string(&sidecar).ok();
+    if existing.is_none() && keywords.is_empty() {
+        return Ok(());
+    }
+    fs::write(&sidecar, update_keywords(existing.as_deref(), keywords))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_sidecar() {
+        let keywords = split_keywords("holiday, beach & sea,, holiday ");
+        assert_eq!(keywords, ["holiday", "beach & sea"]);
+        let xmp = update_keywords(None, &keywords);
+        assert!(xmp.contains("<rdf:li>beach &amp; sea</rdf:li>"));
+        assert_eq!(parse_keywords(&xmp), keywords);
+        let xmp = update_keywords(Some(&xmp), &[]);
+        assert!(parse_keywords(&xmp).is_empty());
+        assert!(xmp.contains("</rdf:RDF>"));
+    }
+
+    #[test]
+    fn existing_sidecar() {
+        let xmp = r#"<x:xmpmeta xmlns:x="adobe:ns:meta/">
+ <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+  <rdf:Description rdf:about="" xmlns:xmp="http://ns.adobe.com/xap/1.0/"
+    xmlns:dc="http://purl.org/dc/elements/1.1/" xmp:Rating="4">
+   <dc:subject>
+    <rdf:Bag>
+     <rdf:li>old</rdf:li>
+    </rdf:Bag>
+   </dc:subject>
+  </rdf:Description>
+ </rdf:RDF>
+</x:xmpmeta>"#;
+        assert_eq!(parse_keywords(xmp), ["old"]);
+        let keywords = vec!["new".to_string(), "city".to_string()];
+        let updated = update_keywords(Some(xmp), &keywords);
+        assert_eq!(parse_keywords(&updated), keywords);
+        assert!(updated.contains(r#"xmp:Rating="4""#));
+        assert_eq!(updated.matches("<rdf:Description").count(), 1);
+    }
+
+    #[test]
+    fn self_closing_description() {
+        let xmp = r#"<x:xmpmeta xmlns:x="adobe:ns:meta/">
+ <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+  <rdf:Description rdf:about="" xmlns:dc="http://purl.org/dc/elements/1.1/"
+    dc:format="image/jpeg"/>
+ </rdf:RDF>
+</x:xmpmeta>"#;
+        let keywords = vec!["beach".to_string()];
+        let updated = update_keywords(Some(xmp), &keywords);
+        assert_eq!(parse_keywords(&updated), keywords);
+        assert!(updated.contains(
+            "dc:format=\"image/jpeg\">\n   <dc:subject>\n    <rdf:Bag>\n     <rdf:li>beach</rdf:li>"
+        ));
+        assert!(updated.contains("</dc:subject>\n  </rdf:Description>\n </rdf:RDF>"));
+        assert!(!updated.contains("/>"));
+    }
+}
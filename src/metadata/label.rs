@@ -0,0 +1,211 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Color labels (xmp:Label) stored in XMP sidecar files
+
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{Mutex, OnceLock},
+};
+
+use regex::Regex;
+
+use super::keywords::{sidecar_path, XMP_TEMPLATE};
+use crate::error::MviewResult;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColorLabel {
+    Red,
+    Yellow,
+    Green,
+    Blue,
+    Purple,
+}
+
+impl ColorLabel {
+    pub const ALL: [ColorLabel; 5] = [
+        Self::Red,
+        Self::Yellow,
+        Self::Green,
+        Self::Blue,
+        Self::Purple,
+    ];
+
+    /// Name as written by common photo managers
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Red => "Red",
+            Self::Yellow => "Yellow",
+            Self::Green => "Green",
+            Self::Blue => "Blue",
+            Self::Purple => "Purple",
+        }
+    }
+
+    pub fn rgb(&self) -> (f64, f64, f64) {
+        match self {
+            Self::Red => (0.88, 0.11, 0.14),
+            Self::Yellow => (0.96, 0.83, 0.18),
+            Self::Green => (0.2, 0.82, 0.48),
+            Self::Blue => (0.21, 0.52, 0.89),
+            Self::Purple => (0.57, 0.25, 0.67),
+        }
+    }
+
+    pub fn hex(&self) -> String {
+        let (r, g, b) = self.rgb();
+        format!(
+            "#{:02x}{:02x}{:02x}",
+            (r * 255.0).round() as u8,
+            (g * 255.0).round() as u8,
+            (b * 255.0).round() as u8
+        )
+    }
+}
+
+impl fmt::Display for ColorLabel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for ColorLabel {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|label| label.name().eq_ignore_ascii_case(s.trim()))
+            .ok_or(())
+    }
+}
+
+fn label_regex() -> Regex {
+    Regex::new(r#"(?s)\s*xmp:Label="[^"]*"|[ \t]*<xmp:Label>.*?</xmp:Label>[ \t]*\n?"#).unwrap()
+}
+
+/// Color label of an XMP packet, as attribute or as element
+pub fn parse_label(xmp: &str) -> Option<ColorLabel> {
+    let label = Regex::new(r#"(?s)xmp:Label="([^"]*)"|<xmp:Label>(.*?)</xmp:Label>"#).unwrap();
+    let captures = label.captures(xmp)?;
+    captures.get(1).or(captures.get(2))?.as_str().parse().ok()
+}
+
+/// Replace the color label of an XMP packet (or create a new packet), leaving all other
+/// metadata untouched
+pub fn update_label(xmp: Option<&str>, label: Option<ColorLabel>) -> String {
+    let xmp = label_regex().replace_all(xmp.unwrap_or(XMP_TEMPLATE), "");
+    let Some(label) = label else {
+        return xmp.into_owned();
+    };
+    let attribute = format!(" xmp:Label=\"{label}\"");
+
+    // Add the attribute to a description that declares the xmp namespace
+    let description = Regex::new(r#"<rdf:Description[^>]*xmlns:xmp="[^"]*"[^>]*?(/?>)"#).unwrap();
+    if let Some(captures) = description.captures(&xmp) {
+        let end = captures.get(1).map_or(0, |m| m.start());
+        return format!("{}{attribute}{}", &xmp[..end], &xmp[end..]);
+    }
+    let description = format!(
+        "  <rdf:Description rdf:about=\"\"\n    xmlns:xmp=\"http://ns.adobe.com/xap/1.0/\"{attribute}/>\n"
+    );
+    match xmp.find(" </rdf:RDF>").or_else(|| xmp.find("</rdf:RDF>")) {
+        Some(pos) => format!("{}{description}{}", &xmp[..pos], &xmp[pos..]),
+        None => xmp.into_owned(),
+    }
+}
+
+fn read_label(path: &Path) -> Option<ColorLabel> {
+    parse_label(&fs::read_to_string(sidecar_path(path)).ok()?)
+}
+
+fn known_labels() -> &'static Mutex<HashMap<PathBuf, Option<ColorLabel>>> {
+    static LABELS: OnceLock<Mutex<HashMap<PathBuf, Option<ColorLabel>>>> = OnceLock::new();
+    LABELS.get_or_init(Default::default)
+}
+
+/// Color label of a file. The sidecar is only read when the label is first needed, after
+/// that the label is remembered until `forget_labels`.
+pub fn file_label(path: &Path) -> Option<ColorLabel> {
+    let mut labels = known_labels().lock().unwrap();
+    *labels
+        .entry(path.to_path_buf())
+        .or_insert_with(|| read_label(path))
+}
+
+/// Read the labels again when they are needed, e.g. when a folder is opened
+pub fn forget_labels() {
+    known_labels().lock().unwrap().clear();
+}
+
+pub fn write_label(path: &Path, label: Option<ColorLabel>) -> MviewResult<()> {
+    let sidecar = sidecar_path(path);
+    let existing = fs::read_to_string(&sidecar).ok();
+    if existing.is_none() && label.is_none() {
+        return Ok(());
+    }
+    fs::write(&sidecar, update_label(existing.as_deref(), label))?;
+    known_labels()
+        .lock()
+        .unwrap()
+        .insert(path.to_path_buf(), label);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_sidecar() {
+        let xmp = update_label(None, Some(ColorLabel::Green));
+        assert_eq!(parse_label(&xmp), Some(ColorLabel::Green));
+        let xmp = update_label(Some(&xmp), Some(ColorLabel::Red));
+        assert_eq!(parse_label(&xmp), Some(ColorLabel::Red));
+        assert_eq!(xmp.matches("xmp:Label").count(), 1);
+        let xmp = update_label(Some(&xmp), None);
+        assert_eq!(parse_label(&xmp), None);
+        assert!(xmp.contains("</rdf:RDF>"));
+    }
+
+    #[test]
+    fn existing_sidecar() {
+        let xmp = r#"<x:xmpmeta xmlns:x="adobe:ns:meta/">
+ <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+  <rdf:Description rdf:about="" xmlns:xmp="http://ns.adobe.com/xap/1.0/"
+    xmlns:dc="http://purl.org/dc/elements/1.1/" xmp:Rating="4" xmp:Label="blue">
+   <dc:subject>
+    <rdf:Bag>
+     <rdf:li>old</rdf:li>
+    </rdf:Bag>
+   </dc:subject>
+  </rdf:Description>
+ </rdf:RDF>
+</x:xmpmeta>"#;
+        assert_eq!(parse_label(xmp), Some(ColorLabel::Blue));
+        let updated = update_label(Some(xmp), Some(ColorLabel::Purple));
+        assert_eq!(parse_label(&updated), Some(ColorLabel::Purple));
+        assert!(updated.contains(r#"xmp:Rating="4""#));
+        assert!(updated.contains("<rdf:li>old</rdf:li>"));
+        assert_eq!(updated.matches("<rdf:Description").count(), 1);
+    }
+}
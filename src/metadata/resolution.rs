@@ -0,0 +1,144 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Print resolution (DPI) from the EXIF resolution tags, used to show at what physical
+//! size an image, or the part of it that is visible, would be printed
+
+use exif::{Exif, In, Tag, Value};
+
+use crate::rect::SizeD;
+
+const CM_PER_INCH: f64 = 2.54;
+
+/// EXIF ResolutionUnit without an absolute unit, the values only give the aspect ratio
+const UNIT_NONE: u32 = 1;
+
+/// EXIF ResolutionUnit for centimeters (2 is inches, which is also the default)
+const UNIT_CENTIMETER: u32 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PrintResolution {
+    /// Pixels per inch, horizontally and vertically
+    pub dpi_x: f64,
+    pub dpi_y: f64,
+    /// The resolution was specified in pixels per centimeter
+    pub metric: bool,
+}
+
+fn rational(exif: &Exif, tag: Tag) -> Option<f64> {
+    match &exif.get_field(tag, In::PRIMARY)?.value {
+        Value::Rational(values) => values.first().map(|value| value.to_f64()),
+        _ => None,
+    }
+}
+
+impl PrintResolution {
+    pub fn new(x: f64, y: f64, unit: u32) -> Option<Self> {
+        if unit == UNIT_NONE || !(x.is_finite() && y.is_finite() && x > 0.0 && y > 0.0) {
+            return None;
+        }
+        let metric = unit == UNIT_CENTIMETER;
+        let factor = if metric { CM_PER_INCH } else { 1.0 };
+        Some(Self {
+            dpi_x: x * factor,
+            dpi_y: y * factor,
+            metric,
+        })
+    }
+
+    pub fn from_exif(exif: &Exif) -> Option<Self> {
+        let x = rational(exif, Tag::XResolution)?;
+        let y = rational(exif, Tag::YResolution).unwrap_or(x);
+        let unit = exif
+            .get_field(Tag::ResolutionUnit, In::PRIMARY)
+            .and_then(|field| field.value.get_uint(0))
+            .unwrap_or(2);
+        Self::new(x, y, unit)
+    }
+
+    /// Physical size in inches of an area of `pixels`
+    pub fn print_size(&self, pixels: SizeD) -> SizeD {
+        SizeD::new(pixels.width() / self.dpi_x, pixels.height() / self.dpi_y)
+    }
+
+    /// Print size of an area of `pixels` in the unit the resolution was given in, e.g.
+    /// "20.3 × 13.5 cm"
+    pub fn size_text(&self, pixels: SizeD) -> String {
+        let size = self.print_size(pixels);
+        if self.metric {
+            format!(
+                "{:.1} × {:.1} cm",
+                size.width() * CM_PER_INCH,
+                size.height() * CM_PER_INCH
+            )
+        } else {
+            format!("{:.1} × {:.1} in", size.width(), size.height())
+        }
+    }
+
+    pub fn dpi_text(&self) -> String {
+        if (self.dpi_x - self.dpi_y).abs() < 0.5 {
+            format!("{:.0} dpi", self.dpi_x)
+        } else {
+            format!("{:.0}×{:.0} dpi", self.dpi_x, self.dpi_y)
+        }
+    }
+
+    /// E.g. "20.3 × 13.5 cm at 300 dpi"
+    pub fn describe(&self, pixels: SizeD) -> String {
+        format!("{} at {}", self.size_text(pixels), self.dpi_text())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inches() {
+        let resolution = PrintResolution::new(300.0, 300.0, 2).unwrap();
+        assert!(!resolution.metric);
+        assert_eq!(
+            resolution.print_size(SizeD::new(3000.0, 1500.0)),
+            SizeD::new(10.0, 5.0)
+        );
+        assert_eq!(
+            resolution.describe(SizeD::new(3000.0, 1500.0)),
+            "10.0 × 5.0 in at 300 dpi"
+        );
+    }
+
+    #[test]
+    fn centimeters() {
+        // 118.11 pixels per centimeter is 300 dpi
+        let resolution = PrintResolution::new(118.11, 118.11, 3).unwrap();
+        assert!(resolution.metric);
+        assert_eq!(
+            resolution.describe(SizeD::new(2362.2, 1181.1)),
+            "20.0 × 10.0 cm at 300 dpi"
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(PrintResolution::new(0.0, 300.0, 2), None);
+        assert_eq!(PrintResolution::new(f64::NAN, 300.0, 2), None);
+        assert_eq!(PrintResolution::new(72.0, 72.0, 1), None);
+    }
+}
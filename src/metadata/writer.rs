@@ -0,0 +1,646 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Minimal EXIF metadata writer
+//!
+//! Date/time fields are updated in place, so the layout of the file does not change. They
+//! always have a fixed length of 20 bytes ("YYYY:MM:DD HH:MM:SS" followed by a NUL byte).
+//!
+//! GPS coordinates need new entries. These are appended to the TIFF structure together
+//! with a copy of IFD0 pointing to them, which means the whole file is rewritten.
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+use chrono::{NaiveDateTime, TimeDelta};
+
+use crate::{error::MviewResult, metadata::gpx::GpsPosition, mview6_error};
+
+pub const TAG_DATE_TIME: u16 = 0x0132;
+pub const TAG_DATE_TIME_ORIGINAL: u16 = 0x9003;
+pub const TAG_DATE_TIME_DIGITIZED: u16 = 0x9004;
+const TAG_EXIF_IFD: u16 = 0x8769;
+const TAG_GPS_IFD: u16 = 0x8825;
+
+const TAG_GPS_VERSION_ID: u16 = 0x0000;
+const TAG_GPS_LATITUDE_REF: u16 = 0x0001;
+const TAG_GPS_LATITUDE: u16 = 0x0002;
+const TAG_GPS_LONGITUDE_REF: u16 = 0x0003;
+const TAG_GPS_LONGITUDE: u16 = 0x0004;
+const TAG_GPS_ALTITUDE_REF: u16 = 0x0005;
+const TAG_GPS_ALTITUDE: u16 = 0x0006;
+
+const TYPE_BYTE: u16 = 1;
+const TYPE_ASCII: u16 = 2;
+const TYPE_LONG: u16 = 4;
+const TYPE_RATIONAL: u16 = 5;
+const DATE_TIME_LEN: usize = 19;
+const DATE_TIME_FORMAT: &str = "%Y:%m:%d %H:%M:%S";
+/// Metadata is expected near the start of the file, no need to read (large) images completely
+const MAX_HEADER_SIZE: u64 = 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DateTimeField {
+    pub tag: u16,
+    /// Absolute position of the value in the file
+    pub offset: usize,
+    pub value: NaiveDateTime,
+}
+
+struct Tiff<'a> {
+    data: &'a [u8],
+    start: usize,
+    little_endian: bool,
+}
+
+impl<'a> Tiff<'a> {
+    fn new(data: &'a [u8], block: &TiffBlock) -> Self {
+        Self {
+            data,
+            start: block.start,
+            little_endian: data.get(block.start..block.start + 2) == Some(b"II"),
+        }
+    }
+
+    fn u16_bytes(&self, value: u16) -> [u8; 2] {
+        if self.little_endian {
+            value.to_le_bytes()
+        } else {
+            value.to_be_bytes()
+        }
+    }
+
+    fn u32_bytes(&self, value: u32) -> [u8; 4] {
+        if self.little_endian {
+            value.to_le_bytes()
+        } else {
+            value.to_be_bytes()
+        }
+    }
+
+    fn entry(&self, tag: u16, field_type: u16, count: u32, value: [u8; 4]) -> [u8; 12] {
+        let mut entry = [0; 12];
+        entry[0..2].copy_from_slice(&self.u16_bytes(tag));
+        entry[2..4].copy_from_slice(&self.u16_bytes(field_type));
+        entry[4..8].copy_from_slice(&self.u32_bytes(count));
+        entry[8..12].copy_from_slice(&value);
+        entry
+    }
+
+    fn u16(&self, pos: usize) -> Option<u16> {
+        let bytes = self.data.get(self.start + pos..self.start + pos + 2)?;
+        let bytes = [bytes[0], bytes[1]];
+        Some(if self.little_endian {
+            u16::from_le_bytes(bytes)
+        } else {
+            u16::from_be_bytes(bytes)
+        })
+    }
+
+    fn u32(&self, pos: usize) -> Option<u32> {
+        let bytes = self.data.get(self.start + pos..self.start + pos + 4)?;
+        let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        Some(if self.little_endian {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        })
+    }
+
+    /// Walk an IFD, collecting the date/time fields and following the pointer to the
+    /// EXIF sub-IFD
+    fn scan_ifd(&self, ifd: usize, fields: &mut Vec<DateTimeField>, depth: u32) -> Option<()> {
+        if depth > 2 {
+            return None;
+        }
+        let count = self.u16(ifd)? as usize;
+        for i in 0..count {
+            let entry = ifd + 2 + 12 * i;
+            let tag = self.u16(entry)?;
+            let field_type = self.u16(entry + 2)?;
+            let field_count = self.u32(entry + 4)? as usize;
+            match tag {
+                TAG_DATE_TIME | TAG_DATE_TIME_ORIGINAL | TAG_DATE_TIME_DIGITIZED
+                    if field_type == TYPE_ASCII && field_count >= DATE_TIME_LEN =>
+                {
+                    let offset = self.start + self.u32(entry + 8)? as usize;
+                    let text = self.data.get(offset..offset + DATE_TIME_LEN)?;
+                    let text = std::str::from_utf8(text).ok()?;
+                    if let Ok(value) = NaiveDateTime::parse_from_str(text, DATE_TIME_FORMAT) {
+                        fields.push(DateTimeField { tag, offset, value });
+                    }
+                }
+                TAG_EXIF_IFD if field_type == TYPE_LONG => {
+                    let sub_ifd = self.u32(entry + 8)? as usize;
+                    self.scan_ifd(sub_ifd, fields, depth + 1);
+                }
+                _ => (),
+            }
+        }
+        Some(())
+    }
+}
+
+/// Location of the TIFF structure (holding the EXIF data) within a file
+struct TiffBlock {
+    start: usize,
+    len: usize,
+    /// Position of the JPEG APP1 segment containing the TIFF structure
+    app1: Option<usize>,
+}
+
+/// Find the TIFF structure: inside the APP1 segment of a JPEG or at the start of a TIFF
+/// based file (TIFF, most camera raw formats)
+fn tiff_block(data: &[u8]) -> Option<TiffBlock> {
+    if data.starts_with(b"II*\0") || data.starts_with(b"MM\0*") {
+        return Some(TiffBlock {
+            start: 0,
+            len: data.len(),
+            app1: None,
+        });
+    }
+    if !data.starts_with(&[0xff, 0xd8]) {
+        return None;
+    }
+    let mut pos = 2;
+    while pos + 4 <= data.len() {
+        if data[pos] != 0xff {
+            return None;
+        }
+        let marker = data[pos + 1];
+        let length = u16::from_be_bytes([data[pos + 2], data[pos + 3]]) as usize;
+        if marker == 0xe1 && data.get(pos + 4..pos + 10) == Some(b"Exif\0\0") && length >= 8 {
+            return Some(TiffBlock {
+                start: pos + 10,
+                len: (length - 8).min(data.len() - pos - 10),
+                app1: Some(pos),
+            });
+        }
+        if marker == 0xda {
+            // start of scan: no more metadata
+            return None;
+        }
+        pos += 2 + length;
+    }
+    None
+}
+
+pub fn find_date_time_fields(data: &[u8]) -> Vec<DateTimeField> {
+    let mut fields = Vec::new();
+    if let Some(block) = tiff_block(data) {
+        let tiff = Tiff::new(data, &block);
+        if let Some(ifd0) = tiff.u32(4) {
+            tiff.scan_ifd(ifd0 as usize, &mut fields, 0);
+        }
+    }
+    fields
+}
+
+pub fn read_date_time_fields(path: &Path) -> MviewResult<Vec<DateTimeField>> {
+    let mut data = Vec::new();
+    File::open(path)?
+        .take(MAX_HEADER_SIZE)
+        .read_to_end(&mut data)?;
+    Ok(find_date_time_fields(&data))
+}
+
+/// The capture time of an image: DateTimeOriginal if present, otherwise the first
+/// date/time field found
+pub fn capture_time(fields: &[DateTimeField]) -> Option<NaiveDateTime> {
+    fields
+        .iter()
+        .find(|f| f.tag == TAG_DATE_TIME_ORIGINAL)
+        .or(fields.first())
+        .map(|f| f.value)
+}
+
+/// New values (offset, text) of the date/time fields shifted by `delta`. Fails without
+/// result when any of them cannot be shifted.
+fn shifted_values(fields: &[DateTimeField], delta: TimeDelta) -> MviewResult<Vec<(usize, String)>> {
+    fields
+        .iter()
+        .map(|field| {
+            let Some(shifted) = field.value.checked_add_signed(delta) else {
+                return mview6_error!(format!("Cannot shift {} by {delta}", field.value)).into();
+            };
+            let text = shifted.format(DATE_TIME_FORMAT).to_string();
+            if text.len() != DATE_TIME_LEN {
+                return mview6_error!(format!("Date/time out of range: {text}")).into();
+            }
+            Ok((field.offset, text))
+        })
+        .collect()
+}
+
+/// Write the shifted values, preserving the modification time of the file
+fn write_values(path: &Path, values: &[(usize, String)]) -> MviewResult<()> {
+    let modified = fs::metadata(path)?.modified()?;
+    let mut file = OpenOptions::new().write(true).open(path)?;
+    for (offset, text) in values {
+        file.seek(SeekFrom::Start(*offset as u64))?;
+        file.write_all(text.as_bytes())?;
+    }
+    file.flush()?;
+    file.set_modified(modified)?;
+    Ok(())
+}
+
+/// Shift all date/time fields of the files by `delta`. All files are checked before any
+/// of them is written, so a value that cannot be shifted leaves every file untouched.
+/// Returns the files that were updated.
+pub fn shift_date_time(files: &[PathBuf], delta: TimeDelta) -> MviewResult<Vec<PathBuf>> {
+    let mut changes = Vec::new();
+    for path in files {
+        let values = read_date_time_fields(path)
+            .and_then(|fields| shifted_values(&fields, delta))
+            .map_err(|e| mview6_error!(format!("{}: {e}", path.display())))?;
+        if !values.is_empty() {
+            changes.push((path, values));
+        }
+    }
+    let mut shifted = Vec::new();
+    for (path, values) in changes {
+        match write_values(path, &values) {
+            Ok(()) => shifted.push(path.clone()),
+            Err(e) => eprintln!("Failed to shift timestamps of {path:?}: {e}"),
+        }
+    }
+    Ok(shifted)
+}
+
+/// Degrees as degrees, minutes and seconds rationals
+fn to_dms(value: f64) -> [(u32, u32); 3] {
+    // Rounded as a whole, so 59.99999 seconds carries into the minutes and degrees
+    let total = (value.abs() * 3600.0 * 10000.0).round() as u64;
+    let seconds = total % 600_000;
+    let minutes = total / 600_000 % 60;
+    let degrees = total / 36_000_000;
+    [
+        (degrees as u32, 1),
+        (minutes as u32, 1),
+        (seconds as u32, 10000),
+    ]
+}
+
+/// Build the data to append to the TIFF structure: a copy of IFD0 with an (additional)
+/// pointer to a new GPS IFD, followed by that GPS IFD and its values. The existing data is
+/// left untouched, so all offsets in it remain valid.
+///
+/// Returns the data and the offset of the new IFD0.
+fn gps_extension(tiff: &Tiff, tiff_len: usize, position: &GpsPosition) -> Option<(Vec<u8>, u32)> {
+    let ifd0 = tiff.u32(4)? as usize;
+    let count = tiff.u16(ifd0)? as usize;
+    let mut entries = (0..count)
+        .map(|i| {
+            let pos = tiff.start + ifd0 + 2 + 12 * i;
+            let entry: [u8; 12] = tiff.data.get(pos..pos + 12)?.try_into().ok()?;
+            Some((tiff.u16(ifd0 + 2 + 12 * i)?, entry))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let next_ifd = tiff.u32(ifd0 + 2 + 12 * count)?;
+    entries.retain(|(tag, _)| *tag != TAG_GPS_IFD);
+
+    // Offsets must be word aligned
+    let padding = tiff_len % 2;
+    let new_ifd0 = tiff_len + padding;
+    let gps_ifd = new_ifd0 + 2 + 12 * (entries.len() + 1) + 4;
+    let gps_count = if position.elevation.is_some() { 7 } else { 5 };
+    let values_start = gps_ifd + 2 + 12 * gps_count + 4;
+
+    let mut values = Vec::new();
+    let mut rationals = |rationals: &[(u32, u32)]| -> [u8; 4] {
+        let offset = (values_start + values.len()) as u32;
+        for (numerator, denominator) in rationals {
+            values.extend(tiff.u32_bytes(*numerator));
+            values.extend(tiff.u32_bytes(*denominator));
+        }
+        tiff.u32_bytes(offset)
+    };
+
+    let latitude_ref = if position.latitude < 0.0 { b'S' } else { b'N' };
+    let longitude_ref = if position.longitude < 0.0 { b'W' } else { b'E' };
+    let mut gps_entries = vec![
+        tiff.entry(TAG_GPS_VERSION_ID, TYPE_BYTE, 4, [2, 3, 0, 0]),
+        tiff.entry(TAG_GPS_LATITUDE_REF, TYPE_ASCII, 2, [latitude_ref, 0, 0, 0]),
+        tiff.entry(
+            TAG_GPS_LATITUDE,
+            TYPE_RATIONAL,
+            3,
+            rationals(&to_dms(position.latitude)),
+        ),
+        tiff.entry(
+            TAG_GPS_LONGITUDE_REF,
+            TYPE_ASCII,
+            2,
+            [longitude_ref, 0, 0, 0],
+        ),
+        tiff.entry(
+            TAG_GPS_LONGITUDE,
+            TYPE_RATIONAL,
+            3,
+            rationals(&to_dms(position.longitude)),
+        ),
+    ];
+    if let Some(elevation) = position.elevation {
+        let below_sea_level = if elevation < 0.0 { 1 } else { 0 };
+        gps_entries.push(tiff.entry(
+            TAG_GPS_ALTITUDE_REF,
+            TYPE_BYTE,
+            1,
+            [below_sea_level, 0, 0, 0],
+        ));
+        gps_entries.push(tiff.entry(
+            TAG_GPS_ALTITUDE,
+            TYPE_RATIONAL,
+            1,
+            rationals(&[((elevation.abs() * 100.0).round() as u32, 100)]),
+        ));
+    }
+
+    entries.push((
+        TAG_GPS_IFD,
+        tiff.entry(TAG_GPS_IFD, TYPE_LONG, 1, tiff.u32_bytes(gps_ifd as u32)),
+    ));
+    entries.sort_by_key(|(tag, _)| *tag);
+
+    let mut extension = vec![0; padding];
+    extension.extend(tiff.u16_bytes(entries.len() as u16));
+    for (_, entry) in &entries {
+        extension.extend(entry);
+    }
+    extension.extend(tiff.u32_bytes(next_ifd));
+    extension.extend(tiff.u16_bytes(gps_count as u16));
+    for entry in &gps_entries {
+        extension.extend(entry);
+    }
+    extension.extend(tiff.u32_bytes(0));
+    extension.extend(values);
+
+    Some((extension, new_ifd0 as u32))
+}
+
+/// Add GPS coordinates to the EXIF data, replacing the existing ones (if any)
+fn add_gps(data: &[u8], position: &GpsPosition) -> MviewResult<Vec<u8>> {
+    let Some(block) = tiff_block(data) else {
+        return mview6_error!("No EXIF data found").into();
+    };
+    let tiff = Tiff::new(data, &block);
+    let Some((extension, ifd0)) = gps_extension(&tiff, block.len, position) else {
+        return mview6_error!("Invalid EXIF data").into();
+    };
+
+    let end = block.start + block.len;
+    let mut output = Vec::with_capacity(data.len() + extension.len());
+    output.extend_from_slice(&data[..end]);
+    output[block.start + 4..block.start + 8].copy_from_slice(&tiff.u32_bytes(ifd0));
+    if let Some(app1) = block.app1 {
+        // segment length includes the length field itself and the "Exif\0\0" header
+        let length = 8 + block.len + extension.len();
+        if length > u16::MAX as usize {
+            return mview6_error!("EXIF data too large").into();
+        }
+        output[app1 + 2..app1 + 4].copy_from_slice(&(length as u16).to_be_bytes());
+    }
+    output.extend(extension);
+    output.extend_from_slice(&data[end..]);
+    Ok(output)
+}
+
+/// Write GPS coordinates into the EXIF data of the file. The file is replaced atomically
+/// and keeps its permissions and modification time.
+pub fn write_gps(path: &Path, position: &GpsPosition) -> MviewResult<()> {
+    let data = fs::read(path)?;
+    let output = add_gps(&data, position)?;
+
+    let metadata = fs::metadata(path)?;
+    let temp_path = path.with_file_name(format!(
+        ".{}.mview6-tmp",
+        path.file_name().unwrap_or_default().to_string_lossy()
+    ));
+    let mut file = File::create(&temp_path)?;
+    file.write_all(&output)?;
+    file.flush()?;
+    file.set_modified(metadata.modified()?)?;
+    fs::set_permissions(&temp_path, metadata.permissions())?;
+    fs::rename(&temp_path, path)?;
+    Ok(())
+}
+
+/// Check if the file already has GPS coordinates
+pub fn has_gps(path: &Path) -> bool {
+    let mut data = Vec::new();
+    if File::open(path)
+        .and_then(|f| f.take(MAX_HEADER_SIZE).read_to_end(&mut data))
+        .is_err()
+    {
+        return false;
+    }
+    let Some(block) = tiff_block(&data) else {
+        return false;
+    };
+    let tiff = Tiff::new(&data, &block);
+    let find = || -> Option<bool> {
+        let ifd0 = tiff.u32(4)? as usize;
+        let count = tiff.u16(ifd0)? as usize;
+        Some((0..count).any(|i| tiff.u16(ifd0 + 2 + 12 * i) == Some(TAG_GPS_IFD)))
+    };
+    find().unwrap_or_default()
+}
+
+/// Parse a time offset like "+1:30", "-0:00:45" or "+2d 01:00:00"
+pub fn parse_time_offset(text: &str) -> Option<TimeDelta> {
+    let text = text.trim();
+    let (negative, text) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text.strip_prefix('+').unwrap_or(text)),
+    };
+    let (days, time) = match text.split_once('d') {
+        Some((days, time)) => (days.trim().parse::<i64>().ok()?, time.trim()),
+        None => (0, text),
+    };
+    let mut seconds = days * 86400;
+    if !time.is_empty() {
+        let parts = time
+            .split(':')
+            .map(|p| p.parse::<i64>().ok())
+            .collect::<Option<Vec<_>>>()?;
+        let (h, m, s) = match parts[..] {
+            [h] => (h, 0, 0),
+            [h, m] => (h, m, 0),
+            [h, m, s] => (h, m, s),
+            _ => return None,
+        };
+        if !(0..60).contains(&m) || !(0..60).contains(&s) || h < 0 {
+            return None;
+        }
+        seconds += h * 3600 + m * 60 + s;
+    }
+    Some(TimeDelta::seconds(if negative {
+        -seconds
+    } else {
+        seconds
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Little endian TIFF with DateTime in IFD0 and DateTimeOriginal in the EXIF IFD
+    fn tiff() -> Vec<u8> {
+        let mut d = b"II*\0".to_vec();
+        d.extend(8u32.to_le_bytes()); // IFD0 at 8
+        d.extend(2u16.to_le_bytes()); // 2 entries
+        d.extend(TAG_DATE_TIME.to_le_bytes());
+        d.extend(TYPE_ASCII.to_le_bytes());
+        d.extend(20u32.to_le_bytes());
+        d.extend(38u32.to_le_bytes()); // value at 38
+        d.extend(TAG_EXIF_IFD.to_le_bytes());
+        d.extend(TYPE_LONG.to_le_bytes());
+        d.extend(1u32.to_le_bytes());
+        d.extend(58u32.to_le_bytes()); // EXIF IFD at 58
+        d.extend(0u32.to_le_bytes()); // no next IFD
+        d.extend(b"2024:02:29 23:30:00\0"); // 38
+        d.extend(1u16.to_le_bytes()); // 58: 1 entry
+        d.extend(TAG_DATE_TIME_ORIGINAL.to_le_bytes());
+        d.extend(TYPE_ASCII.to_le_bytes());
+        d.extend(20u32.to_le_bytes());
+        d.extend(76u32.to_le_bytes()); // value at 76
+        d.extend(0u32.to_le_bytes());
+        d.extend(b"2024:02:29 23:29:59\0"); // 76
+        d
+    }
+
+    #[test]
+    fn find_fields_tiff() {
+        let fields = find_date_time_fields(&tiff());
+        assert_eq!(fields.len(), 2);
+        assert_eq!(fields[0].tag, TAG_DATE_TIME);
+        assert_eq!(fields[0].offset, 38);
+        assert_eq!(fields[1].tag, TAG_DATE_TIME_ORIGINAL);
+        assert_eq!(
+            capture_time(&fields).unwrap().to_string(),
+            "2024-02-29 23:29:59"
+        );
+    }
+
+    #[test]
+    fn shift_values() {
+        let fields = find_date_time_fields(&tiff());
+        let values = shifted_values(&fields, TimeDelta::seconds(1800)).unwrap();
+        assert_eq!(values[0], (38, "2024:03:01 00:00:00".to_string()));
+        assert_eq!(values[1], (76, "2024:02:29 23:59:59".to_string()));
+        assert!(shifted_values(&fields, TimeDelta::days(3_000_000)).is_err());
+    }
+
+    #[test]
+    fn find_fields_jpeg() {
+        let tiff = tiff();
+        let mut jpeg = vec![0xff, 0xd8, 0xff, 0xe1];
+        jpeg.extend(((tiff.len() + 8) as u16).to_be_bytes());
+        jpeg.extend(b"Exif\0\0");
+        jpeg.extend(&tiff);
+        jpeg.extend([0xff, 0xda]);
+        let fields = find_date_time_fields(&jpeg);
+        assert_eq!(fields.len(), 2);
+        assert_eq!(fields[1].offset, 76 + 12);
+    }
+
+    /// Decode the latitude written by `add_gps`
+    fn read_latitude(data: &[u8]) -> Option<f64> {
+        let block = tiff_block(data)?;
+        let tiff = Tiff::new(data, &block);
+        let ifd0 = tiff.u32(4)? as usize;
+        let count = tiff.u16(ifd0)? as usize;
+        let entry = (0..count)
+            .map(|i| ifd0 + 2 + 12 * i)
+            .find(|e| tiff.u16(*e) == Some(TAG_GPS_IFD))?;
+        let gps_ifd = tiff.u32(entry + 8)? as usize;
+        let latitude = tiff.u32(gps_ifd + 2 + 12 * 2 + 8)? as usize;
+        let rational = |i: usize| {
+            Some(tiff.u32(latitude + 8 * i)? as f64 / tiff.u32(latitude + 8 * i + 4)? as f64)
+        };
+        Some(rational(0)? + rational(1)? / 60.0 + rational(2)? / 3600.0)
+    }
+
+    #[test]
+    fn gps_tiff() {
+        let position = GpsPosition {
+            latitude: 52.370216,
+            longitude: -4.895168,
+            elevation: Some(-2.5),
+        };
+        let data = add_gps(&tiff(), &position).unwrap();
+        assert!((read_latitude(&data).unwrap() - position.latitude).abs() < 1e-6);
+        // date/time fields are still found
+        assert_eq!(find_date_time_fields(&data).len(), 2);
+        // replacing existing coordinates
+        let position = GpsPosition {
+            latitude: 10.5,
+            ..position
+        };
+        let data = add_gps(&data, &position).unwrap();
+        assert!((read_latitude(&data).unwrap() - 10.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn dms() {
+        assert_eq!(to_dms(-4.5), [(4, 1), (30, 1), (0, 10000)]);
+        assert_eq!(to_dms(52.370216), [(52, 1), (22, 1), (127_776, 10000)]);
+        // seconds rounding up to 60 carry into the minutes and degrees
+        assert_eq!(to_dms(10.0 - 1e-9), [(10, 1), (0, 1), (0, 10000)]);
+        assert_eq!(to_dms(1.0 / 60.0 - 1e-9), [(0, 1), (1, 1), (0, 10000)]);
+    }
+
+    #[test]
+    fn gps_jpeg() {
+        let tiff = tiff();
+        let mut jpeg = vec![0xff, 0xd8, 0xff, 0xe1];
+        jpeg.extend(((tiff.len() + 8) as u16).to_be_bytes());
+        jpeg.extend(b"Exif\0\0");
+        jpeg.extend(&tiff);
+        jpeg.extend([0xff, 0xda, 0x12, 0x34]);
+        let position = GpsPosition {
+            latitude: -33.8688,
+            longitude: 151.2093,
+            elevation: None,
+        };
+        let data = add_gps(&jpeg, &position).unwrap();
+        assert!((read_latitude(&data).unwrap() - 33.8688).abs() < 1e-6);
+        assert!(data.ends_with(&[0xff, 0xda, 0x12, 0x34]));
+        let block = tiff_block(&data).unwrap();
+        assert_eq!(block.start + block.len + 4, data.len());
+    }
+
+    #[test]
+    fn time_offset() {
+        assert_eq!(parse_time_offset("+1:30"), Some(TimeDelta::seconds(5400)));
+        assert_eq!(parse_time_offset("-0:00:45"), Some(TimeDelta::seconds(-45)));
+        assert_eq!(
+            parse_time_offset("2d 01:00:00"),
+            Some(TimeDelta::seconds(2 * 86400 + 3600))
+        );
+        assert_eq!(parse_time_offset("-1d"), Some(TimeDelta::seconds(-86400)));
+        assert_eq!(parse_time_offset("1:75"), None);
+        assert_eq!(parse_time_offset("abc"), None);
+    }
+}
@@ -0,0 +1,330 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Embedded HTTP server that lets a tablet or another device mirror the current view. It
+//! serves a page showing the view, the view itself as PNG and the state as JSON.
+//!
+//! The window hands over the captured pixels. They are encoded on the server's threads,
+//! when a client first asks for the image.
+
+use std::{
+    io::{self, BufRead, BufReader, Cursor, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use image::{ImageFormat, RgbaImage};
+use serde::Serialize;
+
+use crate::image::provider::surface::rgba8_from_argb32;
+
+/// Address used when none is configured: only reachable from this computer
+pub const DEFAULT_ADDRESS: &str = "127.0.0.1:6060";
+
+/// How often the server checks whether it should stop
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Time a client gets to send its request or to receive the reply
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Clients served at the same time, further connections are closed right away
+const MAX_CLIENTS: usize = 8;
+
+/// Longest request line that is read, the paths served are short
+const MAX_REQUEST_LINE: u64 = 1024;
+
+const INDEX_HTML: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>MView6</title>
+<style>
+html, body { margin: 0; height: 100%; background: #000; color: #ccc; font-family: sans-serif; }
+img { display: block; width: 100%; height: calc(100% - 2em); object-fit: contain; }
+p { margin: 0; height: 2em; line-height: 2em; text-align: center; }
+</style>
+</head>
+<body>
+<img id="view" alt="">
+<p id="name"></p>
+<script>
+let version = -1;
+async function update() {
+    try {
+        const state = await (await fetch("/state.json")).json();
+        if (state.version !== version) {
+            version = state.version;
+            document.getElementById("view").src = "/image.png?v=" + version;
+            document.getElementById("name").textContent =
+                state.total ? `${state.name} (${state.position} of ${state.total})` : state.name;
+        }
+    } catch (e) {}
+}
+setInterval(update, 1000);
+update();
+</script>
+</body>
+</html>
+"#;
+
+/// What is being viewed, as served by `/state.json`
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct PreviewState {
+    /// Incremented on every change, clients reload the image when it differs
+    pub version: u64,
+    pub name: String,
+    pub position: u32,
+    pub total: u32,
+}
+
+/// The view as captured, in the layout of an ARGB32 surface
+#[derive(Debug)]
+pub struct Frame {
+    pub width: u32,
+    pub height: u32,
+    pub stride: usize,
+    pub data: Vec<u8>,
+}
+
+impl Frame {
+    fn to_png(&self) -> Option<Vec<u8>> {
+        let rgba8 = rgba8_from_argb32(
+            &self.data,
+            self.width as usize,
+            self.height as usize,
+            self.stride,
+        );
+        let image = RgbaImage::from_raw(self.width, self.height, rgba8)?;
+        let mut png = Cursor::new(Vec::new());
+        image.write_to(&mut png, ImageFormat::Png).ok()?;
+        Some(png.into_inner())
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct SharedPreview {
+    pub state: PreviewState,
+    frame: Option<Arc<Frame>>,
+    /// The frame as PNG, once a client asked for it
+    png: Option<Vec<u8>>,
+}
+
+impl SharedPreview {
+    pub fn update(&mut self, mut state: PreviewState, frame: Option<Frame>) {
+        state.version = self.state.version + 1;
+        self.state = state;
+        self.frame = frame.map(Arc::new);
+        self.png = None;
+    }
+}
+
+/// Encode the current frame, unless that was done already. The lock is not held while
+/// encoding, the result is dropped when the view changed in the meantime.
+fn encode_frame(shared: &Mutex<SharedPreview>) {
+    let (version, frame) = match shared.lock() {
+        Ok(shared) if shared.png.is_none() => (shared.state.version, shared.frame.clone()),
+        _ => return,
+    };
+    let Some(png) = frame.and_then(|frame| frame.to_png()) else {
+        return;
+    };
+    if let Ok(mut shared) = shared.lock() {
+        if shared.state.version == version {
+            shared.png = Some(png);
+        }
+    }
+}
+
+pub struct PreviewServer {
+    address: String,
+    shared: Arc<Mutex<SharedPreview>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl PreviewServer {
+    pub fn start(address: &str) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        let address = listener.local_addr()?.to_string();
+        let shared = Arc::new(Mutex::new(SharedPreview::default()));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let thread_shared = shared.clone();
+        let thread_stop = stop.clone();
+        thread::spawn(move || {
+            let clients = Arc::new(AtomicUsize::new(0));
+            while !thread_stop.load(Ordering::Relaxed) {
+                match listener.accept() {
+                    Ok((stream, _)) => {
+                        if clients.load(Ordering::Relaxed) >= MAX_CLIENTS {
+                            // Dropping the stream closes the connection
+                            continue;
+                        }
+                        // A slow client must not hold up the others
+                        clients.fetch_add(1, Ordering::Relaxed);
+                        let clients = clients.clone();
+                        let shared = thread_shared.clone();
+                        thread::spawn(move || {
+                            if let Err(e) = serve(stream, &shared) {
+                                eprintln!("Preview server: {e}");
+                            }
+                            clients.fetch_sub(1, Ordering::Relaxed);
+                        });
+                    }
+                    Err(e) if e.kind() == io::ErrorKind::WouldBlock => thread::sleep(POLL_INTERVAL),
+                    Err(e) => eprintln!("Preview server: {e}"),
+                }
+            }
+        });
+
+        Ok(Self {
+            address,
+            shared,
+            stop,
+        })
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}/", self.address)
+    }
+
+    pub fn update(&self, state: PreviewState, frame: Option<Frame>) {
+        if let Ok(mut shared) = self.shared.lock() {
+            shared.update(state, frame);
+        }
+    }
+}
+
+impl Drop for PreviewServer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Path of a `GET` request line, without the query string
+fn request_path(request_line: &str) -> Option<&str> {
+    let mut parts = request_line.split_whitespace();
+    if parts.next()? != "GET" {
+        return None;
+    }
+    let target = parts.next()?;
+    Some(target.split('?').next().unwrap_or(target))
+}
+
+fn response(status: &str, content_type: &str, body: &[u8]) -> Vec<u8> {
+    let mut result = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n",
+        body.len()
+    )
+    .into_bytes();
+    result.extend_from_slice(body);
+    result
+}
+
+fn not_found() -> Vec<u8> {
+    response("404 Not Found", "text/plain", b"Not found")
+}
+
+fn route(path: Option<&str>, shared: &SharedPreview) -> Vec<u8> {
+    match path {
+        Some("/") => response("200 OK", "text/html; charset=utf-8", INDEX_HTML.as_bytes()),
+        Some("/state.json") => match serde_json::to_vec(&shared.state) {
+            Ok(json) => response("200 OK", "application/json", &json),
+            Err(_) => not_found(),
+        },
+        Some("/image.png") => match &shared.png {
+            Some(image) => response("200 OK", "image/png", image),
+            None => not_found(),
+        },
+        Some(_) => not_found(),
+        None => response(
+            "405 Method Not Allowed",
+            "text/plain",
+            b"Method not allowed",
+        ),
+    }
+}
+
+fn serve(mut stream: TcpStream, shared: &Mutex<SharedPreview>) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+    let mut request_line = String::new();
+    BufReader::new((&stream).take(MAX_REQUEST_LINE)).read_line(&mut request_line)?;
+    let path = request_path(&request_line);
+    if path == Some("/image.png") {
+        encode_frame(shared);
+    }
+    // The lock is released before writing, the reply is a copy
+    let reply = match shared.lock() {
+        Ok(shared) => route(path, &shared),
+        Err(_) => not_found(),
+    };
+    stream.write_all(&reply)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_request() {
+        assert_eq!(
+            request_path("GET /image.png?v=3 HTTP/1.1\r\n"),
+            Some("/image.png")
+        );
+        assert_eq!(request_path("GET / HTTP/1.1\r\n"), Some("/"));
+        assert_eq!(request_path("POST / HTTP/1.1\r\n"), None);
+    }
+
+    #[test]
+    fn routes() {
+        let shared = Mutex::new(SharedPreview::default());
+        encode_frame(&shared);
+        assert!(route(Some("/image.png"), &shared.lock().unwrap()).starts_with(b"HTTP/1.1 404"));
+        shared.lock().unwrap().update(
+            PreviewState {
+                name: "a.jpg".to_string(),
+                ..Default::default()
+            },
+            Some(Frame {
+                width: 1,
+                height: 1,
+                stride: 4,
+                data: vec![0, 0, 255, 255],
+            }),
+        );
+        encode_frame(&shared);
+        let shared = shared.lock().unwrap();
+        assert_eq!(shared.state.version, 1);
+        let image = route(Some("/image.png"), &shared);
+        assert!(image.starts_with(b"HTTP/1.1 200 OK"));
+        let body = image.windows(4).position(|w| w == b"\r\n\r\n").unwrap() + 4;
+        let decoded = image::load_from_memory(&image[body..]).unwrap().to_rgba8();
+        assert_eq!(decoded.get_pixel(0, 0).0, [255, 0, 0, 255]);
+        let state = String::from_utf8(route(Some("/state.json"), &shared)).unwrap();
+        assert!(state.contains(r#""name":"a.jpg""#));
+    }
+}
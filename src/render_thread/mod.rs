@@ -19,22 +19,34 @@
 
 pub mod model;
 mod sender;
+pub mod trace;
 mod worker;
 
 use std::{
     sync::{atomic::AtomicU32, Arc},
     thread::{self, JoinHandle},
+    time::Duration,
 };
 
 use async_channel::{Receiver, Sender};
 
-use crate::render_thread::{
-    model::{RenderCommandMessage, RenderReplyMessage},
-    worker::RenderWorker,
+use crate::{
+    config::render_timeout,
+    image::provider::surface::SurfaceData,
+    render_thread::{
+        model::{RenderCommand, RenderCommandMessage, RenderReply, RenderReplyMessage},
+        worker::{try_render_lock, CachedBackend, RenderWorker},
+    },
 };
 
 pub use sender::RenderThreadSender;
 
+const RESTART_DELAY: Duration = Duration::from_millis(100);
+const WATCHDOG_INTERVAL: Duration = Duration::from_millis(250);
+
+/// How long `render_now` waits for the render worker to leave the renderers
+const RENDER_NOW_WAIT: Duration = Duration::from_secs(2);
+
 #[derive(Debug)]
 pub struct RenderThread {
     _handle: JoinHandle<()>,
@@ -48,10 +60,8 @@ impl RenderThread {
     ) -> Self {
         let counter = Arc::new(AtomicU32::new(0));
         let counter_clone = Arc::clone(&counter);
-        let worker = RenderWorker::new(from_rt_sender, to_rt_receiver, counter_clone);
-        let handle = thread::spawn(move || {
-            worker.run();
-        });
+        let handle =
+            thread::spawn(move || supervise(from_rt_sender, to_rt_receiver, counter_clone));
         RenderThread {
             _handle: handle,
             counter,
@@ -62,3 +72,75 @@ impl RenderThread {
         RenderThreadSender::new(to_rt_sender, self.counter.clone())
     }
 }
+
+/// Execute a render command on the calling thread, for offscreen snapshots. It takes turns
+/// with the render worker and gives up (`None`) when the worker stays busy.
+pub fn render_now(command: RenderCommand) -> Option<SurfaceData> {
+    let Some(_guard) = try_render_lock(RENDER_NOW_WAIT) else {
+        eprintln!("Renderer busy, snapshot without a high quality render");
+        return None;
+    };
+    let mut backend = CachedBackend::new();
+    match RenderWorker::execute(command, &mut backend)? {
+        RenderReply::RenderDone(_, surface, _, _) => Some(surface),
+        _ => None,
+    }
+}
+
+/// Run one worker at a time. A new worker replaces one that died from a panic or that is
+/// stuck on a render for longer than the configured timeout. The stuck render cannot be
+/// interrupted, its thread is left to finish and its result is discarded. The new worker
+/// only renders once the stuck one has finished, so at most two worker threads exist.
+fn supervise(
+    from_rt_sender: Sender<RenderReplyMessage>,
+    to_rt_receiver: Receiver<RenderCommandMessage>,
+    counter: Arc<AtomicU32>,
+) {
+    let timeout = render_timeout();
+    loop {
+        let worker = RenderWorker::new(
+            from_rt_sender.clone(),
+            to_rt_receiver.clone(),
+            counter.clone(),
+        );
+        let state = worker.state();
+        let handle = match thread::Builder::new()
+            .name("render-worker".into())
+            .spawn(move || worker.run())
+        {
+            Ok(handle) => handle,
+            Err(e) => {
+                eprintln!("Failed to start render worker: {e}");
+                return;
+            }
+        };
+        loop {
+            thread::sleep(WATCHDOG_INTERVAL);
+            if handle.is_finished() {
+                if handle.join().is_ok() {
+                    return; // channel closed
+                }
+                eprintln!("Render worker died, restarting");
+                thread::sleep(RESTART_DELAY);
+                break;
+            }
+            if let Some(busy) = state.stuck(timeout) {
+                state.abandon();
+                eprintln!(
+                    "Render id {} ({}) did not finish within {}s, starting a new worker",
+                    busy.command_id,
+                    busy.description,
+                    timeout.as_secs()
+                );
+                let reply = RenderReplyMessage {
+                    _id: busy.command_id,
+                    reply: RenderReply::RenderTimedOut(busy.image_id),
+                };
+                if let Err(e) = from_rt_sender.send_blocking(reply) {
+                    eprintln!("Failed to send reply {e}");
+                }
+                break;
+            }
+        }
+    }
+}
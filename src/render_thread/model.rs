@@ -36,6 +36,30 @@ pub enum RenderCommand {
     RenderSvg(u32, Zoom, RectD, Arc<Tree>),
 }
 
+impl RenderCommand {
+    pub fn image_id(&self) -> u32 {
+        match self {
+            RenderCommand::RenderDoc(image_id, _, _, _) => *image_id,
+            RenderCommand::RenderSvg(image_id, _, _, _) => *image_id,
+        }
+    }
+
+    /// Short description for diagnostics
+    pub fn describe(&self) -> String {
+        match self {
+            RenderCommand::RenderDoc(_, zoom, _, doc) => format!(
+                "document {} {:?} at zoom {:.3}",
+                doc.reference.backend.path(),
+                doc.reference.item,
+                zoom.scale()
+            ),
+            RenderCommand::RenderSvg(_, zoom, _, _) => {
+                format!("svg at zoom {:.3}", zoom.scale())
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct RenderCommandMessage {
     pub id: u32,
@@ -46,6 +70,10 @@ pub struct RenderCommandMessage {
 pub enum RenderReply {
     // Image((Reference, PageMode, i32)),
     RenderDone(u32, SurfaceData, Zoom, RectD),
+    /// Rendering of the image panicked, with the panic message
+    RenderFailed(u32, String),
+    /// Rendering of the image took longer than the configured timeout and was abandoned
+    RenderTimedOut(u32),
 }
 
 #[derive(Debug, Clone)]
@@ -0,0 +1,289 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//! Opt-in recording of render commands and headless replay of a recording
+//!
+//! Start MView6 with `MVIEW6_RENDER_TRACE=<file>` to append every render command as a line
+//! of JSON to the file. `mview6 --replay-render <file>` executes the recorded commands again
+//! without opening a window and reports the outcome and duration of each. SVG content is
+//! embedded in the recording, documents are referenced by path and need to be available.
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, BufRead, BufReader, Write},
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, OnceLock},
+    time::Instant,
+};
+
+use resvg::usvg::{self, Tree};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    backends::document::PageMode,
+    content::DocContent,
+    file_view::model::{BackendRef, Reference},
+    image::{provider::surface::SurfaceData, view::Zoom},
+    rect::{RectD, SizeD, VectorD},
+    render_thread::{
+        model::{RenderCommand, RenderReply},
+        worker::{CachedBackend, RenderWorker},
+    },
+};
+
+const TRACE_VARIABLE: &str = "MVIEW6_RENDER_TRACE";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TracedZoom {
+    pub scale: f64,
+    pub rotation: i32,
+    pub offset: (f64, f64),
+    pub image_size: (f64, f64),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TracedSource {
+    Doc {
+        backend: String,
+        path: PathBuf,
+        item: String,
+        page_mode: String,
+        size: (f64, f64),
+    },
+    Svg {
+        svg: String,
+    },
+}
+
+/// One recorded render command
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TracedCommand {
+    pub id: u32,
+    pub zoom: TracedZoom,
+    /// Viewport as (x0, y0, x1, y1)
+    pub viewport: (f64, f64, f64, f64),
+    pub source: TracedSource,
+}
+
+impl TracedCommand {
+    pub fn new(id: u32, command: &RenderCommand) -> Self {
+        let (zoom, viewport, source) = match command {
+            RenderCommand::RenderDoc(_, zoom, viewport, doc) => (
+                zoom,
+                viewport,
+                TracedSource::Doc {
+                    backend: doc.reference.backend.name().to_string(),
+                    path: doc.reference.backend.path().into(),
+                    item: doc.reference.item.to_string(),
+                    page_mode: <&str>::from(doc.page_mode).to_string(),
+                    size: (doc.size.width(), doc.size.height()),
+                },
+            ),
+            RenderCommand::RenderSvg(_, zoom, viewport, tree) => (
+                zoom,
+                viewport,
+                TracedSource::Svg {
+                    svg: tree.to_string(&usvg::WriteOptions::default()),
+                },
+            ),
+        };
+        let image_size = zoom.image_size();
+        TracedCommand {
+            id,
+            zoom: TracedZoom {
+                scale: zoom.scale(),
+                rotation: zoom.rotation_degrees(),
+                offset: (zoom.offset_x(), zoom.offset_y()),
+                image_size: (image_size.width(), image_size.height()),
+            },
+            viewport: (viewport.x0, viewport.y0, viewport.x1, viewport.y1),
+            source,
+        }
+    }
+
+    fn zoom(&self) -> Zoom {
+        let mut zoom = Zoom::new();
+        zoom.set_image_size(SizeD::new(self.zoom.image_size.0, self.zoom.image_size.1));
+        zoom.set_rotation(self.zoom.rotation);
+        zoom.set_zoom_factor(self.zoom.scale);
+        zoom.set_origin(VectorD::new(self.zoom.offset.0, self.zoom.offset.1));
+        zoom
+    }
+
+    /// Rebuild the render command, fails when the recorded svg cannot be parsed
+    pub fn command(&self) -> Result<RenderCommand, String> {
+        let (x0, y0, x1, y1) = self.viewport;
+        let viewport = RectD::new(x0, y0, x1, y1);
+        match &self.source {
+            TracedSource::Doc {
+                backend,
+                path,
+                item,
+                page_mode,
+                size,
+            } => {
+                let doc = DocContent {
+                    page_mode: PageMode::from(page_mode.as_str()),
+                    size: SizeD::new(size.0, size.1),
+                    reference: Reference {
+                        backend: BackendRef::new(backend, path.clone()),
+                        item: item.parse()?,
+                    },
+                    scan_filters: Default::default(),
+                };
+                Ok(RenderCommand::RenderDoc(
+                    self.id,
+                    self.zoom(),
+                    viewport,
+                    doc,
+                ))
+            }
+            TracedSource::Svg { svg } => {
+                let tree =
+                    Tree::from_str(svg, &usvg::Options::default()).map_err(|e| e.to_string())?;
+                Ok(RenderCommand::RenderSvg(
+                    self.id,
+                    self.zoom(),
+                    viewport,
+                    Arc::new(tree),
+                ))
+            }
+        }
+    }
+}
+
+fn trace_file() -> Option<&'static Mutex<File>> {
+    static TRACE: OnceLock<Option<Mutex<File>>> = OnceLock::new();
+    TRACE
+        .get_or_init(|| {
+            let path = std::env::var(TRACE_VARIABLE).ok()?;
+            match OpenOptions::new().create(true).append(true).open(&path) {
+                Ok(file) => {
+                    println!("Recording render commands to {path}");
+                    Some(Mutex::new(file))
+                }
+                Err(e) => {
+                    eprintln!("Cannot record render commands to {path}: {e}");
+                    None
+                }
+            }
+        })
+        .as_ref()
+}
+
+/// Append the command to the recording, if enabled
+pub fn record(id: u32, command: &RenderCommand) {
+    let Some(file) = trace_file() else {
+        return;
+    };
+    let Ok(line) = serde_json::to_string(&TracedCommand::new(id, command)) else {
+        return;
+    };
+    if let Ok(mut file) = file.lock() {
+        // written before rendering, so a command that hangs or crashes is recorded too
+        let _ = writeln!(file, "{line}").and_then(|_| file.flush());
+    }
+}
+
+/// Execute the commands of a recording and report their outcome and duration
+pub fn replay(path: &Path) -> io::Result<()> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut backend = CachedBackend::new();
+    let mut total = 0.0;
+    let mut count = 0;
+    for (number, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let traced: TracedCommand = match serde_json::from_str(&line) {
+            Ok(traced) => traced,
+            Err(e) => {
+                eprintln!("line {}: invalid record: {e}", number + 1);
+                continue;
+            }
+        };
+        let command = match traced.command() {
+            Ok(command) => command,
+            Err(e) => {
+                eprintln!("id {:5}: cannot rebuild command: {e}", traced.id);
+                continue;
+            }
+        };
+        let description = command.describe();
+        let start = Instant::now();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            RenderWorker::execute(command, &mut backend)
+        }));
+        let elapsed = start.elapsed().as_secs_f64() * 1000.0;
+        let outcome = match result {
+            Ok(Some(RenderReply::RenderDone(_, surface, _, _))) => outcome_size(surface),
+            Ok(_) => "no result".to_string(),
+            Err(_) => {
+                backend = CachedBackend::new();
+                "panicked".to_string()
+            }
+        };
+        println!(
+            "id {:5}: {elapsed:9.1} ms  {outcome:<16} {description}",
+            traced.id
+        );
+        total += elapsed;
+        count += 1;
+    }
+    println!("{count} commands replayed in {total:.1} ms");
+    Ok(())
+}
+
+fn outcome_size(mut surface: SurfaceData) -> String {
+    let (_, width, height, _) = surface.pixels_mut();
+    format!("{width}x{height}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_view::model::ItemRef;
+
+    #[test]
+    fn doc_command_round_trip() {
+        let mut zoom = Zoom::new();
+        zoom.set_image_size(SizeD::new(600.0, 800.0));
+        zoom.set_rotation(90);
+        zoom.set_zoom_factor(1.5);
+        zoom.set_offset(12.0, -30.0);
+        let doc = DocContent {
+            page_mode: PageMode::DualOddEven,
+            size: SizeD::new(600.0, 800.0),
+            reference: Reference {
+                backend: BackendRef::Pdfium(PathBuf::from("/tmp/report.pdf")),
+                item: ItemRef::Index(3),
+            },
+            scan_filters: Default::default(),
+        };
+        let command = RenderCommand::RenderDoc(7, zoom, RectD::new(0.0, 0.0, 1280.0, 720.0), doc);
+
+        let traced = TracedCommand::new(42, &command);
+        let json = serde_json::to_string(&traced).unwrap();
+        let parsed: TracedCommand = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, traced);
+        assert_eq!(TracedCommand::new(42, &parsed.command().unwrap()), traced);
+    }
+}
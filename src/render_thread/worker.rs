@@ -18,28 +18,133 @@
 // OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use std::{
+    any::Any,
+    panic::{self, AssertUnwindSafe},
     sync::{
-        atomic::{AtomicU32, Ordering},
-        Arc,
+        atomic::{AtomicBool, AtomicU32, Ordering},
+        Arc, Mutex, MutexGuard, PoisonError, TryLockError,
     },
     thread::{self},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use async_channel::{Receiver, Sender};
 
 use crate::{
-    backends::Backend,
+    backends::{document::split_spreads, Backend},
     file_view::model::BackendRef,
     image::svg::render::render_svg,
-    render_thread::model::{RenderCommand, RenderCommandMessage, RenderReply, RenderReplyMessage},
+    render_thread::{
+        model::{RenderCommand, RenderCommandMessage, RenderReply, RenderReplyMessage},
+        trace,
+    },
 };
 
+/// The document engines are not thread-safe. Rendering holds this lock, so a worker that
+/// replaces a stuck one waits until the stuck render has left the engine.
+static RENDER_LOCK: Mutex<()> = Mutex::new(());
+
+/// Exclusive use of the renderers. A render that panicked does not block the next one.
+pub(super) fn render_lock() -> MutexGuard<'static, ()> {
+    RENDER_LOCK.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Exclusive use of the renderers, giving up when they stay busy for longer than `wait`
+pub(super) fn try_render_lock(wait: Duration) -> Option<MutexGuard<'static, ()>> {
+    let deadline = Instant::now() + wait;
+    loop {
+        match RENDER_LOCK.try_lock() {
+            Ok(guard) => return Some(guard),
+            Err(TryLockError::Poisoned(poisoned)) => return Some(poisoned.into_inner()),
+            Err(TryLockError::WouldBlock) if Instant::now() < deadline => {
+                thread::sleep(Duration::from_millis(10))
+            }
+            Err(TryLockError::WouldBlock) => return None,
+        }
+    }
+}
+
+/// Command that is being rendered
+#[derive(Debug, Clone)]
+pub struct Busy {
+    pub command_id: u32,
+    pub image_id: u32,
+    pub description: String,
+    pub started: Instant,
+}
+
+/// Backend of the last rendered document, kept between commands. It is rebuilt when another
+/// document is rendered or when the split spreads setting changes the page layout.
+pub(super) struct CachedBackend {
+    backend: Box<dyn Backend>,
+    reference: BackendRef,
+    split: bool,
+}
+
+impl CachedBackend {
+    pub fn new() -> Self {
+        CachedBackend {
+            backend: <dyn Backend>::none(),
+            reference: BackendRef::None,
+            split: split_spreads(),
+        }
+    }
+
+    fn get(&mut self, reference: &BackendRef) -> &dyn Backend {
+        let split = split_spreads();
+        if *reference != self.reference || split != self.split {
+            println!("Changing backend to {:?}", reference);
+            self.backend = <dyn Backend>::new_reference(reference);
+            self.reference = reference.clone();
+            self.split = split;
+        }
+        self.backend.as_ref()
+    }
+}
+
+/// Progress of a worker, watched by the render thread to detect stuck renders
+#[derive(Debug, Default)]
+pub struct WorkerState {
+    busy: Mutex<Option<Busy>>,
+    abandoned: AtomicBool,
+}
+
+impl WorkerState {
+    fn begin(&self, busy: Busy) {
+        if let Ok(mut current) = self.busy.lock() {
+            *current = Some(busy);
+        }
+    }
+
+    fn end(&self) -> Option<Busy> {
+        self.busy.lock().ok().and_then(|mut current| current.take())
+    }
+
+    /// The command being rendered for longer than `timeout`
+    pub fn stuck(&self, timeout: Duration) -> Option<Busy> {
+        let current = self.busy.lock().ok()?;
+        current
+            .as_ref()
+            .filter(|busy| busy.started.elapsed() > timeout)
+            .cloned()
+    }
+
+    /// A replacement worker takes over, the result of the current render is discarded
+    pub fn abandon(&self) {
+        self.abandoned.store(true, Ordering::SeqCst);
+    }
+
+    fn is_abandoned(&self) -> bool {
+        self.abandoned.load(Ordering::SeqCst)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct RenderWorker {
     to_rt_receiver: Receiver<RenderCommandMessage>,
     from_rt_sender: Sender<RenderReplyMessage>,
     command_id: Arc<AtomicU32>, // actually contains the id will be given out next
+    state: Arc<WorkerState>,
 }
 
 impl RenderWorker {
@@ -52,78 +157,118 @@ impl RenderWorker {
             to_rt_receiver,
             from_rt_sender,
             command_id: counter,
+            state: Default::default(),
         }
     }
 
+    pub fn state(&self) -> Arc<WorkerState> {
+        self.state.clone()
+    }
+
+    /// Process commands until the window closes the channel. A command that panics (bad
+    /// document, renderer edge case) is answered with a failure instead of taking the
+    /// worker down.
     pub fn run(&self) {
-        let mut backend = <dyn Backend>::none();
-        let mut backend_ref = BackendRef::None;
-        loop {
-            if let Ok(command) = self.to_rt_receiver.recv_blocking() {
-                if self.get_current_command_id() != command.id {
+        let mut backend = CachedBackend::new();
+        while let Ok(command) = self.to_rt_receiver.recv_blocking() {
+            // Not marked busy while waiting, so the wait for a stuck render does not count
+            // as a timeout of this worker
+            let _guard = render_lock();
+            if self.get_current_command_id() != command.id {
+                println!(
+                    "There are newer commands in the queue, skipping id {}",
+                    command.id
+                );
+                continue;
+            }
+
+            let image_id = command.cmd.image_id();
+            self.state.begin(Busy {
+                command_id: command.id,
+                image_id,
+                description: command.cmd.describe(),
+                started: Instant::now(),
+            });
+            trace::record(command.id, &command.cmd);
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                Self::execute(command.cmd, &mut backend)
+            }));
+            let busy = self.state.end();
+            if self.state.is_abandoned() {
+                if let Some(busy) = busy {
                     println!(
-                        "There are newer commands in the queue, skipping id {}",
-                        command.id
+                        "Abandoned render id {} finished after {:.1}s",
+                        busy.command_id,
+                        busy.started.elapsed().as_secs_f64()
                     );
+                }
+                return;
+            }
+            let reply = match result {
+                Ok(Some(reply)) => reply,
+                Ok(None) => {
+                    println!("HqRender: none");
                     continue;
                 }
-
-                match command.cmd {
-                    RenderCommand::RenderDoc(image_id, zoom, viewport, doc) => {
-                        if doc.reference.backend != backend_ref {
-                            println!("Changing backend to {:?}", doc.reference.backend);
-                            backend = <dyn Backend>::new_reference(&doc.reference.backend);
-                            backend_ref = doc.reference.backend;
-                        }
-                        let result =
-                            backend.render(&doc.reference.item, &doc.page_mode, &zoom, &viewport);
-                        if let Some(surface) = result {
-                            if command.id != self.get_current_command_id() {
-                                println!(
-                                    "Result from hq render not needed anymore. Discarding id {}",
-                                    command.id
-                                );
-                                continue;
-                            }
-                            let reply = RenderReplyMessage {
-                                _id: command.id,
-                                reply: RenderReply::RenderDone(image_id, surface, zoom, viewport),
-                            };
-                            if let Err(e) = self.from_rt_sender.send_blocking(reply) {
-                                eprintln!("Failed to send reply {e}");
-                            }
-                        } else {
-                            println!("HqRender: none");
-                        }
-                    }
-                    RenderCommand::RenderSvg(image_id, zoom, viewport, tree) => {
-                        let result = render_svg(&zoom, &viewport, &tree);
-                        if let Some(surface) = result {
-                            if command.id != self.get_current_command_id() {
-                                println!(
-                                    "Result from svg render not needed anymore. Discarding id {}",
-                                    command.id
-                                );
-                                continue;
-                            }
-                            let reply = RenderReplyMessage {
-                                _id: command.id,
-                                reply: RenderReply::RenderDone(image_id, surface, zoom, viewport),
-                            };
-                            if let Err(e) = self.from_rt_sender.send_blocking(reply) {
-                                eprintln!("Failed to send reply {e}");
-                            }
-                        } else {
-                            println!("HqRender: none");
-                        }
-                    }
+                Err(payload) => {
+                    let message = panic_message(payload.as_ref());
+                    eprintln!("Render command {} panicked: {message}", command.id);
+                    // The backend may have been left in an inconsistent state
+                    backend = CachedBackend::new();
+                    RenderReply::RenderFailed(image_id, message)
                 }
+            };
+
+            if command.id != self.get_current_command_id() {
+                println!(
+                    "Result from hq render not needed anymore. Discarding id {}",
+                    command.id
+                );
+                continue;
+            }
+            let reply = RenderReplyMessage {
+                _id: command.id,
+                reply,
+            };
+            if let Err(e) = self.from_rt_sender.send_blocking(reply) {
+                eprintln!("Failed to send reply {e}");
             }
             thread::sleep(Duration::from_millis(10));
         }
     }
 
+    pub(super) fn execute(cmd: RenderCommand, backend: &mut CachedBackend) -> Option<RenderReply> {
+        match cmd {
+            RenderCommand::RenderDoc(image_id, zoom, viewport, doc) => {
+                let mut surface = backend.get(&doc.reference.backend).render(
+                    &doc.reference.item,
+                    &doc.page_mode,
+                    &zoom,
+                    &viewport,
+                )?;
+                if doc.scan_filters.is_active() {
+                    doc.scan_filters.apply_to_surface(&mut surface);
+                }
+                Some(RenderReply::RenderDone(image_id, surface, zoom, viewport))
+            }
+            RenderCommand::RenderSvg(image_id, zoom, viewport, tree) => {
+                let surface = render_svg(&zoom, &viewport, &tree)?;
+                Some(RenderReply::RenderDone(image_id, surface, zoom, viewport))
+            }
+        }
+    }
+
     fn get_current_command_id(&self) -> u32 {
         self.command_id.load(Ordering::SeqCst) - 1
     }
 }
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
@@ -0,0 +1,248 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Copying and moving files into a folder, with the name conflicts resolved the same
+//! way for every file of a batch. An XMP sidecar goes along with its file.
+
+use std::{
+    collections::HashSet,
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    import::unique_target,
+    metadata::keywords::{sidecar_path, with_xmp},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransferMode {
+    Copy,
+    Move,
+}
+
+/// What to do with the files whose name is already taken in the destination
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Resolution {
+    Overwrite,
+    Skip,
+    RenameAll,
+}
+
+#[derive(Debug, Clone)]
+pub struct TransferItem {
+    pub source: PathBuf,
+    pub target: PathBuf,
+    /// Existing sidecar of the source
+    pub sidecar: Option<PathBuf>,
+    /// The target, or the target of the sidecar, already exists
+    pub conflict: bool,
+}
+
+impl TransferItem {
+    /// Where the sidecar goes, named after the target in the same style as the source
+    pub fn sidecar_target(&self) -> Option<PathBuf> {
+        let sidecar = self.sidecar.as_ref()?;
+        Some(if *sidecar == with_xmp(&self.source) {
+            with_xmp(&self.target)
+        } else {
+            self.target.with_extension("xmp")
+        })
+    }
+
+    fn target_exists(&self) -> bool {
+        self.target.exists()
+            || self
+                .sidecar_target()
+                .is_some_and(|sidecar| sidecar.exists())
+    }
+
+    pub fn name(&self) -> String {
+        self.target
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+/// Name for `name` in `dir` that is neither present nor in `taken`
+pub fn suggest_name(dir: &Path, name: &str, taken: &HashSet<PathBuf>) -> PathBuf {
+    let path = Path::new(name);
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = path
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+    unique_target(dir, &stem, &extension, taken)
+}
+
+/// Determine the target of each source in `destination`. Sources that are already in
+/// `destination` are left out.
+pub fn plan_transfer(sources: &[PathBuf], destination: &Path) -> Vec<TransferItem> {
+    let mut plan: Vec<TransferItem> = Vec::new();
+    for source in sources {
+        let Some(name) = source.file_name() else {
+            continue;
+        };
+        let target = destination.join(name);
+        if target == *source {
+            continue;
+        }
+        let sidecar = Some(sidecar_path(source)).filter(|sidecar| sidecar.exists());
+        let mut item = TransferItem {
+            source: source.clone(),
+            target,
+            sidecar,
+            conflict: false,
+        };
+        // Two sources with the same name conflict with each other as well
+        item.conflict =
+            item.target_exists() || plan.iter().any(|other| other.target == item.target);
+        plan.push(item);
+    }
+    plan
+}
+
+pub fn has_conflicts(plan: &[TransferItem]) -> bool {
+    plan.iter().any(|item| item.conflict)
+}
+
+/// Apply the resolution to the conflicting items of the plan
+pub fn resolve(plan: Vec<TransferItem>, resolution: Resolution) -> Vec<TransferItem> {
+    match resolution {
+        Resolution::Overwrite => plan,
+        Resolution::Skip => plan.into_iter().filter(|item| !item.conflict).collect(),
+        Resolution::RenameAll => {
+            let mut taken: HashSet<PathBuf> = plan
+                .iter()
+                .filter(|item| !item.conflict)
+                .map(|item| item.target.clone())
+                .collect();
+            plan.into_iter()
+                .map(|mut item| {
+                    if item.conflict {
+                        let dir = item.target.parent().unwrap_or(Path::new("")).to_owned();
+                        let name = item.name();
+                        loop {
+                            item.target = suggest_name(&dir, &name, &taken);
+                            taken.insert(item.target.clone());
+                            if !item.target_exists() {
+                                break;
+                            }
+                        }
+                        item.conflict = false;
+                    }
+                    item
+                })
+                .collect()
+        }
+    }
+}
+
+/// Copy or move a file and its sidecar, keeping their modification times
+pub fn transfer_file(item: &TransferItem, mode: TransferMode) -> io::Result<()> {
+    if let Some(parent) = item.target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    transfer_one(&item.source, &item.target, mode)?;
+    if let (Some(sidecar), Some(target)) = (&item.sidecar, item.sidecar_target()) {
+        transfer_one(sidecar, &target, mode)?;
+    }
+    Ok(())
+}
+
+fn transfer_one(source: &Path, target: &Path, mode: TransferMode) -> io::Result<()> {
+    if mode == TransferMode::Move && fs::rename(source, target).is_ok() {
+        return Ok(());
+    }
+    // Copy, or move to another file system
+    fs::copy(source, target)?;
+    let modified = fs::metadata(source)?.modified()?;
+    File::options()
+        .write(true)
+        .open(target)?
+        .set_modified(modified)?;
+    if mode == TransferMode::Move {
+        fs::remove_file(source)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+
+    #[test]
+    fn conflicts() {
+        let directory = TestDir::new("transfer");
+        let destination = directory.join("destination");
+        fs::create_dir_all(&destination).unwrap();
+        fs::write(directory.join("a.jpg"), "new a").unwrap();
+        fs::write(directory.join("a.jpg.xmp"), "new a sidecar").unwrap();
+        fs::write(directory.join("b.jpg"), "b").unwrap();
+        fs::write(directory.join("b.xmp"), "b sidecar").unwrap();
+        fs::write(destination.join("a.jpg"), "old a").unwrap();
+        fs::write(destination.join("a_1.jpg"), "old a_1").unwrap();
+        // An orphaned sidecar takes the name as well
+        fs::write(destination.join("a_2.jpg.xmp"), "orphan").unwrap();
+
+        let sources = vec![directory.join("a.jpg"), directory.join("b.jpg")];
+        let plan = plan_transfer(&sources, &destination);
+        assert!(has_conflicts(&plan));
+        assert_eq!(resolve(plan.clone(), Resolution::Skip).len(), 1);
+
+        let renamed = resolve(plan, Resolution::RenameAll);
+        assert_eq!(renamed[0].target, destination.join("a_3.jpg"));
+        assert_eq!(
+            renamed[0].sidecar_target(),
+            Some(destination.join("a_3.jpg.xmp"))
+        );
+        assert_eq!(renamed[1].target, destination.join("b.jpg"));
+        assert_eq!(renamed[1].sidecar_target(), Some(destination.join("b.xmp")));
+
+        for item in &renamed {
+            transfer_file(item, TransferMode::Move).unwrap();
+        }
+        assert!(!directory.join("a.jpg").exists());
+        assert_eq!(
+            fs::read_to_string(destination.join("a.jpg")).unwrap(),
+            "old a"
+        );
+        assert_eq!(
+            fs::read_to_string(destination.join("a_3.jpg")).unwrap(),
+            "new a"
+        );
+        assert!(!directory.join("a.jpg.xmp").exists());
+        assert_eq!(
+            fs::read_to_string(destination.join("a_3.jpg.xmp")).unwrap(),
+            "new a sidecar"
+        );
+        assert_eq!(
+            fs::read_to_string(destination.join("b.xmp")).unwrap(),
+            "b sidecar"
+        );
+        assert_eq!(
+            fs::read_to_string(destination.join("a_2.jpg.xmp")).unwrap(),
+            "orphan"
+        );
+    }
+}
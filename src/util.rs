@@ -49,6 +49,53 @@ pub fn path_to_extension<P: AsRef<Path>>(path: P) -> String {
         .to_lowercase()
 }
 
+/// Decode %XX escapes, invalid UTF-8 in the result is replaced
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| bytes.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// File name for a downloaded URL: the last path segment without query or fragment,
+/// percent-decoded and restricted to characters that are safe on every platform
+pub fn url_to_filename(url: &str) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    let path = path.split_once("://").map_or(path, |(_, rest)| rest);
+    let segment = path
+        .split_once('/')
+        .map_or("", |(_, path)| path.rsplit('/').next().unwrap_or_default());
+    percent_decode(segment)
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>()
+        .trim_start_matches('.')
+        .to_string()
+}
+
 pub fn ellipsis_middle(s: &str, max_len: usize) -> String {
     if s.len() <= max_len {
         return s.to_string();
@@ -87,4 +134,20 @@ mod tests {
         assert_eq!(ellipsis_middle("Hello, World!", 11), "Hell...rld!");
         assert_eq!(ellipsis_middle("", 5), "");
     }
+
+    #[test]
+    fn test_url_to_filename() {
+        assert_eq!(
+            url_to_filename("https://example.com/images/cat%20photo.jpg?w=800#top"),
+            "cat_photo.jpg"
+        );
+        assert_eq!(
+            url_to_filename("https://example.com/caf%C3%A9%2F..%2Fx.png"),
+            "café_.._x.png"
+        );
+        assert_eq!(url_to_filename("https://example.com/100%.png"), "100_.png");
+        assert_eq!(url_to_filename("https://example.com/"), "");
+        assert_eq!(url_to_filename("https://example.com"), "");
+        assert_eq!(url_to_filename("http://example.com/../.hidden"), "hidden");
+    }
 }
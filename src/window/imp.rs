@@ -18,58 +18,107 @@
 // OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 mod actions;
+mod attachments;
+mod autoscroll;
 mod backend;
+mod caches;
+mod camera_import;
+mod collection;
+mod color;
 mod commands;
+mod culling;
 mod dependencies;
+mod directory;
+mod documents;
+mod edit;
+mod entry_dialog;
 mod filter;
+mod frames;
+mod geotag;
+mod goto;
+mod hotfolder;
+mod import;
 mod keyboard;
+mod keywords;
+mod labels;
 mod menu;
 mod mouse;
+mod narrow;
 mod navigate;
+mod page_export;
+mod page_mode;
 mod palette;
 mod panel;
+mod preferences;
+mod preview;
+mod reopen;
 mod resize;
+mod scan;
+mod shortcuts;
 mod slideshow;
 mod sort;
+mod staging;
+mod timeshift;
+mod toolbar;
+mod transfer;
+mod usage;
+mod watcher;
 
 use crate::{
     backends::{
+        collection::CollectionEntry,
         document::PageMode,
         thumbnail::{
-            processing::{handle_thumbnail_result, start_thumbnail_task},
+            processing::{
+                handle_hover_result, handle_thumbnail_result, start_hover_task,
+                start_thumbnail_task,
+            },
             Message, TCommand,
         },
+        usage::UsageStats,
         Backend,
     },
+    config::{config, DirectoryConfig},
     file_view::{
-        model::{BackendRef, ItemRef, Reference},
+        model::{BackendRef, ItemRef, Progress, Reference},
         FileView, Filter, Sort, Target,
     },
-    image::view::{ImageView, SIGNAL_CANVAS_RESIZED, SIGNAL_NAVIGATE, SIGNAL_SHOWN},
+    image::{
+        provider::surface::rgba8_from_surface,
+        scan::ScanFilters,
+        view::{data::PageTint, ImageView, SIGNAL_CANVAS_RESIZED, SIGNAL_NAVIGATE, SIGNAL_SHOWN},
+    },
     info_view::InfoView,
-    rect::PointD,
+    metadata::label::ColorLabel,
+    preview_server::PreviewServer,
+    rect::{PointD, SizeI},
     render_thread::{
         model::{RenderCommand, RenderCommandMessage, RenderReply, RenderReplyMessage},
         RenderThread, RenderThreadSender,
     },
-    window::imp::{dependencies::check_dependencies, panel::Panel},
+    window::imp::{
+        dependencies::check_dependencies, directory::ReplacedSettings, documents::DocumentSettings,
+        edit::EditSession, hotfolder::HotFolder, keywords::keyword_completion, panel::Panel,
+        reopen::ClosedContainer, timeshift::TimeShift, watcher::DirectoryWatcher,
+    },
 };
-use arboard::Clipboard;
+use arboard::{Clipboard, ImageData};
 use async_channel::Sender;
 use gio::{SimpleAction, SimpleActionGroup};
 use glib::{clone, closure_local, idle_add_local, property::PropertySet, ControlFlow, SourceId};
 use gtk4::{
-    glib::Propagation, prelude::*, subclass::prelude::*, Button, EventControllerKey, HeaderBar,
-    MenuButton, ScrolledWindow,
+    glib::Propagation, prelude::*, subclass::prelude::*, Button, ButtonsType, DialogFlags, Entry,
+    EventControllerKey, HeaderBar, ListStore, MenuButton, MessageDialog, MessageType,
+    ScrolledWindow,
 };
 use serde::{Deserialize, Serialize};
 use std::{
     cell::{Cell, OnceCell, RefCell},
-    collections::{HashMap, VecDeque},
+    collections::{BTreeSet, HashMap, VecDeque},
     env, fs,
     path::PathBuf,
     rc::Rc,
-    time::{SystemTime, UNIX_EPOCH},
+    time::{Instant, SystemTime, UNIX_EPOCH},
 };
 
 #[derive(Debug)]
@@ -77,14 +126,18 @@ pub struct MViewWidgets {
     hbox: gtk4::Box,
     file_widget: ScrolledWindow,
     file_view: FileView,
-    info_widget: ScrolledWindow,
+    info_widget: gtk4::Box,
     info_view: InfoView,
+    keyword_entry: Entry,
+    keyword_model: ListStore,
     image_view: ImageView,
     pub tn_sender: Sender<Message>,
     _render_thread: RenderThread,
     pub rt_sender: RenderThreadSender,
     actions: SimpleActionGroup,
     forward_button_top: Button,
+    back_button_top: Button,
+    files_button_top: Button,
     panel: Panel,
 }
 
@@ -114,6 +167,15 @@ impl MViewWidgets {
             .unwrap_or_default()
     }
 
+    pub fn get_action_string(&self, action_name: &str) -> String {
+        self.actions
+            .lookup_action(action_name)
+            .and_then(|a| a.downcast::<SimpleAction>().ok())
+            .and_then(|a| a.state())
+            .and_then(|v| v.get::<String>())
+            .unwrap_or_default()
+    }
+
     pub fn get_action_i32(&self, action_name: &str) -> i32 {
         self.actions
             .lookup_action(action_name)
@@ -134,12 +196,15 @@ pub struct TargetTime {
     pub target: Target,
     // pub sort: Sort,
     pub timestamp: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub progress: Option<Progress>,
 }
 
 impl TargetTime {
-    pub fn new(target: &Target) -> Self {
+    pub fn new(target: &Target, progress: Option<Progress>) -> Self {
         TargetTime {
             target: target.clone(),
+            progress,
             // sort: Sort::Unsorted,
             timestamp: SystemTime::now()
                 .duration_since(UNIX_EPOCH)
@@ -159,13 +224,40 @@ pub struct MViewWindowImp {
     thumbnail_size: Cell<i32>,
     current_sort: Cell<Sort>,
     page_mode: Cell<PageMode>,
+    default_page_mode: Cell<PageMode>,
+    default_page_tint: Cell<PageTint>,
+    document_settings: RefCell<HashMap<PathBuf, DocumentSettings>>,
+    scan_filters: RefCell<HashMap<PathBuf, ScanFilters>>,
     sorting_store: RefCell<HashMap<PathBuf, Sort>>,
     target_store: RefCell<HashMap<PathBuf, TargetTime>>,
     canvas_resized_timeout_id: RefCell<Option<SourceId>>,
     next_slide_timeout_id: RefCell<Option<SourceId>>,
+    auto_scroll_timeout_id: RefCell<Option<SourceId>>,
+    reading_speed: Cell<u32>,
+    plain_text: Cell<bool>,
     clipboard: RefCell<Option<Clipboard>>,
     current_filter: RefCell<Filter>,
     recent_commands: Rc<RefCell<VecDeque<usize>>>,
+    monitor_luts: RefCell<HashMap<String, PathBuf>>,
+    printer_profile: RefCell<Option<PathBuf>>,
+    gamut_warning: Cell<bool>,
+    last_time_shift: RefCell<Option<TimeShift>>,
+    known_keywords: RefCell<BTreeSet<String>>,
+    keyword_filter: RefCell<Option<String>>,
+    hot_folder: RefCell<Option<HotFolder>>,
+    directory_config: RefCell<DirectoryConfig>,
+    replaced_settings: RefCell<ReplacedSettings>,
+    directory_watcher: RefCell<Option<DirectoryWatcher>>,
+    narrow: Cell<bool>,
+    rate_advance: Cell<bool>,
+    edit_session: RefCell<Option<EditSession>>,
+    label_filter: Cell<Option<ColorLabel>>,
+    last_import: RefCell<Option<PathBuf>>,
+    usage: RefCell<UsageStats>,
+    viewing: RefCell<Option<(CollectionEntry, Instant)>>,
+    usage_save_pending: Cell<bool>,
+    preview_server: RefCell<Option<PreviewServer>>,
+    closed_containers: RefCell<VecDeque<ClosedContainer>>,
 }
 
 #[glib::object_subclass]
@@ -240,6 +332,9 @@ impl ObjectImpl for MViewWindowImp {
         self.parent_constructed();
 
         _ = self.load_navigation();
+        _ = self.load_document_settings();
+        self.usage.replace(UsageStats::load());
+        self.load_closed();
 
         let args: Vec<String> = env::args().collect();
         let filename = if args.len() > 1 {
@@ -249,6 +344,7 @@ impl ObjectImpl for MViewWindowImp {
         };
 
         self.thumbnail_size.set(250);
+        self.rate_advance.set(true);
         self.current_sort.set(Sort::sort_on_category());
         self.current_filter.set(Filter::full_set());
 
@@ -302,6 +398,23 @@ impl ObjectImpl for MViewWindowImp {
         ));
         header_bar.pack_start(&forward_button);
 
+        // Toggles the file list drawer, only shown when the window is narrow
+        let files_button = Button::builder()
+            .icon_name("sidebar-show-symbolic")
+            .can_focus(false)
+            .visible(false)
+            .build();
+        files_button.connect_clicked(clone!(
+            #[weak(rename_to = this)]
+            self,
+            move |_button| {
+                this.toggle_pane_files();
+            }
+        ));
+        header_bar.pack_start(&files_button);
+
+        Self::add_toolbar(&header_bar);
+
         // Set the header bar as the title bar of the window
         window.set_titlebar(Some(&header_bar));
 
@@ -331,16 +444,31 @@ impl ObjectImpl for MViewWindowImp {
         let panel = Panel::create(self, &image_view, &menu);
         hbox.append(&panel.overlay);
 
-        let info_widget = ScrolledWindow::new();
-        info_widget.set_policy(gtk4::PolicyType::Never, gtk4::PolicyType::Automatic);
-        info_widget.set_can_focus(false);
+        let info_widget = gtk4::Box::new(gtk4::Orientation::Vertical, 4);
         hbox.append(&info_widget);
 
+        let info_scrolled = ScrolledWindow::new();
+        info_scrolled.set_policy(gtk4::PolicyType::Never, gtk4::PolicyType::Automatic);
+        info_scrolled.set_can_focus(false);
+        info_scrolled.set_vexpand(true);
+        info_widget.append(&info_scrolled);
+
         let info_view = InfoView::new();
         info_view.set_vexpand(true);
         // info_view.set_fixed_height_mode(true);
         info_view.set_can_focus(false);
-        info_widget.set_child(Some(&info_view));
+        info_scrolled.set_child(Some(&info_view));
+
+        let keyword_model = ListStore::new(&[glib::Type::STRING]);
+        let keyword_entry = Entry::new();
+        keyword_entry.set_placeholder_text(Some("Keywords, comma separated"));
+        keyword_entry.set_completion(Some(&keyword_completion(&keyword_model)));
+        keyword_entry.connect_activate(clone!(
+            #[weak(rename_to = this)]
+            self,
+            move |_| this.save_keywords()
+        ));
+        info_widget.append(&keyword_entry);
 
         let key_controller = EventControllerKey::new();
         key_controller.connect_key_pressed(clone!(
@@ -354,6 +482,7 @@ impl ObjectImpl for MViewWindowImp {
             }
         ));
         self.obj().add_controller(key_controller);
+        self.add_drop_target();
 
         let gesture_click = gtk4::GestureClick::new();
         gesture_click.set_button(1);
@@ -401,6 +530,7 @@ impl ObjectImpl for MViewWindowImp {
                 self,
                 move |_view: ImageView| {
                     this.event_shown();
+                    this.schedule_preview_update();
                 }
             ),
         );
@@ -444,12 +574,16 @@ impl ObjectImpl for MViewWindowImp {
                 file_widget,
                 info_widget,
                 info_view,
+                keyword_entry,
+                keyword_model,
                 image_view,
                 tn_sender,
                 _render_thread: render_thread,
                 rt_sender,
                 actions,
                 forward_button_top: forward_button,
+                back_button_top: back_button,
+                files_button_top: files_button,
                 panel,
             })
             .expect("Failed to initialize MView window");
@@ -475,19 +609,19 @@ impl ObjectImpl for MViewWindowImp {
                                 start_thumbnail_task(
                                     &sender,
                                     &image_view,
-                                    &command,
+                                    &mut command,
                                     &mut current_task,
                                 );
                                 start_thumbnail_task(
                                     &sender,
                                     &image_view,
-                                    &command,
+                                    &mut command,
                                     &mut current_task,
                                 );
                                 start_thumbnail_task(
                                     &sender,
                                     &image_view,
-                                    &command,
+                                    &mut command,
                                     &mut current_task,
                                 );
                             } else {
@@ -495,31 +629,73 @@ impl ObjectImpl for MViewWindowImp {
                                 image_view.set_content_post(Default::default());
                             }
                         }
+                        Message::Refresh(path) => {
+                            let idle = !command.needs_work();
+                            let count = command.refresh(&path);
+                            if idle {
+                                for _ in 0..count.min(3) {
+                                    start_thumbnail_task(
+                                        &sender,
+                                        &image_view,
+                                        &mut command,
+                                        &mut current_task,
+                                    );
+                                }
+                            }
+                        }
                         Message::Result(res) => {
                             if handle_thumbnail_result(&image_view, &mut command, res) {
                                 start_thumbnail_task(
                                     &sender,
                                     &image_view,
-                                    &command,
+                                    &mut command,
                                     &mut current_task,
                                 );
                             }
                         }
+                        Message::Hover(image_id, task_id, size) => {
+                            if command.id == image_id {
+                                start_hover_task(&sender, &image_view, &command, task_id, size);
+                            }
+                        }
+                        Message::HoverResult(res) => {
+                            handle_hover_result(&image_view, &command, res);
+                        }
                     }
                 }
             }
         ));
 
         glib::spawn_future_local(clone!(
+            #[weak(rename_to = this)]
+            self,
             #[strong(rename_to = image_view)]
             w.image_view,
             #[strong(rename_to = _sender)]
             w.tn_sender,
             async move {
+                let mut reported_failure = None;
                 while let Ok(msg) = from_rt_receiver.recv().await {
                     match msg.reply {
                         RenderReply::RenderDone(image_id, surface_data, zoom, viewport) => {
                             image_view.event_render_done(image_id, surface_data, zoom, viewport);
+                            this.schedule_preview_update();
+                        }
+                        RenderReply::RenderTimedOut(image_id) => {
+                            image_view.event_render_timed_out(image_id);
+                        }
+                        RenderReply::RenderFailed(image_id, message) => {
+                            // Warn once per image, every zoom or scroll would fail again
+                            if image_view.event_render_failed(image_id)
+                                && reported_failure != Some(image_id)
+                            {
+                                reported_failure = Some(image_id);
+                                this.show_message(
+                                    MessageType::Warning,
+                                    "High quality rendering failed",
+                                    &format!("Showing a preview instead.\n\n{message}"),
+                                );
+                            }
                         }
                     }
                 }
@@ -536,6 +712,9 @@ impl ObjectImpl for MViewWindowImp {
             ControlFlow::Break,
             move || {
                 check_dependencies(&this.obj(), false);
+                if config().first_run {
+                    this.show_keyboard_shortcuts();
+                }
                 if let Some(filename) = &filename {
                     println!("Opening {filename}");
                     // match path::absolute(filename) {
@@ -558,6 +737,10 @@ impl ObjectImpl for MViewWindowImp {
             move |_| {
                 println!("Closing");
                 let _ = this.save_navigation();
+                let _ = this.save_document_settings();
+                this.save_usage();
+                this.remember_closed();
+                this.save_closed();
                 Propagation::Proceed
             }
         ));
@@ -566,11 +749,68 @@ impl ObjectImpl for MViewWindowImp {
     }
 }
 
-impl WidgetImpl for MViewWindowImp {}
+impl WidgetImpl for MViewWindowImp {
+    fn size_allocate(&self, width: i32, height: i32, baseline: i32) {
+        self.parent_size_allocate(width, height, baseline);
+        self.on_window_width(width);
+    }
+
+    fn realize(&self) {
+        self.parent_realize();
+        if let Some(surface) = self.obj().surface() {
+            surface.connect_enter_monitor(clone!(
+                #[weak(rename_to = this)]
+                self,
+                move |_, monitor| this.on_monitor_changed(monitor)
+            ));
+        }
+    }
+}
 impl WindowImpl for MViewWindowImp {}
 impl ApplicationWindowImpl for MViewWindowImp {}
 
 impl MViewWindowImp {
+    pub fn show_message(&self, message_type: MessageType, title: &str, text: &str) {
+        let dialog = MessageDialog::new(
+            Some(&self.obj().clone()),
+            DialogFlags::MODAL,
+            message_type,
+            ButtonsType::Ok,
+            title,
+        );
+        dialog.set_secondary_text(Some(text));
+        dialog.connect_response(|dialog, _| {
+            dialog.close();
+        });
+        dialog.show();
+    }
+
+    /// Put the current view on the clipboard as an image
+    pub fn copy_view_to_clipboard(&self) {
+        let image_view = &self.widgets().image_view;
+        let size = SizeI::new(image_view.width(), image_view.height());
+        let rgba8 = image_view
+            .snapshot_to_surface(size)
+            .and_then(|mut surface| rgba8_from_surface(&mut surface));
+        let rgba8 = match rgba8 {
+            Ok(rgba8) => rgba8,
+            Err(e) => {
+                eprintln!("Failed to take snapshot of the view: {e}");
+                return;
+            }
+        };
+        if let Some(clipboard) = self.clipboard.borrow_mut().as_mut() {
+            let image = ImageData {
+                width: size.width() as usize,
+                height: size.height() as usize,
+                bytes: rgba8.into(),
+            };
+            if let Err(e) = clipboard.set_image(image) {
+                eprintln!("Failed to copy to clipboard: {e:?}");
+            }
+        }
+    }
+
     pub fn copy_to_clipboard(&self, content: &str) {
         if let Some(clipboard) = self.clipboard.borrow_mut().as_mut() {
             if let Err(e) = clipboard.set_text(content) {
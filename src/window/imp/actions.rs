@@ -17,26 +17,34 @@
 // STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
 // OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use std::{
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
 use chrono::Datelike;
+use encoding_rs::Encoding;
 use gio::prelude::FileExt;
 use glib::{clone, subclass::types::ObjectSubclassExt};
 use gtk4::{
     prelude::{DialogExt, FileChooserExt, GtkWindowExt, WidgetExt},
-    AboutDialog, FileChooserAction, FileChooserDialog, FileFilter, License, ResponseType,
+    AboutDialog, FileChooserAction, FileChooserDialog, FileFilter, License, MessageType,
+    ResponseType,
 };
 
 use crate::{
     backends::{
-        document::{pdf_engine, set_pdf_engine, PdfEngine},
+        document::{pdf_engine, set_pdf_engine, set_split_spreads, split_spreads, PdfEngine},
         thumbnail::{model::TParent, Thumbnail},
-        Backend,
+        Backend, ImageParams,
     },
-    content::loader::ContentLoader,
+    config::terminal_command,
+    content::{loader::ContentLoader, Content, ContentData},
     file_view::{Direction, Filter, Target},
-    image::view::ZoomMode,
+    image::{diff::diff_surface, view::ZoomMode},
 };
 
-use super::MViewWindowImp;
+use super::{shortcuts::show_shortcuts, MViewWindowImp};
 
 impl MViewWindowImp {
     pub fn open_file(&self) {
@@ -135,6 +143,10 @@ impl MViewWindowImp {
         self.show_help_page(page_no);
     }
 
+    pub fn show_keyboard_shortcuts(&self) {
+        show_shortcuts(&self.obj());
+    }
+
     pub fn show_help_page(&self, page_no: i32) {
         let image = if page_no == 2 {
             ContentLoader::content_from_svg_data(
@@ -192,12 +204,28 @@ impl MViewWindowImp {
         w.image_view.set_transparency_mode(transparency.into());
     }
 
+    pub fn change_text_encoding(&self, encoding: &str) {
+        let w = self.widgets();
+        w.set_action_string("encoding", encoding);
+        if let Some(encoding) = Encoding::for_label(encoding.as_bytes()) {
+            w.image_view.set_text_encoding(encoding);
+        }
+    }
+
+    pub fn toggle_table_view(&self) {
+        let plain_text = !self.plain_text.get();
+        self.plain_text.set(plain_text);
+        self.widgets().image_view.set_table_view(!plain_text);
+    }
+
     pub fn change_page_mode(&self, page_mode: &str) {
         dbg!(page_mode);
-        self.widgets().set_action_string("page", page_mode);
-        self.page_mode.set(page_mode.into());
+        self.default_page_mode.set(page_mode.into());
         if self.backend.borrow().is_doc() {
-            self.on_cursor_changed();
+            self.set_document_page_mode(page_mode.into());
+        } else {
+            self.widgets().set_action_string("page", page_mode);
+            self.page_mode.set(page_mode.into());
         }
     }
 
@@ -219,6 +247,16 @@ impl MViewWindowImp {
         }
     }
 
+    pub fn toggle_split_spreads(&self) {
+        set_split_spreads(!split_spreads());
+        let current_backend = self.backend.borrow();
+        if current_backend.is_doc() {
+            let path = current_backend.path();
+            drop(current_backend);
+            self.navigate_to(&path);
+        }
+    }
+
     pub fn toggle_fullscreen(&self) {
         let w = self.widgets();
         let is_fullscreen = if self.fullscreen.get() {
@@ -302,7 +340,11 @@ impl MViewWindowImp {
 
     pub fn navigate_page(&self, direction: Direction, count: u32) {
         let w = self.widgets();
-        if !w.image_view.navigate_page(direction, count) {
+        if w.image_view.navigate_page(direction, count) {
+            if w.image_view.frame_index().is_some() {
+                w.image_view.update_info(&w.info_view);
+            }
+        } else {
             w.file_view.navigate_item(direction, &Filter::None, count);
         }
     }
@@ -316,4 +358,107 @@ impl MViewWindowImp {
         let w = self.widgets();
         w.image_view.measure_toggle_tracking();
     }
+
+    pub fn toggle_reading_ruler(&self) {
+        let w = self.widgets();
+        w.image_view
+            .reading_ruler_enable(!w.image_view.reading_ruler_active());
+    }
+
+    /// Show the zoom percentage and the print size at the resolution of the image
+    pub fn toggle_zoom_info(&self) {
+        let w = self.widgets();
+        w.image_view
+            .zoom_info_enable(!w.image_view.zoom_info_active());
+    }
+
+    /// Compare the current image with the next one. The second image is shown with the
+    /// changed regions highlighted, after compensating for a (small) shift between both.
+    /// Invoking again returns to the normal view.
+    pub fn toggle_diff(&self) {
+        let w = self.widgets();
+        if w.image_view.has_tag("diff") {
+            self.on_cursor_changed();
+            return;
+        }
+        let params = ImageParams {
+            tn_sender: Some(&w.tn_sender),
+            page_mode: &self.page_mode.get(),
+            allocation_height: self.obj().height(),
+        };
+        if let Some(current) = w.file_view.current() {
+            let b = self.backend.borrow();
+            let first = b.content(&b.reference(&current).item, &params);
+            if !current.next() {
+                return;
+            }
+            let second = b.content(&b.reference(&current).item, &params);
+            if let (ContentData::Single(first), ContentData::Single(second)) =
+                (first.data, second.data)
+            {
+                match diff_surface(&first.surface(), &second.surface()) {
+                    Ok(surface) => {
+                        let mut content = Content::new_surface(surface, None);
+                        content.tag = Some("diff".to_string());
+                        w.info_view.update(&content);
+                        w.image_view.set_content(content);
+                    }
+                    Err(e) => eprintln!("Failed to create diff: {e:?}"),
+                }
+            }
+        }
+    }
+
+    /// Directory of the current item: the folder itself, the folder containing the
+    /// archive or document, or the selected bookmark
+    fn current_directory(&self) -> Option<PathBuf> {
+        let backend = self.backend.borrow();
+        if backend.is_bookmarks() {
+            let cursor = self.widgets().file_view.current()?;
+            return Some(PathBuf::from(cursor.folder()));
+        }
+        let path = backend.normalized_path();
+        if path.is_dir() {
+            Some(path)
+        } else {
+            path.parent().filter(|p| p.is_dir()).map(Path::to_path_buf)
+        }
+    }
+
+    pub fn open_terminal(&self) {
+        let Some(directory) = self.current_directory() else {
+            eprintln!("No directory to open a terminal in");
+            return;
+        };
+        let command_line = terminal_command();
+        let args = match glib::shell_parse_argv(&command_line) {
+            Ok(args) => args,
+            Err(e) => {
+                self.show_message(
+                    MessageType::Error,
+                    "Open terminal",
+                    &format!("Invalid terminal command {command_line}: {e}"),
+                );
+                return;
+            }
+        };
+        let directory_str = directory.to_string_lossy();
+        let mut args = args
+            .iter()
+            .map(|arg| arg.to_string_lossy().replace("{dir}", &directory_str));
+        let Some(program) = args.next() else {
+            return;
+        };
+        println!("Launch terminal {program} in {directory_str}");
+        let child = Command::new(&program)
+            .args(args)
+            .current_dir(&directory)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        if let Err(error) = child {
+            eprintln!("Failed to launch {program} {:?}", error);
+        };
+    }
 }
@@ -0,0 +1,74 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::fs;
+
+use glib::{clone, subclass::types::ObjectSubclassExt};
+use gtk4::{prelude::*, FileChooserAction, FileChooserDialog, MessageType, ResponseType};
+
+use crate::window::imp::MViewWindowImp;
+
+impl MViewWindowImp {
+    /// Save the selected PDF attachment to disk
+    pub fn export_attachment(&self) {
+        let Some(current) = self.widgets().file_view.current() else {
+            return;
+        };
+        let Some((name, data)) = self.backend.borrow().attachment(&current) else {
+            self.show_message(
+                MessageType::Info,
+                "Export attachment",
+                "The selected item is not a document attachment",
+            );
+            return;
+        };
+
+        let dialog = FileChooserDialog::new(
+            Some("Export attachment"),
+            Some(&self.obj().clone()),
+            FileChooserAction::Save,
+            &[
+                ("Cancel", ResponseType::Cancel),
+                ("Save", ResponseType::Accept),
+            ],
+        );
+        dialog.set_current_name(&name);
+
+        dialog.connect_response(clone!(
+            #[weak(rename_to = this)]
+            self,
+            move |dialog, response| {
+                if response == ResponseType::Accept {
+                    if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                        if let Err(e) = fs::write(&path, &data) {
+                            this.show_message(
+                                MessageType::Error,
+                                "Export attachment",
+                                &format!("Cannot write {}: {e}", path.display()),
+                            );
+                        }
+                    }
+                }
+                dialog.destroy();
+            }
+        ));
+
+        dialog.show();
+    }
+}
@@ -0,0 +1,108 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::time::Duration;
+
+use glib::{clone, ControlFlow};
+use gtk4::MessageType;
+
+use crate::{file_view::Direction, util::remove_source_id};
+
+use super::MViewWindowImp;
+
+/// Reading speed (words per minute) used to time the pages
+const DEFAULT_WPM: u32 = 250;
+const MIN_WPM: u32 = 50;
+const MAX_WPM: u32 = 1000;
+pub const WPM_STEP: i32 = 25;
+
+/// Minimum time a page is shown, also for (nearly) empty pages
+const MIN_PAGE_TIME: f64 = 2.0;
+
+impl MViewWindowImp {
+    pub fn is_auto_scroll_active(&self) -> bool {
+        self.auto_scroll_timeout_id.borrow().is_some()
+    }
+
+    /// Start or stop automatically turning the pages of text content
+    pub fn toggle_auto_scroll(&self) {
+        if self.is_auto_scroll_active() {
+            self.stop_auto_scroll();
+        } else if !self.schedule_auto_scroll() {
+            self.show_message(
+                MessageType::Info,
+                "Auto-scroll",
+                "Auto-scroll is only available for text files",
+            );
+        }
+    }
+
+    pub fn stop_auto_scroll(&self) {
+        if let Some(id) = self.auto_scroll_timeout_id.replace(None) {
+            if let Err(e) = remove_source_id(&id) {
+                println!("remove_source_id: {e}");
+            }
+        }
+    }
+
+    fn reading_speed(&self) -> u32 {
+        match self.reading_speed.get() {
+            0 => DEFAULT_WPM,
+            wpm => wpm,
+        }
+    }
+
+    /// Change the reading speed by `delta` words per minute
+    pub fn change_reading_speed(&self, delta: i32) {
+        let wpm = self.reading_speed().saturating_add_signed(delta);
+        self.reading_speed.set(wpm.clamp(MIN_WPM, MAX_WPM));
+        println!("Reading speed: {} words per minute", self.reading_speed());
+        if self.is_auto_scroll_active() {
+            self.stop_auto_scroll();
+            self.schedule_auto_scroll();
+        }
+    }
+
+    /// Schedule the next page based on the number of words on the current page, returns
+    /// `false` if the content is not text
+    fn schedule_auto_scroll(&self) -> bool {
+        let Some(words) = self.widgets().image_view.page_word_count() else {
+            return false;
+        };
+        let seconds = words as f64 * 60.0 / self.reading_speed() as f64;
+        self.auto_scroll_timeout_id
+            .replace(Some(glib::timeout_add_local(
+                Duration::from_secs_f64(seconds.max(MIN_PAGE_TIME)),
+                clone!(
+                    #[weak(rename_to = this)]
+                    self,
+                    #[upgrade_or]
+                    ControlFlow::Break,
+                    move || {
+                        this.auto_scroll_timeout_id.replace(None);
+                        if this.widgets().image_view.navigate_page(Direction::Down, 1) {
+                            this.schedule_auto_scroll();
+                        }
+                        ControlFlow::Break
+                    }
+                ),
+            )));
+        true
+    }
+}
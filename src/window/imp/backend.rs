@@ -22,40 +22,86 @@ use gtk4::prelude::{GtkWindowExt, TreeSortableExt, TreeSortableExtManual, TreeVi
 
 use crate::{
     backends::{thumbnail::Thumbnail, Backend},
-    file_view::{model::Reference, Column, Sort, Target},
+    file_view::{
+        model::{BackendRef, Reference},
+        Column, Sort, Target,
+    },
+    metadata::label::{file_label, forget_labels},
     util::path_to_filename,
 };
 
-use super::MViewWindowImp;
+use super::{directory::directory_sort, MViewWindowImp};
 
 impl MViewWindowImp {
     pub fn set_backend(&self, new_backend: Box<dyn Backend>, goto: &Target) {
         let skip_loading = self.skip_loading.get();
         self.skip_loading.set(true);
 
+        if new_backend.backend_ref() != self.backend.borrow().backend_ref() {
+            self.remember_closed();
+        }
+
         let w = self.widgets();
         self.backend.replace(new_backend);
         let new_backend = self.backend.borrow();
 
+        self.update_directory_watcher(&new_backend.backend_ref());
+
+        if let Some(config) = new_backend.directory_config() {
+            self.apply_directory_config(config);
+        }
+
         let mut sorting_store = self.sorting_store.borrow_mut();
         let can_be_sorted = new_backend.can_be_sorted();
 
         let new_sort = if can_be_sorted {
             let path = new_backend.normalized_path();
             if let Some(sort) = sorting_store.get(&path) {
-                sort
+                *sort
             } else {
-                sorting_store.insert(path, self.current_sort.get());
-                &self.current_sort.get()
+                let sort = new_backend
+                    .directory_config()
+                    .and_then(directory_sort)
+                    .unwrap_or(self.current_sort.get());
+                sorting_store.insert(path, sort);
+                sort
             }
         } else {
-            &Sort::sort_on_category()
+            Sort::sort_on_category()
         };
 
         // let new_store = new_backend.store();
-        let new_store = Column::store(new_backend.list());
+        let directory = match new_backend.backend_ref() {
+            BackendRef::FileSystem(directory) => Some(directory),
+            _ => None,
+        };
+        let is_folder = directory.is_some();
+        if is_folder {
+            forget_labels();
+        }
+        w.file_view.set_directory(directory.clone());
+        let keyword_filter = self.keyword_filter.borrow().clone();
+        let label_filter = self.label_filter.get();
+        let new_store = match directory {
+            Some(directory) if keyword_filter.is_some() || label_filter.is_some() => {
+                let tagged = keyword_filter
+                    .map(|keyword| self.tagged_files(&directory, new_backend.list(), &keyword));
+                Column::store_filtered(new_backend.list(), |row| {
+                    tagged
+                        .as_ref()
+                        .is_none_or(|tagged| tagged.contains(&row.name))
+                        && label_filter.is_none_or(|label| {
+                            file_label(&directory.join(&row.name)) == Some(label)
+                        })
+                })
+            }
+            _ => Column::store(new_backend.list()),
+        };
+        if is_folder {
+            self.show_reading_progress(&new_store, &new_backend.normalized_path());
+        }
         match new_sort {
-            Sort::Sorted((column, order)) => new_store.set_sort_column_id(*column, *order),
+            Sort::Sorted((column, order)) => new_store.set_sort_column_id(column, order),
             Sort::Unsorted => (),
         };
 
@@ -84,6 +130,7 @@ impl MViewWindowImp {
 
         drop(new_backend);
 
+        self.apply_document_settings();
         self.update_layout();
         w.file_view.set_model(Some(&new_store));
         w.file_view.set_sortable(can_be_sorted);
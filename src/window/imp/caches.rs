@@ -0,0 +1,88 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use glib::subclass::types::ObjectSubclassExt;
+use gtk4::{prelude::*, Button, Dialog, Grid, Label, ResponseType};
+use human_bytes::human_bytes;
+
+use crate::{
+    cache::{clear, stats, CacheKind},
+    window::imp::MViewWindowImp,
+};
+
+fn stats_text(kind: CacheKind) -> String {
+    let stats = stats(kind);
+    let size = human_bytes(stats.size as f64);
+    match kind.max_size() {
+        Some(max_size) => format!(
+            "{} files, {size} of {}",
+            stats.files,
+            human_bytes(max_size as f64)
+        ),
+        None => format!("{} files, {size}", stats.files),
+    }
+}
+
+impl MViewWindowImp {
+    /// Show the location and usage of each cache, with the option to clear it
+    pub fn cache_dialog(&self) {
+        let dialog = Dialog::builder()
+            .title("Caches")
+            .modal(true)
+            .transient_for(&self.obj().clone())
+            .build();
+
+        let grid = Grid::builder()
+            .row_spacing(8)
+            .column_spacing(16)
+            .margin_start(12)
+            .margin_end(12)
+            .margin_top(12)
+            .margin_bottom(12)
+            .build();
+
+        for (row, kind) in CacheKind::ALL.into_iter().enumerate() {
+            let row = row as i32;
+            let name = Label::new(Some(kind.name()));
+            name.set_xalign(0.0);
+            let location = Label::new(Some(&kind.directory().to_string_lossy()));
+            location.set_xalign(0.0);
+            location.set_selectable(true);
+            location.add_css_class("dim-label");
+            let usage = Label::new(Some(&stats_text(kind)));
+            usage.set_xalign(1.0);
+            let clear_btn = Button::with_label("Clear");
+            clear_btn.connect_clicked(move |_| {
+                if let Err(e) = clear(kind) {
+                    eprintln!("Failed to clear the {} cache: {e}", kind.name());
+                }
+                usage.set_text(&stats_text(kind));
+            });
+            grid.attach(&name, 0, row, 1, 1);
+            grid.attach(&location, 1, row, 1, 1);
+            grid.attach(&usage, 2, row, 1, 1);
+            grid.attach(&clear_btn, 3, row, 1, 1);
+        }
+
+        dialog.content_area().append(&grid);
+        dialog.add_button("Close", ResponseType::Close);
+        dialog.connect_response(|dialog, _| dialog.close());
+        dialog.present();
+    }
+}
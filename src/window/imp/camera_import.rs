@@ -0,0 +1,285 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{path::PathBuf, thread};
+
+use glib::{clone, subclass::types::ObjectSubclassExt};
+use gtk4::{
+    prelude::*, Box, Button, Dialog, Entry, FileChooserAction, FileChooserDialog, Grid, Label,
+    Orientation, ProgressBar, ResponseType,
+};
+
+use crate::{
+    import::{import_file, plan_import, removable_media, DEFAULT_FOLDER_PATTERN},
+    window::imp::MViewWindowImp,
+};
+
+struct ImportJob {
+    source: PathBuf,
+    destination: PathBuf,
+    folder_pattern: String,
+    rename_pattern: String,
+}
+
+enum ImportProgress {
+    Planned(usize),
+    Copied(usize, String),
+    Finished {
+        copied: usize,
+        duplicates: usize,
+        failed: usize,
+    },
+    InvalidPattern,
+}
+
+/// Button showing a folder, which opens a folder chooser when clicked
+fn folder_button(dialog: &Dialog, title: &'static str, folder: Option<PathBuf>) -> Button {
+    let button = Button::with_label(
+        &folder
+            .map(|f| f.to_string_lossy().to_string())
+            .unwrap_or_default(),
+    );
+    button.set_hexpand(true);
+    button.connect_clicked(clone!(
+        #[weak]
+        dialog,
+        move |button| {
+            let chooser = FileChooserDialog::new(
+                Some(title),
+                Some(&dialog),
+                FileChooserAction::SelectFolder,
+                &[
+                    ("Cancel", ResponseType::Cancel),
+                    ("Select", ResponseType::Accept),
+                ],
+            );
+            let current = button.label().unwrap_or_default();
+            if !current.is_empty() {
+                let _ = chooser.set_current_folder(Some(&gio::File::for_path(current.as_str())));
+            }
+            chooser.connect_response(clone!(
+                #[weak]
+                button,
+                move |chooser, response| {
+                    if response == ResponseType::Accept {
+                        if let Some(path) = chooser.file().and_then(|file| file.path()) {
+                            button.set_label(&path.to_string_lossy());
+                        }
+                    }
+                    chooser.destroy();
+                }
+            ));
+            chooser.show();
+        }
+    ));
+    button
+}
+
+fn folder_of(button: &Button) -> Option<PathBuf> {
+    button
+        .label()
+        .filter(|label| !label.is_empty())
+        .map(|label| PathBuf::from(label.as_str()))
+}
+
+impl MViewWindowImp {
+    pub fn camera_import_dialog(&self) {
+        let dialog = Dialog::builder()
+            .title("Import from camera or memory card")
+            .modal(true)
+            .transient_for(&self.obj().clone())
+            .default_width(560)
+            .build();
+
+        let vbox = Box::builder()
+            .orientation(Orientation::Vertical)
+            .spacing(12)
+            .margin_start(12)
+            .margin_end(12)
+            .margin_top(12)
+            .margin_bottom(12)
+            .build();
+
+        let source_btn =
+            folder_button(&dialog, "Import from", removable_media().into_iter().next());
+        let destination_btn = folder_button(&dialog, "Import into", dirs::picture_dir());
+        let folder_pattern = Entry::new();
+        folder_pattern.set_text(DEFAULT_FOLDER_PATTERN);
+        let rename_pattern = Entry::new();
+        rename_pattern.set_placeholder_text(Some("e.g. %Y%m%d_%H%M%S_{name}"));
+
+        let grid = Grid::builder().row_spacing(8).column_spacing(12).build();
+        for (row, (text, widget)) in [
+            ("Source", source_btn.upcast_ref::<gtk4::Widget>()),
+            ("Destination", destination_btn.upcast_ref()),
+            ("Folders", folder_pattern.upcast_ref()),
+            ("Rename to", rename_pattern.upcast_ref()),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let label = Label::new(Some(text));
+            label.set_xalign(0.0);
+            grid.attach(&label, 0, row as i32, 1, 1);
+            grid.attach(widget, 1, row as i32, 1, 1);
+        }
+
+        let hint = Label::new(Some(
+            "Patterns use %Y, %m, %d, %H, %M, %S and {name}. Leave 'Rename to' empty to keep the file names.",
+        ));
+        hint.set_xalign(0.0);
+        hint.set_wrap(true);
+        hint.add_css_class("dim-label");
+
+        let progress = ProgressBar::new();
+        progress.set_show_text(true);
+        let status = Label::new(None);
+        status.set_xalign(0.0);
+
+        vbox.append(&grid);
+        vbox.append(&hint);
+        vbox.append(&progress);
+        vbox.append(&status);
+        dialog.content_area().append(&vbox);
+
+        dialog.add_button("Close", ResponseType::Close);
+        let import_btn = dialog.add_button("Import", ResponseType::Ok);
+        dialog.set_default_response(ResponseType::Ok);
+
+        dialog.connect_response(clone!(
+            #[weak]
+            source_btn,
+            #[weak]
+            destination_btn,
+            #[weak]
+            folder_pattern,
+            #[weak]
+            rename_pattern,
+            #[weak]
+            progress,
+            #[weak]
+            status,
+            #[weak]
+            import_btn,
+            move |dialog, response| {
+                if response != ResponseType::Ok {
+                    dialog.close();
+                    return;
+                }
+                let (Some(source), Some(destination)) =
+                    (folder_of(&source_btn), folder_of(&destination_btn))
+                else {
+                    status.set_text("Select a source and destination folder");
+                    return;
+                };
+                import_btn.set_sensitive(false);
+                status.set_text("Scanning files...");
+                progress.set_fraction(0.0);
+                let job = ImportJob {
+                    source,
+                    destination,
+                    folder_pattern: folder_pattern.text().to_string(),
+                    rename_pattern: rename_pattern.text().trim().to_string(),
+                };
+                run_import(job, &progress, &status, &import_btn);
+            }
+        ));
+
+        dialog.present();
+    }
+}
+
+/// Copy the files in a separate thread, reporting progress in the dialog
+fn run_import(job: ImportJob, progress: &ProgressBar, status: &Label, import_btn: &gtk4::Widget) {
+    let (sender, receiver) = async_channel::unbounded::<ImportProgress>();
+
+    thread::spawn(move || {
+        let Some(plan) = plan_import(
+            &job.source,
+            &job.destination,
+            &job.folder_pattern,
+            &job.rename_pattern,
+        ) else {
+            let _ = sender.send_blocking(ImportProgress::InvalidPattern);
+            return;
+        };
+        let _ = sender.send_blocking(ImportProgress::Planned(plan.len()));
+        let (mut copied, mut duplicates, mut failed) = (0, 0, 0);
+        for (i, item) in plan.iter().enumerate() {
+            if item.duplicate {
+                duplicates += 1;
+            } else if let Err(e) = import_file(item) {
+                eprintln!("Failed to import {:?}: {e}", item.source);
+                failed += 1;
+            } else {
+                copied += 1;
+            }
+            let name = item.target.file_name().unwrap_or_default();
+            let _ = sender.send_blocking(ImportProgress::Copied(
+                i + 1,
+                name.to_string_lossy().to_string(),
+            ));
+        }
+        let _ = sender.send_blocking(ImportProgress::Finished {
+            copied,
+            duplicates,
+            failed,
+        });
+    });
+
+    glib::spawn_future_local(clone!(
+        #[weak]
+        progress,
+        #[weak]
+        status,
+        #[weak]
+        import_btn,
+        async move {
+            let mut total = 0;
+            while let Ok(message) = receiver.recv().await {
+                match message {
+                    ImportProgress::Planned(count) => {
+                        total = count;
+                        status.set_text(&format!("Importing {count} files"));
+                    }
+                    ImportProgress::Copied(done, name) => {
+                        progress.set_fraction(done as f64 / total.max(1) as f64);
+                        progress.set_text(Some(&format!("{done} / {total}  {name}")));
+                    }
+                    ImportProgress::Finished {
+                        copied,
+                        duplicates,
+                        failed,
+                    } => {
+                        let summary = format!(
+                            "Copied {copied} files, skipped {duplicates} duplicates, {failed} failed"
+                        );
+                        println!("Import: {summary}");
+                        status.set_text(&summary);
+                        import_btn.set_sensitive(true);
+                    }
+                    ImportProgress::InvalidPattern => {
+                        status.set_text("Invalid folder or rename pattern");
+                        import_btn.set_sensitive(true);
+                    }
+                }
+            }
+        }
+    ));
+}
@@ -0,0 +1,164 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::fs;
+
+use glib::{clone, subclass::types::ObjectSubclassExt};
+use gtk4::{prelude::*, FileChooserAction, FileChooserDialog, MessageType, ResponseType};
+
+use crate::{
+    backends::{
+        collection::{load_collection, move_entry, playlist, save_collection, CollectionEntry},
+        Backend,
+    },
+    file_view::{Direction, Target},
+    window::imp::MViewWindowImp,
+};
+
+impl MViewWindowImp {
+    /// Add the current file, archive entry or page to the collection
+    pub fn add_to_collection(&self) {
+        let backend = self.backend.borrow();
+        if backend.is_collection() || backend.is_thumbnail() || backend.is_bookmarks() {
+            return;
+        }
+        let Some(current) = self.widgets().file_view.current() else {
+            return;
+        };
+        let entry = CollectionEntry::new(&backend.reference(&current), &current.name());
+        drop(backend);
+        let mut entries = load_collection();
+        if entries.contains(&entry) {
+            return;
+        }
+        println!("Add {} to the collection", entry.name);
+        entries.push(entry);
+        if let Err(e) = save_collection(&entries) {
+            eprintln!("Failed to save the collection: {e}");
+        }
+    }
+
+    /// Show the collection, or return from it
+    pub fn toggle_collection(&self) {
+        if self.backend.borrow().is_collection() {
+            self.dir_leave();
+            return;
+        }
+        self.show_files_widget(true);
+        let backend = self.backend.replace(<dyn Backend>::none());
+        let target = match self.widgets().file_view.current() {
+            Some(cursor) => backend.reference(&cursor).into(),
+            None => Target::First,
+        };
+        self.set_backend(<dyn Backend>::collection(backend, target), &Target::First);
+    }
+
+    /// Replace the collection backend by one showing the changed collection, keeping the
+    /// place it returns to
+    fn refresh_collection(&self, target: Target) {
+        let backend = self.backend.replace(<dyn Backend>::none());
+        let Some((parent, parent_target)) = backend.leave() else {
+            return;
+        };
+        self.set_backend(<dyn Backend>::collection(parent, parent_target), &target);
+    }
+
+    /// Move the selected entry of the collection up or down
+    pub fn move_in_collection(&self, direction: Direction) {
+        if !self.backend.borrow().is_collection() {
+            return;
+        }
+        let Some(current) = self.widgets().file_view.current() else {
+            return;
+        };
+        let mut entries = load_collection();
+        let Some(position) = move_entry(&mut entries, current.index() as usize, direction) else {
+            return;
+        };
+        match save_collection(&entries) {
+            Ok(()) => self.refresh_collection(Target::Index(position as u64)),
+            Err(e) => eprintln!("Failed to save the collection: {e}"),
+        }
+    }
+
+    /// Remove the selected entry from the collection
+    pub fn remove_from_collection(&self) {
+        if !self.backend.borrow().is_collection() {
+            return;
+        }
+        let Some(current) = self.widgets().file_view.current() else {
+            return;
+        };
+        let mut entries = load_collection();
+        let index = current.index() as usize;
+        if index >= entries.len() {
+            return;
+        }
+        entries.remove(index);
+        match save_collection(&entries) {
+            Ok(()) => self.refresh_collection(Target::Index(index as u64)),
+            Err(e) => eprintln!("Failed to save the collection: {e}"),
+        }
+    }
+
+    /// Save the collection as an M3U playlist
+    pub fn export_collection(&self) {
+        let entries = load_collection();
+        if entries.is_empty() {
+            self.show_message(
+                MessageType::Info,
+                "Export collection",
+                "The collection is empty, add files with the c key",
+            );
+            return;
+        }
+
+        let dialog = FileChooserDialog::new(
+            Some("Export collection"),
+            Some(&self.obj().clone()),
+            FileChooserAction::Save,
+            &[
+                ("Cancel", ResponseType::Cancel),
+                ("Save", ResponseType::Accept),
+            ],
+        );
+        dialog.set_current_name("collection.m3u");
+
+        dialog.connect_response(clone!(
+            #[weak(rename_to = this)]
+            self,
+            move |dialog, response| {
+                if response == ResponseType::Accept {
+                    if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                        if let Err(e) = fs::write(&path, playlist(&entries)) {
+                            this.show_message(
+                                MessageType::Error,
+                                "Export collection",
+                                &format!("Cannot write {}: {e}", path.display()),
+                            );
+                        }
+                    }
+                }
+                dialog.destroy();
+            }
+        ));
+
+        dialog.show();
+    }
+}
@@ -0,0 +1,253 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::path::PathBuf;
+
+use gio::prelude::FileExt;
+use glib::{clone, subclass::types::ObjectSubclassExt};
+use gtk4::{
+    gdk::{
+        prelude::{DisplayExt, MonitorExt},
+        Monitor,
+    },
+    prelude::{DialogExt, FileChooserExt, GtkWindowExt, NativeExt, WidgetExt},
+    FileChooserAction, FileChooserDialog, FileFilter, ResponseType,
+};
+
+use crate::{
+    config::{config, save_monitor_lut, save_printer_profile},
+    image::{lut::Lut3D, proof::SoftProof},
+};
+
+use super::MViewWindowImp;
+
+pub fn monitor_name(monitor: &Monitor) -> String {
+    monitor
+        .connector()
+        .or_else(|| monitor.model())
+        .map(|s| s.to_string())
+        .unwrap_or_default()
+}
+
+impl MViewWindowImp {
+    fn current_monitor(&self) -> Option<String> {
+        let surface = self.obj().surface()?;
+        let monitor = self.obj().display().monitor_at_surface(&surface)?;
+        Some(monitor_name(&monitor))
+    }
+
+    /// The LUT file for a monitor: one loaded during this session takes precedence over
+    /// the one from the configuration file
+    fn lut_file(&self, monitor: &str) -> Option<PathBuf> {
+        if let Some(file) = self.monitor_luts.borrow().get(monitor) {
+            return Some(file.clone());
+        }
+        config()
+            .config_file
+            .monitor_luts
+            .iter()
+            .find(|m| m.monitor == monitor)
+            .map(|m| PathBuf::from(&m.file))
+    }
+
+    /// Select the color LUT that belongs to the monitor the window is on
+    pub fn update_color_lut(&self, monitor: &str) {
+        let w = self.widgets();
+        let lut = self
+            .lut_file(monitor)
+            .and_then(|file| match Lut3D::load(&file) {
+                Ok(lut) => Some(lut),
+                Err(e) => {
+                    eprintln!("Failed to load color LUT {file:?}: {e}");
+                    None
+                }
+            });
+        if let Some(lut) = &lut {
+            println!("Using color LUT '{}' for monitor {monitor}", lut.title());
+        }
+        let has_lut = lut.is_some();
+        w.image_view.set_lut(lut);
+        if !has_lut && w.image_view.lut_enabled() {
+            w.image_view.lut_enable(false);
+            w.set_action_bool("color.lut", false);
+        }
+    }
+
+    pub fn on_monitor_changed(&self, monitor: &Monitor) {
+        self.update_color_lut(&monitor_name(monitor));
+    }
+
+    pub fn toggle_color_lut(&self) {
+        let w = self.widgets();
+        let enable = !w.image_view.lut_enabled();
+        if enable {
+            let Some(monitor) = self.current_monitor() else {
+                return;
+            };
+            if self.lut_file(&monitor).is_none() {
+                self.load_color_lut();
+                return;
+            }
+            self.update_color_lut(&monitor);
+        }
+        let enable = enable && w.image_view.has_lut();
+        w.image_view.lut_enable(enable);
+        w.set_action_bool("color.lut", enable);
+    }
+
+    /// Choose a .cube file to use for the monitor the window is currently on
+    pub fn load_color_lut(&self) {
+        let Some(monitor) = self.current_monitor() else {
+            return;
+        };
+
+        let dialog = FileChooserDialog::new(
+            Some(&format!("Choose color LUT for monitor {monitor}")),
+            Some(&self.obj().clone()),
+            FileChooserAction::Open,
+            &[
+                ("Cancel", ResponseType::Cancel),
+                ("Open", ResponseType::Accept),
+            ],
+        );
+
+        let cube_files = FileFilter::new();
+        cube_files.set_name(Some("3D LUT (.cube)"));
+        cube_files.add_pattern("*.cube");
+        dialog.add_filter(&cube_files);
+
+        dialog.connect_response(clone!(
+            #[weak(rename_to = this)]
+            self,
+            move |dialog, response| {
+                if response == ResponseType::Accept {
+                    if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                        if let Err(e) = save_monitor_lut(&monitor, &path) {
+                            eprintln!("Failed to save the color LUT: {e}");
+                        }
+                        this.monitor_luts.borrow_mut().insert(monitor.clone(), path);
+                        this.update_color_lut(&monitor);
+                        let w = this.widgets();
+                        let enabled = w.image_view.has_lut();
+                        w.image_view.lut_enable(enabled);
+                        w.set_action_bool("color.lut", enabled);
+                    }
+                }
+                dialog.destroy();
+            }
+        ));
+
+        dialog.show();
+    }
+
+    fn printer_profile(&self) -> Option<PathBuf> {
+        self.printer_profile.borrow().clone().or_else(|| {
+            config()
+                .config_file
+                .printer_profile
+                .as_ref()
+                .map(PathBuf::from)
+        })
+    }
+
+    fn set_soft_proof_active(&self, active: bool) {
+        let w = self.widgets();
+        let soft_proof = if active {
+            self.printer_profile().and_then(|profile| {
+                match SoftProof::new(&profile, self.gamut_warning.get()) {
+                    Ok(soft_proof) => {
+                        println!("Soft-proofing with '{}'", soft_proof.name());
+                        Some(soft_proof)
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to load printer profile {profile:?}: {e}");
+                        None
+                    }
+                }
+            })
+        } else {
+            None
+        };
+        w.image_view.set_soft_proof(soft_proof);
+        w.set_action_bool("color.proof", w.image_view.soft_proof_active());
+    }
+
+    pub fn toggle_soft_proof(&self) {
+        let active = !self.widgets().image_view.soft_proof_active();
+        if active && self.printer_profile().is_none() {
+            self.select_printer_profile();
+        } else {
+            self.set_soft_proof_active(active);
+        }
+    }
+
+    pub fn toggle_gamut_warning(&self) {
+        let w = self.widgets();
+        self.gamut_warning.set(!self.gamut_warning.get());
+        w.set_action_bool("color.gamut", self.gamut_warning.get());
+        if w.image_view.soft_proof_active() {
+            self.set_soft_proof_active(true);
+        }
+    }
+
+    /// Choose the ICC profile of the printer (paper) to simulate
+    pub fn select_printer_profile(&self) {
+        let dialog = FileChooserDialog::new(
+            Some("Choose printer profile"),
+            Some(&self.obj().clone()),
+            FileChooserAction::Open,
+            &[
+                ("Cancel", ResponseType::Cancel),
+                ("Open", ResponseType::Accept),
+            ],
+        );
+
+        let icc_files = FileFilter::new();
+        icc_files.set_name(Some("ICC profiles"));
+        icc_files.add_pattern("*.icc");
+        icc_files.add_pattern("*.icm");
+        dialog.add_filter(&icc_files);
+
+        if let Some(folder) = self
+            .printer_profile()
+            .and_then(|p| p.parent().map(|p| p.to_path_buf()))
+        {
+            let _ = dialog.set_current_folder(Some(&gio::File::for_path(folder)));
+        }
+
+        dialog.connect_response(clone!(
+            #[weak(rename_to = this)]
+            self,
+            move |dialog, response| {
+                if response == ResponseType::Accept {
+                    if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                        if let Err(e) = save_printer_profile(&path) {
+                            eprintln!("Failed to save the printer profile: {e}");
+                        }
+                        this.printer_profile.replace(Some(path));
+                        this.set_soft_proof_active(true);
+                    }
+                }
+                dialog.destroy();
+            }
+        ));
+
+        dialog.show();
+    }
+}
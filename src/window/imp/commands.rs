@@ -17,7 +17,11 @@
 // STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
 // OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use crate::window::imp::MViewWindowImp;
+use crate::{
+    image::scan::ScanFilter,
+    metadata::label::ColorLabel,
+    window::imp::{autoscroll::WPM_STEP, MViewWindowImp},
+};
 
 #[derive(Clone)]
 pub struct Command {
@@ -32,6 +36,111 @@ pub const COMMANDS: &[Command] = &[
         shortcut: None,
         action: |w| w.show_about_dialog(),
     },
+    Command {
+        name: "Auto-scroll: faster",
+        shortcut: Some("]"),
+        action: |w| w.change_reading_speed(WPM_STEP),
+    },
+    Command {
+        name: "Auto-scroll: slower",
+        shortcut: Some("["),
+        action: |w| w.change_reading_speed(-WPM_STEP),
+    },
+    Command {
+        name: "Auto-scroll: start or stop",
+        shortcut: Some("F5"),
+        action: |w| w.toggle_auto_scroll(),
+    },
+    Command {
+        name: "Caches: show usage and clear",
+        shortcut: None,
+        action: |w| w.cache_dialog(),
+    },
+    Command {
+        name: "Collection: add current file",
+        shortcut: Some("c"),
+        action: |w| w.add_to_collection(),
+    },
+    Command {
+        name: "Collection: export as playlist",
+        shortcut: None,
+        action: |w| w.export_collection(),
+    },
+    Command {
+        name: "Collection: remove selected file",
+        shortcut: Some("Delete"),
+        action: |w| w.remove_from_collection(),
+    },
+    Command {
+        name: "Collection: show or leave",
+        shortcut: Some("Shift+C"),
+        action: |w| w.toggle_collection(),
+    },
+    Command {
+        name: "Color: load LUT for this monitor",
+        shortcut: None,
+        action: |w| w.load_color_lut(),
+    },
+    Command {
+        name: "Color: toggle monitor LUT",
+        shortcut: Some("F9"),
+        action: |w| w.toggle_color_lut(),
+    },
+    Command {
+        name: "Color: select printer profile",
+        shortcut: None,
+        action: |w| w.select_printer_profile(),
+    },
+    Command {
+        name: "Color: toggle gamut warning",
+        shortcut: None,
+        action: |w| w.toggle_gamut_warning(),
+    },
+    Command {
+        name: "Color: toggle soft-proof",
+        shortcut: None,
+        action: |w| w.toggle_soft_proof(),
+    },
+    Command {
+        name: "Copy to folder",
+        shortcut: None,
+        action: |w| w.copy_to_folder(),
+    },
+    Command {
+        name: "Copy view as image",
+        shortcut: None,
+        action: |w| w.copy_view_to_clipboard(),
+    },
+    Command {
+        name: "Diff: compare with next image",
+        shortcut: Some("Shift+D"),
+        action: |w| w.toggle_diff(),
+    },
+    Command {
+        name: "EXIF: geotag from GPX track",
+        shortcut: None,
+        action: |w| w.geotag_from_gpx(),
+    },
+    Command {
+        name: "EXIF: shift timestamps",
+        shortcut: None,
+        action: |w| w.timestamp_shift_dialog(),
+    },
+    Command {
+        name: "EXIF: undo timestamp shift",
+        shortcut: None,
+        action: |w| w.undo_timestamp_shift(),
+    },
+    Command {
+        name: "Edit in external editor",
+        shortcut: Some("Shift+E"),
+        action: |w| w.edit_in_external_editor(),
+    },
+    Command {
+        name: "Go to page",
+        shortcut: Some("Ctrl+G"),
+        action: |w| w.goto_page_dialog(),
+    },
     Command {
         name: "Help screen 1",
         shortcut: None,
@@ -42,6 +151,86 @@ pub const COMMANDS: &[Command] = &[
         shortcut: None,
         action: |w| w.show_help_page(2),
     },
+    Command {
+        name: "Help: keyboard shortcuts",
+        shortcut: Some("F1"),
+        action: |w| w.show_keyboard_shortcuts(),
+    },
+    Command {
+        name: "Icon: select frame",
+        shortcut: None,
+        action: |w| w.select_frame_dialog(),
+    },
+    Command {
+        name: "Import from camera or memory card",
+        shortcut: None,
+        action: |w| w.camera_import_dialog(),
+    },
+    Command {
+        name: "Import: save dropped image",
+        shortcut: None,
+        action: |w| w.save_import(),
+    },
+    Command {
+        name: "Keywords: filter on keyword",
+        shortcut: None,
+        action: |w| w.keyword_filter_dialog(),
+    },
+    Command {
+        name: "Label filter: all files",
+        shortcut: None,
+        action: |w| w.change_label_filter("none"),
+    },
+    Command {
+        name: "Label filter: blue",
+        shortcut: None,
+        action: |w| w.change_label_filter("blue"),
+    },
+    Command {
+        name: "Label filter: green",
+        shortcut: None,
+        action: |w| w.change_label_filter("green"),
+    },
+    Command {
+        name: "Label filter: purple",
+        shortcut: None,
+        action: |w| w.change_label_filter("purple"),
+    },
+    Command {
+        name: "Label filter: red",
+        shortcut: None,
+        action: |w| w.change_label_filter("red"),
+    },
+    Command {
+        name: "Label filter: yellow",
+        shortcut: None,
+        action: |w| w.change_label_filter("yellow"),
+    },
+    Command {
+        name: "Label: toggle blue",
+        shortcut: Some("9"),
+        action: |w| w.toggle_color_label(ColorLabel::Blue),
+    },
+    Command {
+        name: "Label: toggle green",
+        shortcut: Some("8"),
+        action: |w| w.toggle_color_label(ColorLabel::Green),
+    },
+    Command {
+        name: "Label: toggle purple",
+        shortcut: Some("5"),
+        action: |w| w.toggle_color_label(ColorLabel::Purple),
+    },
+    Command {
+        name: "Label: toggle red",
+        shortcut: Some("6"),
+        action: |w| w.toggle_color_label(ColorLabel::Red),
+    },
+    Command {
+        name: "Label: toggle yellow",
+        shortcut: Some("7"),
+        action: |w| w.toggle_color_label(ColorLabel::Yellow),
+    },
     Command {
         name: "Measurements: move endpoints",
         shortcut: Some("tab"),
@@ -52,6 +241,11 @@ pub const COMMANDS: &[Command] = &[
         shortcut: Some("f2"),
         action: |w| w.measure_toggle(),
     },
+    Command {
+        name: "Move to folder",
+        shortcut: None,
+        action: |w| w.move_to_folder(),
+    },
     Command {
         name: "Navigation: edit filter",
         shortcut: Some("Shift+F"),
@@ -62,6 +256,11 @@ pub const COMMANDS: &[Command] = &[
         shortcut: None,
         action: |w| w.open_file(),
     },
+    Command {
+        name: "Open terminal here",
+        shortcut: Some("Ctrl+T"),
+        action: |w| w.open_terminal(),
+    },
     Command {
         name: "PDF backend: MuPDF",
         shortcut: None,
@@ -72,6 +271,21 @@ pub const COMMANDS: &[Command] = &[
         shortcut: None,
         action: |w| w.change_pdf_provider("pdfium"),
     },
+    Command {
+        name: "PDF: export attachment",
+        shortcut: None,
+        action: |w| w.export_attachment(),
+    },
+    Command {
+        name: "PDF: export page range",
+        shortcut: None,
+        action: |w| w.export_page_range(),
+    },
+    Command {
+        name: "PDF: split scanned spreads",
+        shortcut: None,
+        action: |w| w.toggle_split_spreads(),
+    },
     Command {
         name: "Page mode: Single",
         shortcut: None,
@@ -87,11 +301,101 @@ pub const COMMANDS: &[Command] = &[
         shortcut: None,
         action: |w| w.change_page_mode("doe"),
     },
+    Command {
+        name: "Page mode: toggle cover page",
+        shortcut: None,
+        action: |w| w.toggle_cover_page(),
+    },
+    Command {
+        name: "Page tint: Dark",
+        shortcut: None,
+        action: |w| w.change_page_tint("dark"),
+    },
+    Command {
+        name: "Page tint: None",
+        shortcut: None,
+        action: |w| w.change_page_tint("none"),
+    },
+    Command {
+        name: "Page tint: Sepia",
+        shortcut: None,
+        action: |w| w.change_page_tint("sepia"),
+    },
+    Command {
+        name: "Page tint: Solarized",
+        shortcut: None,
+        action: |w| w.change_page_tint("solarized"),
+    },
+    Command {
+        name: "Preferences: export",
+        shortcut: None,
+        action: |w| w.export_preferences(),
+    },
+    Command {
+        name: "Preferences: import",
+        shortcut: None,
+        action: |w| w.import_preferences(),
+    },
+    Command {
+        name: "Preview server: start or stop",
+        shortcut: None,
+        action: |w| w.toggle_preview_server(),
+    },
     Command {
         name: "Quit MView6",
         shortcut: Some("q"),
         action: |w| w.quit(),
     },
+    Command {
+        name: "Rating: culling report",
+        shortcut: None,
+        action: |w| w.culling_report(),
+    },
+    Command {
+        name: "Rating: show summary of this folder",
+        shortcut: None,
+        action: |w| w.show_rating_summary(),
+    },
+    Command {
+        name: "Rating: toggle advance after rating",
+        shortcut: None,
+        action: |w| w.toggle_rate_advance(),
+    },
+    Command {
+        name: "Reading ruler: toggle",
+        shortcut: Some("f4"),
+        action: |w| w.toggle_reading_ruler(),
+    },
+    Command {
+        name: "Reopen closed archive or document",
+        shortcut: Some("Ctrl+Shift+T"),
+        action: |w| w.reopen_closed(),
+    },
+    Command {
+        name: "Review trash: delete current file",
+        shortcut: Some("Delete"),
+        action: |w| w.stage_current(),
+    },
+    Command {
+        name: "Review trash: empty",
+        shortcut: None,
+        action: |w| w.empty_staging(),
+    },
+    Command {
+        name: "Review trash: restore selected file",
+        shortcut: Some("Ctrl+Z"),
+        action: |w| w.restore_staged(),
+    },
+    Command {
+        name: "Review trash: show or leave",
+        shortcut: Some("Shift+Delete"),
+        action: |w| w.toggle_staging(),
+    },
+    Command {
+        name: "Review trash: undo last delete",
+        shortcut: Some("Ctrl+Z"),
+        action: |w| w.undo_delete(),
+    },
     Command {
         name: "Rotate 90° Clockwise",
         shortcut: None,
@@ -107,6 +411,21 @@ pub const COMMANDS: &[Command] = &[
         shortcut: None,
         action: |w| w.rotate_image(180),
     },
+    Command {
+        name: "Scan filter: deskew",
+        shortcut: None,
+        action: |w| w.toggle_scan_filter(ScanFilter::Deskew),
+    },
+    Command {
+        name: "Scan filter: despeckle",
+        shortcut: None,
+        action: |w| w.toggle_scan_filter(ScanFilter::Despeckle),
+    },
+    Command {
+        name: "Scan filter: normalize contrast",
+        shortcut: None,
+        action: |w| w.toggle_scan_filter(ScanFilter::Normalize),
+    },
     Command {
         name: "Slideshow interval: 1 second",
         shortcut: None,
@@ -147,6 +466,11 @@ pub const COMMANDS: &[Command] = &[
         shortcut: None,
         action: |w| w.set_slideshow_active(false),
     },
+    Command {
+        name: "Table view: toggle CSV/TSV table or plain text",
+        shortcut: None,
+        action: |w| w.toggle_table_view(),
+    },
     Command {
         name: "Thumbnail size: Extra small (80 px)",
         shortcut: None,
@@ -207,6 +531,31 @@ pub const COMMANDS: &[Command] = &[
         shortcut: None,
         action: |w| w.change_transparency("white"),
     },
+    Command {
+        name: "Usage statistics: most viewed files",
+        shortcut: None,
+        action: |w| w.toggle_most_viewed(),
+    },
+    Command {
+        name: "Usage statistics: toggle recording",
+        shortcut: None,
+        action: |w| w.toggle_usage_stats(),
+    },
+    Command {
+        name: "Watch folder: keep current image",
+        shortcut: Some("k"),
+        action: |w| w.hot_folder_review(true),
+    },
+    Command {
+        name: "Watch folder: reject current image",
+        shortcut: Some("j"),
+        action: |w| w.hot_folder_review(false),
+    },
+    Command {
+        name: "Watch folder: review images as they arrive",
+        shortcut: Some("Shift+W"),
+        action: |w| w.toggle_hot_folder(),
+    },
     Command {
         name: "Zoom: Fill window",
         shortcut: None,
@@ -227,4 +576,9 @@ pub const COMMANDS: &[Command] = &[
         shortcut: None,
         action: |w| w.change_zoom("nozoom"),
     },
+    Command {
+        name: "Zoom: Show zoom and print size",
+        shortcut: Some("%"),
+        action: |w| w.toggle_zoom_info(),
+    },
 ];
@@ -0,0 +1,271 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::path::{Path, PathBuf};
+
+use gio::{prelude::FileExt, Cancellable};
+use glib::{clone, subclass::types::ObjectSubclassExt};
+use gtk4::{
+    prelude::*, Box, Dialog, Label, ListStore, MessageType, Orientation, PolicyType, ResponseType,
+    ScrolledWindow,
+};
+use human_bytes::human_bytes;
+
+use crate::{
+    classification::{FileType, Preference},
+    error::MviewResult,
+    file_view::{model::BackendRef, Direction, Target, TreeModelMviewExt},
+    transfer::TransferMode,
+};
+
+use super::MViewWindowImp;
+
+/// Subfolder the rejected images are moved to
+const REJECTED_FOLDER: &str = "rejected";
+
+const RESPONSE_MOVE: ResponseType = ResponseType::Other(1);
+const RESPONSE_TRASH: ResponseType = ResponseType::Other(2);
+
+/// Number of images per preference in a list
+#[derive(Debug, Default)]
+struct RatingCounts {
+    liked: usize,
+    normal: usize,
+    disliked: usize,
+}
+
+fn trash_file(path: &Path) -> MviewResult<()> {
+    gio::File::for_path(path).trash(None::<&Cancellable>)?;
+    Ok(())
+}
+
+impl RatingCounts {
+    fn count(store: &ListStore) -> Self {
+        let mut counts = Self::default();
+        let Some(iter) = store.iter_first() else {
+            return counts;
+        };
+        loop {
+            if store.content(&iter) == FileType::Image {
+                match store.preference(&iter) {
+                    Preference::Liked => counts.liked += 1,
+                    Preference::Normal => counts.normal += 1,
+                    Preference::Disliked => counts.disliked += 1,
+                }
+            }
+            if !store.iter_next(&iter) {
+                break;
+            }
+        }
+        counts
+    }
+}
+
+impl MViewWindowImp {
+    /// Like (`Up`) or dislike (`Down`) the current item and, in rate and advance mode, move on
+    /// to the next image that passes the filter. Past the last one a summary is shown.
+    pub(super) fn rate_current(&self, direction: Direction) {
+        let w = self.widgets();
+        w.file_view.set_unsorted();
+        let Some(current) = w.file_view.current() else {
+            return;
+        };
+        if !self.backend.borrow().set_preference(&current, direction) || !self.rate_advance.get() {
+            return;
+        }
+        let filter = self.current_filter.borrow().images_only();
+        if !w.file_view.navigate_item(Direction::Down, &filter, 1) {
+            self.show_rating_summary();
+        }
+    }
+
+    pub fn toggle_rate_advance(&self) {
+        self.rate_advance.set(!self.rate_advance.get());
+        self.widgets()
+            .set_action_bool("rate.advance", self.rate_advance.get());
+    }
+
+    pub fn show_rating_summary(&self) {
+        let Some(store) = self.widgets().file_view.store() else {
+            return;
+        };
+        let counts = RatingCounts::count(&store);
+        let text = format!(
+            "Liked: {}\nUnrated: {}\nDisliked: {}\n\nTotal: {} images",
+            counts.liked,
+            counts.normal,
+            counts.disliked,
+            counts.liked + counts.normal + counts.disliked
+        );
+        self.show_message(MessageType::Info, "Ratings in this folder", &text);
+    }
+
+    /// Disliked images of the current folder with their file size
+    fn rejected_images(&self) -> Option<Vec<(PathBuf, u64)>> {
+        let BackendRef::FileSystem(directory) = self.backend.borrow().backend_ref() else {
+            return None;
+        };
+        let store = self.widgets().file_view.store()?;
+        let mut rejected = Vec::new();
+        if let Some(iter) = store.iter_first() {
+            loop {
+                if store.content(&iter) == FileType::Image
+                    && store.preference(&iter) == Preference::Disliked
+                {
+                    rejected.push((directory.join(store.name(&iter)), store.size(&iter)));
+                }
+                if !store.iter_next(&iter) {
+                    break;
+                }
+            }
+        }
+        Some(rejected)
+    }
+
+    /// Report the outcome of culling the current folder, with the option to move the
+    /// rejected images away or to the trash in one go
+    pub fn culling_report(&self) {
+        let Some(rejected) = self.rejected_images() else {
+            self.show_message(
+                MessageType::Info,
+                "Culling report",
+                "A culling report can only be made for images in a folder",
+            );
+            return;
+        };
+        let Some(store) = self.widgets().file_view.store() else {
+            return;
+        };
+        let counts = RatingCounts::count(&store);
+        let reclaimed: u64 = rejected.iter().map(|(_, size)| size).sum();
+
+        let dialog = Dialog::builder()
+            .title("Culling report")
+            .modal(true)
+            .transient_for(&self.obj().clone())
+            .default_width(560)
+            .default_height(420)
+            .build();
+
+        let vbox = Box::builder()
+            .orientation(Orientation::Vertical)
+            .spacing(8)
+            .margin_start(12)
+            .margin_end(12)
+            .margin_top(12)
+            .margin_bottom(12)
+            .build();
+
+        let summary = Label::new(Some(&format!(
+            "Kept: {} ({} liked, {} unrated)\nRejected: {}\nReclaimed when the rejected images are removed: {}",
+            counts.liked + counts.normal,
+            counts.liked,
+            counts.normal,
+            counts.disliked,
+            human_bytes(reclaimed as f64)
+        )));
+        summary.set_xalign(0.0);
+
+        let files = Label::new(Some(
+            &rejected
+                .iter()
+                .map(|(path, size)| {
+                    let name = path.file_name().unwrap_or_default().to_string_lossy();
+                    format!("{name}  {}", human_bytes(*size as f64))
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        ));
+        files.set_xalign(0.0);
+        files.set_yalign(0.0);
+        files.set_selectable(true);
+        files.add_css_class("monospace");
+        let scrolled = ScrolledWindow::builder()
+            .hscrollbar_policy(PolicyType::Automatic)
+            .vscrollbar_policy(PolicyType::Automatic)
+            .vexpand(true)
+            .child(&files)
+            .build();
+
+        vbox.append(&summary);
+        vbox.append(&scrolled);
+        dialog.content_area().append(&vbox);
+
+        dialog.add_button("Close", ResponseType::Close);
+        let move_btn = dialog.add_button(&format!("Move to \"{REJECTED_FOLDER}\""), RESPONSE_MOVE);
+        let trash_btn = dialog.add_button("Move to trash", RESPONSE_TRASH);
+        move_btn.set_sensitive(!rejected.is_empty());
+        trash_btn.set_sensitive(!rejected.is_empty());
+        trash_btn.add_css_class("destructive-action");
+
+        dialog.connect_response(clone!(
+            #[weak(rename_to = this)]
+            self,
+            move |dialog, response| {
+                let files: Vec<PathBuf> = rejected.iter().map(|(path, _)| path.clone()).collect();
+                dialog.close();
+                if response == RESPONSE_MOVE {
+                    this.move_rejected(&files);
+                } else if response == RESPONSE_TRASH {
+                    this.trash_rejected(files);
+                }
+            }
+        ));
+
+        dialog.present();
+    }
+
+    fn current_target(&self) -> Target {
+        match self.widgets().file_view.current() {
+            Some(current) => Target::Name(current.name()),
+            None => Target::First,
+        }
+    }
+
+    fn move_rejected(&self, files: &[PathBuf]) {
+        let Some(directory) = files.first().and_then(|path| path.parent()) else {
+            return;
+        };
+        let folder = directory.join(REJECTED_FOLDER);
+        self.transfer_files(files, &folder, TransferMode::Move, self.current_target());
+    }
+
+    fn trash_rejected(&self, files: Vec<PathBuf>) {
+        let mut failed = Vec::new();
+        for path in &files {
+            if let Err(e) = trash_file(path) {
+                eprintln!("Failed to remove {path:?}: {e}");
+                failed.push(path.file_name().unwrap_or_default().to_string_lossy());
+            }
+        }
+        println!(
+            "Removed {} of {} rejected images",
+            files.len() - failed.len(),
+            files.len()
+        );
+        self.reload(&self.current_target());
+        if !failed.is_empty() {
+            self.show_message(
+                MessageType::Warning,
+                "Culling report",
+                &format!("Failed to remove:\n{}", failed.join("\n")),
+            );
+        }
+    }
+}
@@ -0,0 +1,140 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::collections::HashSet;
+
+use gtk4::{SortColumn, SortType};
+
+use crate::{
+    backends::document::PageMode,
+    classification::{FileType, Preference},
+    config::DirectoryConfig,
+    file_view::{Column, Filter, Sort},
+};
+
+use super::MViewWindowImp;
+
+/// Global settings that were overridden by a directory configuration, put back when
+/// navigating to a directory that does not override them
+#[derive(Debug, Default)]
+pub struct ReplacedSettings {
+    zoom: Option<String>,
+    transparency: Option<String>,
+    page_mode: Option<PageMode>,
+    slideshow_interval: Option<i32>,
+    filter: Option<Filter>,
+}
+
+impl MViewWindowImp {
+    pub fn apply_directory_config(&self, config: &DirectoryConfig) {
+        if *self.directory_config.borrow() == *config {
+            return;
+        }
+        self.directory_config.replace(config.clone());
+
+        let w = self.widgets();
+        let replaced = self.replaced_settings.take();
+        if let Some(zoom) = replaced.zoom {
+            self.change_zoom(&zoom);
+        }
+        if let Some(transparency) = replaced.transparency {
+            self.change_transparency(&transparency);
+        }
+        if let Some(page_mode) = replaced.page_mode {
+            self.change_page_mode(page_mode.into());
+        }
+        if let Some(interval) = replaced.slideshow_interval {
+            w.set_action_string("slideshow.interval", &interval.to_string());
+        }
+        if let Some(filter) = replaced.filter {
+            self.current_filter.replace(filter);
+        }
+
+        let mut replaced = ReplacedSettings::default();
+        if let Some(zoom) = &config.zoom {
+            replaced.zoom = Some(w.get_action_string("zoom"));
+            self.change_zoom(zoom);
+        }
+        if let Some(transparency) = &config.transparency {
+            replaced.transparency = Some(w.get_action_string("transparency"));
+            self.change_transparency(transparency);
+        }
+        if let Some(page_mode) = &config.page_mode {
+            replaced.page_mode = Some(self.default_page_mode.get());
+            self.change_page_mode(page_mode);
+        }
+        if let Some(interval) = config.slideshow_interval {
+            replaced.slideshow_interval = Some(self.get_slideshow_interval());
+            w.set_action_string("slideshow.interval", &interval.to_string());
+        }
+        if let Some(filter) = directory_filter(config) {
+            replaced.filter = Some(self.current_filter.replace(filter));
+        }
+        self.replaced_settings.replace(replaced);
+    }
+}
+
+pub fn directory_sort(config: &DirectoryConfig) -> Option<Sort> {
+    let column = match config.sort.as_deref()? {
+        "type" => Column::ContentType,
+        "name" => Column::Name,
+        "size" => Column::Size,
+        "modified" => Column::Modified,
+        other => {
+            eprintln!("Unknown sort column in directory config: {other}");
+            return None;
+        }
+    };
+    let order = if config.sort_descending.unwrap_or_default() {
+        SortType::Descending
+    } else {
+        SortType::Ascending
+    };
+    Some(Sort::new(SortColumn::Index(column as u32), order))
+}
+
+/// Types and preferences named in the filter, an empty group means no restriction
+fn directory_filter(config: &DirectoryConfig) -> Option<Filter> {
+    let mut file_types = HashSet::new();
+    let mut preferences = HashSet::new();
+    for name in config.filter.as_ref()? {
+        match name.as_str() {
+            "image" => file_types.insert(FileType::Image),
+            "video" => file_types.insert(FileType::Video),
+            "document" => file_types.insert(FileType::Document),
+            "folder" => file_types.insert(FileType::Folder),
+            "archive" => file_types.insert(FileType::Archive),
+            "unsupported" => file_types.insert(FileType::Unsupported),
+            "normal" => preferences.insert(Preference::Normal),
+            "liked" => preferences.insert(Preference::Liked),
+            "disliked" => preferences.insert(Preference::Disliked),
+            other => {
+                eprintln!("Unknown filter in directory config: {other}");
+                false
+            }
+        };
+    }
+    if file_types.is_empty() {
+        file_types = FileType::all();
+    }
+    if preferences.is_empty() {
+        preferences = Preference::all();
+    }
+    Some(Filter::Set((file_types, preferences)))
+}
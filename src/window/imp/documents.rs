@@ -0,0 +1,102 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    collections::HashMap,
+    fs::{create_dir_all, File},
+    io::{self, BufReader, BufWriter},
+    path::PathBuf,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{image::view::data::PageTint, window::imp::MViewWindowImp};
+
+/// Settings remembered per document, `None` means the global setting applies
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct DocumentSettings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_mode: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_tint: Option<String>,
+}
+
+impl MViewWindowImp {
+    fn document_settings_file(create_dir: bool) -> io::Result<PathBuf> {
+        let mut path = dirs::config_dir().unwrap_or_default();
+        path.push("mview6");
+        if create_dir {
+            create_dir_all(&path)?;
+        }
+        path.push("documents.json");
+        Ok(path)
+    }
+
+    pub fn save_document_settings(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let file = File::create(Self::document_settings_file(true)?)?;
+        let writer = BufWriter::new(file);
+        serde_json::to_writer_pretty(writer, &*self.document_settings.borrow())?;
+        Ok(())
+    }
+
+    pub fn load_document_settings(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let file = File::open(Self::document_settings_file(false)?)?;
+        let reader = BufReader::new(file);
+        let loaded_data: HashMap<PathBuf, DocumentSettings> = serde_json::from_reader(reader)?;
+        *self.document_settings.borrow_mut() = loaded_data;
+        Ok(())
+    }
+
+    /// Update the settings of the current document, if it is one
+    pub(super) fn update_document_settings<F: FnOnce(&mut DocumentSettings)>(&self, update: F) {
+        let backend = self.backend.borrow();
+        if backend.is_doc() {
+            update(
+                self.document_settings
+                    .borrow_mut()
+                    .entry(backend.normalized_path())
+                    .or_default(),
+            );
+        }
+    }
+
+    /// Apply the remembered settings for a newly opened document
+    pub(super) fn apply_document_settings(&self) {
+        self.apply_document_page_mode();
+        let backend = self.backend.borrow();
+        let page_tint = self
+            .document_settings
+            .borrow()
+            .get(&backend.normalized_path())
+            .and_then(|settings| settings.page_tint.as_deref().map(PageTint::from))
+            .unwrap_or(self.default_page_tint.get());
+        drop(backend);
+        let w = self.widgets();
+        w.set_action_string("tint", page_tint.into());
+        w.image_view.set_page_tint(page_tint);
+    }
+
+    pub fn change_page_tint(&self, page_tint: &str) {
+        let w = self.widgets();
+        w.set_action_string("tint", page_tint);
+        w.image_view.set_page_tint(page_tint.into());
+        self.default_page_tint.set(page_tint.into());
+        self.update_document_settings(|settings| settings.page_tint = Some(page_tint.to_string()));
+    }
+}
@@ -0,0 +1,191 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::atomic::{AtomicU32, Ordering},
+    time::SystemTime,
+};
+
+use gtk4::MessageType;
+
+use crate::{
+    backends::extract::session_folder,
+    classification::{FileClassification, FileType},
+    config::editor_command,
+    content::{loader::ContentLoader, Content, ContentData},
+    file_view::model::BackendRef,
+    window::imp::MViewWindowImp,
+};
+
+/// An image handed to the external editor, its result is shown next to it once it is saved
+pub struct EditSession {
+    original: PathBuf,
+    /// Copy of the original, in case the editor overwrites it. Removed once it has been
+    /// loaded for the first result.
+    backup: PathBuf,
+    original_surface: Option<cairo::ImageSurface>,
+    started: SystemTime,
+}
+
+impl Drop for EditSession {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.backup);
+    }
+}
+
+/// Unique location for the copy of `name` in the session folder, which is removed on exit
+fn backup_path(name: &str) -> std::io::Result<PathBuf> {
+    static COUNTER: AtomicU32 = AtomicU32::new(0);
+    let counter = COUNTER.fetch_add(1, Ordering::Relaxed);
+    Ok(session_folder("edit")?.join(format!("{counter}-{name}")))
+}
+
+impl EditSession {
+    /// The image as it was before editing
+    fn original_surface(&mut self) -> Option<cairo::ImageSurface> {
+        if self.original_surface.is_none() {
+            self.original_surface = image_surface(ContentLoader::content_from_file(&self.backup));
+            let _ = fs::remove_file(&self.backup);
+        }
+        self.original_surface.clone()
+    }
+
+    /// Files written by the editor: the original itself or an export that starts with the
+    /// same name, like `IMG_1234-edit.jpg` or `IMG_1234.tif` for `IMG_1234.jpg`
+    fn is_result(&self, path: &Path) -> bool {
+        if path.parent() != self.original.parent() {
+            return false;
+        }
+        let (Some(stem), Some(name)) = (self.original.file_stem(), path.file_name()) else {
+            return false;
+        };
+        if !name.to_string_lossy().starts_with(&*stem.to_string_lossy()) {
+            return false;
+        }
+        let modified = fs::metadata(path).and_then(|metadata| metadata.modified());
+        modified.is_ok_and(|modified| modified >= self.started)
+            && FileClassification::determine(path, false).file_type == FileType::Image
+    }
+}
+
+fn image_surface(content: Content) -> Option<cairo::ImageSurface> {
+    match content.data {
+        ContentData::Single(single) => Some(single.surface()),
+        _ => None,
+    }
+}
+
+impl MViewWindowImp {
+    /// Open the current image in the external editor and watch for the edited result
+    pub fn edit_in_external_editor(&self) {
+        let backend = self.backend.borrow();
+        let BackendRef::FileSystem(directory) = backend.backend_ref() else {
+            drop(backend);
+            self.show_message(
+                MessageType::Info,
+                "Edit in external editor",
+                "Only images in a folder can be edited",
+            );
+            return;
+        };
+        drop(backend);
+        let Some(current) = self.widgets().file_view.current() else {
+            return;
+        };
+        let original = directory.join(current.name());
+        if FileClassification::determine(&original, false).file_type != FileType::Image {
+            return;
+        }
+
+        let command_line = editor_command();
+        let args = match glib::shell_parse_argv(&command_line) {
+            Ok(args) => args,
+            Err(e) => {
+                self.show_message(
+                    MessageType::Error,
+                    "Edit in external editor",
+                    &format!("Invalid editor command {command_line}: {e}"),
+                );
+                return;
+            }
+        };
+        let original_str = original.to_string_lossy();
+        let mut args = args
+            .iter()
+            .map(|arg| arg.to_string_lossy().replace("{file}", &original_str));
+        let Some(program) = args.next() else {
+            return;
+        };
+
+        let backup = match backup_path(&current.name()) {
+            Ok(backup) => backup,
+            Err(e) => {
+                eprintln!("Failed to create the backup folder: {e}");
+                return;
+            }
+        };
+        if let Err(e) = fs::copy(&original, &backup) {
+            eprintln!("Failed to keep a copy of {original:?}: {e}");
+            return;
+        }
+
+        println!("Launch editor {program} for {original_str}");
+        let child = Command::new(&program)
+            .args(args)
+            .current_dir(&directory)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        if let Err(error) = child {
+            eprintln!("Failed to launch {program} {:?}", error);
+            let _ = fs::remove_file(&backup);
+            return;
+        }
+
+        self.edit_session.replace(Some(EditSession {
+            original,
+            backup,
+            original_surface: None,
+            started: SystemTime::now(),
+        }));
+    }
+
+    /// Called by the directory watcher for every file that was written
+    pub(super) fn check_edit_result(&self, path: &Path) {
+        let mut session = self.edit_session.borrow_mut();
+        let Some(session) = session.as_mut() else {
+            return;
+        };
+        if !session.is_result(path) {
+            return;
+        }
+        println!("Edited result {}", path.display());
+        let original = session.original_surface();
+        let edited = image_surface(ContentLoader::content_from_file(path));
+        let mut content = Content::new_dual_surface(original, edited, None);
+        content.tag = Some("edit".to_string());
+        let w = self.widgets();
+        w.info_view.update(&content);
+        w.image_view.set_content(content);
+    }
+}
@@ -0,0 +1,92 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use glib::{clone, subclass::types::ObjectSubclassExt};
+use gtk4::{prelude::*, Box, Dialog, Entry, Label, Orientation, ResponseType};
+
+use crate::window::imp::MViewWindowImp;
+
+impl MViewWindowImp {
+    /// Ask for a line of text. The dialog stays open as long as `accept` rejects the text,
+    /// showing the message it returns.
+    pub(super) fn entry_dialog(
+        &self,
+        title: &str,
+        prompt: &str,
+        initial: Option<&str>,
+        button: &str,
+        accept: impl Fn(&MViewWindowImp, &str) -> Result<(), String> + 'static,
+    ) {
+        let dialog = Dialog::builder()
+            .title(title)
+            .modal(true)
+            .transient_for(&self.obj().clone())
+            .default_width(280)
+            .build();
+
+        let vbox = Box::builder()
+            .orientation(Orientation::Vertical)
+            .spacing(8)
+            .margin_start(12)
+            .margin_end(12)
+            .margin_top(12)
+            .margin_bottom(12)
+            .build();
+
+        let label = Label::new(Some(prompt));
+        label.set_xalign(0.0);
+        let entry = Entry::new();
+        if let Some(initial) = initial {
+            entry.set_text(initial);
+        }
+        entry.set_activates_default(true);
+        let error = Label::new(None);
+        error.set_xalign(0.0);
+
+        vbox.append(&label);
+        vbox.append(&entry);
+        vbox.append(&error);
+        dialog.content_area().append(&vbox);
+
+        dialog.add_button("Cancel", ResponseType::Cancel);
+        dialog.add_button(button, ResponseType::Ok);
+        dialog.set_default_response(ResponseType::Ok);
+
+        dialog.connect_response(clone!(
+            #[weak(rename_to = this)]
+            self,
+            #[weak]
+            entry,
+            #[weak]
+            error,
+            move |dialog, response| {
+                if response == ResponseType::Ok {
+                    if let Err(message) = accept(&this, &entry.text()) {
+                        error.set_text(&message);
+                        entry.grab_focus();
+                        return;
+                    }
+                }
+                dialog.close();
+            }
+        ));
+
+        dialog.present();
+    }
+}
@@ -0,0 +1,80 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use glib::{clone, subclass::types::ObjectSubclassExt};
+use gtk4::{prelude::*, Dialog, Label, ListBox, MessageType, ResponseType, SelectionMode};
+
+use crate::window::imp::MViewWindowImp;
+
+impl MViewWindowImp {
+    /// Choose which image of a file with multiple images (icon sizes) is shown
+    pub fn select_frame_dialog(&self) {
+        let w = self.widgets();
+        let Some(current) = w.image_view.frame_index() else {
+            self.show_message(
+                MessageType::Info,
+                "Select frame",
+                "The current image has only one frame",
+            );
+            return;
+        };
+
+        let dialog = Dialog::builder()
+            .title("Select frame")
+            .modal(true)
+            .transient_for(&self.obj().clone())
+            .default_width(300)
+            .build();
+
+        let list = ListBox::new();
+        list.set_selection_mode(SelectionMode::Single);
+        list.set_margin_start(12);
+        list.set_margin_end(12);
+        list.set_margin_top(12);
+        list.set_margin_bottom(12);
+        for info in w.image_view.frame_infos() {
+            let label = Label::new(Some(&info));
+            label.set_xalign(0.0);
+            label.set_margin_start(6);
+            label.set_margin_end(6);
+            label.set_margin_top(4);
+            label.set_margin_bottom(4);
+            list.append(&label);
+        }
+        list.select_row(list.row_at_index(current as i32).as_ref());
+
+        list.connect_row_selected(clone!(
+            #[weak(rename_to = this)]
+            self,
+            move |_, row| {
+                if let Some(row) = row {
+                    let w = this.widgets();
+                    if w.image_view.select_frame(row.index() as usize) {
+                        w.image_view.update_info(&w.info_view);
+                    }
+                }
+            }
+        ));
+
+        dialog.content_area().append(&list);
+        dialog.add_button("Close", ResponseType::Close);
+        dialog.connect_response(|dialog, _| dialog.close());
+        dialog.present();
+    }
+}
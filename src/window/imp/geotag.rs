@@ -0,0 +1,280 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{path::PathBuf, rc::Rc};
+
+use chrono::{Local, NaiveDateTime, TimeDelta};
+use glib::{clone, subclass::types::ObjectSubclassExt};
+use gtk4::{
+    prelude::*, Box, CheckButton, Dialog, Entry, FileChooserAction, FileChooserDialog, FileFilter,
+    Label, MessageType, Orientation, PolicyType, ResponseType, ScrolledWindow,
+};
+
+use crate::{
+    metadata::{
+        gpx::{GpsPosition, Track},
+        writer::{capture_time, has_gps, parse_time_offset, read_date_time_fields, write_gps},
+    },
+    window::imp::MViewWindowImp,
+};
+
+/// Image to geotag: file, capture time (camera clock) and whether it already has a position
+struct GeotagImage {
+    path: PathBuf,
+    time: NaiveDateTime,
+    has_gps: bool,
+}
+
+/// Offset of the local time zone, as default for the camera clock
+fn local_utc_offset() -> String {
+    let seconds = Local::now().offset().local_minus_utc();
+    let sign = if seconds < 0 { '-' } else { '+' };
+    let seconds = seconds.abs();
+    format!("{sign}{}:{:02}", seconds / 3600, seconds % 3600 / 60)
+}
+
+/// Positions of the images, matched on capture time converted to UTC
+fn match_positions(
+    track: &Track,
+    images: &[GeotagImage],
+    utc_offset: TimeDelta,
+    interpolate: bool,
+) -> Vec<Option<GpsPosition>> {
+    images
+        .iter()
+        .map(|image| {
+            let utc = image.time.checked_sub_signed(utc_offset)?.and_utc();
+            track.position_at(utc, interpolate)
+        })
+        .collect()
+}
+
+impl MViewWindowImp {
+    pub fn geotag_from_gpx(&self) {
+        let dialog = FileChooserDialog::new(
+            Some("Choose GPX track"),
+            Some(&self.obj().clone()),
+            FileChooserAction::Open,
+            &[
+                ("Cancel", ResponseType::Cancel),
+                ("Open", ResponseType::Accept),
+            ],
+        );
+
+        let gpx_files = FileFilter::new();
+        gpx_files.set_name(Some("GPS track (.gpx)"));
+        gpx_files.add_pattern("*.gpx");
+        gpx_files.add_pattern("*.GPX");
+        dialog.add_filter(&gpx_files);
+
+        dialog.connect_response(clone!(
+            #[weak(rename_to = this)]
+            self,
+            move |dialog, response| {
+                if response == ResponseType::Accept {
+                    if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                        match Track::load(&path) {
+                            Ok(track) => this.geotag_dialog(track),
+                            Err(e) => this.show_message(
+                                MessageType::Error,
+                                "Geotag from GPX track",
+                                &format!("Cannot load {}: {e}", path.display()),
+                            ),
+                        }
+                    }
+                }
+                dialog.destroy();
+            }
+        ));
+
+        dialog.show();
+    }
+
+    /// Review the matched positions before writing them
+    fn geotag_dialog(&self, track: Track) {
+        let Some(files) = self.filtered_folder_images() else {
+            self.show_message(
+                MessageType::Info,
+                "Geotag from GPX track",
+                "Only images in a folder can be geotagged",
+            );
+            return;
+        };
+
+        let images = files
+            .into_iter()
+            .filter_map(|path| {
+                let fields = read_date_time_fields(&path).ok()?;
+                let time = capture_time(&fields)?;
+                let has_gps = has_gps(&path);
+                Some(GeotagImage {
+                    path,
+                    time,
+                    has_gps,
+                })
+            })
+            .collect::<Vec<_>>();
+        let images = Rc::new(images);
+        let track = Rc::new(track);
+
+        let dialog = Dialog::builder()
+            .title("Geotag from GPX track")
+            .modal(true)
+            .transient_for(&self.obj().clone())
+            .default_width(640)
+            .default_height(480)
+            .build();
+
+        let vbox = Box::builder()
+            .orientation(Orientation::Vertical)
+            .spacing(8)
+            .margin_start(12)
+            .margin_end(12)
+            .margin_top(12)
+            .margin_bottom(12)
+            .build();
+
+        let track_info = match track.time_range() {
+            Some((first, last)) => format!(
+                "Track of {} points from {first} to {last}",
+                track.point_count()
+            ),
+            None => "Empty track".to_string(),
+        };
+        let info = Label::new(Some(&track_info));
+        info.set_xalign(0.0);
+        let label = Label::new(Some(
+            "Time zone of the camera clock (UTC offset, e.g. +2:00)",
+        ));
+        label.set_xalign(0.0);
+        let entry = Entry::new();
+        entry.set_text(&local_utc_offset());
+        entry.set_activates_default(true);
+        let interpolate = CheckButton::with_label("Interpolate between track points");
+        interpolate.set_active(true);
+
+        let preview = Label::new(None);
+        preview.set_xalign(0.0);
+        preview.set_yalign(0.0);
+        preview.set_selectable(true);
+        preview.add_css_class("monospace");
+        let scrolled = ScrolledWindow::builder()
+            .hscrollbar_policy(PolicyType::Automatic)
+            .vscrollbar_policy(PolicyType::Automatic)
+            .vexpand(true)
+            .child(&preview)
+            .build();
+
+        vbox.append(&info);
+        vbox.append(&label);
+        vbox.append(&entry);
+        vbox.append(&interpolate);
+        vbox.append(&scrolled);
+        dialog.content_area().append(&vbox);
+
+        dialog.add_button("Cancel", ResponseType::Cancel);
+        let ok_btn = dialog.add_button("Write", ResponseType::Ok);
+        dialog.set_default_response(ResponseType::Ok);
+
+        let update_preview = Rc::new(clone!(
+            #[strong]
+            images,
+            #[strong]
+            track,
+            #[weak]
+            entry,
+            #[weak]
+            interpolate,
+            #[weak]
+            ok_btn,
+            move || {
+                let offset = parse_time_offset(&entry.text());
+                let positions = offset
+                    .map(|offset| match_positions(&track, &images, offset, interpolate.is_active()))
+                    .unwrap_or_default();
+                let lines = images
+                    .iter()
+                    .enumerate()
+                    .map(|(i, image)| {
+                        let name = image.path.file_name().unwrap_or_default().to_string_lossy();
+                        match positions.get(i).copied().flatten() {
+                            Some(p) => format!(
+                                "{name}  {}  →  {:.6}, {:.6}{}",
+                                image.time,
+                                p.latitude,
+                                p.longitude,
+                                if image.has_gps { "  (replaces)" } else { "" }
+                            ),
+                            None => format!("{name}  {}  no match", image.time),
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                preview.set_text(&lines.join("\n"));
+                ok_btn.set_sensitive(positions.iter().any(Option::is_some));
+            }
+        ));
+        update_preview();
+
+        entry.connect_changed(clone!(
+            #[strong]
+            update_preview,
+            move |_| update_preview()
+        ));
+        interpolate.connect_toggled(clone!(
+            #[strong]
+            update_preview,
+            move |_| update_preview()
+        ));
+
+        dialog.connect_response(clone!(
+            #[weak(rename_to = this)]
+            self,
+            #[weak]
+            entry,
+            #[weak]
+            interpolate,
+            move |dialog, response| {
+                if response == ResponseType::Ok {
+                    if let Some(offset) = parse_time_offset(&entry.text()) {
+                        let positions =
+                            match_positions(&track, &images, offset, interpolate.is_active());
+                        this.write_geotags(&images, &positions);
+                    }
+                }
+                dialog.close();
+            }
+        ));
+
+        dialog.present();
+    }
+
+    fn write_geotags(&self, images: &[GeotagImage], positions: &[Option<GpsPosition>]) {
+        let mut written = 0;
+        for (image, position) in images.iter().zip(positions) {
+            if let Some(position) = position {
+                match write_gps(&image.path, position) {
+                    Ok(()) => written += 1,
+                    Err(e) => eprintln!("Failed to geotag {:?}: {e}", image.path),
+                }
+            }
+        }
+        println!("Geotagged {written} of {} images", images.len());
+        self.on_cursor_changed();
+    }
+}
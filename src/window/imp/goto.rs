@@ -0,0 +1,84 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use glib::subclass::types::ObjectSubclassExt;
+
+use crate::{
+    backends::document::{labels::find_page, split_spreads},
+    file_view::{Filter, Target},
+    window::imp::MViewWindowImp,
+};
+
+impl MViewWindowImp {
+    /// Select a page of the current document, `None` is the last page
+    pub fn goto_page(&self, index: Option<u64>) {
+        let Some(page_count) = self.backend.borrow().page_count() else {
+            return;
+        };
+        let index = index.unwrap_or(page_count - 1).min(page_count - 1);
+        // Split spreads have two rows per page
+        let row = if split_spreads() { index * 2 } else { index };
+        self.widgets()
+            .file_view
+            .goto(&Target::Index(row), &Filter::None, &self.obj());
+    }
+
+    /// Jump to a page by number or page label
+    pub fn goto_page_dialog(&self) {
+        let backend = self.backend.borrow();
+        let Some(page_count) = backend.page_count() else {
+            return;
+        };
+        let labels = backend.page_labels().to_vec();
+        drop(backend);
+
+        let prompt = match (labels.first(), labels.last()) {
+            (Some(first), Some(last)) => {
+                format!("Page number (1-{page_count}) or label ({first}-{last})")
+            }
+            _ => format!("Page number (1-{page_count})"),
+        };
+        let current = self.widgets().file_view.current().and_then(|current| {
+            let mut index = current.index() as usize;
+            if split_spreads() {
+                index /= 2;
+            }
+            (index < page_count as usize).then(|| {
+                labels
+                    .get(index)
+                    .cloned()
+                    .unwrap_or((index + 1).to_string())
+            })
+        });
+
+        self.entry_dialog(
+            "Go to page",
+            &prompt,
+            current.as_deref(),
+            "Go",
+            move |this, text| match find_page(&labels, page_count, text) {
+                Some(index) => {
+                    this.goto_page(Some(index));
+                    Ok(())
+                }
+                None => Err(format!("No page \"{}\"", text.trim())),
+            },
+        );
+    }
+}
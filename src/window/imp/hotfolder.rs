@@ -0,0 +1,128 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::path::PathBuf;
+
+use gio::{prelude::*, Cancellable, FileMonitor, FileMonitorEvent, FileMonitorFlags};
+use glib::{clone, subclass::types::ObjectSubclassExt};
+
+use crate::{
+    classification::{FileClassification, FileType},
+    file_view::{model::BackendRef, Target},
+    transfer::TransferMode,
+    util::path_to_filename,
+    window::imp::MViewWindowImp,
+};
+
+const KEEP_FOLDER: &str = "keep";
+const REJECT_FOLDER: &str = "reject";
+
+/// Folder (e.g. tethering output) that is watched for new images
+pub struct HotFolder {
+    directory: PathBuf,
+    monitor: FileMonitor,
+}
+
+impl MViewWindowImp {
+    fn hot_folder_directory(&self) -> Option<PathBuf> {
+        let hot_folder = self.hot_folder.borrow();
+        let directory = &hot_folder.as_ref()?.directory;
+        // Only when the watched folder is being shown
+        match self.backend.borrow().backend_ref() {
+            BackendRef::FileSystem(current) if current == *directory => Some(current),
+            _ => None,
+        }
+    }
+
+    /// Start or stop watching the current folder, showing each new image as it arrives
+    pub fn toggle_hot_folder(&self) {
+        if let Some(hot_folder) = self.hot_folder.take() {
+            hot_folder.monitor.cancel();
+            println!("Stopped watching {}", hot_folder.directory.display());
+            return;
+        }
+        let BackendRef::FileSystem(directory) = self.backend.borrow().backend_ref() else {
+            return;
+        };
+        let monitor = match gio::File::for_path(&directory)
+            .monitor_directory(FileMonitorFlags::WATCH_MOVES, None::<&Cancellable>)
+        {
+            Ok(monitor) => monitor,
+            Err(e) => {
+                eprintln!("Cannot watch {}: {e}", directory.display());
+                return;
+            }
+        };
+        monitor.connect_changed(clone!(
+            #[weak(rename_to = this)]
+            self,
+            move |_, file, _, event| this.on_hot_folder_event(file, event)
+        ));
+        println!("Watching {} (k = keep, j = reject)", directory.display());
+        self.hot_folder
+            .replace(Some(HotFolder { directory, monitor }));
+    }
+
+    fn on_hot_folder_event(&self, file: &gio::File, event: FileMonitorEvent) {
+        // Wait until the file is completely written, or moved in as a whole
+        if !matches!(
+            event,
+            FileMonitorEvent::ChangesDoneHint | FileMonitorEvent::MovedIn
+        ) {
+            return;
+        }
+        let Some(path) = file.path() else {
+            return;
+        };
+        let name = path_to_filename(&path);
+        if name.starts_with('.')
+            || FileClassification::determine(&path, false).file_type != FileType::Image
+        {
+            return;
+        }
+        if self.hot_folder_directory().is_some() {
+            self.reload(&Target::Name(name.to_string()));
+        }
+    }
+
+    /// Move the current image of the watched folder to the keep or reject subfolder and
+    /// continue with the next one
+    pub fn hot_folder_review(&self, keep: bool) {
+        let Some(directory) = self.hot_folder_directory() else {
+            return;
+        };
+        let w = self.widgets();
+        let Some(current) = w.file_view.current() else {
+            return;
+        };
+        if current.content() == FileType::Folder {
+            return;
+        }
+        let name = current.name();
+        let next = current.next().then(|| current.name());
+
+        let folder = directory.join(if keep { KEEP_FOLDER } else { REJECT_FOLDER });
+        self.transfer_files(
+            &[directory.join(&name)],
+            &folder,
+            TransferMode::Move,
+            next.map(Target::Name).unwrap_or(Target::Last),
+        );
+    }
+}
@@ -0,0 +1,241 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use glib::{clone, subclass::types::ObjectSubclassExt};
+use gtk4::{
+    gdk::{DragAction, FileList, Texture},
+    prelude::*,
+    DropTarget, FileChooserAction, FileChooserDialog, MessageType, ResponseType,
+};
+
+use crate::{
+    backends::extract::session_folder,
+    classification::{
+        detect::{extension_from_data, format_from_extension},
+        file_formats::FileFormat,
+    },
+    util::{path_to_filename, url_to_filename},
+    window::imp::MViewWindowImp,
+};
+
+/// Folder in the session directory for images dropped from other applications
+const IMPORT_FOLDER: &str = "import";
+
+/// Largest download accepted from a dropped URL
+const MAX_IMPORT_SIZE: usize = 100 << 20;
+
+const CHUNK_SIZE: usize = 1 << 16;
+
+/// Unused file name in the import folder, with an image extension matching the content
+fn import_path(folder: &Path, name: &str, data: &[u8]) -> PathBuf {
+    let mut name = if name.is_empty() {
+        "dropped".to_string()
+    } else {
+        name.to_string()
+    };
+    let extension = name
+        .rsplit_once('.')
+        .map(|(_, ext)| ext)
+        .unwrap_or_default();
+    if !matches!(format_from_extension(extension), FileFormat::Image(_)) {
+        if let Some(extension) = extension_from_data(data) {
+            name = format!("{name}.{extension}");
+        }
+    }
+    let mut path = folder.join(&name);
+    let mut counter = 1;
+    while path.exists() {
+        path = folder.join(format!("{counter}-{name}"));
+        counter += 1;
+    }
+    path
+}
+
+/// Contents of `file`, refusing files larger than `MAX_IMPORT_SIZE`
+async fn download(file: &gio::File) -> Result<Vec<u8>, String> {
+    let stream = file
+        .read_future(glib::Priority::DEFAULT)
+        .await
+        .map_err(|e| e.to_string())?;
+    let mut data = Vec::new();
+    loop {
+        let chunk = stream
+            .read_bytes_future(CHUNK_SIZE, glib::Priority::DEFAULT)
+            .await
+            .map_err(|e| e.to_string())?;
+        if chunk.is_empty() {
+            return Ok(data);
+        }
+        if data.len() + chunk.len() > MAX_IMPORT_SIZE {
+            return Err(format!("larger than {} MB", MAX_IMPORT_SIZE >> 20));
+        }
+        data.extend_from_slice(&chunk);
+    }
+}
+
+impl MViewWindowImp {
+    /// Accept files, image URLs and image data dragged from other applications (e.g. a
+    /// web browser) onto the window
+    pub(super) fn add_drop_target(&self) {
+        let drop_target = DropTarget::new(glib::Type::INVALID, DragAction::COPY);
+        drop_target.set_types(&[
+            FileList::static_type(),
+            Texture::static_type(),
+            String::static_type(),
+        ]);
+        drop_target.connect_drop(clone!(
+            #[weak(rename_to = this)]
+            self,
+            #[upgrade_or]
+            false,
+            move |_, value, _, _| this.on_drop(value)
+        ));
+        self.obj().add_controller(drop_target);
+    }
+
+    fn on_drop(&self, value: &glib::Value) -> bool {
+        if let Ok(files) = value.get::<FileList>() {
+            let Some(file) = files.files().into_iter().next() else {
+                return false;
+            };
+            match file.path() {
+                Some(path) => self.navigate_to(&path),
+                None => self.import_uri(file),
+            }
+            true
+        } else if let Ok(texture) = value.get::<Texture>() {
+            self.import_texture(&texture);
+            true
+        } else if let Ok(text) = value.get::<String>() {
+            // text/uri-list or a single URL as plain text
+            let Some(uri) = text
+                .lines()
+                .map(str::trim)
+                .find(|line| !line.is_empty() && !line.starts_with('#'))
+            else {
+                return false;
+            };
+            if !uri.contains("://") {
+                return false;
+            }
+            let file = gio::File::for_uri(uri);
+            match file.path() {
+                Some(path) => self.navigate_to(&path),
+                None => self.import_uri(file),
+            }
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Download in the background and show the image as soon as it has arrived
+    fn import_uri(&self, file: gio::File) {
+        let uri = file.uri().to_string();
+        println!("Importing {uri}");
+        glib::spawn_future_local(clone!(
+            #[weak(rename_to = this)]
+            self,
+            async move {
+                match download(&file).await {
+                    Ok(data) => this.import_data(&url_to_filename(&uri), &data),
+                    Err(e) => this.show_message(
+                        MessageType::Error,
+                        "Import image",
+                        &format!("Cannot download {uri}: {e}"),
+                    ),
+                }
+            }
+        ));
+    }
+
+    fn import_texture(&self, texture: &Texture) {
+        let bytes = texture.save_to_png_bytes();
+        self.import_data("", &bytes);
+    }
+
+    fn import_data(&self, name: &str, data: &[u8]) {
+        let result = session_folder(IMPORT_FOLDER).and_then(|folder| {
+            let path = import_path(&folder, name, data);
+            fs::write(&path, data).map(|_| path)
+        });
+        match result {
+            Ok(path) => {
+                self.navigate_to(&path);
+                self.last_import.replace(Some(path));
+            }
+            Err(e) => self.show_message(
+                MessageType::Error,
+                "Import image",
+                &format!("Cannot store the dropped image: {e}"),
+            ),
+        }
+    }
+
+    /// Keep the last image dropped from another application by saving it outside the
+    /// temporary import folder
+    pub fn save_import(&self) {
+        let Some(source) = self.last_import.borrow().clone() else {
+            self.show_message(
+                MessageType::Info,
+                "Save dropped image",
+                "No image has been dropped onto the window",
+            );
+            return;
+        };
+
+        let dialog = FileChooserDialog::new(
+            Some("Save dropped image"),
+            Some(&self.obj().clone()),
+            FileChooserAction::Save,
+            &[
+                ("Cancel", ResponseType::Cancel),
+                ("Save", ResponseType::Accept),
+            ],
+        );
+        dialog.set_current_name(&path_to_filename(&source));
+
+        dialog.connect_response(clone!(
+            #[weak(rename_to = this)]
+            self,
+            move |dialog, response| {
+                if response == ResponseType::Accept {
+                    if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                        match fs::copy(&source, &path) {
+                            Ok(_) => this.navigate_to(&path),
+                            Err(e) => this.show_message(
+                                MessageType::Error,
+                                "Save dropped image",
+                                &format!("Cannot write {}: {e}", path.display()),
+                            ),
+                        }
+                    }
+                }
+                dialog.destroy();
+            }
+        ));
+
+        dialog.show();
+    }
+}
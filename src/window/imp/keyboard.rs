@@ -31,12 +31,18 @@ use crate::{
     content::{Content, ContentData},
     file_view::{Column, Direction, Filter, Target},
     image::view::ZoomMode,
-    window::imp::palette::CommandPalette,
+    metadata::label::ColorLabel,
+    window::imp::{autoscroll::WPM_STEP, palette::CommandPalette},
 };
 
 impl MViewWindowImp {
     pub(super) fn on_key_press(&self, key: Key, modifiers: ModifierType) {
         let w = self.widgets();
+        if self.is_auto_scroll_active()
+            && !matches!(key, Key::F5 | Key::bracketleft | Key::bracketright)
+        {
+            self.stop_auto_scroll();
+        }
         match key {
             Key::q => {
                 self.quit();
@@ -44,6 +50,12 @@ impl MViewWindowImp {
             Key::h => {
                 self.show_help();
             }
+            Key::F1 | Key::question => {
+                self.show_keyboard_shortcuts();
+            }
+            Key::percent => {
+                self.toggle_zoom_info();
+            }
             Key::d => {
                 self.show_files_widget(true);
                 if !self.backend.borrow().is_bookmarks() {
@@ -56,8 +68,36 @@ impl MViewWindowImp {
                     self.set_backend(<dyn Backend>::bookmarks(backend, target), &Target::First);
                 }
             }
+            Key::D => {
+                self.toggle_diff();
+            }
+            Key::c => {
+                self.add_to_collection();
+            }
+            Key::C => {
+                self.toggle_collection();
+            }
+            Key::E => {
+                self.edit_in_external_editor();
+            }
+            Key::W => {
+                self.toggle_hot_folder();
+            }
+            Key::k => {
+                self.hot_folder_review(true);
+            }
+            Key::j => {
+                self.hot_folder_review(false);
+            }
+            Key::T if modifiers.contains(ModifierType::CONTROL_MASK) => {
+                self.reopen_closed();
+            }
             Key::t => {
-                self.toggle_thumbnail_view();
+                if modifiers.contains(ModifierType::CONTROL_MASK) {
+                    self.open_terminal();
+                } else {
+                    self.toggle_thumbnail_view();
+                }
             }
             Key::w | Key::KP_7 | Key::KP_Home => {
                 self.hop(Direction::Up);
@@ -77,11 +117,17 @@ impl MViewWindowImp {
             Key::F => {
                 self.filter_dialog();
             }
+            Key::g => {
+                if modifiers.contains(ModifierType::CONTROL_MASK) {
+                    self.goto_page_dialog();
+                }
+            }
             Key::Escape => {
                 self.obj().unfullscreen();
                 self.fullscreen.set(false);
                 self.widgets().set_action_bool("fullscreen", false);
                 w.image_view.measure_enable(false);
+                w.image_view.reading_ruler_enable(false);
             }
             Key::r => {
                 self.rotate_image(270);
@@ -106,30 +152,10 @@ impl MViewWindowImp {
                 self.toggle_zoom();
             }
             Key::minus | Key::KP_Subtract => {
-                w.file_view.set_unsorted();
-                if let Some(current) = w.file_view.current() {
-                    if self
-                        .backend
-                        .borrow()
-                        .set_preference(&current, Direction::Down)
-                    {
-                        w.file_view
-                            .navigate_item(Direction::Down, &Filter::Image, 1);
-                    }
-                }
+                self.rate_current(Direction::Down);
             }
             Key::equal | Key::KP_Add => {
-                w.file_view.set_unsorted();
-                if let Some(current) = w.file_view.current() {
-                    if self
-                        .backend
-                        .borrow()
-                        .set_preference(&current, Direction::Up)
-                    {
-                        w.file_view
-                            .navigate_item(Direction::Down, &Filter::Image, 1);
-                    }
-                }
+                self.rate_current(Direction::Up);
             }
             Key::a => {
                 w.file_view.navigate_item(Direction::Up, &Filter::Liked, 1);
@@ -138,6 +164,42 @@ impl MViewWindowImp {
                 w.file_view
                     .navigate_item(Direction::Down, &Filter::Liked, 1);
             }
+            Key::Up
+                if modifiers.contains(ModifierType::CONTROL_MASK)
+                    && self.backend.borrow().is_collection() =>
+            {
+                self.move_in_collection(Direction::Up);
+            }
+            Key::Down
+                if modifiers.contains(ModifierType::CONTROL_MASK)
+                    && self.backend.borrow().is_collection() =>
+            {
+                self.move_in_collection(Direction::Down);
+            }
+            Key::Delete if modifiers.contains(ModifierType::SHIFT_MASK) => {
+                self.toggle_staging();
+            }
+            Key::Delete if self.backend.borrow().is_collection() => {
+                self.remove_from_collection();
+            }
+            Key::Delete => {
+                self.stage_current();
+            }
+            Key::z
+                if modifiers.contains(ModifierType::CONTROL_MASK)
+                    && self.backend.borrow().is_staging() =>
+            {
+                self.restore_staged();
+            }
+            Key::z if modifiers.contains(ModifierType::CONTROL_MASK) => {
+                self.undo_delete();
+            }
+            Key::Up if w.image_view.reading_ruler_active() => {
+                w.image_view.reading_ruler_step(-1);
+            }
+            Key::Down if w.image_view.reading_ruler_active() => {
+                w.image_view.reading_ruler_step(1);
+            }
             Key::Up | Key::z => {
                 w.file_view.navigate_item(
                     Direction::Up,
@@ -175,14 +237,25 @@ impl MViewWindowImp {
                     .navigate_item(Direction::Down, &self.current_filter.borrow(), 25);
             }
             Key::Home => {
-                self.reload(&Target::First);
+                if modifiers.contains(ModifierType::CONTROL_MASK) {
+                    self.goto_page(Some(0));
+                } else {
+                    self.reload(&Target::First);
+                }
             }
             Key::End => {
-                self.reload(&Target::Last);
+                if modifiers.contains(ModifierType::CONTROL_MASK) {
+                    self.goto_page(None);
+                } else {
+                    self.reload(&Target::Last);
+                }
             }
             Key::F2 => {
                 self.measure_toggle();
             }
+            Key::F4 => {
+                self.toggle_reading_ruler();
+            }
             Key::Tab => {
                 self.measure_move_endpoints();
                 // // set reference
@@ -210,6 +283,15 @@ impl MViewWindowImp {
             //     // );
             //     // dbg!(img, reference, delta);
             // }
+            Key::F5 => {
+                self.toggle_auto_scroll();
+            }
+            Key::bracketleft => {
+                self.change_reading_speed(-WPM_STEP);
+            }
+            Key::bracketright => {
+                self.change_reading_speed(WPM_STEP);
+            }
             Key::F6 => {
                 contrast_delta(-1);
                 dbg!(contrast());
@@ -222,6 +304,9 @@ impl MViewWindowImp {
             Key::F8 => {
                 self.toggle_pdf_engine();
             }
+            Key::F9 => {
+                self.toggle_color_lut();
+            }
             Key::_1 => {
                 self.change_sort(Column::ContentType, &w.file_view);
             }
@@ -234,6 +319,21 @@ impl MViewWindowImp {
             Key::_4 => {
                 self.change_sort(Column::Modified, &w.file_view);
             }
+            Key::_5 => {
+                self.toggle_color_label(ColorLabel::Purple);
+            }
+            Key::_6 => {
+                self.toggle_color_label(ColorLabel::Red);
+            }
+            Key::_7 => {
+                self.toggle_color_label(ColorLabel::Yellow);
+            }
+            Key::_8 => {
+                self.toggle_color_label(ColorLabel::Green);
+            }
+            Key::_9 => {
+                self.toggle_color_label(ColorLabel::Blue);
+            }
             Key::p => {
                 match self.page_mode.get() {
                     PageMode::DualEvenOdd => self.change_page_mode(PageMode::Single.into()),
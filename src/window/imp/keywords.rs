@@ -0,0 +1,223 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    collections::{BTreeSet, HashSet},
+    path::Path,
+};
+
+use glib::{clone, subclass::types::ObjectSubclassExt, Propagation};
+use gtk4::{
+    prelude::*, Box, Dialog, Entry, EntryCompletion, Label, ListStore, Orientation, ResponseType,
+    Widget,
+};
+
+use crate::{
+    classification::FileType,
+    file_view::{
+        model::{BackendRef, Row},
+        Target,
+    },
+    metadata::keywords::{read_keywords, split_keywords, write_keywords},
+    window::imp::MViewWindowImp,
+};
+
+/// The keyword being typed: the text after the last comma
+fn last_keyword(text: &str) -> &str {
+    text.rsplit(',').next().unwrap_or_default().trim_start()
+}
+
+/// Completion of the last keyword of a comma separated list, based on the known keywords
+pub fn keyword_completion(model: &ListStore) -> EntryCompletion {
+    let completion = EntryCompletion::new();
+    completion.set_model(Some(model));
+    completion.set_text_column(0);
+    completion.set_minimum_key_length(1);
+    completion.set_match_func(|completion, key, iter| {
+        let prefix = last_keyword(key);
+        !prefix.is_empty()
+            && completion
+                .model()
+                .and_then(|model| model.get_value(iter, 0).get::<String>().ok())
+                .is_some_and(|keyword| keyword.to_lowercase().starts_with(prefix))
+    });
+    completion.connect_match_selected(|completion, model, iter| {
+        if let (Some(entry), Ok(keyword)) =
+            (completion.entry(), model.get_value(iter, 0).get::<String>())
+        {
+            let text = entry.text();
+            let head = match text.rfind(',') {
+                Some(pos) => format!("{}, ", &text[..pos]),
+                None => String::new(),
+            };
+            entry.set_text(&format!("{head}{keyword}, "));
+            entry.set_position(-1);
+        }
+        Propagation::Stop
+    });
+    completion
+}
+
+impl MViewWindowImp {
+    /// Remember keywords that were read for autocompletion
+    fn add_known_keywords(&self, keywords: &[String]) {
+        let mut known = self.known_keywords.borrow_mut();
+        let count = known.len();
+        known.extend(keywords.iter().cloned());
+        if known.len() != count {
+            self.update_keyword_completion(&known);
+        }
+    }
+
+    /// Names of the files in `directory` tagged with `keyword`. The sidecars are only read
+    /// when the keyword filter is active.
+    pub(super) fn tagged_files(
+        &self,
+        directory: &Path,
+        rows: &[Row],
+        keyword: &str,
+    ) -> HashSet<String> {
+        let mut tagged = HashSet::new();
+        let mut found = Vec::new();
+        for row in rows {
+            if row.content_type == FileType::Folder.id() {
+                continue;
+            }
+            let keywords = read_keywords(&directory.join(&row.name));
+            if keywords.iter().any(|k| k == keyword) {
+                tagged.insert(row.name.clone());
+            }
+            found.extend(keywords);
+        }
+        self.add_known_keywords(&found);
+        tagged
+    }
+
+    fn update_keyword_completion(&self, known: &BTreeSet<String>) {
+        let model = &self.widgets().keyword_model;
+        model.clear();
+        for keyword in known {
+            model.insert_with_values(None, &[(0, keyword)]);
+        }
+    }
+
+    /// Show the keywords of the current file; keywords can only be edited for files in a folder
+    pub(super) fn update_keyword_entry(&self) {
+        let w = self.widgets();
+        let directory = match self.backend.borrow().backend_ref() {
+            BackendRef::FileSystem(directory) => Some(directory),
+            _ => None,
+        };
+        match (directory, w.file_view.current()) {
+            (Some(directory), Some(current)) if current.content() != FileType::Folder => {
+                let keywords = read_keywords(&directory.join(current.name()));
+                w.keyword_entry.set_text(&keywords.join(", "));
+                w.keyword_entry.set_sensitive(true);
+                self.add_known_keywords(&keywords);
+            }
+            _ => {
+                w.keyword_entry.set_text("");
+                w.keyword_entry.set_sensitive(false);
+            }
+        }
+    }
+
+    /// Write the keywords of the entry to the sidecar of the current file
+    pub(super) fn save_keywords(&self) {
+        let w = self.widgets();
+        let BackendRef::FileSystem(directory) = self.backend.borrow().backend_ref() else {
+            return;
+        };
+        let Some(current) = w.file_view.current() else {
+            return;
+        };
+        let keywords = split_keywords(&w.keyword_entry.text());
+        let path = directory.join(current.name());
+        match write_keywords(&path, &keywords) {
+            Ok(()) => {
+                w.keyword_entry.set_text(&keywords.join(", "));
+                self.add_known_keywords(&keywords);
+            }
+            Err(e) => eprintln!("Failed to write keywords of {path:?}: {e}"),
+        }
+        // Return the keyboard to the shortcuts
+        self.obj().set_focus(None::<&Widget>);
+    }
+
+    /// Only show the files tagged with a keyword (or all files when no keyword is given)
+    pub fn keyword_filter_dialog(&self) {
+        let dialog = Dialog::builder()
+            .title("Filter on keyword")
+            .modal(true)
+            .transient_for(&self.obj().clone())
+            .default_width(360)
+            .build();
+
+        let vbox = Box::builder()
+            .orientation(Orientation::Vertical)
+            .spacing(8)
+            .margin_start(12)
+            .margin_end(12)
+            .margin_top(12)
+            .margin_bottom(12)
+            .build();
+
+        let label = Label::new(Some("Keyword (leave empty to show all files)"));
+        label.set_xalign(0.0);
+        let entry = Entry::new();
+        entry.set_text(self.keyword_filter.borrow().as_deref().unwrap_or_default());
+        entry.set_activates_default(true);
+        let completion = EntryCompletion::new();
+        completion.set_model(Some(&self.widgets().keyword_model));
+        completion.set_text_column(0);
+        entry.set_completion(Some(&completion));
+
+        vbox.append(&label);
+        vbox.append(&entry);
+        dialog.content_area().append(&vbox);
+
+        dialog.add_button("Cancel", ResponseType::Cancel);
+        dialog.add_button("OK", ResponseType::Ok);
+        dialog.set_default_response(ResponseType::Ok);
+
+        dialog.connect_response(clone!(
+            #[weak(rename_to = this)]
+            self,
+            #[weak]
+            entry,
+            move |dialog, response| {
+                if response == ResponseType::Ok {
+                    let keyword = entry.text().trim().to_string();
+                    this.keyword_filter
+                        .replace((!keyword.is_empty()).then_some(keyword));
+                    let target = this
+                        .widgets()
+                        .file_view
+                        .current()
+                        .map(|current| Target::Name(current.name()))
+                        .unwrap_or(Target::First);
+                    this.reload(&target);
+                }
+                dialog.close();
+            }
+        ));
+
+        dialog.present();
+    }
+}
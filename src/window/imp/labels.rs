@@ -0,0 +1,60 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{
+    classification::FileType,
+    file_view::{model::BackendRef, Target},
+    metadata::label::{file_label, write_label, ColorLabel},
+    window::imp::MViewWindowImp,
+};
+
+impl MViewWindowImp {
+    /// Give the current file a color label, or remove it when it already has that label
+    pub fn toggle_color_label(&self, label: ColorLabel) {
+        let w = self.widgets();
+        let BackendRef::FileSystem(directory) = self.backend.borrow().backend_ref() else {
+            return;
+        };
+        let Some(current) = w.file_view.current() else {
+            return;
+        };
+        if current.content() == FileType::Folder {
+            return;
+        }
+        let path = directory.join(current.name());
+        let label = (file_label(&path) != Some(label)).then_some(label);
+        match write_label(&path, label) {
+            Ok(()) => w.file_view.queue_draw(),
+            Err(e) => eprintln!("Failed to write color label of {path:?}: {e}"),
+        }
+    }
+
+    /// Only show the files with a color label, `none` shows all files
+    pub fn change_label_filter(&self, label: &str) {
+        let w = self.widgets();
+        w.set_action_string("label.filter", label);
+        self.label_filter.set(label.parse().ok());
+        let target = w
+            .file_view
+            .current()
+            .map(|current| Target::Name(current.name()))
+            .unwrap_or(Target::First);
+        self.reload(&target);
+    }
+}
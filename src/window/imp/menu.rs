@@ -17,8 +17,10 @@
 // STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
 // OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use gio::{prelude::ActionMapExt, Menu, SimpleAction, SimpleActionGroup};
-use glib::VariantTy;
+use gio::{prelude::ActionMapExt, Menu, MenuItem, SimpleAction, SimpleActionGroup};
+use glib::{prelude::ToVariant, VariantTy};
+
+use crate::{content::encoding::ENCODINGS, metadata::label::ColorLabel};
 
 use super::MViewWindowImp;
 
@@ -29,6 +31,26 @@ impl MViewWindowImp {
 
         let top_section = Menu::new();
         top_section.append(Some("Open"), Some("win.open"));
+        top_section.append(Some("Reopen closed"), Some("win.reopen"));
+        top_section.append(Some("Open terminal here"), Some("win.terminal"));
+        top_section.append(Some("Edit in external editor"), Some("win.edit"));
+        top_section.append(Some("Copy to folder..."), Some("win.transfer.copy"));
+        top_section.append(Some("Move to folder..."), Some("win.transfer.move"));
+        top_section.append(Some("Save dropped image..."), Some("win.import.save"));
+
+        let collection_submenu = Menu::new();
+        collection_submenu.append(Some("Add current file"), Some("win.collection.add"));
+        collection_submenu.append(Some("Show collection"), Some("win.collection.show"));
+        collection_submenu.append(Some("Export as playlist..."), Some("win.collection.export"));
+
+        let staging_submenu = Menu::new();
+        staging_submenu.append(Some("Show review trash"), Some("win.staging.show"));
+        staging_submenu.append(Some("Restore selected file"), Some("win.staging.restore"));
+        staging_submenu.append(Some("Empty review trash..."), Some("win.staging.empty"));
+
+        let usage_submenu = Menu::new();
+        usage_submenu.append(Some("Record views"), Some("win.usage.record"));
+        usage_submenu.append(Some("Most viewed"), Some("win.usage.most"));
 
         let zoom_submenu = Menu::new();
         zoom_submenu.append(Some("No scaling"), Some("win.zoom::nozoom"));
@@ -51,8 +73,16 @@ impl MViewWindowImp {
         page_section.append(Some("Dual (1, 2-3, 4-5, ...)"), Some("win.page::deo"));
         page_section.append(Some("Dual (1-2, 3-4, 5-6, ...)"), Some("win.page::doe"));
 
+        let tint_section = Menu::new();
+        tint_section.append(Some("None"), Some("win.tint::none"));
+        tint_section.append(Some("Sepia"), Some("win.tint::sepia"));
+        tint_section.append(Some("Solarized"), Some("win.tint::solarized"));
+        tint_section.append(Some("Dark"), Some("win.tint::dark"));
+
         let pdf_submenu = Menu::new();
         pdf_submenu.append_section(Some("Page mode"), &page_section);
+        pdf_submenu.append_section(Some("Page tint"), &tint_section);
+        pdf_submenu.append(Some("Export pages..."), Some("win.pdf.export"));
 
         #[cfg(feature = "mupdf")]
         {
@@ -63,6 +93,29 @@ impl MViewWindowImp {
             pdf_submenu.append_section(Some("PDF backend"), &pdf_provider_section);
         }
 
+        let encoding_submenu = Menu::new();
+        for &(name, label) in ENCODINGS {
+            let item = MenuItem::new(Some(label), None);
+            item.set_action_and_target_value(Some("win.encoding"), Some(&name.to_variant()));
+            encoding_submenu.append_item(&item);
+        }
+
+        let color_submenu = Menu::new();
+        color_submenu.append(Some("Apply monitor LUT"), Some("win.color.lut"));
+        color_submenu.append(
+            Some("Load LUT for this monitor..."),
+            Some("win.color.lut.load"),
+        );
+
+        let proof_section = Menu::new();
+        proof_section.append(Some("Soft-proof"), Some("win.color.proof"));
+        proof_section.append(Some("Gamut warning"), Some("win.color.gamut"));
+        proof_section.append(
+            Some("Select printer profile..."),
+            Some("win.color.proof.select"),
+        );
+        color_submenu.append_section(Some("Print preview"), &proof_section);
+
         let panes_submenu = Menu::new();
         panes_submenu.append(Some("Files"), Some("win.pane.files"));
         panes_submenu.append(Some("Information"), Some("win.pane.info"));
@@ -90,19 +143,49 @@ impl MViewWindowImp {
         slideshow_submentu.append(Some("Run slideshow"), Some("win.slideshow.active"));
         slideshow_submentu.append_section(Some("Interval"), &slideshow_interval_submenu);
 
+        let rating_submenu = Menu::new();
+        rating_submenu.append(Some("Advance after rating"), Some("win.rate.advance"));
+        rating_submenu.append(Some("Summary of this folder"), Some("win.rate.summary"));
+        rating_submenu.append(Some("Culling report..."), Some("win.rate.report"));
+
+        let label_filter_section = Menu::new();
+        label_filter_section.append(Some("All files"), Some("win.label.filter::none"));
+        for label in ColorLabel::ALL {
+            let item = MenuItem::new(Some(label.name()), None);
+            item.set_action_and_target_value(
+                Some("win.label.filter"),
+                Some(&label.name().to_lowercase().to_variant()),
+            );
+            label_filter_section.append_item(&item);
+        }
+        rating_submenu.append_section(Some("Show color label"), &label_filter_section);
+
         let flag_section = Menu::new();
         flag_section.append(Some("Full screen"), Some("win.fullscreen"));
+        flag_section.append(Some("Preview server"), Some("win.preview.server"));
         flag_section.append_submenu(Some("Slideshow"), &slideshow_submentu);
         flag_section.append_submenu(Some("Thumbnails"), &thumbnail_submenu);
+        flag_section.append_submenu(Some("Rating"), &rating_submenu);
+        flag_section.append_submenu(Some("Collection"), &collection_submenu);
+        flag_section.append_submenu(Some("Review trash"), &staging_submenu);
+        flag_section.append_submenu(Some("Usage statistics"), &usage_submenu);
         flag_section.append_submenu(Some("Rotate"), &rotate_submenu);
         flag_section.append_submenu(Some("Zoom"), &zoom_submenu);
         flag_section.append_submenu(Some("Transparency"), &transparency_submenu);
+        flag_section.append_submenu(Some("Color"), &color_submenu);
         flag_section.append_submenu(Some("PDF"), &pdf_submenu);
+        flag_section.append_submenu(Some("Text encoding"), &encoding_submenu);
         flag_section.append_submenu(Some("Panes"), &panes_submenu);
 
+        let preferences_submenu = Menu::new();
+        preferences_submenu.append(Some("Export..."), Some("win.preferences.export"));
+        preferences_submenu.append(Some("Import..."), Some("win.preferences.import"));
+
         let bottom_section = Menu::new();
+        bottom_section.append_submenu(Some("Preferences"), &preferences_submenu);
         bottom_section.append(Some("About"), Some("win.about"));
         bottom_section.append(Some("Help"), Some("win.help"));
+        bottom_section.append(Some("Keyboard shortcuts"), Some("win.shortcuts"));
         bottom_section.append(Some("Quit"), Some("win.quit"));
 
         main_menu.append_section(None, &top_section);
@@ -115,10 +198,47 @@ impl MViewWindowImp {
     pub fn setup_actions(&self) -> SimpleActionGroup {
         let action_group = SimpleActionGroup::new();
         self.add_action(&action_group, "open", Self::open_file);
+        self.add_action(&action_group, "terminal", Self::open_terminal);
+        self.add_action(&action_group, "edit", Self::edit_in_external_editor);
+        self.add_action(&action_group, "import.save", Self::save_import);
+        self.add_action(&action_group, "collection.add", Self::add_to_collection);
+        self.add_action(&action_group, "collection.show", Self::toggle_collection);
+        self.add_action(&action_group, "collection.export", Self::export_collection);
+        self.add_action(&action_group, "reopen", Self::reopen_closed);
+        self.add_action(
+            &action_group,
+            "preferences.export",
+            Self::export_preferences,
+        );
+        self.add_action(
+            &action_group,
+            "preferences.import",
+            Self::import_preferences,
+        );
+        self.add_action(&action_group, "pdf.export", Self::export_page_range);
+        self.add_action(&action_group, "transfer.copy", Self::copy_to_folder);
+        self.add_action(&action_group, "transfer.move", Self::move_to_folder);
+        self.add_action(&action_group, "staging.show", Self::toggle_staging);
+        self.add_action(&action_group, "staging.restore", Self::restore_staged);
+        self.add_action(&action_group, "staging.empty", Self::empty_staging);
+        self.add_action_bool(
+            &action_group,
+            "usage.record",
+            self.usage.borrow().enabled,
+            Self::toggle_usage_stats,
+        );
+        self.add_action(&action_group, "usage.most", Self::toggle_most_viewed);
         self.add_action(&action_group, "about", Self::show_about_dialog);
         self.add_action(&action_group, "help", Self::show_help);
+        self.add_action(&action_group, "shortcuts", Self::show_keyboard_shortcuts);
         self.add_action(&action_group, "quit", Self::quit);
         self.add_action_bool(&action_group, "fullscreen", false, Self::toggle_fullscreen);
+        self.add_action_bool(
+            &action_group,
+            "preview.server",
+            false,
+            Self::toggle_preview_server,
+        );
         self.add_action_int(&action_group, "rotate", 0, Self::rotate_image);
         self.add_action_string(&action_group, "zoom", "fill", Self::change_zoom);
         self.add_action_string(
@@ -128,7 +248,28 @@ impl MViewWindowImp {
             Self::change_transparency,
         );
         self.add_action_string(&action_group, "page", "deo", Self::change_page_mode);
+        self.add_action_string(&action_group, "tint", "none", Self::change_page_tint);
+        self.add_action_string(
+            &action_group,
+            "encoding",
+            "UTF-8",
+            Self::change_text_encoding,
+        );
         self.add_action_string(&action_group, "pdf", "mupdf", Self::change_pdf_provider);
+        self.add_action_bool(&action_group, "color.lut", false, Self::toggle_color_lut);
+        self.add_action(&action_group, "color.lut.load", Self::load_color_lut);
+        self.add_action_bool(&action_group, "color.proof", false, Self::toggle_soft_proof);
+        self.add_action_bool(
+            &action_group,
+            "color.gamut",
+            false,
+            Self::toggle_gamut_warning,
+        );
+        self.add_action(
+            &action_group,
+            "color.proof.select",
+            Self::select_printer_profile,
+        );
         self.add_action_bool(&action_group, "pane.files", true, Self::toggle_pane_files);
         self.add_action_bool(&action_group, "pane.info", false, Self::toggle_pane_info);
         self.add_action_bool(
@@ -144,6 +285,20 @@ impl MViewWindowImp {
             false,
             Self::toggle_slideshow,
         );
+        self.add_action_bool(
+            &action_group,
+            "rate.advance",
+            true,
+            Self::toggle_rate_advance,
+        );
+        self.add_action(&action_group, "rate.summary", Self::show_rating_summary);
+        self.add_action(&action_group, "rate.report", Self::culling_report);
+        self.add_action_string(
+            &action_group,
+            "label.filter",
+            "none",
+            Self::change_label_filter,
+        );
         self.add_action_int(
             &action_group,
             "slideshow.interval",
@@ -0,0 +1,68 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use glib::clone;
+use gtk4::{prelude::*, Align};
+
+use super::MViewWindowImp;
+
+/// Below this width (e.g. when tiled to half of a laptop screen) the file list becomes a
+/// drawer over the image and the header bar is reduced
+const NARROW_WIDTH: i32 = 900;
+
+impl MViewWindowImp {
+    pub fn on_window_width(&self, width: i32) {
+        let narrow = width < NARROW_WIDTH;
+        if narrow != self.narrow.get() && self.widget_cell.get().is_some() {
+            self.narrow.set(narrow);
+            // Not while the window is being allocated
+            glib::idle_add_local_once(clone!(
+                #[weak(rename_to = this)]
+                self,
+                move || this.apply_narrow_layout(narrow)
+            ));
+        }
+    }
+
+    fn apply_narrow_layout(&self, narrow: bool) {
+        let w = self.widgets();
+        if narrow {
+            w.hbox.remove(&w.file_widget);
+            w.file_widget.set_halign(Align::Start);
+            w.file_widget.add_css_class("drawer");
+            w.panel.overlay.add_overlay(&w.file_widget);
+            // Starts closed, the image gets all the space
+            self.show_files_widget(false);
+        } else {
+            w.panel.overlay.remove_overlay(&w.file_widget);
+            w.file_widget.set_halign(Align::Fill);
+            w.file_widget.remove_css_class("drawer");
+            w.hbox.prepend(&w.file_widget);
+            self.show_files_widget(true);
+        }
+        w.back_button_top.set_visible(!narrow);
+        w.files_button_top.set_visible(narrow);
+        if narrow {
+            w.forward_button_top.set_visible(false);
+        } else {
+            w.forward_button_top.set_visible(w.panel.can_enter());
+        }
+        self.update_layout();
+    }
+}
@@ -28,13 +28,16 @@ use super::MViewWindowImp;
 
 use crate::{
     backends::{Backend, ImageParams},
-    classification::FileClassification,
-    file_view::{Direction, Filter, Target},
+    classification::{FileClassification, FileType},
+    file_view::{model::Progress, Column, Direction, Filter, Target, TreeModelMviewExt},
     util::path_to_filename,
     window::imp::TargetTime,
 };
 use glib::subclass::types::ObjectSubclassExt;
-use gtk4::{prelude::WidgetExt, TreePath, TreeViewColumn};
+use gtk4::{
+    prelude::{TreeModelExt, WidgetExt},
+    ListStore, TreePath, TreeViewColumn,
+};
 
 impl MViewWindowImp {
     pub(super) fn on_cursor_changed(&self) {
@@ -48,18 +51,29 @@ impl MViewWindowImp {
                     allocation_height: self.obj().height(),
                 };
                 let backend = self.backend.borrow();
+                let total = current.store.iter_n_children(None) as u32;
                 self.target_store.borrow_mut().insert(
                     backend.normalized_path(),
-                    TargetTime::new(&backend.reference(&current).into()),
+                    TargetTime::new(
+                        &backend.reference(&current).into(),
+                        Progress::new(current.position() as u32 + 1, total),
+                    ),
                 );
 
                 let reference = backend.reference(&current);
 
                 let mut content = backend.content(&reference.item, &params);
                 content.sort(&self.current_sort.get().str_repr());
+                content.set_table_view(!self.plain_text.get());
+                if let Some(scan_filters) =
+                    self.scan_filters.borrow().get(&backend.normalized_path())
+                {
+                    content.set_scan_filters(*scan_filters);
+                }
 
                 let can_enter = content.can_enter();
-                w.forward_button_top.set_visible(can_enter);
+                w.forward_button_top
+                    .set_visible(can_enter && !self.narrow.get());
                 w.panel.enable_enter(can_enter);
 
                 // if reference.supports_bot() {
@@ -73,7 +87,13 @@ impl MViewWindowImp {
                 //     };
                 //     w.rb_send(command);
                 // }
+                if let Some(encoding) = content.text_encoding() {
+                    w.set_action_string("encoding", encoding);
+                }
+
                 w.info_view.update(&content);
+                self.record_view(&**backend, &current);
+                self.update_keyword_entry();
                 if backend.is_thumbnail() {
                     w.image_view.set_content_pre(content);
                 } else {
@@ -137,6 +157,27 @@ impl MViewWindowImp {
         self.dir_enter();
     }
 
+    /// Show how far the archives and documents in a folder listing were read
+    pub(super) fn show_reading_progress(&self, store: &ListStore, directory: &Path) {
+        let target_store = self.target_store.borrow();
+        let Some(iter) = store.iter_first() else {
+            return;
+        };
+        loop {
+            if matches!(store.content(&iter), FileType::Archive | FileType::Document) {
+                if let Some(progress) = target_store
+                    .get(&directory.join(store.name(&iter)))
+                    .and_then(|tt| tt.progress)
+                {
+                    store.set(&iter, &[(Column::Progress as u32, &progress.to_string())]);
+                }
+            }
+            if !store.iter_next(&iter) {
+                break;
+            }
+        }
+    }
+
     fn navigation_cache_file(create_dir: bool) -> io::Result<PathBuf> {
         let mut path = dirs::config_dir().unwrap_or_default();
         path.push("mview6");
@@ -164,6 +205,7 @@ impl MViewWindowImp {
                     TargetTime {
                         target: v.target.clone(),
                         timestamp: v.timestamp,
+                        progress: v.progress,
                     },
                 )
             })
@@ -0,0 +1,136 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::path::{Path, PathBuf};
+
+use glib::{clone, subclass::types::ObjectSubclassExt};
+use gtk4::{prelude::*, FileChooserAction, FileChooserDialog, MessageType, ResponseType};
+
+use crate::{
+    backends::document::{parse_page_range, shown_pages, split_spreads},
+    error::MviewResult,
+    window::imp::MViewWindowImp,
+};
+
+#[cfg(feature = "mupdf")]
+fn export_pages(source: &Path, first: i32, last: i32, target: &Path) -> MviewResult<()> {
+    crate::backends::document::mupdf::export_pages(source, first, last, target)
+}
+
+#[cfg(not(feature = "mupdf"))]
+fn export_pages(_source: &Path, _first: i32, _last: i32, _target: &Path) -> MviewResult<()> {
+    crate::mview6_error!("Exporting pages needs MView6 built with MuPDF").into()
+}
+
+/// Suggested file name, e.g. `report-p3-4.pdf`
+fn export_name(source: &Path, first: i32, last: i32) -> String {
+    let stem = source.file_stem().unwrap_or_default().to_string_lossy();
+    if first == last {
+        format!("{stem}-p{}.pdf", first + 1)
+    } else {
+        format!("{stem}-p{}-{}.pdf", first + 1, last + 1)
+    }
+}
+
+impl MViewWindowImp {
+    /// Ask for a page range of the current document, by default the pages shown, and
+    /// save it as a new PDF
+    pub fn export_page_range(&self) {
+        let backend = self.backend.borrow();
+        if !backend.is_doc() {
+            return;
+        }
+        let Some(page_count) = backend.page_count() else {
+            return;
+        };
+        let source = backend.path();
+        drop(backend);
+        let page_count = page_count as i32;
+
+        let current = self.widgets().file_view.current().and_then(|current| {
+            let (first, last) = shown_pages(
+                current.index() as i32,
+                page_count,
+                &self.page_mode.get(),
+                split_spreads(),
+            );
+            (last < page_count).then(|| {
+                if first == last {
+                    (first + 1).to_string()
+                } else {
+                    format!("{}-{}", first + 1, last + 1)
+                }
+            })
+        });
+
+        self.entry_dialog(
+            "Export pages",
+            &format!("Pages to export, e.g. 3 or 3-7 (1-{page_count})"),
+            current.as_deref(),
+            "Export",
+            move |this, text| match parse_page_range(text, page_count) {
+                Some((first, last)) => {
+                    this.save_page_range(source.clone(), first, last);
+                    Ok(())
+                }
+                None => Err(format!("No page range \"{}\"", text.trim())),
+            },
+        );
+    }
+
+    fn save_page_range(&self, source: PathBuf, first: i32, last: i32) {
+        let dialog = FileChooserDialog::new(
+            Some("Export pages"),
+            Some(&self.obj().clone()),
+            FileChooserAction::Save,
+            &[
+                ("Cancel", ResponseType::Cancel),
+                ("Save", ResponseType::Accept),
+            ],
+        );
+        dialog.set_current_name(&export_name(&source, first, last));
+
+        dialog.connect_response(clone!(
+            #[weak(rename_to = this)]
+            self,
+            move |dialog, response| {
+                if response == ResponseType::Accept {
+                    if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                        match export_pages(&source, first, last, &path) {
+                            Ok(()) => println!(
+                                "Exported pages {}-{} to {}",
+                                first + 1,
+                                last + 1,
+                                path.display()
+                            ),
+                            Err(e) => this.show_message(
+                                MessageType::Error,
+                                "Export pages",
+                                &format!("Cannot write {}: {e}", path.display()),
+                            ),
+                        }
+                    }
+                }
+                dialog.destroy();
+            }
+        ));
+
+        dialog.show();
+    }
+}
@@ -0,0 +1,67 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{backends::document::PageMode, window::imp::MViewWindowImp};
+
+impl MViewWindowImp {
+    /// Choose the page mode for a newly opened document: the persisted override if
+    /// there is one, otherwise the global mode with spreads aligned to a cover page.
+    pub(super) fn apply_document_page_mode(&self) {
+        let backend = self.backend.borrow();
+        if !backend.is_doc() {
+            return;
+        }
+        let page_mode = match self
+            .document_settings
+            .borrow()
+            .get(&backend.normalized_path())
+            .and_then(|settings| settings.page_mode.as_deref())
+        {
+            Some(page_mode) => page_mode.into(),
+            None => match self.default_page_mode.get() {
+                PageMode::Single => PageMode::Single,
+                _ if backend.first_page_is_cover() => PageMode::DualEvenOdd,
+                page_mode => page_mode,
+            },
+        };
+        self.widgets().set_action_string("page", page_mode.into());
+        self.page_mode.set(page_mode);
+    }
+
+    /// Remember the page mode of the current document
+    pub(super) fn set_document_page_mode(&self, page_mode: PageMode) {
+        if !self.backend.borrow().is_doc() {
+            return;
+        }
+        self.update_document_settings(|settings| {
+            settings.page_mode = Some(<&str>::from(page_mode).to_string())
+        });
+        self.widgets().set_action_string("page", page_mode.into());
+        self.page_mode.set(page_mode);
+        self.on_cursor_changed();
+    }
+
+    /// Switch between showing the first page on its own (cover) or as part of a spread
+    pub fn toggle_cover_page(&self) {
+        match self.page_mode.get() {
+            PageMode::DualEvenOdd => self.set_document_page_mode(PageMode::DualOddEven),
+            _ => self.set_document_page_mode(PageMode::DualEvenOdd),
+        }
+    }
+}
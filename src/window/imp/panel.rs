@@ -67,6 +67,10 @@ impl Panel {
         }
     }
 
+    pub fn can_enter(&self) -> bool {
+        self.can_enter.get()
+    }
+
     pub fn enable_slideshow_mode(&self, enabled: bool) {
         if self.slideshow_mode.get() != enabled {
             self.slideshow_mode.set(enabled);
@@ -0,0 +1,159 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::path::{Path, PathBuf};
+
+use glib::{clone, subclass::types::ObjectSubclassExt};
+use gtk4::{
+    prelude::*, ButtonsType, DialogFlags, FileChooserAction, FileChooserDialog, FileFilter,
+    MessageDialog, MessageType, ResponseType,
+};
+
+use crate::{config, window::imp::MViewWindowImp};
+
+fn json_filter() -> FileFilter {
+    let filter = FileFilter::new();
+    filter.set_name(Some("Preferences (*.json)"));
+    filter.add_pattern("*.json");
+    filter
+}
+
+impl MViewWindowImp {
+    /// Save the settings, bookmarks, document settings and collection to a single file
+    pub fn export_preferences(&self) {
+        // The document settings are only written when closing
+        if let Err(e) = self.save_document_settings() {
+            eprintln!("Failed to save the document settings: {e}");
+        }
+
+        let dialog = FileChooserDialog::new(
+            Some("Export preferences"),
+            Some(&self.obj().clone()),
+            FileChooserAction::Save,
+            &[
+                ("Cancel", ResponseType::Cancel),
+                ("Save", ResponseType::Accept),
+            ],
+        );
+        dialog.add_filter(&json_filter());
+        dialog.set_current_name("mview6-preferences.json");
+
+        dialog.connect_response(clone!(
+            #[weak(rename_to = this)]
+            self,
+            move |dialog, response| {
+                if response == ResponseType::Accept {
+                    if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                        if let Err(e) = config::export_preferences(&path) {
+                            this.show_message(
+                                MessageType::Error,
+                                "Export preferences",
+                                &format!("Cannot write {}: {e}", path.display()),
+                            );
+                        }
+                    }
+                }
+                dialog.destroy();
+            }
+        ));
+
+        dialog.show();
+    }
+
+    /// Replace the preferences by the ones of an exported file
+    pub fn import_preferences(&self) {
+        let dialog = FileChooserDialog::new(
+            Some("Import preferences"),
+            Some(&self.obj().clone()),
+            FileChooserAction::Open,
+            &[
+                ("Cancel", ResponseType::Cancel),
+                ("Open", ResponseType::Accept),
+            ],
+        );
+        dialog.add_filter(&json_filter());
+
+        dialog.connect_response(clone!(
+            #[weak(rename_to = this)]
+            self,
+            move |dialog, response| {
+                if response == ResponseType::Accept {
+                    if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                        this.confirm_import(path);
+                    }
+                }
+                dialog.destroy();
+            }
+        ));
+
+        dialog.show();
+    }
+
+    fn confirm_import(&self, path: PathBuf) {
+        let dialog = MessageDialog::new(
+            Some(&self.obj().clone()),
+            DialogFlags::MODAL,
+            MessageType::Warning,
+            ButtonsType::None,
+            "Import preferences",
+        );
+        dialog.set_secondary_text(Some(
+            "The current settings, bookmarks, document settings and collection are replaced by the imported ones.",
+        ));
+        dialog.add_button("Cancel", ResponseType::Cancel);
+        dialog.add_button("Import", ResponseType::Accept);
+
+        dialog.connect_response(clone!(
+            #[weak(rename_to = this)]
+            self,
+            move |dialog, response| {
+                dialog.close();
+                if response == ResponseType::Accept {
+                    this.install_preferences(&path);
+                }
+            }
+        ));
+
+        dialog.present();
+    }
+
+    fn install_preferences(&self, path: &Path) {
+        match config::import_preferences(path) {
+            Ok(files) => {
+                // Otherwise overwritten by the ones in memory when closing
+                if let Err(e) = self.load_document_settings() {
+                    eprintln!("Failed to load the document settings: {e}");
+                }
+                self.show_message(
+                    MessageType::Info,
+                    "Import preferences",
+                    &format!(
+                        "Imported {}.\nRestart MView6 to apply all settings.",
+                        files.join(", ")
+                    ),
+                );
+            }
+            Err(e) => self.show_message(
+                MessageType::Error,
+                "Import preferences",
+                &format!("Cannot import {}: {e}", path.display()),
+            ),
+        }
+    }
+}
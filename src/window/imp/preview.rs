@@ -0,0 +1,107 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use glib::clone;
+use gtk4::{prelude::*, MessageType};
+
+use crate::{
+    config::config,
+    preview_server::{Frame, PreviewServer, PreviewState, DEFAULT_ADDRESS},
+    window::imp::MViewWindowImp,
+};
+
+impl MViewWindowImp {
+    /// Start or stop serving the current view over HTTP, for mirroring on another device
+    pub fn toggle_preview_server(&self) {
+        let w = self.widgets();
+        if self.preview_server.take().is_some() {
+            println!("Stopped the preview server");
+            w.set_action_bool("preview.server", false);
+            return;
+        }
+        let address = config()
+            .config_file
+            .preview_server
+            .clone()
+            .unwrap_or_else(|| DEFAULT_ADDRESS.to_string());
+        match PreviewServer::start(&address) {
+            Ok(server) => {
+                let url = server.url();
+                println!("Preview server at {url}");
+                self.preview_server.replace(Some(server));
+                self.update_preview();
+                w.set_action_bool("preview.server", true);
+                self.show_message(
+                    MessageType::Info,
+                    "Preview server",
+                    &format!("The current view is served at {url}"),
+                );
+            }
+            Err(e) => {
+                w.set_action_bool("preview.server", false);
+                self.show_message(
+                    MessageType::Error,
+                    "Preview server",
+                    &format!("Cannot listen on {address}: {e}"),
+                );
+            }
+        }
+    }
+
+    /// Update the preview server once the view has been drawn, or redrawn with the high
+    /// quality render
+    pub(super) fn schedule_preview_update(&self) {
+        if self.preview_server.borrow().is_none() {
+            return;
+        }
+        glib::idle_add_local_once(clone!(
+            #[weak(rename_to = this)]
+            self,
+            move || this.update_preview()
+        ));
+    }
+
+    fn update_preview(&self) {
+        let server = self.preview_server.borrow();
+        let Some(server) = server.as_ref() else {
+            return;
+        };
+        let w = self.widgets();
+        // Only the copy is made here, the server converts and encodes it
+        let frame = w.image_view.capture_view().ok().and_then(|mut surface| {
+            surface.flush();
+            Some(Frame {
+                width: surface.width() as u32,
+                height: surface.height() as u32,
+                stride: surface.stride() as usize,
+                data: surface.data().ok()?.to_vec(),
+            })
+        });
+        let state = match w.file_view.current() {
+            Some(cursor) => PreviewState {
+                name: cursor.name(),
+                position: cursor.position() as u32 + 1,
+                total: cursor.store.iter_n_children(None) as u32,
+                ..Default::default()
+            },
+            None => PreviewState::default(),
+        };
+        server.update(state, frame);
+    }
+}
@@ -0,0 +1,103 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    backends::Backend,
+    config::JsonStore,
+    file_view::{model::BackendRef, Target},
+    window::imp::MViewWindowImp,
+};
+
+/// Number of closed archives and documents that can be reopened
+const MAX_CLOSED: usize = 20;
+
+/// Closed containers are kept between sessions
+const CLOSED: JsonStore = JsonStore::new("closed.json");
+
+/// An archive or document that was left and the place it was left at
+#[derive(Serialize, Deserialize)]
+pub struct ClosedContainer {
+    /// Name of the backend, as in `BackendRef::name`
+    backend: String,
+    path: PathBuf,
+    target: Target,
+}
+
+impl ClosedContainer {
+    fn backend_ref(&self) -> BackendRef {
+        BackendRef::new(&self.backend, self.path.clone())
+    }
+}
+
+impl MViewWindowImp {
+    /// Remember the archive or document that is about to be left. Called when the backend
+    /// is replaced and when the window is closed.
+    pub(super) fn remember_closed(&self) {
+        let backend = self.backend.borrow();
+        if !backend.is_container() {
+            return;
+        }
+        let target = match self.widgets().file_view.current() {
+            Some(cursor) => backend.reference(&cursor).into(),
+            None => Target::First,
+        };
+        let backend = backend.backend_ref();
+        let mut closed = self.closed_containers.borrow_mut();
+        closed.retain(|container| container.backend_ref() != backend);
+        closed.push_front(ClosedContainer {
+            backend: backend.name().to_string(),
+            path: backend.path().into(),
+            target,
+        });
+        closed.truncate(MAX_CLOSED);
+    }
+
+    pub(super) fn load_closed(&self) {
+        self.closed_containers.replace(CLOSED.load());
+    }
+
+    pub(super) fn save_closed(&self) {
+        if let Err(e) = CLOSED.save(&*self.closed_containers.borrow()) {
+            eprintln!("Failed to save the closed archives and documents: {e}");
+        }
+    }
+
+    /// Open the most recently closed archive or document again, at the entry or page
+    /// that was shown when it was left
+    pub fn reopen_closed(&self) {
+        let container = loop {
+            let Some(container) = self.closed_containers.borrow_mut().pop_front() else {
+                return;
+            };
+            // Skip the ones that were deleted or moved in the meantime
+            if Path::new(&container.path).exists() {
+                break container;
+            }
+        };
+        println!("Reopen {}", container.path.display());
+        self.set_backend(
+            <dyn Backend>::new_from_ref(&container.backend_ref()),
+            &container.target,
+        );
+    }
+}
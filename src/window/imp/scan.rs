@@ -0,0 +1,45 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use gtk4::MessageType;
+
+use crate::{image::scan::ScanFilter, window::imp::MViewWindowImp};
+
+impl MViewWindowImp {
+    /// Toggle a display filter for the scanned pages of the current document
+    pub fn toggle_scan_filter(&self, filter: ScanFilter) {
+        let backend = self.backend.borrow();
+        if !backend.is_doc() {
+            drop(backend);
+            self.show_message(
+                MessageType::Info,
+                "Scan filters",
+                "Scan filters are only available for documents",
+            );
+            return;
+        }
+        self.scan_filters
+            .borrow_mut()
+            .entry(backend.normalized_path())
+            .or_default()
+            .toggle(filter);
+        drop(backend);
+        self.on_cursor_changed();
+    }
+}
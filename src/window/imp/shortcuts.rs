@@ -0,0 +1,178 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use glib::{clone, Propagation};
+use gtk4::{
+    gdk::Key, prelude::*, Align, Box, EventControllerKey, FlowBox, Label, Orientation, PolicyType,
+    ScrolledWindow, SelectionMode, Window,
+};
+
+use crate::window::MViewWindow;
+
+/// Shortcut groups shown in the cheat sheet: group title and (keys, description) pairs
+const SHORTCUTS: &[(&str, &[(&str, &str)])] = &[
+    (
+        "Navigation",
+        &[
+            ("Up / z", "Previous item"),
+            ("Down / x", "Next item"),
+            ("Page Up / Page Down", "Skip 25 items"),
+            ("Home / End", "First or last item"),
+            ("Enter", "Open folder, archive or document"),
+            ("Backspace", "Leave folder, archive or document"),
+            ("Ctrl+Shift+T", "Reopen closed archive or document"),
+            ("w / e", "Previous or next folder"),
+            ("d", "Bookmarks"),
+            ("1 2 3 4", "Sort on type, name, size or date"),
+            ("Shift+F", "Filter"),
+            ("c / Shift+C", "Add to collection or show collection"),
+            ("Ctrl+Up / Ctrl+Down", "Reorder collection"),
+            ("Delete", "Move to review trash, or remove from collection"),
+            ("Ctrl+Z", "Undo delete, or restore from review trash"),
+            ("Shift+Delete", "Show review trash"),
+        ],
+    ),
+    (
+        "Zoom and view",
+        &[
+            ("m", "Cycle zoom mode"),
+            ("n", "Toggle fit and no scaling"),
+            ("%", "Zoom and print size"),
+            ("r / Shift+R", "Rotate clockwise or counterclockwise"),
+            ("f", "Full screen"),
+            ("t", "Thumbnails"),
+            ("space", "Files pane"),
+            ("i", "Information pane"),
+            ("F9", "Monitor LUT"),
+        ],
+    ),
+    (
+        "Rating",
+        &[
+            ("=", "Like and go to next"),
+            ("-", "Dislike and go to next"),
+            ("a / s", "Previous or next liked"),
+            ("6 7 8 9 5", "Red, yellow, green, blue or purple label"),
+            ("k / j", "Keep or discard in hot folder review"),
+            ("Shift+W", "Watch hot folder"),
+        ],
+    ),
+    (
+        "Documents",
+        &[
+            ("Left / Right", "Previous or next page"),
+            ("Ctrl+Home / Ctrl+End", "First or last page"),
+            ("Ctrl+G", "Go to page"),
+            ("p", "Cycle page mode"),
+            ("F5", "Auto-scroll"),
+            ("[ / ]", "Auto-scroll slower or faster"),
+            ("F4", "Reading ruler"),
+            ("F2", "Measure"),
+        ],
+    ),
+    (
+        "General",
+        &[
+            ("F1 / ?", "This overview"),
+            ("h", "Help pages"),
+            ("Ctrl+Shift+P", "Command palette"),
+            ("Ctrl+T", "Open terminal here"),
+            ("Shift+E", "Edit in external editor"),
+            ("Escape", "Leave full screen"),
+            ("q", "Quit"),
+        ],
+    ),
+];
+
+/// Shows the keyboard shortcut cheat sheet as a modal window
+pub fn show_shortcuts(parent: &MViewWindow) {
+    let window = Window::builder()
+        .transient_for(parent)
+        .modal(true)
+        .default_width(900)
+        .default_height(560)
+        .title("MView6 Keyboard Shortcuts")
+        .build();
+
+    let flow_box = FlowBox::builder()
+        .selection_mode(SelectionMode::None)
+        .valign(Align::Start)
+        .max_children_per_line(3)
+        .min_children_per_line(1)
+        .column_spacing(24)
+        .row_spacing(12)
+        .margin_start(12)
+        .margin_end(12)
+        .margin_top(12)
+        .margin_bottom(12)
+        .build();
+
+    for &(title, shortcuts) in SHORTCUTS {
+        let group = Box::new(Orientation::Vertical, 4);
+
+        let header = Label::new(Some(title));
+        header.set_halign(Align::Start);
+        header.add_css_class("cp-header");
+        group.append(&header);
+
+        for &(keys, description) in shortcuts {
+            let row = Box::new(Orientation::Horizontal, 12);
+
+            let label = Label::new(Some(description));
+            label.set_halign(Align::Start);
+            label.set_hexpand(true);
+            row.append(&label);
+
+            let shortcut_label = Label::new(Some(keys));
+            shortcut_label.add_css_class("cp-shortcut");
+            shortcut_label.set_halign(Align::End);
+            row.append(&shortcut_label);
+
+            group.append(&row);
+        }
+
+        flow_box.insert(&group, -1);
+    }
+
+    let scrolled = ScrolledWindow::builder()
+        .hscrollbar_policy(PolicyType::Never)
+        .vscrollbar_policy(PolicyType::Automatic)
+        .child(&flow_box)
+        .build();
+    window.set_child(Some(&scrolled));
+
+    let key_controller = EventControllerKey::new();
+    key_controller.connect_key_pressed(clone!(
+        #[weak]
+        window,
+        #[upgrade_or]
+        Propagation::Proceed,
+        move |_, key, _, _| {
+            if matches!(key, Key::Escape | Key::F1 | Key::question) {
+                window.close();
+                Propagation::Stop
+            } else {
+                Propagation::Proceed
+            }
+        }
+    ));
+    window.add_controller(key_controller);
+
+    window.present();
+}
@@ -0,0 +1,219 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use glib::{clone, subclass::types::ObjectSubclassExt};
+use gtk4::{prelude::*, ButtonsType, DialogFlags, MessageDialog, MessageType, ResponseType};
+
+use crate::{
+    backends::{
+        staging::{load_staging, purge, restore, save_staging, stage},
+        Backend,
+    },
+    file_view::{model::BackendRef, Direction, Filter, Target},
+    window::imp::MViewWindowImp,
+};
+
+impl MViewWindowImp {
+    /// Move the current file into the staging area and continue with the next one
+    pub fn stage_current(&self) {
+        let BackendRef::FileSystem(directory) = self.backend.borrow().backend_ref() else {
+            return;
+        };
+        let w = self.widgets();
+        let Some(current) = w.file_view.current() else {
+            return;
+        };
+        let path = directory.join(current.name());
+        if !path.is_file() {
+            return;
+        }
+
+        // Step away from the file before it disappears
+        let target = if w.file_view.navigate_item(Direction::Down, &Filter::None, 1)
+            || w.file_view.navigate_item(Direction::Up, &Filter::None, 1)
+        {
+            w.file_view
+                .current()
+                .map(|cursor| Target::Name(cursor.name()))
+                .unwrap_or(Target::First)
+        } else {
+            Target::First
+        };
+
+        let entry = match stage(&path) {
+            Ok(entry) => entry,
+            Err(e) => {
+                self.show_message(
+                    MessageType::Error,
+                    "Delete",
+                    &format!("Cannot move {} to the review trash: {e}", path.display()),
+                );
+                self.reload(&Target::Name(current.name()));
+                return;
+            }
+        };
+        println!("Staged {}", entry.original.display());
+        let mut entries = load_staging();
+        entries.push(entry);
+        if let Err(e) = save_staging(&entries) {
+            eprintln!("Failed to save the review trash: {e}");
+        }
+        self.reload(&target);
+    }
+
+    /// Restore the most recently deleted file and show it
+    pub fn undo_delete(&self) {
+        if self.backend.borrow().is_staging() {
+            return;
+        }
+        let mut entries = load_staging();
+        let Some(entry) = entries.pop() else {
+            return;
+        };
+        if let Err(e) = restore(&entry) {
+            self.show_message(
+                MessageType::Error,
+                "Undo delete",
+                &format!("Cannot restore {}: {e}", entry.original.display()),
+            );
+            return;
+        }
+        if let Err(e) = save_staging(&entries) {
+            eprintln!("Failed to save the review trash: {e}");
+        }
+        self.navigate_to(&entry.original);
+    }
+
+    /// Show the review trash, or return from it
+    pub fn toggle_staging(&self) {
+        if self.backend.borrow().is_staging() {
+            self.dir_leave();
+            return;
+        }
+        self.show_files_widget(true);
+        let backend = self.backend.replace(<dyn Backend>::none());
+        let target = match self.widgets().file_view.current() {
+            Some(cursor) => backend.reference(&cursor).into(),
+            None => Target::First,
+        };
+        self.set_backend(<dyn Backend>::staging(backend, target), &Target::First);
+    }
+
+    /// Replace the staging backend by one showing the changed staging area, keeping the
+    /// place it returns to
+    fn refresh_staging(&self, target: Target) {
+        let backend = self.backend.replace(<dyn Backend>::none());
+        let Some((parent, parent_target)) = backend.leave() else {
+            return;
+        };
+        self.set_backend(<dyn Backend>::staging(parent, parent_target), &target);
+    }
+
+    /// Move the selected file of the review trash back to where it was deleted from
+    pub fn restore_staged(&self) {
+        if !self.backend.borrow().is_staging() {
+            return;
+        }
+        let Some(current) = self.widgets().file_view.current() else {
+            return;
+        };
+        let mut entries = load_staging();
+        let index = current.index() as usize;
+        if index >= entries.len() {
+            return;
+        }
+        if let Err(e) = restore(&entries[index]) {
+            self.show_message(
+                MessageType::Error,
+                "Restore",
+                &format!("Cannot restore {}: {e}", entries[index].original.display()),
+            );
+            return;
+        }
+        entries.remove(index);
+        match save_staging(&entries) {
+            Ok(()) => self.refresh_staging(Target::Index(index as u64)),
+            Err(e) => eprintln!("Failed to save the review trash: {e}"),
+        }
+    }
+
+    /// Delete all files of the review trash for good, after confirmation
+    pub fn empty_staging(&self) {
+        let entries = load_staging();
+        if entries.is_empty() {
+            self.show_message(
+                MessageType::Info,
+                "Empty review trash",
+                "The review trash is empty, files deleted with the Delete key end up here",
+            );
+            return;
+        }
+
+        let dialog = MessageDialog::new(
+            Some(&self.obj().clone()),
+            DialogFlags::MODAL,
+            MessageType::Warning,
+            ButtonsType::None,
+            "Empty review trash",
+        );
+        dialog.set_secondary_text(Some(&format!(
+            "Permanently delete {} files? This cannot be undone.",
+            entries.len()
+        )));
+        dialog.add_button("Cancel", ResponseType::Cancel);
+        let delete_btn = dialog.add_button("Delete", ResponseType::Accept);
+        delete_btn.add_css_class("destructive-action");
+
+        dialog.connect_response(clone!(
+            #[weak(rename_to = this)]
+            self,
+            move |dialog, response| {
+                if response == ResponseType::Accept {
+                    this.purge_staging();
+                }
+                dialog.close();
+            }
+        ));
+
+        dialog.present();
+    }
+
+    fn purge_staging(&self) {
+        let mut remaining = Vec::new();
+        for entry in load_staging() {
+            if let Err(e) = purge(&entry) {
+                eprintln!("Failed to delete {:?}: {e}", entry.staged);
+                remaining.push(entry);
+            }
+        }
+        if let Err(e) = save_staging(&remaining) {
+            eprintln!("Failed to save the review trash: {e}");
+        }
+        if self.backend.borrow().is_staging() {
+            self.refresh_staging(Target::First);
+        }
+        if !remaining.is_empty() {
+            self.show_message(
+                MessageType::Warning,
+                "Empty review trash",
+                &format!("Failed to delete {} files", remaining.len()),
+            );
+        }
+    }
+}
@@ -0,0 +1,206 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::path::PathBuf;
+
+use chrono::TimeDelta;
+use glib::{clone, subclass::types::ObjectSubclassExt};
+use gtk4::{
+    prelude::*, Box, Dialog, Entry, Label, MessageType, Orientation, PolicyType, ResponseType,
+    ScrolledWindow,
+};
+
+use crate::{
+    classification::{FileClassification, FileType},
+    file_view::model::BackendRef,
+    metadata::writer::{capture_time, parse_time_offset, read_date_time_fields, shift_date_time},
+    window::imp::MViewWindowImp,
+};
+
+/// Last applied timestamp shift, kept for undo
+pub struct TimeShift {
+    files: Vec<PathBuf>,
+    delta: TimeDelta,
+}
+
+impl MViewWindowImp {
+    /// Images in the current folder that match the navigation filter
+    pub(super) fn filtered_folder_images(&self) -> Option<Vec<PathBuf>> {
+        let backend = self.backend.borrow();
+        let BackendRef::FileSystem(directory) = backend.backend_ref() else {
+            return None;
+        };
+        let filter = self.current_filter.borrow();
+        Some(
+            backend
+                .list()
+                .iter()
+                .map(|row| directory.join(&row.name))
+                .filter(|path| {
+                    let classification = FileClassification::determine(path, false);
+                    classification.file_type == FileType::Image && filter.matches(classification)
+                })
+                .collect(),
+        )
+    }
+
+    pub fn timestamp_shift_dialog(&self) {
+        let Some(files) = self.filtered_folder_images() else {
+            self.show_message(
+                MessageType::Info,
+                "Shift EXIF timestamps",
+                "Timestamps can only be shifted for images in a folder",
+            );
+            return;
+        };
+
+        // (file, capture time) of the images that have a timestamp
+        let images = files
+            .into_iter()
+            .filter_map(|path| {
+                let fields = read_date_time_fields(&path).ok()?;
+                Some((path, capture_time(&fields)?))
+            })
+            .collect::<Vec<_>>();
+
+        let dialog = Dialog::builder()
+            .title("Shift EXIF timestamps")
+            .modal(true)
+            .transient_for(&self.obj().clone())
+            .default_width(560)
+            .default_height(480)
+            .build();
+
+        let vbox = Box::builder()
+            .orientation(Orientation::Vertical)
+            .spacing(8)
+            .margin_start(12)
+            .margin_end(12)
+            .margin_top(12)
+            .margin_bottom(12)
+            .build();
+
+        let label = Label::new(Some(&format!(
+            "Offset for {} images (e.g. +1:30, -0:00:45 or +2d 01:00:00)",
+            images.len()
+        )));
+        label.set_xalign(0.0);
+        let entry = Entry::new();
+        entry.set_activates_default(true);
+
+        let preview = Label::new(None);
+        preview.set_xalign(0.0);
+        preview.set_yalign(0.0);
+        preview.set_selectable(true);
+        preview.add_css_class("monospace");
+        let scrolled = ScrolledWindow::builder()
+            .hscrollbar_policy(PolicyType::Automatic)
+            .vscrollbar_policy(PolicyType::Automatic)
+            .vexpand(true)
+            .child(&preview)
+            .build();
+
+        vbox.append(&label);
+        vbox.append(&entry);
+        vbox.append(&scrolled);
+        dialog.content_area().append(&vbox);
+
+        let update_preview = {
+            let images = images.clone();
+            move |text: &str| -> Option<TimeDelta> {
+                let delta = parse_time_offset(text);
+                let lines = images
+                    .iter()
+                    .map(|(path, time)| {
+                        let name = path.file_name().unwrap_or_default().to_string_lossy();
+                        match delta.and_then(|delta| time.checked_add_signed(delta)) {
+                            Some(shifted) => format!("{name}  {time}  →  {shifted}"),
+                            None => format!("{name}  {time}"),
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                preview.set_text(&lines.join("\n"));
+                delta
+            }
+        };
+        update_preview("");
+
+        dialog.add_button("Cancel", ResponseType::Cancel);
+        let ok_btn = dialog.add_button("Shift", ResponseType::Ok);
+        ok_btn.set_sensitive(false);
+        dialog.set_default_response(ResponseType::Ok);
+
+        entry.connect_changed(clone!(
+            #[weak]
+            ok_btn,
+            move |entry| {
+                let delta = update_preview(&entry.text());
+                ok_btn.set_sensitive(delta.is_some_and(|d| !d.is_zero()));
+            }
+        ));
+
+        dialog.connect_response(clone!(
+            #[weak(rename_to = this)]
+            self,
+            #[weak]
+            entry,
+            move |dialog, response| {
+                if response == ResponseType::Ok {
+                    if let Some(delta) = parse_time_offset(&entry.text()) {
+                        let files = images.iter().map(|(path, _)| path.clone()).collect();
+                        this.apply_time_shift(files, delta);
+                    }
+                }
+                dialog.close();
+            }
+        ));
+
+        dialog.present();
+    }
+
+    fn apply_time_shift(&self, files: Vec<PathBuf>, delta: TimeDelta) {
+        let shifted = match shift_date_time(&files, delta) {
+            Ok(shifted) => shifted,
+            Err(e) => {
+                self.show_message(
+                    MessageType::Error,
+                    "Shift EXIF timestamps",
+                    &format!("No timestamps were changed.\n\n{e}"),
+                );
+                return;
+            }
+        };
+        println!("Shifted timestamps of {} images by {delta}", shifted.len());
+        self.last_time_shift.replace(Some(TimeShift {
+            files: shifted,
+            delta,
+        }));
+        self.on_cursor_changed();
+    }
+
+    pub fn undo_timestamp_shift(&self) {
+        if let Some(time_shift) = self.last_time_shift.take() {
+            match shift_date_time(&time_shift.files, -time_shift.delta) {
+                Ok(restored) => println!("Restored timestamps of {} images", restored.len()),
+                Err(e) => eprintln!("Failed to restore timestamps: {e}"),
+            }
+            self.on_cursor_changed();
+        }
+    }
+}
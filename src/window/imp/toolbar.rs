@@ -0,0 +1,138 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use glib::prelude::ToVariant;
+use gtk4::{prelude::*, Button, HeaderBar, ToggleButton};
+
+use crate::config::config;
+
+use super::MViewWindowImp;
+
+/// Button id, icon, tooltip, window action and action target
+type ToolbarButton = (
+    &'static str,
+    &'static str,
+    &'static str,
+    &'static str,
+    Option<&'static str>,
+);
+
+const TOOLBAR_BUTTONS: &[ToolbarButton] = &[
+    (
+        "rotate-left",
+        "object-rotate-left-symbolic",
+        "Rotate counterclockwise",
+        "win.rotate",
+        Some("90"),
+    ),
+    (
+        "rotate-right",
+        "object-rotate-right-symbolic",
+        "Rotate clockwise",
+        "win.rotate",
+        Some("270"),
+    ),
+    (
+        "zoom-nozoom",
+        "zoom-original-symbolic",
+        "No scaling",
+        "win.zoom",
+        Some("nozoom"),
+    ),
+    (
+        "zoom-fit",
+        "zoom-fit-best-symbolic",
+        "Fit window",
+        "win.zoom",
+        Some("fit"),
+    ),
+    (
+        "zoom-fill",
+        "zoom-fit-width-symbolic",
+        "Fill window",
+        "win.zoom",
+        Some("fill"),
+    ),
+    (
+        "zoom-max",
+        "zoom-in-symbolic",
+        "Maximum zoom",
+        "win.zoom",
+        Some("max"),
+    ),
+    (
+        "slideshow",
+        "media-playback-start-symbolic",
+        "Run slideshow",
+        "win.slideshow.active",
+        None,
+    ),
+    (
+        "thumbnails",
+        "view-grid-symbolic",
+        "Show thumbnails",
+        "win.thumb.show",
+        None,
+    ),
+    (
+        "fullscreen",
+        "view-fullscreen-symbolic",
+        "Full screen",
+        "win.fullscreen",
+        None,
+    ),
+];
+
+impl MViewWindowImp {
+    /// Adds the buttons listed in the `toolbar` setting of the configuration file to the
+    /// end of the header bar, in the order they are listed
+    pub(super) fn add_toolbar(header_bar: &HeaderBar) {
+        let Some(ids) = &config().config_file.toolbar else {
+            return;
+        };
+        // pack_end fills the header bar from the right
+        for id in ids.iter().rev() {
+            let Some(&(_, icon, tooltip, action, target)) =
+                TOOLBAR_BUTTONS.iter().find(|(name, ..)| name == id)
+            else {
+                eprintln!("Unknown toolbar button '{id}'");
+                continue;
+            };
+            // Stateful boolean actions keep the toggle button in sync
+            let button: gtk4::Widget = match target {
+                Some(target) => Button::builder()
+                    .icon_name(icon)
+                    .tooltip_text(tooltip)
+                    .action_name(action)
+                    .action_target(&target.to_variant())
+                    .can_focus(false)
+                    .build()
+                    .upcast(),
+                None => ToggleButton::builder()
+                    .icon_name(icon)
+                    .tooltip_text(tooltip)
+                    .action_name(action)
+                    .can_focus(false)
+                    .build()
+                    .upcast(),
+            };
+            header_bar.pack_end(&button);
+        }
+    }
+}
@@ -0,0 +1,204 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::path::{Path, PathBuf};
+
+use glib::{clone, subclass::types::ObjectSubclassExt};
+use gtk4::{
+    prelude::*, ButtonsType, DialogFlags, FileChooserAction, FileChooserDialog, MessageDialog,
+    MessageType, ResponseType,
+};
+
+use crate::{
+    file_view::{model::BackendRef, Target},
+    transfer::{
+        has_conflicts, plan_transfer, resolve, transfer_file, Resolution, TransferItem,
+        TransferMode,
+    },
+    window::imp::MViewWindowImp,
+};
+
+const RESPONSE_OVERWRITE: ResponseType = ResponseType::Other(1);
+const RESPONSE_SKIP: ResponseType = ResponseType::Other(2);
+const RESPONSE_RENAME: ResponseType = ResponseType::Other(3);
+
+/// Conflicts listed in the dialog, the others are summarized
+const MAX_LISTED: usize = 10;
+
+fn title(mode: TransferMode) -> &'static str {
+    match mode {
+        TransferMode::Copy => "Copy to folder",
+        TransferMode::Move => "Move to folder",
+    }
+}
+
+/// One line per conflict with the name it gets when renamed
+fn conflict_text(plan: &[TransferItem]) -> String {
+    let renamed = resolve(plan.to_vec(), Resolution::RenameAll);
+    let conflicts = plan
+        .iter()
+        .zip(renamed.iter())
+        .filter(|(item, _)| item.conflict)
+        .map(|(item, renamed)| format!("{}  →  {}", item.name(), renamed.name()))
+        .collect::<Vec<_>>();
+    let mut text = conflicts
+        .iter()
+        .take(MAX_LISTED)
+        .cloned()
+        .collect::<Vec<_>>()
+        .join("\n");
+    if conflicts.len() > MAX_LISTED {
+        text.push_str(&format!("\n... and {} more", conflicts.len() - MAX_LISTED));
+    }
+    text
+}
+
+impl MViewWindowImp {
+    pub fn copy_to_folder(&self) {
+        self.choose_destination(TransferMode::Copy);
+    }
+
+    pub fn move_to_folder(&self) {
+        self.choose_destination(TransferMode::Move);
+    }
+
+    /// Ask for the folder to copy or move the current file to
+    fn choose_destination(&self, mode: TransferMode) {
+        let BackendRef::FileSystem(directory) = self.backend.borrow().backend_ref() else {
+            return;
+        };
+        let Some(current) = self.widgets().file_view.current() else {
+            return;
+        };
+        let source = directory.join(current.name());
+        if !source.is_file() {
+            return;
+        }
+        // After a move, continue with the next file
+        let target = if mode == TransferMode::Copy || current.next() {
+            Target::Name(current.name())
+        } else {
+            Target::Last
+        };
+
+        let dialog = FileChooserDialog::new(
+            Some(title(mode)),
+            Some(&self.obj().clone()),
+            FileChooserAction::SelectFolder,
+            &[
+                ("Cancel", ResponseType::Cancel),
+                ("Select", ResponseType::Accept),
+            ],
+        );
+
+        dialog.connect_response(clone!(
+            #[weak(rename_to = this)]
+            self,
+            move |dialog, response| {
+                if response == ResponseType::Accept {
+                    if let Some(destination) = dialog.file().and_then(|file| file.path()) {
+                        this.transfer_files(&[source.clone()], &destination, mode, target.clone());
+                    }
+                }
+                dialog.destroy();
+            }
+        ));
+
+        dialog.show();
+    }
+
+    /// Copy or move files to `destination`. When names are already taken there, the user
+    /// chooses once how to handle all of them. Afterwards the view is reloaded at `target`.
+    pub fn transfer_files(
+        &self,
+        sources: &[PathBuf],
+        destination: &Path,
+        mode: TransferMode,
+        target: Target,
+    ) {
+        let plan = plan_transfer(sources, destination);
+        if !has_conflicts(&plan) {
+            self.run_transfer(plan, mode, &target);
+            return;
+        }
+
+        let count = plan.iter().filter(|item| item.conflict).count();
+        let dialog = MessageDialog::new(
+            Some(&self.obj().clone()),
+            DialogFlags::MODAL,
+            MessageType::Question,
+            ButtonsType::None,
+            &format!("{count} of {} files already exist", plan.len()),
+        );
+        dialog.set_secondary_text(Some(&format!(
+            "{}\n\n{}",
+            destination.display(),
+            conflict_text(&plan)
+        )));
+        dialog.add_button("Cancel", ResponseType::Cancel);
+        dialog.add_button("Skip", RESPONSE_SKIP);
+        let overwrite_btn = dialog.add_button("Overwrite", RESPONSE_OVERWRITE);
+        overwrite_btn.add_css_class("destructive-action");
+        dialog.add_button("Rename all", RESPONSE_RENAME);
+        dialog.set_default_response(RESPONSE_RENAME);
+
+        dialog.connect_response(clone!(
+            #[weak(rename_to = this)]
+            self,
+            move |dialog, response| {
+                let resolution = if response == RESPONSE_OVERWRITE {
+                    Some(Resolution::Overwrite)
+                } else if response == RESPONSE_SKIP {
+                    Some(Resolution::Skip)
+                } else if response == RESPONSE_RENAME {
+                    Some(Resolution::RenameAll)
+                } else {
+                    None
+                };
+                dialog.close();
+                if let Some(resolution) = resolution {
+                    this.run_transfer(resolve(plan.clone(), resolution), mode, &target);
+                }
+            }
+        ));
+
+        dialog.present();
+    }
+
+    fn run_transfer(&self, plan: Vec<TransferItem>, mode: TransferMode, target: &Target) {
+        let mut failed = Vec::new();
+        for item in &plan {
+            match transfer_file(item, mode) {
+                Ok(()) => println!("{} → {}", item.source.display(), item.target.display()),
+                Err(e) => {
+                    eprintln!("Failed to transfer {:?}: {e}", item.source);
+                    failed.push(item.name());
+                }
+            }
+        }
+        self.reload(target);
+        if !failed.is_empty() {
+            self.show_message(
+                MessageType::Warning,
+                title(mode),
+                &format!("Failed to transfer:\n{}", failed.join("\n")),
+            );
+        }
+    }
+}
@@ -0,0 +1,153 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use glib::clone;
+use gtk4::MessageType;
+
+use crate::{
+    backends::{collection::CollectionEntry, Backend},
+    classification::FileType,
+    file_view::{Cursor, Target},
+    window::imp::MViewWindowImp,
+};
+
+/// Shortest time a file must be shown to count as viewed, skipping past it does not count
+const MIN_VIEW_SECONDS: u64 = 2;
+
+/// Longest time a single view counts for, beyond it the viewer was probably left alone
+const MAX_VIEW_SECONDS: u64 = 600;
+
+/// Delay before recorded views are saved, so browsing writes the file once in a while
+const SAVE_DELAY: Duration = Duration::from_secs(60);
+
+impl MViewWindowImp {
+    /// Count a view of the file under the cursor, when usage statistics are enabled
+    pub(super) fn record_view(&self, backend: &dyn Backend, cursor: &Cursor) {
+        self.finish_view();
+        if !self.usage.borrow().enabled
+            || backend.is_thumbnail()
+            || backend.is_bookmarks()
+            || backend.is_staging()
+            || cursor.content() == FileType::Folder
+        {
+            return;
+        }
+        // Entries of the collection and most viewed list count for the file they refer to
+        let reference = backend
+            .resolve(cursor)
+            .unwrap_or_else(|| backend.reference(cursor));
+        let entry = CollectionEntry::new(&reference, &cursor.name());
+        if let Some(file_usage) = self.usage.borrow().get(&entry) {
+            self.widgets().info_view.add_usage(file_usage);
+        }
+        self.viewing.replace(Some((entry, Instant::now())));
+    }
+
+    /// Count the view of the file being viewed, if it was shown long enough
+    fn finish_view(&self) {
+        let Some((entry, start)) = self.viewing.take() else {
+            return;
+        };
+        let seconds = start.elapsed().as_secs();
+        if seconds < MIN_VIEW_SECONDS {
+            return;
+        }
+        let start = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.as_secs().saturating_sub(seconds))
+            .unwrap_or_default();
+        self.usage
+            .borrow_mut()
+            .record_view(&entry, start, seconds.min(MAX_VIEW_SECONDS));
+        self.schedule_usage_save();
+    }
+
+    fn schedule_usage_save(&self) {
+        if self.usage_save_pending.replace(true) {
+            return;
+        }
+        glib::timeout_add_local_once(
+            SAVE_DELAY,
+            clone!(
+                #[weak(rename_to = this)]
+                self,
+                move || {
+                    this.usage_save_pending.set(false);
+                    this.save_usage_stats();
+                }
+            ),
+        );
+    }
+
+    fn save_usage_stats(&self) {
+        if let Err(e) = self.usage.borrow().save() {
+            eprintln!("Failed to save the usage statistics: {e}");
+        }
+    }
+
+    pub(super) fn save_usage(&self) {
+        self.finish_view();
+        if self.usage.borrow().enabled {
+            self.save_usage_stats();
+        }
+    }
+
+    /// Start or stop recording how often and how long files are viewed
+    pub fn toggle_usage_stats(&self) {
+        self.finish_view();
+        let enabled = {
+            let mut usage = self.usage.borrow_mut();
+            usage.enabled = !usage.enabled;
+            usage.enabled
+        };
+        // Also saved when disabled, to remember the choice
+        self.save_usage_stats();
+        self.widgets().set_action_bool("usage.record", enabled);
+    }
+
+    /// Show the most viewed files, or return from them
+    pub fn toggle_most_viewed(&self) {
+        if self.backend.borrow().is_most_viewed() {
+            self.dir_leave();
+            return;
+        }
+        self.finish_view();
+        let entries = self.usage.borrow().most_viewed();
+        if entries.is_empty() {
+            self.show_message(
+                MessageType::Info,
+                "Most viewed",
+                "No views were recorded yet, enable \"Record views\" in the usage statistics menu",
+            );
+            return;
+        }
+        self.show_files_widget(true);
+        let backend = self.backend.replace(<dyn Backend>::none());
+        let target = match self.widgets().file_view.current() {
+            Some(cursor) => backend.reference(&cursor).into(),
+            None => Target::First,
+        };
+        self.set_backend(
+            <dyn Backend>::most_viewed(entries, backend, target),
+            &Target::First,
+        );
+    }
+}
@@ -0,0 +1,117 @@
+// MView6 -- High-performance PDF and photo viewer built with Rust and GTK4
+//
+// Copyright (c) 2024-2025 Martin van der Werff <github (at) newinnovations.nl>
+//
+// This file is part of MView6.
+//
+// MView6 is free software: you can redistribute it and/or modify it under the terms of
+// the GNU Affero General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR
+// IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND
+// FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR ANY
+// DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT
+// LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR
+// BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT,
+// STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::path::{Path, PathBuf};
+
+use gio::{prelude::*, Cancellable, FileMonitor, FileMonitorEvent, FileMonitorFlags};
+use glib::clone;
+
+use crate::{
+    backends::{archive_rar, filesystem, thumbnail::Message},
+    classification::{
+        detect,
+        file_formats::{ArchiveFormat, FileFormat},
+    },
+    file_view::model::BackendRef,
+    window::imp::MViewWindowImp,
+};
+
+/// Monitor of the directory holding the files currently shown, used to refresh the
+/// thumbnails of files that are modified
+pub struct DirectoryWatcher {
+    directory: PathBuf,
+    monitor: FileMonitor,
+}
+
+/// Directory containing the files of a backend: the folder itself or the folder holding
+/// the archive or document
+fn watched_directory(backend: &BackendRef) -> Option<PathBuf> {
+    match backend {
+        BackendRef::FileSystem(directory) => Some(directory.clone()),
+        BackendRef::Thumbnail
+        | BackendRef::Bookmarks
+        | BackendRef::Collection
+        | BackendRef::Staging
+        | BackendRef::MostViewed
+        | BackendRef::None => None,
+        _ => Path::new(backend.path()).parent().map(Path::to_path_buf),
+    }
+}
+
+impl MViewWindowImp {
+    /// Follow the backend that was navigated to. Thumbnail sheets keep watching the
+    /// directory of the backend they were opened from.
+    pub fn update_directory_watcher(&self, backend: &BackendRef) {
+        if *backend == BackendRef::Thumbnail {
+            return;
+        }
+        let directory = watched_directory(backend);
+        let current = self
+            .directory_watcher
+            .borrow()
+            .as_ref()
+            .map(|watcher| watcher.directory.clone());
+        if directory == current {
+            return;
+        }
+        if let Some(watcher) = self.directory_watcher.take() {
+            watcher.monitor.cancel();
+        }
+        let Some(directory) = directory else {
+            return;
+        };
+        let monitor = match gio::File::for_path(&directory)
+            .monitor_directory(FileMonitorFlags::NONE, None::<&Cancellable>)
+        {
+            Ok(monitor) => monitor,
+            Err(e) => {
+                eprintln!("Cannot watch {}: {e}", directory.display());
+                return;
+            }
+        };
+        monitor.connect_changed(clone!(
+            #[weak(rename_to = this)]
+            self,
+            move |_, file, _, event| this.on_watched_file_event(file, event)
+        ));
+        self.directory_watcher
+            .replace(Some(DirectoryWatcher { directory, monitor }));
+    }
+
+    fn on_watched_file_event(&self, file: &gio::File, event: FileMonitorEvent) {
+        // Only once the file is completely written
+        if event != FileMonitorEvent::ChangesDoneHint {
+            return;
+        }
+        let Some(path) = file.path() else {
+            return;
+        };
+        if detect::format_from_path(&path) == FileFormat::Archive(ArchiveFormat::Rar) {
+            archive_rar::invalidate_thumbnails(&path);
+        }
+        filesystem::invalidate_thumbnail(&path);
+        self.check_edit_result(&path);
+        if self.backend.borrow().is_thumbnail() {
+            let _ = self
+                .widgets()
+                .tn_sender
+                .send_blocking(Message::Refresh(path));
+        }
+    }
+}